@@ -403,8 +403,16 @@ pub async fn analyze_staged_changes(
         });
     }
 
-    // Fast regex-based secret detection (no AI needed)
-    let mut findings = detect_secrets(&diff);
+    // Fast regex-based secret detection (no AI needed). An unreadable
+    // committed config falls back to the built-in rules here; the commit
+    // gate reports the config error itself.
+    let mut findings = match git2::Repository::open(&repo_path)
+        .ok()
+        .and_then(|r| crate::commands::secret_scan::SecretScanner::for_repo(&r).ok())
+    {
+        Some(scanner) => detect_secrets_with(&scanner, &diff),
+        None => detect_secrets(&diff),
+    };
 
     // AI analysis for complexity and quality
     let truncated = truncate_content(&diff, MAX_DIFF_BYTES);
@@ -562,64 +570,39 @@ fn get_diff_stats(repo_path: &str, base_ref: &str, compare_ref: &str) -> Result<
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-/// Detect secrets in a diff using regex patterns (fast, no AI needed)
+/// Detect secrets in a diff with the built-in secret scanner rules (fast, no
+/// AI needed)
 fn detect_secrets(diff: &str) -> Vec<AnalysisFinding> {
-    let mut findings = Vec::new();
-
-    let patterns: Vec<(&str, &str)> = vec![
-        (r"AKIA[0-9A-Z]{16}", "Possible AWS Access Key ID detected"),
-        (
-            r"-----BEGIN[A-Z ]*PRIVATE KEY-----",
-            "Private key detected in diff",
-        ),
-        (
-            r#"(?i)(password|passwd|pwd)\s*[:=]\s*['"][^'"]{4,}['"]"#,
-            "Possible hardcoded password detected",
-        ),
-        (
-            r#"(?i)(api[_-]?key|apikey|secret[_-]?key)\s*[:=]\s*['"][A-Za-z0-9]{16,}['"]"#,
-            "Possible hardcoded API key detected",
-        ),
-        (
-            r#"(?i)(token)\s*[:=]\s*['"][A-Za-z0-9_\-.]{20,}['"]"#,
-            "Possible hardcoded token detected",
-        ),
-    ];
-
-    for (pattern, message) in patterns {
-        if let Ok(re) = regex::Regex::new(pattern) {
-            for mat in re.find_iter(diff) {
-                // Try to find the file path from the nearest diff header
-                let before = &diff[..mat.start()];
-                let file_path = before
-                    .rfind("\n+++ b/")
-                    .and_then(|pos| {
-                        let line_start = pos + 7; // skip "\n+++ b/"
-                        before[line_start..]
-                            .find('\n')
-                            .map(|end| before[line_start..line_start + end].to_string())
-                    })
-                    .or_else(|| {
-                        before.rfind("\n+++ ").map(|pos| {
-                            let line_start = pos + 5;
-                            before[line_start..]
-                                .find('\n')
-                                .map(|end| before[line_start..line_start + end].to_string())
-                                .unwrap_or_default()
-                        })
-                    });
-
-                findings.push(AnalysisFinding {
-                    category: FindingCategory::Secret,
-                    severity: Severity::Error,
-                    message: message.to_string(),
-                    file_path,
-                });
-            }
-        }
+    match crate::commands::secret_scan::SecretScanner::new(&Default::default()) {
+        Ok(scanner) => detect_secrets_with(&scanner, diff),
+        Err(_) => Vec::new(),
     }
+}
 
-    findings
+/// Detect secrets in a diff with a repository's scanner, so its committed
+/// allowlist applies here exactly as it does to the commit gate.
+fn detect_secrets_with(
+    scanner: &crate::commands::secret_scan::SecretScanner,
+    diff: &str,
+) -> Vec<AnalysisFinding> {
+    use crate::commands::secret_scan::SecretSeverity;
+
+    scanner
+        .scan_diff_text(diff)
+        .into_iter()
+        .map(|f| AnalysisFinding {
+            category: FindingCategory::Secret,
+            severity: match f.severity {
+                SecretSeverity::Error => Severity::Error,
+                SecretSeverity::Warning => Severity::Warning,
+            },
+            message: format!(
+                "{} detected (line {}): {}",
+                f.description, f.line, f.preview
+            ),
+            file_path: Some(f.file_path),
+        })
+        .collect()
 }
 
 /// Parse the AI vibe check response
@@ -1,12 +1,15 @@
 //! Archive command handlers
-//! Export repository snapshots as zip/tar archives
+//! Export repository snapshots as zip/tar archives, and release archives
+//! that include submodules and LFS content
 
+use std::collections::HashSet;
+use std::io::{Read, Write};
 use std::path::Path;
 use std::process::Command;
 use tauri::command;
 
 use crate::error::{LeviathanError, Result};
-use crate::utils::reject_flag_like;
+use crate::utils::{create_command, reject_flag_like};
 
 /// Map a user-supplied format string to the value understood by
 /// `git archive --format=<fmt>`. Returns an error for unsupported formats.
@@ -144,6 +147,796 @@ pub async fn get_archive_files(path: String, tree_ref: Option<String>) -> Result
     Ok(files)
 }
 
+// ============================================================================
+// Release archives
+// ============================================================================
+
+/// Guard against pathological submodule nesting
+const MAX_SUBMODULE_DEPTH: usize = 16;
+
+/// Output formats for release archives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReleaseFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+}
+
+impl ReleaseFormat {
+    fn parse(format: Option<&str>) -> Result<Self> {
+        match format.unwrap_or("tar.gz") {
+            "zip" => Ok(Self::Zip),
+            "tar" => Ok(Self::Tar),
+            "tar.gz" | "tgz" => Ok(Self::TarGz),
+            "tar.xz" | "txz" => Ok(Self::TarXz),
+            "tar.zst" | "tzst" => Ok(Self::TarZst),
+            other => Err(LeviathanError::OperationFailed(format!(
+                "Unsupported archive format: {}",
+                other
+            ))),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::Tar => "tar",
+            Self::TarGz => "tar.gz",
+            Self::TarXz => "tar.xz",
+            Self::TarZst => "tar.zst",
+        }
+    }
+}
+
+/// Options for a release archive
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseArchiveOptions {
+    pub output_path: String,
+    /// Commit or tree to archive; defaults to HEAD
+    #[serde(default)]
+    pub tree_ref: Option<String>,
+    /// `zip`, `tar`, `tar.gz` (default), `tar.xz` or `tar.zst`
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Directory every entry is nested under, e.g. `project-1.2.0`
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Files or directories to include, relative to the repository root;
+    /// everything when empty. Paths inside a submodule select within it.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Include initialized submodules, recursively (default true)
+    #[serde(default)]
+    pub include_submodules: Option<bool>,
+    /// Replace LFS pointers with their content from the local LFS cache
+    /// (default true)
+    #[serde(default)]
+    pub resolve_lfs: Option<bool>,
+    /// Keep the pointer for LFS objects that aren't cached locally instead
+    /// of failing
+    #[serde(default)]
+    pub allow_missing_lfs: Option<bool>,
+    /// Modification time (Unix seconds) for every entry, e.g.
+    /// `SOURCE_DATE_EPOCH`. Defaults to each repository's commit time.
+    #[serde(default)]
+    pub mtime: Option<i64>,
+}
+
+/// A submodule included in a release archive
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedSubmodule {
+    /// Path from the top-level repository
+    pub path: String,
+    pub commit: String,
+}
+
+/// A submodule left out of a release archive
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedSubmodule {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Result of creating a release archive
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseArchiveResult {
+    pub output_path: String,
+    pub format: String,
+    /// Files and symlinks written
+    pub entry_count: usize,
+    pub size_bytes: u64,
+    pub submodules: Vec<ArchivedSubmodule>,
+    pub skipped_submodules: Vec<SkippedSubmodule>,
+    /// LFS pointers replaced with their content
+    pub lfs_resolved: usize,
+    /// Paths whose LFS object wasn't in the local cache and kept the pointer
+    pub lfs_missing: Vec<String>,
+}
+
+/// The archive file being written, through whichever compressor the format
+/// needs. xz and zstd go through their command-line tools, as `git archive`
+/// does for its own `tar.<format>.command` filters.
+enum ArchiveOutput {
+    Plain(std::io::BufWriter<std::fs::File>),
+    Gzip(flate2::write::GzEncoder<std::io::BufWriter<std::fs::File>>),
+    Pipe {
+        tool: &'static str,
+        child: std::process::Child,
+        stdin: std::process::ChildStdin,
+    },
+}
+
+impl ArchiveOutput {
+    fn create(path: &Path, format: ReleaseFormat) -> Result<Self> {
+        let file = std::fs::File::create(path)?;
+        let (tool, args): (&'static str, &[&str]) = match format {
+            ReleaseFormat::Zip | ReleaseFormat::Tar => {
+                return Ok(Self::Plain(std::io::BufWriter::new(file)))
+            }
+            // The gzip header's mtime is left at zero, so equal input gives
+            // equal output.
+            ReleaseFormat::TarGz => {
+                return Ok(Self::Gzip(flate2::write::GzEncoder::new(
+                    std::io::BufWriter::new(file),
+                    flate2::Compression::default(),
+                )))
+            }
+            // A single thread keeps the compressed stream byte-identical
+            // across machines.
+            ReleaseFormat::TarXz => ("xz", &["-q", "-T1", "-c"]),
+            ReleaseFormat::TarZst => ("zstd", &["-q", "-T1", "-c"]),
+        };
+        let mut child = create_command(tool)
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(file)
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                LeviathanError::OperationFailed(format!(
+                    "{} archives need the `{}` tool on PATH: {}",
+                    format.name(),
+                    tool,
+                    e
+                ))
+            })?;
+        let stdin = child.stdin.take().ok_or_else(|| {
+            LeviathanError::OperationFailed(format!("Failed to open {} input", tool))
+        })?;
+        Ok(Self::Pipe { tool, child, stdin })
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::Plain(mut w) => w.flush()?,
+            Self::Gzip(w) => w.finish()?.flush()?,
+            Self::Pipe { tool, child, stdin } => {
+                drop(stdin);
+                let output = child.wait_with_output()?;
+                if !output.status.success() {
+                    return Err(LeviathanError::OperationFailed(format!(
+                        "{} failed: {}",
+                        tool,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Write for ArchiveOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(w) => w.write(buf),
+            Self::Gzip(w) => w.write(buf),
+            Self::Pipe { stdin, .. } => stdin.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::Plain(w) => w.flush(),
+            Self::Gzip(w) => w.flush(),
+            Self::Pipe { stdin, .. } => stdin.flush(),
+        }
+    }
+}
+
+/// Where archive entries are written: a tar stream or a zip file
+trait ArchiveSink {
+    fn set_commit_id(&mut self, commit: &str, mtime: u64) -> Result<()>;
+    fn add_dir(&mut self, path: &str, mode: u32, mtime: u64) -> Result<()>;
+    fn add_file(
+        &mut self,
+        path: &str,
+        mode: u32,
+        mtime: u64,
+        size: u64,
+        data: &mut dyn Read,
+    ) -> Result<()>;
+    fn add_symlink(&mut self, path: &str, target: &str, mtime: u64) -> Result<()>;
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+fn archive_error(e: impl std::fmt::Display) -> LeviathanError {
+    LeviathanError::OperationFailed(format!("Failed to write archive: {}", e))
+}
+
+struct TarSink {
+    builder: tar::Builder<ArchiveOutput>,
+}
+
+impl TarSink {
+    /// Headers as `git archive` writes them: owned by root, uid/gid 0
+    fn header(entry_type: tar::EntryType, mode: u32, mtime: u64, size: u64) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_mode(mode);
+        header.set_mtime(mtime);
+        header.set_size(size);
+        header.set_uid(0);
+        header.set_gid(0);
+        let _ = header.set_username("root");
+        let _ = header.set_groupname("root");
+        header
+    }
+}
+
+impl ArchiveSink for TarSink {
+    /// The pax global header `git get-tar-commit-id` reads
+    fn set_commit_id(&mut self, commit: &str, mtime: u64) -> Result<()> {
+        let record = format!(" comment={}\n", commit);
+        // The length prefix counts its own digits.
+        let mut len = record.len() + 1;
+        while len != record.len() + len.to_string().len() {
+            len = record.len() + len.to_string().len();
+        }
+        let data = format!("{}{}", len, record);
+        let mut header = tar::Header::new_ustar();
+        header.set_entry_type(tar::EntryType::XGlobalHeader);
+        header
+            .set_path("pax_global_header")
+            .map_err(archive_error)?;
+        header.set_mode(0o666);
+        header.set_mtime(mtime);
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        self.builder
+            .append(&header, data.as_bytes())
+            .map_err(archive_error)
+    }
+
+    fn add_dir(&mut self, path: &str, mode: u32, mtime: u64) -> Result<()> {
+        let mut header = Self::header(tar::EntryType::Directory, mode, mtime, 0);
+        self.builder
+            .append_data(&mut header, path, std::io::empty())
+            .map_err(archive_error)
+    }
+
+    fn add_file(
+        &mut self,
+        path: &str,
+        mode: u32,
+        mtime: u64,
+        size: u64,
+        data: &mut dyn Read,
+    ) -> Result<()> {
+        let mut header = Self::header(tar::EntryType::Regular, mode, mtime, size);
+        self.builder
+            .append_data(&mut header, path, data)
+            .map_err(archive_error)
+    }
+
+    fn add_symlink(&mut self, path: &str, target: &str, mtime: u64) -> Result<()> {
+        let mut header = Self::header(tar::EntryType::Symlink, 0o777, mtime, 0);
+        self.builder
+            .append_link(&mut header, path, target)
+            .map_err(archive_error)
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.builder.into_inner().map_err(archive_error)?.finish()
+    }
+}
+
+struct ZipSink {
+    writer: zip::ZipWriter<std::io::BufWriter<std::fs::File>>,
+}
+
+impl ZipSink {
+    /// Zip stores local DOS time; UTC keeps the bytes independent of the
+    /// machine's timezone. Times before 1980 can't be represented.
+    fn options(mode: u32, mtime: u64) -> zip::write::SimpleFileOptions {
+        use chrono::{Datelike, Timelike};
+        let time = chrono::DateTime::from_timestamp(mtime as i64, 0)
+            .and_then(|t| {
+                zip::DateTime::from_date_and_time(
+                    u16::try_from(t.year()).ok()?,
+                    t.month() as u8,
+                    t.day() as u8,
+                    t.hour() as u8,
+                    t.minute() as u8,
+                    t.second() as u8,
+                )
+                .ok()
+            })
+            .unwrap_or_default();
+        zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .unix_permissions(mode)
+            .last_modified_time(time)
+    }
+}
+
+impl ArchiveSink for ZipSink {
+    /// The archive comment, as `git archive --format=zip` writes it
+    fn set_commit_id(&mut self, commit: &str, _mtime: u64) -> Result<()> {
+        self.writer
+            .set_comment(commit.to_string())
+            .map_err(archive_error)
+    }
+
+    fn add_dir(&mut self, path: &str, mode: u32, mtime: u64) -> Result<()> {
+        self.writer
+            .add_directory(path, Self::options(mode, mtime))
+            .map_err(archive_error)
+    }
+
+    fn add_file(
+        &mut self,
+        path: &str,
+        mode: u32,
+        mtime: u64,
+        size: u64,
+        data: &mut dyn Read,
+    ) -> Result<()> {
+        let options = Self::options(mode, mtime).large_file(size >= u32::MAX as u64);
+        self.writer
+            .start_file(path, options)
+            .map_err(archive_error)?;
+        std::io::copy(data, &mut self.writer)?;
+        Ok(())
+    }
+
+    fn add_symlink(&mut self, path: &str, target: &str, mtime: u64) -> Result<()> {
+        self.writer
+            .add_symlink(path, target, Self::options(0o777, mtime))
+            .map_err(archive_error)
+    }
+
+    fn finish(self: Box<Self>) -> Result<()> {
+        self.writer.finish().map_err(archive_error)?.flush()?;
+        Ok(())
+    }
+}
+
+/// State shared across the top-level repository and its submodules
+struct ReleaseArchiveRun<'a> {
+    sink: Box<dyn ArchiveSink>,
+    options: &'a ReleaseArchiveOptions,
+    /// Directory entries already written; `git archive` of a submodule
+    /// repeats the directories leading to it
+    dirs: HashSet<String>,
+    result: ReleaseArchiveResult,
+}
+
+/// Whether `path` is `dir` or lies below it
+fn path_within(path: &str, dir: &str) -> bool {
+    path == dir
+        || path
+            .strip_prefix(dir)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Gitlink (submodule) entries of a tree, with their commits
+fn tree_gitlinks(tree: &git2::Tree) -> Vec<(String, git2::Oid)> {
+    let mut links = Vec::new();
+    let _ = tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(git2::ObjectType::Commit) {
+            if let Ok(name) = entry.name() {
+                links.push((format!("{}{}", root, name), entry.id()));
+            }
+        }
+        git2::TreeWalkResult::Ok
+    });
+    links
+}
+
+impl ReleaseArchiveRun<'_> {
+    /// Archive `treeish` of one repository under `prefix` (empty or ending
+    /// in `/`), then recurse into its submodules.
+    fn append_repository(
+        &mut self,
+        repo_path: &Path,
+        treeish: &str,
+        prefix: &str,
+        logical_prefix: &str,
+        filters: &[String],
+        depth: usize,
+    ) -> Result<()> {
+        let repo = git2::Repository::open(repo_path)?;
+        let object = repo.revparse_single(treeish)?;
+        let tree = object.peel_to_tree().map_err(|_| {
+            LeviathanError::OperationFailed(format!("Cannot resolve '{}' to a tree", treeish))
+        })?;
+        let mtime_default = object
+            .peel_to_commit()
+            .map(|c| c.time().seconds().max(0) as u64)
+            .unwrap_or(0);
+        let mtime = self
+            .options
+            .mtime
+            .map(|t| t.max(0) as u64)
+            .unwrap_or(mtime_default);
+
+        let gitlinks = if self.options.include_submodules.unwrap_or(true) {
+            tree_gitlinks(&tree)
+        } else {
+            Vec::new()
+        };
+        // Filters inside a submodule can't go to this repository's `git
+        // archive` (they match nothing here); they select within the
+        // submodule instead.
+        let inside_submodule = |filter: &str| {
+            gitlinks
+                .iter()
+                .any(|(link, _)| filter.len() > link.len() && path_within(filter, link))
+        };
+        let own_filters: Vec<&String> = filters.iter().filter(|f| !inside_submodule(f)).collect();
+
+        let mut emitted = HashSet::new();
+        if filters.is_empty() || !own_filters.is_empty() {
+            emitted =
+                self.copy_git_archive(&repo, repo_path, treeish, prefix, &own_filters, mtime)?;
+        }
+
+        for (link, commit) in gitlinks {
+            let full_path = format!("{}{}", logical_prefix, link);
+            // `git archive` writes an empty directory for each submodule it
+            // includes, having applied export-ignore and the path filters.
+            let whole = emitted.contains(&format!("{}{}", prefix, link));
+            let sub_filters: Vec<String> = filters
+                .iter()
+                .filter(|f| f.len() > link.len() && path_within(f, &link))
+                .map(|f| f[link.len() + 1..].to_string())
+                .collect();
+            if !whole && sub_filters.is_empty() {
+                continue;
+            }
+            let skip = |reason: String| SkippedSubmodule {
+                path: full_path.clone(),
+                reason,
+            };
+            if depth >= MAX_SUBMODULE_DEPTH {
+                self.result
+                    .skipped_submodules
+                    .push(skip("Submodules nested too deeply".to_string()));
+                continue;
+            }
+            let Some(sub_path) = repo.workdir().map(|w| w.join(&link)) else {
+                continue;
+            };
+            let sub_repo = match git2::Repository::open(&sub_path) {
+                Ok(r) => r,
+                Err(_) => {
+                    self.result
+                        .skipped_submodules
+                        .push(skip("Not initialized".to_string()));
+                    continue;
+                }
+            };
+            if sub_repo.find_commit(commit).is_err() {
+                self.result.skipped_submodules.push(skip(format!(
+                    "Commit {} is not in the submodule; update it first",
+                    &commit.to_string()[..7]
+                )));
+                continue;
+            }
+            drop(sub_repo);
+            self.result.submodules.push(ArchivedSubmodule {
+                path: full_path.clone(),
+                commit: commit.to_string(),
+            });
+            self.append_repository(
+                &sub_path,
+                &commit.to_string(),
+                &format!("{}{}/", prefix, link),
+                &format!("{}/", full_path),
+                if whole { &[] } else { &sub_filters },
+                depth + 1,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Stream one `git archive` tar into the sink, resolving LFS pointers
+    /// on the way. Returns the directory entries it contained.
+    fn copy_git_archive(
+        &mut self,
+        repo: &git2::Repository,
+        repo_path: &Path,
+        treeish: &str,
+        prefix: &str,
+        filters: &[&String],
+        mtime: u64,
+    ) -> Result<HashSet<String>> {
+        let mut cmd = create_command("git");
+        cmd.current_dir(repo_path)
+            // Filters are paths, not glob patterns.
+            .env("GIT_LITERAL_PATHSPECS", "1")
+            .arg("archive")
+            .arg("--format=tar");
+        if !prefix.is_empty() {
+            cmd.arg(format!("--prefix={}", prefix));
+        }
+        cmd.arg("--").arg(treeish).args(filters);
+        let mut child = cmd
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                LeviathanError::OperationFailed(format!("Failed to execute git archive: {}", e))
+            })?;
+        let stdout = child.stdout.take().ok_or_else(|| {
+            LeviathanError::OperationFailed("Failed to read git archive output".to_string())
+        })?;
+
+        // A failed copy (the sink or the compressor gave out) is the error
+        // to report; git was killed for it and has nothing useful to say.
+        let copied = match self.copy_tar_entries(repo, stdout, mtime) {
+            Ok(copied) => copied,
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+        };
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(LeviathanError::OperationFailed(format!(
+                "git archive failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(copied)
+    }
+
+    fn copy_tar_entries(
+        &mut self,
+        repo: &git2::Repository,
+        stream: impl Read,
+        mtime: u64,
+    ) -> Result<HashSet<String>> {
+        let resolve_lfs = self.options.resolve_lfs.unwrap_or(true);
+        let lfs_objects = crate::commands::lfs_analysis::lfs_storage_dir(repo).join("objects");
+        let read_error = |e: std::io::Error| {
+            LeviathanError::OperationFailed(format!("Failed to read archive: {}", e))
+        };
+        let mut emitted = HashSet::new();
+        let mut archive = tar::Archive::new(stream);
+        for entry in archive.entries().map_err(read_error)? {
+            let mut entry = entry.map_err(read_error)?;
+            let path = entry
+                .path()
+                .map_err(read_error)?
+                .to_string_lossy()
+                .to_string();
+            let header = entry.header().clone();
+            let mode = header.mode().unwrap_or(0o644);
+            match header.entry_type() {
+                tar::EntryType::Directory => {
+                    let dir = path.trim_end_matches('/').to_string();
+                    if self.dirs.insert(dir.clone()) {
+                        self.sink.add_dir(&format!("{}/", dir), mode, mtime)?;
+                    }
+                    emitted.insert(dir);
+                }
+                tar::EntryType::Symlink => {
+                    let target = entry
+                        .link_name()
+                        .map_err(read_error)?
+                        .map(|t| t.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    self.sink.add_symlink(&path, &target, mtime)?;
+                    self.result.entry_count += 1;
+                }
+                tar::EntryType::Regular => {
+                    let size = entry.size();
+                    if resolve_lfs && size <= crate::commands::lfs_analysis::MAX_POINTER_SIZE {
+                        let mut data = Vec::with_capacity(size as usize);
+                        entry.read_to_end(&mut data)?;
+                        self.add_small_file(&path, mode, mtime, data, &lfs_objects)?;
+                    } else {
+                        self.sink.add_file(&path, mode, mtime, size, &mut entry)?;
+                    }
+                    self.result.entry_count += 1;
+                }
+                // git's pax global header carries the commit id, which is
+                // written once for the top-level repository instead.
+                _ => {}
+            }
+        }
+        Ok(emitted)
+    }
+
+    /// Write a small file, swapping an LFS pointer for the object it names
+    fn add_small_file(
+        &mut self,
+        path: &str,
+        mode: u32,
+        mtime: u64,
+        data: Vec<u8>,
+        lfs_objects: &Path,
+    ) -> Result<()> {
+        if let Some(pointer) = crate::commands::lfs_analysis::parse_lfs_pointer(&data) {
+            let object = lfs_objects
+                .join(&pointer.oid[0..2])
+                .join(&pointer.oid[2..4])
+                .join(&pointer.oid);
+            let cached = std::fs::metadata(&object)
+                .map(|m| m.len() == pointer.size)
+                .unwrap_or(false);
+            if cached {
+                let mut file = std::fs::File::open(&object)?;
+                self.sink
+                    .add_file(path, mode, mtime, pointer.size, &mut file)?;
+                self.result.lfs_resolved += 1;
+                return Ok(());
+            }
+            self.result.lfs_missing.push(path.to_string());
+        }
+        self.sink
+            .add_file(path, mode, mtime, data.len() as u64, &mut data.as_slice())
+    }
+}
+
+/// Write the whole archive: commit id, the top-level repository and its
+/// submodules
+fn write_release_entries(
+    run: &mut ReleaseArchiveRun,
+    commit: Option<&git2::Commit>,
+    repo_path: &Path,
+    treeish: &str,
+    prefix: &str,
+    filters: &[String],
+) -> Result<()> {
+    if let Some(commit) = commit {
+        let mtime = run
+            .options
+            .mtime
+            .unwrap_or_else(|| commit.time().seconds())
+            .max(0) as u64;
+        run.sink.set_commit_id(&commit.id().to_string(), mtime)?;
+    }
+    run.append_repository(repo_path, treeish, prefix, "", filters, 0)?;
+    if !run.result.lfs_missing.is_empty() && !run.options.allow_missing_lfs.unwrap_or(false) {
+        return Err(LeviathanError::OperationFailed(format!(
+            "LFS objects are not in the local cache for: {}; fetch them with `git lfs fetch` or allow missing objects",
+            run.result.lfs_missing.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn build_release_archive(
+    path: &str,
+    options: &ReleaseArchiveOptions,
+) -> Result<ReleaseArchiveResult> {
+    let format = ReleaseFormat::parse(options.format.as_deref())?;
+    let repo_path = Path::new(path);
+    let repo = git2::Repository::open(repo_path)?;
+    let ref_str = options.tree_ref.as_deref().unwrap_or("HEAD");
+    reject_flag_like(ref_str, "Reference")?;
+    reject_flag_like(&options.output_path, "Output path")?;
+    let object = repo.revparse_single(ref_str)?;
+    object.peel_to_tree().map_err(|_| {
+        LeviathanError::OperationFailed(format!("Cannot resolve '{}' to a tree", ref_str))
+    })?;
+    let commit = object.peel_to_commit().ok();
+    // Submodules are archived at their own commits, so the top level is
+    // pinned to an id too rather than a ref that could move meanwhile.
+    let treeish = object.id().to_string();
+
+    let prefix = options
+        .prefix
+        .as_deref()
+        .map(|p| p.trim_matches('/'))
+        .unwrap_or("");
+    if !prefix.is_empty() {
+        reject_flag_like(prefix, "Prefix")?;
+    }
+    let prefix = if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", prefix)
+    };
+    let filters: Vec<String> = options
+        .paths
+        .iter()
+        .map(|p| p.trim().trim_matches('/').to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    for filter in &filters {
+        reject_flag_like(filter, "Path")?;
+    }
+
+    // Written beside the destination and moved into place at the end, so a
+    // failure never leaves a truncated archive under the requested name.
+    let output_path = Path::new(&options.output_path);
+    let partial = output_path.with_file_name(format!(
+        ".{}.partial",
+        output_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "archive".to_string())
+    ));
+    // Zip needs to seek back to its headers, so it's never piped through a
+    // compressor and writes the file directly.
+    let sink: Box<dyn ArchiveSink> = match format {
+        ReleaseFormat::Zip => Box::new(ZipSink {
+            writer: zip::ZipWriter::new(std::io::BufWriter::new(std::fs::File::create(&partial)?)),
+        }),
+        _ => Box::new(TarSink {
+            builder: tar::Builder::new(ArchiveOutput::create(&partial, format)?),
+        }),
+    };
+
+    let mut run = ReleaseArchiveRun {
+        sink,
+        options,
+        dirs: HashSet::new(),
+        result: ReleaseArchiveResult {
+            output_path: options.output_path.clone(),
+            format: format.name().to_string(),
+            ..Default::default()
+        },
+    };
+    let written = write_release_entries(
+        &mut run,
+        commit.as_ref(),
+        repo_path,
+        &treeish,
+        &prefix,
+        &filters,
+    );
+    let finished = written.and_then(|_| run.sink.finish());
+    if let Err(e) = finished {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e);
+    }
+    std::fs::rename(&partial, output_path)?;
+
+    let mut result = run.result;
+    result.size_bytes = std::fs::metadata(output_path)?.len();
+    Ok(result)
+}
+
+/// Create a release archive: submodules included recursively, LFS pointers
+/// replaced by their content, optionally limited to some paths under a
+/// prefix, in zip or tar (plain, gzip, xz or zstd).
+///
+/// Each repository's content still comes from `git archive`, so
+/// `export-ignore` and `export-subst` apply inside submodules too. Output
+/// is reproducible: entry times are the commit time (or `mtime`), and the
+/// compressors run without timestamps or thread-dependent output.
+#[command]
+pub async fn create_release_archive(
+    path: String,
+    options: ReleaseArchiveOptions,
+) -> Result<ReleaseArchiveResult> {
+    tokio::task::spawn_blocking(move || build_release_archive(&path, &options))
+        .await
+        .map_err(|e| LeviathanError::OperationFailed(format!("Archive task failed: {}", e)))?
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -471,4 +1264,246 @@ mod tests {
         );
         assert!(files.iter().any(|f| f == "public.txt"));
     }
+
+    /// Entries of a release archive as (path, content), decompressing
+    /// through the matching tool
+    fn release_entries(path: &Path) -> Vec<(String, Vec<u8>)> {
+        let name = path.to_string_lossy().to_string();
+        let raw = if name.ends_with(".tar") {
+            std::fs::read(path).unwrap()
+        } else {
+            let tool = if name.ends_with(".xz") {
+                "xz"
+            } else if name.ends_with(".zst") {
+                "zstd"
+            } else {
+                "gzip"
+            };
+            let output = Command::new(tool).arg("-dc").arg(path).output().unwrap();
+            assert!(output.status.success(), "{} -dc failed", tool);
+            output.stdout
+        };
+        let mut archive = tar::Archive::new(raw.as_slice());
+        let mut out = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let p = entry.path().unwrap().to_string_lossy().to_string();
+            let mut data = Vec::new();
+            entry.read_to_end(&mut data).unwrap();
+            out.push((p, data));
+        }
+        out
+    }
+
+    fn tool_available(tool: &str) -> bool {
+        Command::new(tool).arg("--version").output().is_ok()
+    }
+
+    fn release_options(output: &Path, format: &str) -> ReleaseArchiveOptions {
+        ReleaseArchiveOptions {
+            output_path: output.to_string_lossy().to_string(),
+            format: Some(format.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_release_archive_filters_paths_under_prefix() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_commit(
+            "Add files",
+            &[
+                (".gitattributes", "VERSION export-subst\n"),
+                ("VERSION", "$Format:%H$\n"),
+                ("src/lib.rs", "pub fn f() {}\n"),
+                ("docs/guide.md", "# Guide\n"),
+            ],
+        );
+        let head = repo.head_oid().to_string();
+
+        for format in ["tar.gz", "tar.xz", "tar.zst"] {
+            let tool = format.rsplit('.').next().unwrap();
+            if format != "tar.gz" && !tool_available(if tool == "zst" { "zstd" } else { tool }) {
+                continue;
+            }
+            let output = repo.path.join(format!("release.{}", format));
+            let mut options = release_options(&output, format);
+            options.prefix = Some("project-1.0/".to_string());
+            options.paths = vec!["src".to_string(), "VERSION".to_string()];
+            let result = create_release_archive(repo.path_str(), options)
+                .await
+                .unwrap();
+            assert_eq!(result.entry_count, 2);
+
+            let entries = release_entries(&output);
+            let files: Vec<&str> = entries
+                .iter()
+                .filter(|(p, _)| !p.ends_with('/'))
+                .map(|(p, _)| p.as_str())
+                .collect();
+            assert_eq!(
+                files,
+                vec![
+                    "pax_global_header",
+                    "project-1.0/VERSION",
+                    "project-1.0/src/lib.rs"
+                ],
+                "{}",
+                format
+            );
+            let version = &entries
+                .iter()
+                .find(|(p, _)| p == "project-1.0/VERSION")
+                .unwrap()
+                .1;
+            assert_eq!(String::from_utf8_lossy(version).trim(), head);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_release_archive_includes_submodules() {
+        let inner = TestRepo::with_initial_commit();
+        inner.create_commit("Add lib", &[("lib/code.c", "int x;\n")]);
+        let outer = TestRepo::with_initial_commit();
+        let git = |args: &[&str]| {
+            let output = crate::utils::create_command("git")
+                .current_dir(&outer.path)
+                .args(["-c", "protocol.file.allow=always"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "{:?}", output);
+        };
+        git(&["submodule", "add", "-q", &inner.path_str(), "vendor/inner"]);
+        // A gitlink with no checkout behind it
+        git(&[
+            "update-index",
+            "--add",
+            "--cacheinfo",
+            &format!("160000,{},missing", inner.head_oid()),
+        ]);
+        git(&["commit", "-q", "-m", "Add submodules"]);
+
+        let output = outer.path.join("release.tar");
+        let mut options = release_options(&output, "tar");
+        options.prefix = Some("rel".to_string());
+        let result = create_release_archive(outer.path_str(), options)
+            .await
+            .unwrap();
+
+        assert_eq!(result.submodules.len(), 1);
+        assert_eq!(result.submodules[0].path, "vendor/inner");
+        assert_eq!(result.skipped_submodules.len(), 1);
+        assert_eq!(result.skipped_submodules[0].path, "missing");
+        let entries = release_entries(&output);
+        let names: Vec<&str> = entries.iter().map(|(p, _)| p.as_str()).collect();
+        assert!(
+            names.contains(&"rel/vendor/inner/lib/code.c"),
+            "{:?}",
+            names
+        );
+        assert!(names.contains(&"rel/README.md"));
+        assert_eq!(
+            names.iter().filter(|n| **n == "rel/vendor/inner/").count(),
+            1,
+            "directory written once: {:?}",
+            names
+        );
+
+        // A path inside the submodule selects within it.
+        let output = outer.path.join("only-lib.tar");
+        let mut options = release_options(&output, "tar");
+        options.paths = vec!["vendor/inner/lib".to_string()];
+        create_release_archive(outer.path_str(), options)
+            .await
+            .unwrap();
+        let files: Vec<String> = release_entries(&output)
+            .into_iter()
+            .map(|(p, _)| p)
+            .filter(|p| !p.ends_with('/') && p != "pax_global_header")
+            .collect();
+        assert_eq!(files, vec!["vendor/inner/lib/code.c"]);
+    }
+
+    #[tokio::test]
+    async fn test_release_archive_resolves_lfs_pointers() {
+        let repo = TestRepo::with_initial_commit();
+        let content = b"real binary content";
+        let oid = {
+            use sha2::Digest;
+            sha2::Sha256::digest(content)
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        };
+        let pointer = format!(
+            "version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize {}\n",
+            oid,
+            content.len()
+        );
+        repo.create_commit("Add asset", &[("asset.bin", &pointer)]);
+
+        let output = repo.path.join("missing.zip");
+        let result = create_release_archive(repo.path_str(), release_options(&output, "zip")).await;
+        assert!(result.unwrap_err().to_string().contains("asset.bin"));
+        assert!(!output.exists(), "no partial archive left behind");
+
+        let mut options = release_options(&output, "zip");
+        options.allow_missing_lfs = Some(true);
+        let result = create_release_archive(repo.path_str(), options)
+            .await
+            .unwrap();
+        assert_eq!(result.lfs_missing, vec!["asset.bin"]);
+
+        let object_dir = repo
+            .path
+            .join(".git/lfs/objects")
+            .join(&oid[0..2])
+            .join(&oid[2..4]);
+        std::fs::create_dir_all(&object_dir).unwrap();
+        std::fs::write(object_dir.join(&oid), content).unwrap();
+        let output = repo.path.join("resolved.zip");
+        let result = create_release_archive(repo.path_str(), release_options(&output, "zip"))
+            .await
+            .unwrap();
+        assert_eq!(result.lfs_resolved, 1);
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&output).unwrap()).unwrap();
+        let mut data = Vec::new();
+        zip.by_name("asset.bin")
+            .unwrap()
+            .read_to_end(&mut data)
+            .unwrap();
+        assert_eq!(data, content);
+        assert_eq!(zip.comment(), repo.head_oid().to_string().as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_release_archive_is_reproducible() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_commit("Add file", &[("file.txt", "content\n")]);
+
+        let mut archives = Vec::new();
+        for name in ["a.tar.gz", "b.tar.gz"] {
+            let output = repo.path.join(name);
+            let mut options = release_options(&output, "tar.gz");
+            options.mtime = Some(1_700_000_000);
+            create_release_archive(repo.path_str(), options)
+                .await
+                .unwrap();
+            archives.push(std::fs::read(&output).unwrap());
+        }
+        assert_eq!(archives[0], archives[1]);
+
+        let raw = Command::new("gzip")
+            .arg("-dc")
+            .arg(repo.path.join("a.tar.gz"))
+            .output()
+            .unwrap()
+            .stdout;
+        let mut archive = tar::Archive::new(raw.as_slice());
+        for entry in archive.entries().unwrap() {
+            assert_eq!(entry.unwrap().header().mtime().unwrap(), 1_700_000_000);
+        }
+    }
 }
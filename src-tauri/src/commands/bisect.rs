@@ -1,10 +1,16 @@
 //! Bisect command handlers
 //! Binary search through commits to find bug-introducing changes
+//!
+//! Steps are marked by hand (good/bad/skip) or automatically by `bisect run`,
+//! which tests each candidate with a command in the background. A session's
+//! log can be exported and replayed to share or resume it.
 
 use std::path::{Path, PathBuf};
-use tauri::command;
+use tauri::{command, AppHandle, Emitter, Manager, State};
 
+use crate::commands::custom_actions::{execute_shell, ActionStream};
 use crate::error::{LeviathanError, Result};
+use crate::services::cancellation::{CancellationRegistry, CancellationToken};
 use crate::utils::create_command;
 
 /// Current state of a bisect session
@@ -586,6 +592,333 @@ fn parse_culprit_from_output(output: &str) -> Option<CulpritCommit> {
     })
 }
 
+/// Export the session's log in `git bisect log` format
+///
+/// The text is what `git bisect replay` consumes, so it can be saved, sent to a
+/// colleague, and fed back through [`replay_bisect_log`] on another clone.
+#[command]
+pub async fn export_bisect_log(path: String) -> Result<String> {
+    let repo_path = Path::new(&path);
+    if !is_bisect_active(repo_path) {
+        return Err(LeviathanError::OperationFailed(
+            "No bisect session in progress".to_string(),
+        ));
+    }
+    let log = run_git_command(repo_path, &["bisect", "log"])?;
+    Ok(format!("{}\n", log))
+}
+
+/// Replay a log produced by [`export_bisect_log`] (or `git bisect log`)
+///
+/// Any session in progress is replaced. git applies the log step by step, so
+/// a log whose last step identified the culprit reports it again here.
+#[command]
+pub async fn replay_bisect_log(path: String, log: String) -> Result<BisectStepResult> {
+    let repo_path = Path::new(&path);
+    if !log
+        .lines()
+        .any(|l| l.trim_start().starts_with("git bisect start"))
+    {
+        return Err(LeviathanError::OperationFailed(
+            "Not a bisect log: it has no 'git bisect start' line".to_string(),
+        ));
+    }
+
+    let mut file = tempfile::NamedTempFile::new()?;
+    std::io::Write::write_all(&mut file, log.as_bytes())?;
+    let file_path = file.path().to_string_lossy().to_string();
+
+    // A log that names a commit this clone lacks fails halfway, after git has
+    // already started a session from its first lines. Rolled back for the
+    // same reason bisect_start rolls back a bogus ref.
+    let output = match run_bisect_step(repo_path, &["bisect", "replay", &file_path]) {
+        Ok(output) => output,
+        Err(e) => {
+            let _ = run_git_command(repo_path, &["bisect", "reset"]);
+            return Err(e);
+        }
+    };
+
+    let culprit = if announces_culprit(&output) {
+        parse_culprit_from_output(&output)
+    } else {
+        None
+    };
+    let status = get_bisect_status(path.clone()).await?;
+
+    Ok(BisectStepResult {
+        status,
+        culprit,
+        message: if output.is_empty() {
+            "Bisect log replayed".to_string()
+        } else {
+            output
+        },
+    })
+}
+
+/// Exit code with which a `bisect run` command says "this commit cannot be
+/// tested", the same convention `git bisect run` uses.
+const BISECT_RUN_SKIP_CODE: i32 = 125;
+
+/// How the run command's exit code marked a commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BisectRunVerdict {
+    Good,
+    Bad,
+    Skip,
+}
+
+/// One tested commit of a `bisect run`, emitted as `bisect-run-step`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectRunStep {
+    pub run_id: String,
+    /// 1-based position in this run
+    pub step: u32,
+    pub commit_oid: String,
+    pub summary: String,
+    pub exit_code: Option<i32>,
+    pub verdict: BisectRunVerdict,
+    /// git's response to marking the commit
+    pub message: String,
+}
+
+/// Payload of the `bisect-run-output` event
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectRunOutputEvent {
+    pub run_id: String,
+    /// The commit the command is testing
+    pub commit_oid: String,
+    pub stream: ActionStream,
+    /// One line of output, without its trailing newline
+    pub line: String,
+}
+
+/// How a `bisect run` ended, emitted as `bisect-run-finished`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectRunResult {
+    pub run_id: String,
+    pub steps: Vec<BisectRunStep>,
+    pub culprit: Option<CulpritCommit>,
+    /// Only skipped commits were left, so git could not name a single culprit
+    pub inconclusive: bool,
+    pub cancelled: bool,
+    /// Why the run stopped early, when it did not end with a result
+    pub error: Option<String>,
+    /// git's final message, or the error
+    pub message: String,
+}
+
+/// Map a run command's exit code to a verdict the way `git bisect run` does:
+/// 0 is good, 125 is skip, anything else up to 127 is bad. 128 and above (and
+/// death by signal) mean the command itself broke, so the run must stop
+/// rather than blame the commit.
+fn verdict_for_exit(code: Option<i32>) -> Option<BisectRunVerdict> {
+    match code? {
+        0 => Some(BisectRunVerdict::Good),
+        BISECT_RUN_SKIP_CODE => Some(BisectRunVerdict::Skip),
+        1..=127 => Some(BisectRunVerdict::Bad),
+        _ => None,
+    }
+}
+
+/// Drive the session to its end by testing each candidate with `command`.
+///
+/// Runs synchronously; [`start_bisect_run`] puts it on a blocking thread and
+/// turns the callbacks into events. Never fails outright: whatever stops the
+/// run is reported in the result alongside the steps already marked, which
+/// stay marked in the session so it can be continued by hand.
+fn execute_bisect_run(
+    repo_path: &Path,
+    run_id: &str,
+    command: &str,
+    token: Option<&CancellationToken>,
+    on_line: &(dyn Fn(&str, ActionStream, &str) + Sync),
+    on_step: &dyn Fn(&BisectRunStep),
+) -> BisectRunResult {
+    let (term_bad, term_good) = session_terms(repo_path);
+    let mut result = BisectRunResult {
+        run_id: run_id.to_string(),
+        steps: Vec::new(),
+        culprit: None,
+        inconclusive: false,
+        cancelled: false,
+        error: None,
+        message: String::new(),
+    };
+    let fail = |result: &mut BisectRunResult, message: String| {
+        result.error = Some(message.clone());
+        result.message = message;
+    };
+
+    loop {
+        if token.is_some_and(|t| t.is_cancelled()) {
+            result.cancelled = true;
+            result.message = "Bisect run cancelled".to_string();
+            break;
+        }
+
+        let head = match run_git_command(repo_path, &["rev-parse", "HEAD"]) {
+            Ok(head) => head,
+            Err(e) => {
+                fail(&mut result, e.to_string());
+                break;
+            }
+        };
+        let summary =
+            run_git_command(repo_path, &["log", "-1", "--format=%s", &head]).unwrap_or_default();
+
+        let forward = |stream: ActionStream, line: &str| on_line(&head, stream, line);
+        let outcome = match execute_shell(command, repo_path, token, &forward) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                fail(&mut result, e.to_string());
+                break;
+            }
+        };
+        if outcome.cancelled {
+            result.cancelled = true;
+            result.message = "Bisect run cancelled".to_string();
+            break;
+        }
+
+        let Some(verdict) = verdict_for_exit(outcome.exit_code) else {
+            let message = match outcome.exit_code {
+                Some(code) => format!(
+                    "The command exited with {} on {}; bisect run stopped",
+                    code,
+                    &head[..head.len().min(7)]
+                ),
+                None => format!(
+                    "The command was killed on {}; bisect run stopped",
+                    &head[..head.len().min(7)]
+                ),
+            };
+            fail(&mut result, message);
+            break;
+        };
+        let term = match verdict {
+            BisectRunVerdict::Good => term_good.as_str(),
+            BisectRunVerdict::Bad => term_bad.as_str(),
+            BisectRunVerdict::Skip => "skip",
+        };
+        let output = match run_bisect_step(repo_path, &["bisect", term, &head]) {
+            Ok(output) => output,
+            Err(e) => {
+                fail(&mut result, e.to_string());
+                break;
+            }
+        };
+
+        let step = BisectRunStep {
+            run_id: run_id.to_string(),
+            step: result.steps.len() as u32 + 1,
+            commit_oid: head,
+            summary,
+            exit_code: outcome.exit_code,
+            verdict,
+            message: output.clone(),
+        };
+        on_step(&step);
+        result.steps.push(step);
+
+        if announces_culprit(&output) {
+            result.culprit = parse_culprit_from_output(&output);
+            result.message = output;
+            break;
+        }
+        if output.contains("We cannot bisect more") {
+            result.inconclusive = true;
+            result.message = output;
+            break;
+        }
+    }
+
+    result
+}
+
+/// Automate the session: test each candidate with `command` until the culprit
+/// is found
+///
+/// Like `git bisect run`, the command runs through the shell in the repository
+/// root with the candidate checked out; exit 0 marks it good, 125 skips it,
+/// 1-127 marks it bad, and anything else stops the run. Returns the run id
+/// immediately. Command output streams as `bisect-run-output` events, each
+/// marked commit as `bisect-run-step`, and the outcome as
+/// `bisect-run-finished`. `cancel_operation(runId)` stops the run, killing the
+/// command's process group; commits already marked stay marked.
+#[command]
+pub async fn start_bisect_run(
+    app: AppHandle,
+    registry: State<'_, CancellationRegistry>,
+    path: String,
+    command: String,
+) -> Result<String> {
+    if command.trim().is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "A command to test each commit is required".to_string(),
+        ));
+    }
+    let status = get_bisect_status(path.clone()).await?;
+    if !status.active {
+        return Err(LeviathanError::OperationFailed(
+            "No bisect session in progress".to_string(),
+        ));
+    }
+    // Without both ends git is still waiting for input rather than offering
+    // candidates, and HEAD would be tested as if git had picked it.
+    if status.bad_commit.is_none() || status.good_commit.is_none() {
+        return Err(LeviathanError::OperationFailed(
+            "Mark a good and a bad commit before starting bisect run".to_string(),
+        ));
+    }
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let run_id = format!("bisect-run-{}", nanos);
+    let token = registry.register(run_id.clone());
+
+    tracing::info!(run_id = %run_id, command = %command, "Starting bisect run");
+
+    let thread_run_id = run_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let run_id = thread_run_id;
+        let emit_line = |commit: &str, stream: ActionStream, line: &str| {
+            let _ = app.emit(
+                "bisect-run-output",
+                BisectRunOutputEvent {
+                    run_id: run_id.clone(),
+                    commit_oid: commit.to_string(),
+                    stream,
+                    line: line.to_string(),
+                },
+            );
+        };
+        let emit_step = |step: &BisectRunStep| {
+            let _ = app.emit("bisect-run-step", step.clone());
+        };
+
+        let result = execute_bisect_run(
+            Path::new(&path),
+            &run_id,
+            &command,
+            Some(&token),
+            &emit_line,
+            &emit_step,
+        );
+        app.state::<CancellationRegistry>().remove(&run_id);
+        let _ = app.emit("bisect-run-finished", result);
+    });
+
+    Ok(run_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1188,4 +1521,187 @@ Date:   Mon Jan 1 12:00:00 2024 +0000
 
         let _ = run_git_command(&repo.path, &["bisect", "reset"]);
     }
+
+    /// History where `bug.txt` arrives in the third of six commits, started
+    /// as a session with HEAD bad and the first commit good.
+    fn bisect_fixture() -> (TestRepo, git2::Oid) {
+        let repo = TestRepo::with_initial_commit();
+        let first = repo.head_oid().to_string();
+        repo.create_commit("two", &[("a.txt", "2\n")]);
+        let culprit = repo.create_commit("three", &[("bug.txt", "bug\n")]);
+        repo.create_commit("four", &[("a.txt", "4\n")]);
+        repo.create_commit("five", &[("a.txt", "5\n")]);
+        repo.create_commit("six", &[("a.txt", "6\n")]);
+        run_git_command(&repo.path, &["bisect", "start", "HEAD", &first]).unwrap();
+        (repo, culprit)
+    }
+
+    #[test]
+    fn test_verdict_for_exit_follows_git_bisect_run() {
+        assert_eq!(verdict_for_exit(Some(0)), Some(BisectRunVerdict::Good));
+        assert_eq!(verdict_for_exit(Some(1)), Some(BisectRunVerdict::Bad));
+        assert_eq!(verdict_for_exit(Some(127)), Some(BisectRunVerdict::Bad));
+        assert_eq!(verdict_for_exit(Some(125)), Some(BisectRunVerdict::Skip));
+        assert_eq!(verdict_for_exit(Some(128)), None);
+        assert_eq!(verdict_for_exit(None), None);
+    }
+
+    #[test]
+    fn test_bisect_run_finds_the_culprit_and_streams_steps() {
+        let (repo, culprit) = bisect_fixture();
+        let lines = std::sync::Mutex::new(Vec::new());
+        let steps = std::sync::Mutex::new(Vec::new());
+
+        let result = execute_bisect_run(
+            &repo.path,
+            "run-1",
+            "echo testing; test ! -f bug.txt",
+            None,
+            &|commit, _, line| {
+                lines
+                    .lock()
+                    .unwrap()
+                    .push((commit.to_string(), line.to_string()))
+            },
+            &|step| steps.lock().unwrap().push(step.commit_oid.clone()),
+        );
+
+        assert!(result.error.is_none(), "{:?}", result.error);
+        assert_eq!(
+            result.culprit.map(|c| c.oid),
+            Some(culprit.to_string()),
+            "the run must end on the commit that added bug.txt"
+        );
+        assert!(!result.steps.is_empty());
+        assert_eq!(
+            steps.into_inner().unwrap(),
+            result
+                .steps
+                .iter()
+                .map(|s| s.commit_oid.clone())
+                .collect::<Vec<_>>(),
+            "every marked commit must be reported as it happens"
+        );
+        assert!(lines
+            .into_inner()
+            .unwrap()
+            .iter()
+            .any(|(_, line)| line == "testing"));
+
+        let _ = run_git_command(&repo.path, &["bisect", "reset"]);
+    }
+
+    #[test]
+    fn test_bisect_run_skips_on_125() {
+        let repo = TestRepo::with_initial_commit();
+        let first = repo.head_oid().to_string();
+        let mut culprit = None;
+        for n in 1..=9 {
+            let name = format!("f{}", n);
+            let oid = if n == 3 {
+                repo.create_commit("c3", &[(name.as_str(), "x\n"), ("bug.txt", "bug\n")])
+            } else {
+                repo.create_commit(&format!("c{}", n), &[(name.as_str(), "x\n")])
+            };
+            if n == 3 {
+                culprit = Some(oid);
+            }
+        }
+        run_git_command(&repo.path, &["bisect", "start", "HEAD", &first]).unwrap();
+
+        // The first candidate cannot be tested; the search must route around
+        // it and still land on the culprit.
+        let result = execute_bisect_run(
+            &repo.path,
+            "run-1",
+            "if [ ! -e .git/skipped-once ]; then touch .git/skipped-once; exit 125; fi; \
+             test ! -f bug.txt",
+            None,
+            &|_, _, _| {},
+            &|_| {},
+        );
+
+        assert!(result.error.is_none(), "{:?}", result.error);
+        assert_eq!(result.steps[0].verdict, BisectRunVerdict::Skip);
+        assert_eq!(result.steps[0].exit_code, Some(125));
+        assert_eq!(
+            result.culprit.map(|c| c.oid),
+            culprit.map(|c| c.to_string())
+        );
+        let _ = run_git_command(&repo.path, &["bisect", "reset"]);
+    }
+
+    /// An exit code above 127 means the test command itself broke (git treats
+    /// it as an abort); blaming the commit for it would corrupt the search.
+    #[test]
+    fn test_bisect_run_stops_when_the_command_aborts() {
+        let (repo, _) = bisect_fixture();
+
+        let result = execute_bisect_run(
+            &repo.path,
+            "run-1",
+            "exit 200",
+            None,
+            &|_, _, _| {},
+            &|_| {},
+        );
+
+        assert!(result.error.is_some());
+        assert!(result.steps.is_empty(), "nothing may be marked on an abort");
+        assert!(result.culprit.is_none());
+        let _ = run_git_command(&repo.path, &["bisect", "reset"]);
+    }
+
+    #[test]
+    fn test_bisect_run_honours_cancellation() {
+        let (repo, _) = bisect_fixture();
+        let token = CancellationToken::new();
+        token.cancel();
+
+        let result = execute_bisect_run(
+            &repo.path,
+            "run-1",
+            "test ! -f bug.txt",
+            Some(&token),
+            &|_, _, _| {},
+            &|_| {},
+        );
+
+        assert!(result.cancelled);
+        assert!(result.steps.is_empty());
+        assert!(
+            is_bisect_active(&repo.path),
+            "cancelling must keep the session"
+        );
+        let _ = run_git_command(&repo.path, &["bisect", "reset"]);
+    }
+
+    #[tokio::test]
+    async fn test_export_and_replay_bisect_log_round_trip() {
+        let (repo, _) = bisect_fixture();
+        bisect_good(repo.path_str(), None).await.unwrap();
+        let before = get_bisect_status(repo.path_str()).await.unwrap();
+
+        let log = export_bisect_log(repo.path_str()).await.unwrap();
+        assert!(log.contains("git bisect start"));
+        bisect_reset(repo.path_str()).await.unwrap();
+
+        let replayed = replay_bisect_log(repo.path_str(), log).await.unwrap();
+        assert!(replayed.status.active);
+        assert_eq!(replayed.status.current_commit, before.current_commit);
+        assert_eq!(replayed.status.log.len(), before.log.len());
+        let _ = run_git_command(&repo.path, &["bisect", "reset"]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_bisect_log_rolls_back_an_unknown_commit() {
+        let repo = TestRepo::with_initial_commit();
+        let log = format!(
+            "git bisect start\n# bad: [{0}] gone\ngit bisect bad {0}\n",
+            "1234567890123456789012345678901234567890"
+        );
+
+        assert!(replay_bisect_log(repo.path_str(), log).await.is_err());
+        assert_eq!(repo.repo().state(), git2::RepositoryState::Clean);
+    }
 }
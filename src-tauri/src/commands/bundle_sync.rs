@@ -0,0 +1,985 @@
+//! Incremental bundle sync
+//!
+//! Moving a repository to an air-gapped network means carrying bundles
+//! across by hand, and each one should hold only what the other side
+//! doesn't have yet. A sync profile remembers, per destination, which refs
+//! to export and the tip of each ref as of the last export; the next export
+//! bundles the refs that moved since, with every previously exported tip as
+//! a prerequisite, so the bundle carries only new objects.
+//!
+//! Profiles live in `<commondir>/leviathan/bundle_sync.json`. They are
+//! local state — what was carried to which network is a fact about this
+//! machine, not about the project — so they are never committed.
+//!
+//! On the receiving side, [`bundle_sync_import`] fetches a bundle into a
+//! remote-tracking namespace (`refs/remotes/<namespace>/`) rather than over
+//! local branches, and reports for each ref whether it fast-forwarded or
+//! diverged, both for the tracking ref and against the local branch of the
+//! same name.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use tauri::command;
+
+use crate::commands::bundle::bundle_verify;
+use crate::error::{LeviathanError, Result};
+use crate::utils::{create_command, reject_flag_like};
+
+/// Refs exported when a profile doesn't list any
+const DEFAULT_REFS: &[&str] = &["refs/heads/*", "refs/tags/*"];
+
+/// Namespace imported bundles land in when none is given
+const DEFAULT_NAMESPACE: &str = "bundle";
+
+/// A destination that bundles are exported to
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSyncProfile {
+    /// Name of the destination, unique per repository
+    pub name: String,
+    /// Refs to export: full ref names, globs such as `refs/heads/*`, or
+    /// short names such as `main`
+    #[serde(default)]
+    pub refs: Vec<String>,
+    /// Directory bundles are written to when an export doesn't name a file
+    #[serde(default)]
+    pub output_dir: Option<String>,
+    /// Tip of every ref exported to this destination so far, as of its last
+    /// export. A ref deleted here stays listed: the destination still has
+    /// its objects, so they stay excluded from later bundles.
+    #[serde(default)]
+    pub exported_tips: BTreeMap<String, String>,
+    /// Unix timestamp of the last export
+    #[serde(default)]
+    pub last_exported_at: Option<i64>,
+    #[serde(default)]
+    pub last_bundle_path: Option<String>,
+    #[serde(default)]
+    pub export_count: u32,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleSyncConfig {
+    #[serde(default)]
+    profiles: Vec<BundleSyncProfile>,
+}
+
+/// A ref written to an exported bundle
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSyncExportedRef {
+    pub name: String,
+    pub oid: String,
+    /// Tip exported last time; None for a ref new to this destination
+    pub previous_oid: Option<String>,
+}
+
+/// A ref that moved but could not be carried by the bundle
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSyncSkippedRef {
+    pub name: String,
+    pub oid: String,
+    pub reason: String,
+}
+
+/// Result of exporting a profile
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSyncExportResult {
+    /// The bundle written; None when nothing changed
+    pub bundle_path: Option<String>,
+    /// True when no exported ref moved since the last export
+    pub up_to_date: bool,
+    /// True when no previous tips were used, so the bundle is self-contained
+    pub full: bool,
+    pub refs: Vec<BundleSyncExportedRef>,
+    pub skipped: Vec<BundleSyncSkippedRef>,
+    /// Commits the destination must already have to apply the bundle
+    pub prerequisites: Vec<String>,
+    pub size_bytes: u64,
+}
+
+/// How an imported ref's remote-tracking ref moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleRefUpdate {
+    Created,
+    FastForward,
+    Unchanged,
+    /// The new tip does not contain the old one; the tracking ref was
+    /// force-updated, as `git fetch` does for a rewritten remote branch
+    Diverged,
+    /// Not updated; tags are never overwritten
+    Rejected,
+    /// Not a branch or tag, so there is no namespace to import it into
+    Skipped,
+}
+
+/// How a local branch relates to the imported tip of the same name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalBranchState {
+    UpToDate,
+    /// The local branch can fast-forward to the imported tip
+    FastForward,
+    /// The local branch has commits the import doesn't
+    Ahead,
+    Diverged,
+}
+
+/// Local branch comparison for an imported branch
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalBranchRelation {
+    pub branch: String,
+    pub state: LocalBranchState,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+/// One ref from an imported bundle
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSyncImportedRef {
+    /// Ref name inside the bundle
+    pub name: String,
+    /// Where it was imported to; None when skipped
+    pub target: Option<String>,
+    pub oid: String,
+    pub previous_oid: Option<String>,
+    pub update: BundleRefUpdate,
+    /// For branches, how the local branch of the same name compares
+    pub local: Option<LocalBranchRelation>,
+}
+
+/// Result of importing a bundle
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSyncImportResult {
+    pub namespace: String,
+    pub refs: Vec<BundleSyncImportedRef>,
+}
+
+fn open_repo(path: &str) -> Result<git2::Repository> {
+    git2::Repository::open(path).map_err(|_| LeviathanError::RepositoryNotFound(path.to_string()))
+}
+
+fn config_path(repo: &git2::Repository) -> PathBuf {
+    repo.commondir().join("leviathan").join("bundle_sync.json")
+}
+
+fn load_config(repo: &git2::Repository) -> Result<BundleSyncConfig> {
+    let path = config_path(repo);
+    if !path.exists() {
+        return Ok(BundleSyncConfig::default());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|e| {
+        LeviathanError::OperationFailed(format!("Failed to parse bundle sync profiles: {}", e))
+    })
+}
+
+fn save_config(repo: &git2::Repository, config: &BundleSyncConfig) -> Result<()> {
+    let path = config_path(repo);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(config)?)?;
+    Ok(())
+}
+
+fn find_profile<'a>(
+    config: &'a mut BundleSyncConfig,
+    name: &str,
+) -> Result<&'a mut BundleSyncProfile> {
+    config
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == name)
+        .ok_or_else(|| {
+            LeviathanError::OperationFailed(format!("Bundle sync profile '{}' not found", name))
+        })
+}
+
+/// Resolve a profile's ref list to the refs it currently names, with their
+/// tips. Symbolic refs (a remote's `HEAD`) are left out: the ref they point
+/// at is exported under its own name if it matches.
+fn resolve_refs(repo: &git2::Repository, patterns: &[String]) -> Result<BTreeMap<String, String>> {
+    let defaults: Vec<String> = DEFAULT_REFS.iter().map(|s| s.to_string()).collect();
+    let patterns = if patterns.is_empty() {
+        &defaults
+    } else {
+        patterns
+    };
+
+    let mut tips = BTreeMap::new();
+    let mut add = |reference: &git2::Reference| {
+        if reference.kind() != Some(git2::ReferenceType::Direct) {
+            return;
+        }
+        if let (Ok(name), Some(oid)) = (reference.name(), reference.target()) {
+            tips.insert(name.to_string(), oid.to_string());
+        }
+    };
+    for pattern in patterns {
+        let pattern = pattern.trim();
+        if pattern.contains('*') {
+            for reference in repo.references_glob(pattern)? {
+                add(&reference?);
+            }
+        } else if pattern.starts_with("refs/") {
+            let reference = repo.find_reference(pattern).map_err(|_| {
+                LeviathanError::OperationFailed(format!("Ref '{}' not found", pattern))
+            })?;
+            add(&reference);
+        } else {
+            let reference = repo
+                .resolve_reference_from_short_name(pattern)
+                .map_err(|_| {
+                    LeviathanError::OperationFailed(format!("Ref '{}' not found", pattern))
+                })?;
+            add(&reference);
+        }
+    }
+    Ok(tips)
+}
+
+fn peel_commit(repo: &git2::Repository, oid: &str) -> Option<git2::Oid> {
+    let oid = git2::Oid::from_str(oid).ok()?;
+    repo.find_object(oid, None)
+        .ok()?
+        .peel_to_commit()
+        .ok()
+        .map(|c| c.id())
+}
+
+/// Whether the object `tip` names is a prerequisite or reachable from one.
+/// git drops such refs from a bundle ("excluded by the rev-list options"),
+/// since there are no objects to carry for them. The ref's own object is
+/// checked, not the commit it peels to: a new annotated tag on a commit the
+/// destination already has still carries the tag object.
+fn already_covered(
+    repo: &git2::Repository,
+    tip: &str,
+    prerequisites: &[git2::Oid],
+    prerequisite_commits: &[git2::Oid],
+) -> bool {
+    let Ok(tip) = git2::Oid::from_str(tip) else {
+        return false;
+    };
+    if prerequisites.contains(&tip) {
+        return true;
+    }
+    let is_commit = repo
+        .find_object(tip, None)
+        .is_ok_and(|obj| obj.kind() == Some(git2::ObjectType::Commit));
+    is_commit
+        && prerequisite_commits
+            .iter()
+            .any(|&base| base == tip || repo.graph_descendant_of(base, tip).unwrap_or(false))
+}
+
+fn default_bundle_path(
+    repo: &git2::Repository,
+    profile: &BundleSyncProfile,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<String> {
+    let Some(dir) = profile.output_dir.as_deref().filter(|d| !d.is_empty()) else {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Profile '{}' has no output directory; choose where to write the bundle",
+            profile.name
+        )));
+    };
+    let repo_name = repo
+        .workdir()
+        .unwrap_or_else(|| repo.commondir())
+        .file_name()
+        .map(|n| n.to_string_lossy().trim_end_matches(".git").to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| "repository".to_string());
+    let profile_name: String = profile
+        .name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let file = format!(
+        "{}-{}-{}.bundle",
+        repo_name,
+        profile_name,
+        now.format("%Y%m%dT%H%M%SZ")
+    );
+    Ok(Path::new(dir).join(file).to_string_lossy().to_string())
+}
+
+/// Get the repository's bundle sync profiles
+#[command]
+pub async fn get_bundle_sync_profiles(path: String) -> Result<Vec<BundleSyncProfile>> {
+    let repo = open_repo(&path)?;
+    Ok(load_config(&repo)?.profiles)
+}
+
+/// Create or update a bundle sync profile
+///
+/// Only the name, refs and output directory are taken from `profile`; the
+/// export state of an existing profile is kept, so editing which refs a
+/// destination gets doesn't make the next export a full one.
+#[command]
+pub async fn save_bundle_sync_profile(
+    path: String,
+    profile: BundleSyncProfile,
+) -> Result<BundleSyncProfile> {
+    let name = profile.name.trim().to_string();
+    if name.is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "Profile name cannot be empty".to_string(),
+        ));
+    }
+    let repo = open_repo(&path)?;
+    resolve_refs(&repo, &profile.refs)?;
+
+    let mut config = load_config(&repo)?;
+    let saved = match config.profiles.iter_mut().find(|p| p.name == name) {
+        Some(existing) => {
+            existing.refs = profile.refs;
+            existing.output_dir = profile.output_dir;
+            existing.clone()
+        }
+        None => {
+            let created = BundleSyncProfile {
+                name,
+                refs: profile.refs,
+                output_dir: profile.output_dir,
+                exported_tips: BTreeMap::new(),
+                last_exported_at: None,
+                last_bundle_path: None,
+                export_count: 0,
+            };
+            config.profiles.push(created.clone());
+            created
+        }
+    };
+    save_config(&repo, &config)?;
+    Ok(saved)
+}
+
+/// Delete a bundle sync profile
+#[command]
+pub async fn delete_bundle_sync_profile(path: String, name: String) -> Result<()> {
+    let repo = open_repo(&path)?;
+    let mut config = load_config(&repo)?;
+    let before = config.profiles.len();
+    config.profiles.retain(|p| p.name != name);
+    if config.profiles.len() == before {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Bundle sync profile '{}' not found",
+            name
+        )));
+    }
+    save_config(&repo, &config)
+}
+
+/// Forget what was exported to a destination
+///
+/// For when a bundle was lost on the way or the destination was rebuilt:
+/// the next export is a full bundle again.
+#[command]
+pub async fn reset_bundle_sync_profile(path: String, name: String) -> Result<BundleSyncProfile> {
+    let repo = open_repo(&path)?;
+    let mut config = load_config(&repo)?;
+    let profile = find_profile(&mut config, &name)?;
+    profile.exported_tips.clear();
+    let reset = profile.clone();
+    save_config(&repo, &config)?;
+    Ok(reset)
+}
+
+/// Export an incremental bundle for a sync profile
+///
+/// Bundles every profile ref whose tip moved since the last export, with
+/// every previously exported tip as a prerequisite, and records the new
+/// tips once the bundle is written. `full` ignores previous exports.
+/// `bundle_path` defaults to a timestamped file in the profile's output
+/// directory. When nothing moved, no bundle is written.
+#[command]
+pub async fn bundle_sync_export(
+    path: String,
+    profile_name: String,
+    bundle_path: Option<String>,
+    full: Option<bool>,
+) -> Result<BundleSyncExportResult> {
+    let repo = open_repo(&path)?;
+    let mut config = load_config(&repo)?;
+    let profile = find_profile(&mut config, &profile_name)?;
+    let full = full.unwrap_or(false);
+
+    let current = resolve_refs(&repo, &profile.refs)?;
+    let previous = if full {
+        BTreeMap::new()
+    } else {
+        profile.exported_tips.clone()
+    };
+
+    // Previous tips that no longer exist here (the branch was deleted and
+    // gc'd) can't be named as prerequisites; the bundle just gets a little
+    // bigger without them.
+    let odb = repo.odb()?;
+    let mut prerequisites: Vec<git2::Oid> = previous
+        .values()
+        .filter_map(|oid| git2::Oid::from_str(oid).ok())
+        .filter(|oid| odb.exists(*oid))
+        .collect();
+    prerequisites.sort();
+    prerequisites.dedup();
+    let prerequisite_commits: Vec<git2::Oid> = prerequisites
+        .iter()
+        .filter_map(|oid| peel_commit(&repo, &oid.to_string()))
+        .collect();
+
+    let mut refs = Vec::new();
+    let mut skipped = Vec::new();
+    for (name, oid) in &current {
+        let previous_oid = previous.get(name).cloned();
+        if previous_oid.as_deref() == Some(oid.as_str()) {
+            continue;
+        }
+        if already_covered(&repo, oid, &prerequisites, &prerequisite_commits) {
+            skipped.push(BundleSyncSkippedRef {
+                name: name.clone(),
+                oid: oid.clone(),
+                reason: "The destination already has every commit of this ref; a bundle \
+                         can't carry a ref without new objects, so create it there by hand \
+                         or run a full export."
+                    .to_string(),
+            });
+            continue;
+        }
+        refs.push(BundleSyncExportedRef {
+            name: name.clone(),
+            oid: oid.clone(),
+            previous_oid,
+        });
+    }
+
+    // A skipped ref's commits are at the destination already; recording it
+    // reports it once rather than on every export, and makes it a
+    // prerequisite once it moves.
+    for r in &skipped {
+        profile.exported_tips.insert(r.name.clone(), r.oid.clone());
+    }
+
+    let prerequisites: Vec<String> = prerequisites.iter().map(|o| o.to_string()).collect();
+    if refs.is_empty() {
+        if !skipped.is_empty() {
+            save_config(&repo, &config)?;
+        }
+        return Ok(BundleSyncExportResult {
+            bundle_path: None,
+            up_to_date: true,
+            full: prerequisites.is_empty(),
+            refs,
+            skipped,
+            prerequisites,
+            size_bytes: 0,
+        });
+    }
+
+    let now = chrono::Utc::now();
+    let bundle_path = match bundle_path {
+        Some(p) => p,
+        None => default_bundle_path(&repo, profile, now)?,
+    };
+    reject_flag_like(&bundle_path, "Bundle path")?;
+    if let Some(parent) = Path::new(&bundle_path).parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    // Rev-list arguments go after the file, the tips to exclude after
+    // --not; see bundle_create for why `--` precedes the file.
+    let mut cmd = create_command("git");
+    cmd.current_dir(&path)
+        .args(["bundle", "create", "--quiet", "--"])
+        .arg(&bundle_path);
+    for r in &refs {
+        cmd.arg(&r.name);
+    }
+    if !prerequisites.is_empty() {
+        cmd.arg("--not").args(&prerequisites);
+    }
+    let output = cmd.output().map_err(|e| {
+        LeviathanError::OperationFailed(format!("Failed to execute git bundle create: {}", e))
+    })?;
+    if !output.status.success() {
+        return Err(LeviathanError::OperationFailed(format!(
+            "git bundle create failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    for r in &refs {
+        profile.exported_tips.insert(r.name.clone(), r.oid.clone());
+    }
+    if full {
+        // A full export replaces what the destination is known to have.
+        profile
+            .exported_tips
+            .retain(|name, _| refs.iter().any(|r| &r.name == name));
+    }
+    profile.last_exported_at = Some(now.timestamp());
+    profile.last_bundle_path = Some(bundle_path.clone());
+    profile.export_count += 1;
+    save_config(&repo, &config)?;
+
+    let size_bytes = std::fs::metadata(&bundle_path)
+        .map(|m| m.len())
+        .unwrap_or(0);
+    Ok(BundleSyncExportResult {
+        bundle_path: Some(bundle_path),
+        up_to_date: false,
+        full: prerequisites.is_empty(),
+        refs,
+        skipped,
+        prerequisites,
+        size_bytes,
+    })
+}
+
+/// Prerequisite commits listed in a bundle's header (`-<oid> <subject>`
+/// lines before the first blank line).
+///
+/// Read directly because `git bundle verify` stops before listing them
+/// when they are missing, which is exactly when they're needed.
+fn bundle_prerequisites(bundle: &Path) -> Vec<String> {
+    use std::io::BufRead;
+
+    let Ok(file) = std::fs::File::open(bundle) else {
+        return Vec::new();
+    };
+    let mut prerequisites = Vec::new();
+    for line in std::io::BufReader::new(file).split(b'\n').skip(1) {
+        let Ok(line) = line else { break };
+        if line.is_empty() {
+            break;
+        }
+        if let Some(rest) = line.strip_prefix(b"-") {
+            let oid = String::from_utf8_lossy(rest);
+            if let Some(oid) = oid.split_whitespace().next() {
+                prerequisites.push(oid.to_string());
+            }
+        }
+    }
+    prerequisites
+}
+
+fn ref_oid(repo: &git2::Repository, name: &str) -> Option<git2::Oid> {
+    repo.find_reference(name).ok().and_then(|r| r.target())
+}
+
+fn classify_update(
+    repo: &git2::Repository,
+    previous: Option<git2::Oid>,
+    new: git2::Oid,
+) -> BundleRefUpdate {
+    match previous {
+        None => BundleRefUpdate::Created,
+        Some(old) if old == new => BundleRefUpdate::Unchanged,
+        Some(old) => match (
+            peel_commit(repo, &old.to_string()),
+            peel_commit(repo, &new.to_string()),
+        ) {
+            (Some(old), Some(new)) if repo.graph_descendant_of(new, old).unwrap_or(false) => {
+                BundleRefUpdate::FastForward
+            }
+            _ => BundleRefUpdate::Diverged,
+        },
+    }
+}
+
+fn local_relation(
+    repo: &git2::Repository,
+    branch: &str,
+    imported: git2::Oid,
+) -> Option<LocalBranchRelation> {
+    let local = peel_commit(
+        repo,
+        &ref_oid(repo, &format!("refs/heads/{}", branch))?.to_string(),
+    )?;
+    let imported = peel_commit(repo, &imported.to_string())?;
+    let (ahead, behind) = repo.graph_ahead_behind(local, imported).ok()?;
+    let state = match (ahead, behind) {
+        (0, 0) => LocalBranchState::UpToDate,
+        (0, _) => LocalBranchState::FastForward,
+        (_, 0) => LocalBranchState::Ahead,
+        _ => LocalBranchState::Diverged,
+    };
+    Some(LocalBranchRelation {
+        branch: branch.to_string(),
+        state,
+        ahead,
+        behind,
+    })
+}
+
+/// Import a bundle into a remote-tracking namespace
+///
+/// Branches land in `refs/remotes/<namespace>/` (default `bundle`) and are
+/// force-updated like any remote-tracking ref; tags land in `refs/tags/`
+/// and are never overwritten. Local branches are left alone: each imported
+/// branch reports whether the local branch of the same name can
+/// fast-forward to it or has diverged. Fails up front, naming the missing
+/// commits, when an earlier bundle in the sequence wasn't imported.
+#[command]
+pub async fn bundle_sync_import(
+    path: String,
+    bundle_path: String,
+    namespace: Option<String>,
+) -> Result<BundleSyncImportResult> {
+    let namespace = namespace
+        .map(|n| n.trim().trim_matches('/').to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| DEFAULT_NAMESPACE.to_string());
+    if !git2::Reference::is_valid_name(&format!("refs/remotes/{}/HEAD", namespace)) {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Invalid namespace '{}'",
+            namespace
+        )));
+    }
+
+    let repo = open_repo(&path)?;
+    let verify = bundle_verify(path.clone(), bundle_path.clone()).await?;
+    if !verify.is_valid {
+        let missing: Vec<String> = bundle_prerequisites(Path::new(&bundle_path))
+            .into_iter()
+            .filter(|oid| {
+                git2::Oid::from_str(oid)
+                    .map(|o| repo.find_commit(o).is_err())
+                    .unwrap_or(true)
+            })
+            .collect();
+        return Err(LeviathanError::OperationFailed(if missing.is_empty() {
+            format!(
+                "Bundle is not valid: {}",
+                verify.message.unwrap_or_default().trim()
+            )
+        } else {
+            format!(
+                "This repository lacks {} commit(s) the bundle builds on ({}). Import the \
+                 earlier bundles from this profile first.",
+                missing.len(),
+                missing.join(", ")
+            )
+        }));
+    }
+
+    let mut planned = Vec::new();
+    let mut refspecs = Vec::new();
+    for bundle_ref in &verify.refs {
+        let target = if let Some(branch) = bundle_ref.name.strip_prefix("refs/heads/") {
+            refspecs.push(format!(
+                "+{}:refs/remotes/{}/{}",
+                bundle_ref.name, namespace, branch
+            ));
+            Some(format!("refs/remotes/{}/{}", namespace, branch))
+        } else if bundle_ref.name.starts_with("refs/tags/") && !bundle_ref.name.starts_with('-') {
+            refspecs.push(format!("{}:{}", bundle_ref.name, bundle_ref.name));
+            Some(bundle_ref.name.clone())
+        } else {
+            None
+        };
+        let previous = target.as_deref().and_then(|t| ref_oid(&repo, t));
+        planned.push((bundle_ref, target, previous));
+    }
+
+    if !refspecs.is_empty() {
+        // Not atomic on purpose: one tag that already exists with another
+        // target must not stop every branch from importing. Each ref's
+        // outcome is read back from the repository below.
+        let output = create_command("git")
+            .current_dir(&path)
+            .args([
+                "fetch",
+                "--no-tags",
+                "--no-write-fetch-head",
+                "--quiet",
+                "--",
+            ])
+            .arg(&bundle_path)
+            .args(&refspecs)
+            .output()
+            .map_err(|e| {
+                LeviathanError::OperationFailed(format!("Failed to fetch from bundle: {}", e))
+            })?;
+        if !output.status.success() {
+            tracing::warn!(
+                "bundle import reported errors: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+    }
+
+    let mut refs = Vec::new();
+    for (bundle_ref, target, previous) in planned {
+        let Ok(bundle_oid) = git2::Oid::from_str(&bundle_ref.oid) else {
+            continue;
+        };
+        let update = match &target {
+            None => BundleRefUpdate::Skipped,
+            Some(t) if ref_oid(&repo, t) != Some(bundle_oid) => BundleRefUpdate::Rejected,
+            Some(_) => classify_update(&repo, previous, bundle_oid),
+        };
+        let local = match bundle_ref.name.strip_prefix("refs/heads/") {
+            Some(branch) if update != BundleRefUpdate::Rejected => {
+                local_relation(&repo, branch, bundle_oid)
+            }
+            _ => None,
+        };
+        refs.push(BundleSyncImportedRef {
+            name: bundle_ref.name.clone(),
+            target,
+            oid: bundle_ref.oid.clone(),
+            previous_oid: previous.map(|o| o.to_string()),
+            update,
+            local,
+        });
+    }
+
+    Ok(BundleSyncImportResult { namespace, refs })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    async fn add_profile(repo: &TestRepo, name: &str, refs: &[&str]) {
+        save_bundle_sync_profile(
+            repo.path_str(),
+            BundleSyncProfile {
+                name: name.to_string(),
+                refs: refs.iter().map(|r| r.to_string()).collect(),
+                output_dir: Some(repo.path.join("out").to_string_lossy().to_string()),
+                exported_tips: BTreeMap::new(),
+                last_exported_at: None,
+                last_bundle_path: None,
+                export_count: 0,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn export(repo: &TestRepo, name: &str) -> BundleSyncExportResult {
+        bundle_sync_export(repo.path_str(), name.to_string(), None, None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_second_export_only_carries_new_objects() {
+        let source = TestRepo::with_initial_commit();
+        add_profile(&source, "offline", &[]).await;
+
+        let first = export(&source, "offline").await;
+        assert!(first.full);
+        assert!(first.prerequisites.is_empty());
+        assert_eq!(first.refs.len(), 1);
+
+        let base = source.head_oid();
+        source.create_commit("more", &[("a.txt", "a")]);
+        let second = export(&source, "offline").await;
+
+        assert!(!second.full);
+        assert_eq!(second.prerequisites, vec![base.to_string()]);
+        assert_eq!(second.refs[0].previous_oid, Some(base.to_string()));
+        let verify = bundle_verify(source.path_str(), second.bundle_path.clone().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(verify.requires, vec![base.to_string()]);
+
+        let profiles = get_bundle_sync_profiles(source.path_str()).await.unwrap();
+        assert_eq!(profiles[0].export_count, 2);
+        assert_eq!(
+            profiles[0].exported_tips.get("refs/heads/main"),
+            Some(&source.head_oid().to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_with_nothing_new_writes_no_bundle() {
+        let source = TestRepo::with_initial_commit();
+        add_profile(&source, "offline", &["main"]).await;
+        export(&source, "offline").await;
+
+        let again = export(&source, "offline").await;
+
+        assert!(again.up_to_date);
+        assert!(again.bundle_path.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_new_ref_without_new_objects_is_skipped_not_failed() {
+        let source = TestRepo::with_initial_commit();
+        add_profile(&source, "offline", &[]).await;
+        export(&source, "offline").await;
+
+        source.create_branch("alias");
+        let result = export(&source, "offline").await;
+
+        assert!(result.up_to_date);
+        assert_eq!(result.skipped[0].name, "refs/heads/alias");
+
+        let again = export(&source, "offline").await;
+        assert!(again.skipped.is_empty(), "a skipped ref is reported once");
+    }
+
+    #[tokio::test]
+    async fn test_annotated_tag_on_exported_commit_is_carried() {
+        let source = TestRepo::with_initial_commit();
+        add_profile(&source, "offline", &[]).await;
+        export(&source, "offline").await;
+
+        let git = source.repo();
+        let head = git.head().unwrap().peel_to_commit().unwrap();
+        let signature = git.signature().unwrap();
+        let tag = git
+            .tag("v1", head.as_object(), &signature, "Release 1", false)
+            .unwrap();
+        let result = export(&source, "offline").await;
+
+        assert!(result.skipped.is_empty(), "{:?}", result.skipped);
+        assert_eq!(result.refs.len(), 1);
+        assert_eq!(result.refs[0].name, "refs/tags/v1");
+        assert_eq!(result.refs[0].oid, tag.to_string());
+        let heads = crate::commands::bundle::bundle_list_heads(result.bundle_path.unwrap())
+            .await
+            .unwrap();
+        assert!(heads.iter().any(|h| h.name == "refs/tags/v1"));
+    }
+
+    #[tokio::test]
+    async fn test_reset_makes_next_export_full() {
+        let source = TestRepo::with_initial_commit();
+        add_profile(&source, "offline", &[]).await;
+        export(&source, "offline").await;
+
+        reset_bundle_sync_profile(source.path_str(), "offline".to_string())
+            .await
+            .unwrap();
+        let result = export(&source, "offline").await;
+
+        assert!(result.full);
+        assert!(!result.up_to_date);
+    }
+
+    #[tokio::test]
+    async fn test_saving_a_profile_keeps_its_export_state() {
+        let source = TestRepo::with_initial_commit();
+        add_profile(&source, "offline", &[]).await;
+        export(&source, "offline").await;
+
+        add_profile(&source, "offline", &["refs/heads/*"]).await;
+        let profiles = get_bundle_sync_profiles(source.path_str()).await.unwrap();
+
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].refs, vec!["refs/heads/*"]);
+        assert_eq!(profiles[0].exported_tips.len(), 1);
+
+        delete_bundle_sync_profile(source.path_str(), "offline".to_string())
+            .await
+            .unwrap();
+        assert!(get_bundle_sync_profiles(source.path_str())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_reports_fast_forward_and_divergence() {
+        let source = TestRepo::with_initial_commit();
+        source.create_branch("feature");
+        add_profile(&source, "offline", &["refs/heads/*"]).await;
+        let first = export(&source, "offline").await;
+
+        let target = TestRepo::new();
+        let imported = bundle_sync_import(target.path_str(), first.bundle_path.unwrap(), None)
+            .await
+            .unwrap();
+        assert!(imported
+            .refs
+            .iter()
+            .all(|r| r.update == BundleRefUpdate::Created));
+        let main_tip = ref_oid(&target.repo(), "refs/remotes/bundle/main").unwrap();
+        target
+            .repo()
+            .reference("refs/heads/main", main_tip, true, "test")
+            .unwrap();
+
+        // main moves forward; feature is rewritten onto a new root.
+        source.create_commit("forward", &[("a.txt", "a")]);
+        {
+            let git = source.repo();
+            let old = git.find_commit(first.refs[0].oid.parse().unwrap()).unwrap();
+            let sig = git.signature().unwrap();
+            let rewritten = git
+                .commit(None, &sig, &sig, "rewritten", &old.tree().unwrap(), &[])
+                .unwrap();
+            git.reference("refs/heads/feature", rewritten, true, "rewrite")
+                .unwrap();
+        }
+
+        let second = export(&source, "offline").await;
+        let imported = bundle_sync_import(target.path_str(), second.bundle_path.unwrap(), None)
+            .await
+            .unwrap();
+
+        let main = imported
+            .refs
+            .iter()
+            .find(|r| r.name == "refs/heads/main")
+            .unwrap();
+        assert_eq!(main.update, BundleRefUpdate::FastForward);
+        let local = main.local.as_ref().unwrap();
+        assert_eq!(local.state, LocalBranchState::FastForward);
+        assert_eq!(local.behind, 1);
+        let feature = imported
+            .refs
+            .iter()
+            .find(|r| r.name == "refs/heads/feature")
+            .unwrap();
+        assert_eq!(feature.update, BundleRefUpdate::Diverged);
+        assert_eq!(
+            feature.target.as_deref(),
+            Some("refs/remotes/bundle/feature")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_names_missing_prerequisites() {
+        let source = TestRepo::with_initial_commit();
+        add_profile(&source, "offline", &[]).await;
+        export(&source, "offline").await;
+        source.create_commit("more", &[("a.txt", "a")]);
+        let second = export(&source, "offline").await;
+
+        let target = TestRepo::new();
+        let err = bundle_sync_import(target.path_str(), second.bundle_path.unwrap(), None)
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string().contains("earlier bundles"),
+            "unexpected error: {}",
+            err
+        );
+    }
+}
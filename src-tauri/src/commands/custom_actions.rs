@@ -1,11 +1,17 @@
 //! Custom Actions command handlers
 //! Allow users to define and run custom scripts/commands from the UI
+//!
+//! Actions run either blocking (`run_custom_action`) or in the background with
+//! streamed output and cancellation (`start_custom_action`); both record the
+//! run in a per-repository history.
 
+use std::collections::HashMap;
 use std::path::Path;
-use std::process::Command;
-use tauri::command;
+use std::process::{Command, Stdio};
+use tauri::{command, AppHandle, Emitter, Manager, State};
 
 use crate::error::{LeviathanError, Result};
+use crate::services::cancellation::{CancellationRegistry, CancellationToken};
 
 /// A user-defined custom action
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
@@ -20,6 +26,44 @@ pub struct CustomAction {
     pub show_in_toolbar: bool,
     pub open_in_terminal: bool,
     pub confirm_before_run: bool,
+    /// Where the action is offered. Actions saved before scopes existed
+    /// default to the repository scope.
+    #[serde(default)]
+    pub scope: ActionScope,
+}
+
+/// The UI context a custom action is offered in
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionScope {
+    /// Toolbar / repository menu; needs no selection.
+    #[default]
+    Repository,
+    /// Commit context menu; requires `ActionContext::commit`.
+    Commit,
+    /// File context menu; requires `ActionContext::files`.
+    File,
+    /// Branch context menu; requires `ActionContext::branch`.
+    Branch,
+}
+
+/// The current selection a custom action is run against
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionContext {
+    /// Selected commit (any revision git can resolve). Defaults to HEAD.
+    pub commit: Option<String>,
+    /// Selected branch. Defaults to the current branch.
+    pub branch: Option<String>,
+    /// Selected files, relative to the repository root.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Remote whose URL `${remoteUrl}` expands to. Defaults to the current
+    /// branch's upstream remote, then `origin`.
+    pub remote: Option<String>,
+    /// Answers to `${prompt:Label}` placeholders, keyed by label.
+    #[serde(default)]
+    pub prompts: HashMap<String, String>,
 }
 
 /// Result of executing a custom action
@@ -120,34 +164,275 @@ fn shell_quote_windows(value: &str) -> Result<String> {
     Ok(format!("\"{}\"", value))
 }
 
+/// Values available to an action's placeholders.
+///
+/// `commit` and `remote_url` are `None` when they cannot be resolved (unborn
+/// HEAD, no remote); that is only an error for an action that uses them.
+struct ActionVariables {
+    repo: String,
+    branch: String,
+    commit: Option<String>,
+    files: Vec<String>,
+    remote_url: Option<String>,
+    prompts: HashMap<String, String>,
+}
+
+impl ActionVariables {
+    /// Only the legacy `$REPO` / `$BRANCH` values, for callers and tests that
+    /// have no selection.
+    #[cfg(test)]
+    fn basic(repo: &str, branch: &str) -> Self {
+        Self {
+            repo: repo.to_string(),
+            branch: branch.to_string(),
+            commit: None,
+            files: Vec::new(),
+            remote_url: None,
+            prompts: HashMap::new(),
+        }
+    }
+}
+
+/// Quote one value for the target shell, or pass it through unchanged when
+/// the result is not going to a shell.
+fn quote_value(value: &str, for_shell: bool) -> Result<String> {
+    if !for_shell {
+        Ok(value.to_string())
+    } else if cfg!(target_os = "windows") {
+        shell_quote_windows(value)
+    } else {
+        Ok(shell_quote_posix(value))
+    }
+}
+
 /// Replace template variables in a string. Substituted values are shell-quoted
 /// for the target shell so that branch names containing metacharacters
 /// (e.g. `` `;rm -rf ~;# ``) cannot break out of the surrounding command.
 /// Returns Err on Windows if a substituted value contains a metacharacter
 /// that cmd.exe quoting cannot safely handle.
-fn substitute_variables(
-    input: &str,
-    repo_path: &str,
-    branch: &str,
-    for_shell: bool,
-) -> Result<String> {
-    if for_shell {
-        let (repo_q, branch_q) = if cfg!(target_os = "windows") {
-            (
-                shell_quote_windows(repo_path)?,
-                shell_quote_windows(branch)?,
-            )
+///
+/// Supported placeholders: `${repo}`, `${branch}`, `${commit}`,
+/// `${selectedFiles}` (each file quoted separately, space-separated),
+/// `${remoteUrl}` and `${prompt:Label}`, plus the original `$REPO` and
+/// `$BRANCH`. The input is scanned once, so a substituted value containing
+/// placeholder syntax is never expanded again. Any other `${...}` is left
+/// untouched for the shell (`${HOME}`, `${1}`, `${VAR:-default}`); only a
+/// malformed reserved or `prompt:` placeholder, such as `${branch:-x}` or an
+/// unterminated `${repo`, is an error.
+fn substitute_variables(input: &str, vars: &ActionVariables, for_shell: bool) -> Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if let Some(body) = tail.strip_prefix("${") {
+            let Some(close) = body.find('}') else {
+                if is_reserved_placeholder(body) {
+                    return Err(LeviathanError::OperationFailed(format!(
+                        "Unterminated placeholder in action: {}",
+                        tail
+                    )));
+                }
+                out.push_str("${");
+                rest = body;
+                continue;
+            };
+            let name = &body[..close];
+            // `${` whose `}` belongs to a later placeholder (`'${' ${repo}`,
+            // `${VAR:-${HOME}}`): keep it literal and scan on from inside.
+            if name.contains("${") && !is_reserved_placeholder(name) {
+                out.push_str("${");
+                rest = body;
+                continue;
+            }
+            match expand_placeholder(name, vars, for_shell)? {
+                Some(value) => out.push_str(&value),
+                None => {
+                    out.push_str("${");
+                    out.push_str(name);
+                    out.push('}');
+                }
+            }
+            rest = &body[close + 1..];
+        } else if let Some(after) = tail.strip_prefix("$REPO") {
+            out.push_str(&quote_value(&vars.repo, for_shell)?);
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("$BRANCH") {
+            out.push_str(&quote_value(&vars.branch, for_shell)?);
+            rest = after;
         } else {
-            (shell_quote_posix(repo_path), shell_quote_posix(branch))
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Placeholder names expanded by the app; everything else belongs to the shell.
+const RESERVED_PLACEHOLDERS: &[&str] = &["repo", "branch", "commit", "remoteUrl", "selectedFiles"];
+
+/// Whether the text after `${` starts a reserved or `prompt:` placeholder:
+/// its leading identifier is a reserved name or `prompt`.
+fn is_reserved_placeholder(body: &str) -> bool {
+    let body = body.trim_start();
+    let ident_len = body
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(body.len());
+    let ident = &body[..ident_len];
+    RESERVED_PLACEHOLDERS.contains(&ident)
+        || (ident == "prompt" && body[ident_len..].starts_with(':'))
+}
+
+/// Expand the body of one `${...}` placeholder, or None when it is not one of
+/// ours and should reach the shell unchanged.
+fn expand_placeholder(
+    name: &str,
+    vars: &ActionVariables,
+    for_shell: bool,
+) -> Result<Option<String>> {
+    let missing = |what: &str| {
+        LeviathanError::OperationFailed(format!("Action uses ${{{}}} but {}", name, what))
+    };
+
+    if let Some(label) = name.strip_prefix("prompt:") {
+        if label.trim().is_empty() {
+            return Err(LeviathanError::OperationFailed(
+                "Prompt placeholder in action has no label: ${prompt:}".to_string(),
+            ));
+        }
+        let value = vars
+            .prompts
+            .get(label)
+            .ok_or_else(|| missing("no value was provided for this prompt"))?;
+        return quote_value(value, for_shell).map(Some);
+    }
+
+    if !RESERVED_PLACEHOLDERS.contains(&name) {
+        if is_reserved_placeholder(name) {
+            return Err(LeviathanError::OperationFailed(format!(
+                "Malformed placeholder in action: ${{{}}}",
+                name
+            )));
+        }
+        return Ok(None);
+    }
+
+    let value = match name {
+        "repo" => quote_value(&vars.repo, for_shell),
+        "branch" => quote_value(&vars.branch, for_shell),
+        "commit" => {
+            let commit = vars
+                .commit
+                .as_deref()
+                .ok_or_else(|| missing("there is no commit to use"))?;
+            quote_value(commit, for_shell)
+        }
+        "remoteUrl" => {
+            let url = vars
+                .remote_url
+                .as_deref()
+                .ok_or_else(|| missing("the repository has no matching remote"))?;
+            quote_value(url, for_shell)
+        }
+        "selectedFiles" => {
+            let quoted = vars
+                .files
+                .iter()
+                .map(|f| quote_value(f, for_shell))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(quoted.join(" "))
+        }
+        _ => unreachable!("checked against RESERVED_PLACEHOLDERS"),
+    };
+    value.map(Some)
+}
+
+/// Labels of the `${prompt:Label}` placeholders in `input`, in order of first
+/// appearance.
+fn prompt_labels(input: &str, labels: &mut Vec<String>) {
+    let mut rest = input;
+    while let Some(pos) = rest.find("${prompt:") {
+        let body = &rest[pos + "${prompt:".len()..];
+        let Some(close) = body.find('}') else {
+            return;
         };
-        Ok(input
-            .replace("$REPO", &repo_q)
-            .replace("$BRANCH", &branch_q))
-    } else {
-        Ok(input.replace("$REPO", repo_path).replace("$BRANCH", branch))
+        let label = body[..close].to_string();
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+        rest = &body[close + 1..];
+    }
+}
+
+/// Check that the selection matches the action's scope, so a commit action
+/// invoked without a commit fails clearly instead of running against HEAD.
+fn check_scope(action: &CustomAction, context: &ActionContext) -> Result<()> {
+    let missing = match action.scope {
+        ActionScope::Repository => None,
+        ActionScope::Commit if context.commit.is_none() => Some("a selected commit"),
+        ActionScope::File if context.files.is_empty() => Some("selected files"),
+        ActionScope::Branch if context.branch.is_none() => Some("a selected branch"),
+        _ => None,
+    };
+    match missing {
+        Some(what) => Err(LeviathanError::OperationFailed(format!(
+            "Action '{}' requires {}",
+            action.name, what
+        ))),
+        None => Ok(()),
     }
 }
 
+/// Resolve the placeholder values for a run from the repository and the
+/// caller's selection.
+fn resolve_variables(repo_path: &str, context: &ActionContext) -> Result<ActionVariables> {
+    let repo = git2::Repository::open(repo_path)?;
+
+    let branch = match &context.branch {
+        Some(b) => b.clone(),
+        None => get_current_branch(Path::new(repo_path)),
+    };
+
+    let commit = match &context.commit {
+        Some(spec) => Some(
+            repo.revparse_single(spec)
+                .and_then(|o| o.peel_to_commit())
+                .map_err(|_| LeviathanError::CommitNotFound(spec.clone()))?
+                .id()
+                .to_string(),
+        ),
+        None => repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(|o| o.to_string()),
+    };
+
+    // Explicit remote, else the selected/current branch's upstream remote,
+    // else origin.
+    let remote_name = context.remote.clone().or_else(|| {
+        repo.branch_upstream_remote(&format!("refs/heads/{}", branch))
+            .ok()
+            .and_then(|b| b.as_str().ok().map(str::to_string))
+    });
+    let remote_url = repo
+        .find_remote(remote_name.as_deref().unwrap_or("origin"))
+        .ok()
+        .and_then(|r| r.url().ok().map(str::to_string));
+
+    Ok(ActionVariables {
+        repo: repo_path.to_string(),
+        branch,
+        commit,
+        files: context.files.clone(),
+        remote_url,
+        prompts: context.prompts.clone(),
+    })
+}
+
 /// Get all custom actions for a repository
 #[command]
 pub async fn get_custom_actions(path: String) -> Result<Vec<CustomAction>> {
@@ -193,6 +478,30 @@ pub async fn delete_custom_action(path: String, action_id: String) -> Result<Vec
     Ok(actions)
 }
 
+/// Get the `${prompt:Label}` labels an action needs answered before it runs
+#[command]
+pub async fn get_custom_action_prompts(path: String, action_id: String) -> Result<Vec<String>> {
+    let repo_path = Path::new(&path);
+    if !repo_path.join(".git").exists() {
+        return Err(LeviathanError::RepositoryNotFound(path));
+    }
+
+    let actions = read_actions(repo_path)?;
+    let action = actions.iter().find(|a| a.id == action_id).ok_or_else(|| {
+        LeviathanError::OperationFailed(format!("Action not found: {}", action_id))
+    })?;
+
+    let mut labels = Vec::new();
+    prompt_labels(&action.command, &mut labels);
+    if let Some(args) = &action.arguments {
+        prompt_labels(args, &mut labels);
+    }
+    if let Some(dir) = &action.working_directory {
+        prompt_labels(dir, &mut labels);
+    }
+    Ok(labels)
+}
+
 /// Execute a custom action
 ///
 /// # Security
@@ -210,14 +519,163 @@ pub async fn delete_custom_action(path: String, action_id: String) -> Result<Vec
 ///   unexpected executions are auditable.
 /// - Actions with `confirm_before_run` set to `true` are gated by a
 ///   confirmation dialog in the frontend before this command is invoked.
-/// - Variable substitution (`$REPO`, `$BRANCH`) is limited to known tokens.
+/// - Only known tokens are substituted (see [`substitute_variables`]), and
+///   every substituted value is shell-quoted. Any other `${...}` is passed
+///   to the shell unchanged and expanded there, as in a terminal; only a
+///   malformed reserved placeholder is rejected.
+/// - The selection in `context` must match the action's declared scope.
+#[command]
+pub async fn run_custom_action(
+    path: String,
+    action_id: String,
+    context: Option<ActionContext>,
+) -> Result<ActionResult> {
+    let prepared = prepare_run(&path, &action_id, context)?;
+    let started = std::time::SystemTime::now();
+    let timer = std::time::Instant::now();
+    let outcome = execute_action(&prepared, None, &|_, _| {})?;
+
+    record_run(
+        Path::new(&path),
+        &new_run_record(
+            next_run_id(&action_id),
+            &prepared,
+            &outcome,
+            started,
+            timer.elapsed(),
+        ),
+    );
+
+    Ok(ActionResult {
+        exit_code: outcome.exit_code.unwrap_or(-1),
+        success: outcome.exit_code == Some(0),
+        stdout: outcome.stdout,
+        stderr: outcome.stderr,
+    })
+}
+
+/// Start a custom action in the background and stream its output
+///
+/// Returns the run id immediately; any number of actions can run at once.
+/// Output is emitted line by line as `custom-action-output` events and the
+/// finished run (also appended to the run history) as a
+/// `custom-action-finished` event. The run is registered with the
+/// [`CancellationRegistry`] under its run id, so `cancel_operation(runId)`
+/// stops it: the whole process group is terminated, not just the shell, so
+/// a test runner or deploy script started by the action dies with it.
+///
+/// The same validation and quoting as [`run_custom_action`] apply.
+#[command]
+pub async fn start_custom_action(
+    app: AppHandle,
+    registry: State<'_, CancellationRegistry>,
+    path: String,
+    action_id: String,
+    context: Option<ActionContext>,
+) -> Result<String> {
+    let prepared = prepare_run(&path, &action_id, context)?;
+    let run_id = next_run_id(&action_id);
+    let token = registry.register(run_id.clone());
+
+    let thread_run_id = run_id.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let run_id = thread_run_id;
+        let started = std::time::SystemTime::now();
+        let timer = std::time::Instant::now();
+
+        let emit_line = |stream: ActionStream, line: &str| {
+            let _ = app.emit(
+                "custom-action-output",
+                ActionOutputEvent {
+                    run_id: run_id.clone(),
+                    action_id: prepared.action.id.clone(),
+                    stream,
+                    line: line.to_string(),
+                },
+            );
+        };
+
+        let outcome = match execute_action(&prepared, Some(&token), &emit_line) {
+            Ok(outcome) => outcome,
+            // The process never started; surface it like a failed run.
+            Err(e) => ActionOutcome {
+                exit_code: None,
+                stdout: String::new(),
+                stderr: e.to_string(),
+                cancelled: false,
+            },
+        };
+
+        let record = new_run_record(
+            run_id.clone(),
+            &prepared,
+            &outcome,
+            started,
+            timer.elapsed(),
+        );
+        record_run(Path::new(&path), &record);
+        app.state::<CancellationRegistry>().remove(&run_id);
+        let _ = app.emit("custom-action-finished", record);
+    });
+
+    Ok(run_id)
+}
+
+/// Get past custom action runs, newest first
+#[command]
+pub async fn get_custom_action_runs(
+    path: String,
+    action_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<ActionRunRecord>> {
+    let repo_path = Path::new(&path);
+    if !repo_path.join(".git").exists() {
+        return Err(LeviathanError::RepositoryNotFound(path));
+    }
+
+    let runs = read_runs(repo_path)?;
+    Ok(runs
+        .into_iter()
+        .rev()
+        .filter(|r| match &action_id {
+            Some(id) => &r.action_id == id,
+            None => true,
+        })
+        .take(limit.unwrap_or(MAX_RUN_HISTORY))
+        .collect())
+}
+
+/// Delete the custom action run history
 #[command]
-pub async fn run_custom_action(path: String, action_id: String) -> Result<ActionResult> {
+pub async fn clear_custom_action_runs(path: String) -> Result<()> {
     let repo_path = Path::new(&path);
     if !repo_path.join(".git").exists() {
         return Err(LeviathanError::RepositoryNotFound(path));
     }
 
+    let _guard = RUNS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let file_path = runs_file_path(repo_path);
+    if file_path.exists() {
+        std::fs::remove_file(file_path)?;
+    }
+    Ok(())
+}
+
+/// A validated action with its command line and working directory resolved.
+struct PreparedRun {
+    action: CustomAction,
+    full_command: String,
+    working_dir: String,
+}
+
+/// Look up `action_id`, check the selection against its scope and substitute
+/// every placeholder.
+fn prepare_run(path: &str, action_id: &str, context: Option<ActionContext>) -> Result<PreparedRun> {
+    let repo_path = Path::new(path);
+    if !repo_path.join(".git").exists() {
+        return Err(LeviathanError::RepositoryNotFound(path.to_string()));
+    }
+
     let actions = read_actions(repo_path)?;
     let action = actions
         .iter()
@@ -225,22 +683,24 @@ pub async fn run_custom_action(path: String, action_id: String) -> Result<Action
         .ok_or_else(|| LeviathanError::OperationFailed(format!("Action not found: {}", action_id)))?
         .clone();
 
-    let branch = get_current_branch(repo_path);
+    let context = context.unwrap_or_default();
+    check_scope(&action, &context)?;
+    let vars = resolve_variables(path, &context)?;
     // Command and arguments are passed to `sh -c` / `cmd /C`, so substituted
     // values must be shell-quoted to prevent injection via branch names like
     // `` `;rm -rf ~;# ``. On Windows, values with `cmd.exe` metacharacters
     // are rejected outright (see shell_quote_windows).
-    let command_str = substitute_variables(&action.command, &path, &branch, true)?;
+    let command_str = substitute_variables(&action.command, &vars, true)?;
     let arguments_str = match action.arguments.as_deref() {
-        Some(args) => substitute_variables(args, &path, &branch, true)?,
+        Some(args) => substitute_variables(args, &vars, true)?,
         None => String::new(),
     };
 
     // Working directory is passed via `current_dir`, not interpolated into a
     // shell command, so plain substitution is correct here.
     let working_dir = match action.working_directory.as_deref() {
-        Some("repo_root") | None => path.clone(),
-        Some(custom_path) => substitute_variables(custom_path, &path, &branch, false)?,
+        Some("repo_root") | None => path.to_string(),
+        Some(custom_path) => substitute_variables(custom_path, &vars, false)?,
     };
 
     // Log the command being executed for auditability
@@ -253,43 +713,333 @@ pub async fn run_custom_action(path: String, action_id: String) -> Result<Action
         "Executing custom action"
     );
 
-    // Build the command
-    let output = if cfg!(target_os = "windows") {
-        let mut full_command = command_str.clone();
-        if !arguments_str.is_empty() {
-            full_command.push(' ');
-            full_command.push_str(&arguments_str);
-        }
-        Command::new("cmd")
-            .args(["/C", &full_command])
-            .current_dir(&working_dir)
-            .output()
+    let mut full_command = command_str;
+    if !arguments_str.is_empty() {
+        full_command.push(' ');
+        full_command.push_str(&arguments_str);
+    }
+
+    Ok(PreparedRun {
+        action,
+        full_command,
+        working_dir,
+    })
+}
+
+/// Which output stream a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionStream {
+    Stdout,
+    Stderr,
+}
+
+/// Payload of the `custom-action-output` event
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionOutputEvent {
+    pub run_id: String,
+    pub action_id: String,
+    pub stream: ActionStream,
+    /// One line of output, without its trailing newline
+    pub line: String,
+}
+
+/// How a run ended, with its complete output.
+pub(crate) struct ActionOutcome {
+    /// None when the process was killed by a signal
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) cancelled: bool,
+}
+
+/// How long a cancelled action gets to exit after SIGTERM before the process
+/// group is killed outright.
+const CANCEL_GRACE: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Run a prepared action, passing each output line to `on_line` as it is
+/// produced and killing the process group once `token` is cancelled.
+///
+/// Returns Err only when the shell could not be started.
+fn execute_action(
+    prepared: &PreparedRun,
+    token: Option<&CancellationToken>,
+    on_line: &(dyn Fn(ActionStream, &str) + Sync),
+) -> Result<ActionOutcome> {
+    execute_shell(
+        &prepared.full_command,
+        Path::new(&prepared.working_dir),
+        token,
+        on_line,
+    )
+}
+
+/// Run `command` through the platform shell in `working_dir`, streaming its
+/// output and honouring cancellation exactly like a custom action.
+///
+/// Shared with `bisect run`, whose test command needs the same line streaming
+/// and process-group kill; a second copy would drift the first time either
+/// learned something new about stuck children.
+pub(crate) fn execute_shell(
+    command: &str,
+    working_dir: &Path,
+    token: Option<&CancellationToken>,
+    on_line: &(dyn Fn(ActionStream, &str) + Sync),
+) -> Result<ActionOutcome> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
     } else {
-        let mut full_command = command_str.clone();
-        if !arguments_str.is_empty() {
-            full_command.push(' ');
-            full_command.push_str(&arguments_str);
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    };
+    cmd.current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Own process group, so cancellation reaches everything the shell starts.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let mut child = cmd.spawn().map_err(|e| {
+        LeviathanError::OperationFailed(format!("Failed to execute command: {}", e))
+    })?;
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    std::thread::scope(|scope| {
+        let out_reader = scope.spawn(|| read_lines(stdout, ActionStream::Stdout, on_line));
+        let err_reader = scope.spawn(|| read_lines(stderr, ActionStream::Stderr, on_line));
+
+        let mut status = None;
+        let mut cancelled_at: Option<std::time::Instant> = None;
+        let mut force_killed = false;
+        // Wait for the shell AND both pipes: a background job the action
+        // started can keep the pipes open after the shell exits, and must
+        // still be cancellable.
+        loop {
+            if status.is_none() {
+                match child.try_wait() {
+                    Ok(Some(s)) => status = Some(s),
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("Failed to wait for custom action: {}", e);
+                        break;
+                    }
+                }
+            }
+            if status.is_some() && out_reader.is_finished() && err_reader.is_finished() {
+                break;
+            }
+
+            if token.is_some_and(|t| t.is_cancelled()) {
+                match cancelled_at {
+                    None => {
+                        kill_process_group(&mut child, false);
+                        cancelled_at = Some(std::time::Instant::now());
+                    }
+                    Some(at) if !force_killed && at.elapsed() >= CANCEL_GRACE => {
+                        kill_process_group(&mut child, true);
+                        force_killed = true;
+                    }
+                    _ => {}
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(25));
         }
-        Command::new("sh")
-            .args(["-c", &full_command])
-            .current_dir(&working_dir)
-            .output()
+
+        Ok(ActionOutcome {
+            exit_code: status.and_then(|s| s.code()),
+            stdout: out_reader.join().unwrap_or_default(),
+            stderr: err_reader.join().unwrap_or_default(),
+            cancelled: cancelled_at.is_some(),
+        })
+    })
+}
+
+/// Read a pipe to EOF, reporting each line and returning everything read.
+fn read_lines(
+    pipe: Option<impl std::io::Read>,
+    stream: ActionStream,
+    on_line: &(dyn Fn(ActionStream, &str) + Sync),
+) -> String {
+    use std::io::BufRead;
+
+    let Some(pipe) = pipe else {
+        return String::new();
     };
+    let mut reader = std::io::BufReader::new(pipe);
+    let mut all = String::new();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                on_line(stream, line.trim_end_matches(['\n', '\r']));
+                all.push_str(&line);
+            }
+        }
+    }
+    all
+}
+
+/// Terminate the action's whole process tree. `force` escalates from a
+/// polite SIGTERM to SIGKILL on unix; Windows has only the forced kind.
+fn kill_process_group(child: &mut std::process::Child, force: bool) {
+    let pid = child.id().to_string();
+    if cfg!(target_os = "windows") {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    } else {
+        let signal = if force { "-KILL" } else { "-TERM" };
+        let _ = Command::new("kill")
+            .args([signal, "--", &format!("-{}", pid)])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    if force {
+        let _ = child.kill();
+    }
+}
+
+/// Runs kept in the history; older ones are dropped.
+const MAX_RUN_HISTORY: usize = 50;
+
+/// Bytes of stdout and of stderr kept per recorded run (the tail, where the
+/// verdict of a test suite or deploy is).
+const MAX_RECORDED_OUTPUT: usize = 64 * 1024;
+
+/// Serialises read-modify-write of the history between concurrent runs.
+static RUNS_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// One finished custom action run
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionRunRecord {
+    pub run_id: String,
+    pub action_id: String,
+    pub action_name: String,
+    /// The command line as executed, after substitution
+    pub command: String,
+    pub working_directory: String,
+    /// Unix timestamp (seconds) the run started
+    pub started_at: i64,
+    pub duration_ms: u64,
+    /// None when the process was killed or could not be started
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub cancelled: bool,
+    pub stdout: String,
+    pub stderr: String,
+    /// True when stdout or stderr was cut to its last 64 KiB
+    pub truncated: bool,
+}
+
+/// A run id unique within this process: the action id, the start time and a
+/// counter for runs started in the same millisecond.
+fn next_run_id(action_id: &str) -> String {
+    static COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let n = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    format!("custom-action-{}-{}-{}", action_id, millis, n)
+}
+
+/// Keep the last [`MAX_RECORDED_OUTPUT`] bytes of `text`, cut on a char
+/// boundary.
+fn output_tail(text: &str) -> (String, bool) {
+    if text.len() <= MAX_RECORDED_OUTPUT {
+        return (text.to_string(), false);
+    }
+    let mut start = text.len() - MAX_RECORDED_OUTPUT;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    (text[start..].to_string(), true)
+}
+
+fn new_run_record(
+    run_id: String,
+    prepared: &PreparedRun,
+    outcome: &ActionOutcome,
+    started: std::time::SystemTime,
+    duration: std::time::Duration,
+) -> ActionRunRecord {
+    let (stdout, out_cut) = output_tail(&outcome.stdout);
+    let (stderr, err_cut) = output_tail(&outcome.stderr);
+    ActionRunRecord {
+        run_id,
+        action_id: prepared.action.id.clone(),
+        action_name: prepared.action.name.clone(),
+        command: prepared.full_command.clone(),
+        working_directory: prepared.working_dir.clone(),
+        started_at: started
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0),
+        duration_ms: duration.as_millis() as u64,
+        exit_code: outcome.exit_code,
+        success: outcome.exit_code == Some(0) && !outcome.cancelled,
+        cancelled: outcome.cancelled,
+        stdout,
+        stderr,
+        truncated: out_cut || err_cut,
+    }
+}
+
+/// Path to the run history, next to the action definitions
+fn runs_file_path(repo_path: &Path) -> std::path::PathBuf {
+    repo_path
+        .join(".git")
+        .join("leviathan")
+        .join("custom_action_runs.json")
+}
+
+/// Read the run history, oldest first
+fn read_runs(repo_path: &Path) -> Result<Vec<ActionRunRecord>> {
+    let file_path = runs_file_path(repo_path);
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&file_path)?;
+    Ok(serde_json::from_str(&content)?)
+}
 
-    match output {
-        Ok(output) => {
-            let exit_code = output.status.code().unwrap_or(-1);
-            Ok(ActionResult {
-                exit_code,
-                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-                success: output.status.success(),
-            })
+/// Append a run to the history. History is a convenience: failing to write
+/// it is logged and never fails the run itself.
+fn record_run(repo_path: &Path, record: &ActionRunRecord) {
+    let _guard = RUNS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let result = (|| -> Result<()> {
+        // A corrupt history is replaced rather than blocking every new entry.
+        let mut runs = read_runs(repo_path).unwrap_or_default();
+        runs.push(record.clone());
+        if runs.len() > MAX_RUN_HISTORY {
+            let excess = runs.len() - MAX_RUN_HISTORY;
+            runs.drain(..excess);
         }
-        Err(e) => Err(LeviathanError::OperationFailed(format!(
-            "Failed to execute command: {}",
-            e
-        ))),
+        let file_path = runs_file_path(repo_path);
+        if let Some(parent) = file_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&file_path, serde_json::to_string(&runs)?)?;
+        Ok(())
+    })();
+    if let Err(e) = result {
+        tracing::warn!("Failed to record custom action run: {}", e);
     }
 }
 
@@ -309,6 +1059,7 @@ mod tests {
             show_in_toolbar: false,
             open_in_terminal: false,
             confirm_before_run: false,
+            scope: ActionScope::Repository,
         }
     }
 
@@ -393,7 +1144,7 @@ mod tests {
         let action = make_action("1", "Echo", "echo hello");
         save_custom_action(repo.path_str(), action).await.unwrap();
 
-        let result = run_custom_action(repo.path_str(), "1".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "1".to_string(), None).await;
         assert!(result.is_ok());
         let action_result = result.unwrap();
         assert!(action_result.success);
@@ -408,7 +1159,7 @@ mod tests {
         action.arguments = Some("hello world".to_string());
         save_custom_action(repo.path_str(), action).await.unwrap();
 
-        let result = run_custom_action(repo.path_str(), "1".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "1".to_string(), None).await;
         assert!(result.is_ok());
         let action_result = result.unwrap();
         assert!(action_result.success);
@@ -418,7 +1169,7 @@ mod tests {
     #[tokio::test]
     async fn test_run_custom_action_not_found() {
         let repo = TestRepo::with_initial_commit();
-        let result = run_custom_action(repo.path_str(), "nonexistent".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "nonexistent".to_string(), None).await;
         assert!(result.is_err());
     }
 
@@ -429,7 +1180,7 @@ mod tests {
         action.arguments = Some("$BRANCH".to_string());
         save_custom_action(repo.path_str(), action).await.unwrap();
 
-        let result = run_custom_action(repo.path_str(), "1".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "1".to_string(), None).await;
         assert!(result.is_ok());
         let action_result = result.unwrap();
         assert!(action_result.success);
@@ -447,7 +1198,7 @@ mod tests {
         };
         save_custom_action(repo.path_str(), action).await.unwrap();
 
-        let result = run_custom_action(repo.path_str(), "1".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "1".to_string(), None).await;
         assert!(result.is_ok());
         let action_result = result.unwrap();
         assert!(!action_result.success);
@@ -456,15 +1207,23 @@ mod tests {
 
     #[test]
     fn test_substitute_variables() {
-        let result = substitute_variables("echo $REPO on $BRANCH", "/my/repo", "main", false)
-            .expect("substitution should succeed");
+        let result = substitute_variables(
+            "echo $REPO on $BRANCH",
+            &ActionVariables::basic("/my/repo", "main"),
+            false,
+        )
+        .expect("substitution should succeed");
         assert_eq!(result, "echo /my/repo on main");
     }
 
     #[test]
     fn test_substitute_variables_no_placeholders() {
-        let result = substitute_variables("echo hello", "/my/repo", "main", false)
-            .expect("substitution should succeed");
+        let result = substitute_variables(
+            "echo hello",
+            &ActionVariables::basic("/my/repo", "main"),
+            false,
+        )
+        .expect("substitution should succeed");
         assert_eq!(result, "echo hello");
     }
 
@@ -472,8 +1231,12 @@ mod tests {
     fn test_substitute_variables_shell_quotes_metacharacters() {
         // Must defeat shell injection via branch name (POSIX path).
         if !cfg!(target_os = "windows") {
-            let result = substitute_variables("git log $BRANCH", "/repo", "`;rm -rf ~;#", true)
-                .expect("POSIX quoting should succeed");
+            let result = substitute_variables(
+                "git log $BRANCH",
+                &ActionVariables::basic("/repo", "`;rm -rf ~;#"),
+                true,
+            )
+            .expect("POSIX quoting should succeed");
             assert_eq!(result, "git log '`;rm -rf ~;#'");
         }
     }
@@ -483,7 +1246,11 @@ mod tests {
         // Windows path: substitution must be rejected when value contains
         // characters cmd.exe quoting cannot handle.
         if cfg!(target_os = "windows") {
-            let err = substitute_variables("git log $BRANCH", "/repo", "%USERPROFILE%", true);
+            let err = substitute_variables(
+                "git log $BRANCH",
+                &ActionVariables::basic("/repo", "%USERPROFILE%"),
+                true,
+            );
             assert!(err.is_err(), "expected rejection of % in branch name");
         }
     }
@@ -492,8 +1259,12 @@ mod tests {
     fn test_substitute_variables_quotes_apostrophe_branch() {
         // POSIX-only check; ensures embedded single quote is escaped properly
         if !cfg!(target_os = "windows") {
-            let result = substitute_variables("echo $BRANCH", "/repo", "it's", true)
-                .expect("POSIX quoting should succeed");
+            let result = substitute_variables(
+                "echo $BRANCH",
+                &ActionVariables::basic("/repo", "it's"),
+                true,
+            )
+            .expect("POSIX quoting should succeed");
             assert_eq!(result, "echo 'it'\\''s'");
         }
     }
@@ -526,7 +1297,7 @@ mod tests {
         action.working_directory = Some("repo_root".to_string());
         save_custom_action(repo.path_str(), action).await.unwrap();
 
-        let result = run_custom_action(repo.path_str(), "1".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "1".to_string(), None).await;
         assert!(result.is_ok());
         assert!(result.unwrap().success);
     }
@@ -535,8 +1306,7 @@ mod tests {
     fn test_substitute_variables_both_placeholders() {
         let result = substitute_variables(
             "$REPO is on $BRANCH and $REPO again",
-            "/my/repo",
-            "develop",
+            &ActionVariables::basic("/my/repo", "develop"),
             false,
         )
         .expect("substitution should succeed");
@@ -545,15 +1315,19 @@ mod tests {
 
     #[test]
     fn test_substitute_variables_empty_input() {
-        let result =
-            substitute_variables("", "/repo", "main", false).expect("substitution should succeed");
+        let result = substitute_variables("", &ActionVariables::basic("/repo", "main"), false)
+            .expect("substitution should succeed");
         assert_eq!(result, "");
     }
 
     #[test]
     fn test_substitute_variables_empty_branch() {
-        let result = substitute_variables("branch is $BRANCH", "/repo", "", false)
-            .expect("substitution should succeed");
+        let result = substitute_variables(
+            "branch is $BRANCH",
+            &ActionVariables::basic("/repo", ""),
+            false,
+        )
+        .expect("substitution should succeed");
         assert_eq!(result, "branch is ");
     }
 
@@ -586,6 +1360,7 @@ mod tests {
             show_in_toolbar: true,
             open_in_terminal: false,
             confirm_before_run: true,
+            scope: ActionScope::Commit,
         };
 
         let json = serde_json::to_string(&action).unwrap();
@@ -593,6 +1368,7 @@ mod tests {
         assert!(json.contains("openInTerminal"));
         assert!(json.contains("confirmBeforeRun"));
         assert!(json.contains("workingDirectory"));
+        assert!(json.contains("\"scope\":\"commit\""));
     }
 
     #[tokio::test]
@@ -606,7 +1382,7 @@ mod tests {
         action.working_directory = Some(format!("{}/subdir", repo.path_str()));
         save_custom_action(repo.path_str(), action).await.unwrap();
 
-        let result = run_custom_action(repo.path_str(), "1".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "1".to_string(), None).await;
         assert!(result.is_ok());
         let action_result = result.unwrap();
         assert!(action_result.success);
@@ -620,7 +1396,7 @@ mod tests {
         action.working_directory = Some("$REPO".to_string());
         save_custom_action(repo.path_str(), action).await.unwrap();
 
-        let result = run_custom_action(repo.path_str(), "1".to_string()).await;
+        let result = run_custom_action(repo.path_str(), "1".to_string(), None).await;
         assert!(result.is_ok());
         assert!(result.unwrap().success);
     }
@@ -643,7 +1419,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_custom_action_without_git_repo() {
-        let result = run_custom_action("/nonexistent/path".to_string(), "1".to_string()).await;
+        let result =
+            run_custom_action("/nonexistent/path".to_string(), "1".to_string(), None).await;
         assert!(result.is_err());
     }
 
@@ -670,6 +1447,268 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_custom_action_context_placeholders() {
+        let repo = TestRepo::with_initial_commit();
+        repo.add_remote("origin", "https://example.com/team/repo.git");
+        let mut action = make_action(
+            "1",
+            "Inspect",
+            "printf '%s|' ${branch} ${commit} ${remoteUrl} ${selectedFiles} ${prompt:Ticket}",
+        );
+        action.scope = ActionScope::File;
+        save_custom_action(repo.path_str(), action).await.unwrap();
+
+        let mut prompts = HashMap::new();
+        prompts.insert("Ticket".to_string(), "ABC-1; rm -rf /".to_string());
+        let context = ActionContext {
+            commit: Some("HEAD".to_string()),
+            files: vec!["a file.txt".to_string(), "it's.md".to_string()],
+            prompts,
+            ..Default::default()
+        };
+        let result = run_custom_action(repo.path_str(), "1".to_string(), Some(context))
+            .await
+            .unwrap();
+        assert!(result.success, "{}", result.stderr);
+        assert_eq!(
+            result.stdout,
+            format!(
+                "main|{}|https://example.com/team/repo.git|a file.txt|it's.md|ABC-1; rm -rf /|",
+                repo.head_oid()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_custom_action_enforces_scope() {
+        let repo = TestRepo::with_initial_commit();
+        let mut action = make_action("1", "Show", "echo ${commit}");
+        action.scope = ActionScope::Commit;
+        save_custom_action(repo.path_str(), action).await.unwrap();
+
+        let err = run_custom_action(repo.path_str(), "1".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("a selected commit"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_run_custom_action_rejects_bad_commit() {
+        let repo = TestRepo::with_initial_commit();
+        save_custom_action(repo.path_str(), make_action("1", "Show", "echo ${commit}"))
+            .await
+            .unwrap();
+        let context = ActionContext {
+            commit: Some("no-such-rev".to_string()),
+            ..Default::default()
+        };
+        let result = run_custom_action(repo.path_str(), "1".to_string(), Some(context)).await;
+        assert!(matches!(result, Err(LeviathanError::CommitNotFound(_))));
+    }
+
+    #[test]
+    fn test_substitute_variables_rejects_malformed_placeholder() {
+        let vars = ActionVariables::basic("/repo", "main");
+        assert!(substitute_variables("echo ${repo", &vars, false).is_err());
+        assert!(substitute_variables("echo ${branch:-x}", &vars, false).is_err());
+        assert!(substitute_variables("echo ${prompt:}", &vars, false).is_err());
+        assert!(substitute_variables("echo ${prompt:Name", &vars, false).is_err());
+        assert!(substitute_variables("echo ${prompt:Name}", &vars, false).is_err());
+        assert!(substitute_variables("echo ${commit}", &vars, false).is_err());
+        // A bare `$` that is not a known token is left alone.
+        assert_eq!(
+            substitute_variables("echo $HOME ${repo}", &vars, false).unwrap(),
+            "echo $HOME /repo"
+        );
+    }
+
+    #[test]
+    fn test_substitute_variables_passes_shell_expansions_through() {
+        let vars = ActionVariables::basic("/repo", "main");
+        assert_eq!(
+            substitute_variables(
+                "echo ${HOME} ${1} ${VAR:-default} ${repoRoot} ${branch}",
+                &vars,
+                false
+            )
+            .unwrap(),
+            "echo ${HOME} ${1} ${VAR:-default} ${repoRoot} main"
+        );
+        assert_eq!(
+            substitute_variables("echo '${' ${repo}", &vars, false).unwrap(),
+            "echo '${' /repo"
+        );
+        assert_eq!(
+            substitute_variables("echo ${X:-${repo}}", &vars, false).unwrap(),
+            "echo ${X:-/repo}"
+        );
+    }
+
+    #[test]
+    fn test_substitute_variables_does_not_reexpand_values() {
+        let vars = ActionVariables::basic("/repo", "${repo}$REPO");
+        assert_eq!(
+            substitute_variables("${branch}", &vars, false).unwrap(),
+            "${repo}$REPO"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_custom_action_prompts() {
+        let repo = TestRepo::with_initial_commit();
+        let mut action = make_action("1", "Tag", "git tag ${prompt:Tag name}");
+        action.arguments = Some("-m ${prompt:Message} ${prompt:Tag name}".to_string());
+        save_custom_action(repo.path_str(), action).await.unwrap();
+
+        let labels = get_custom_action_prompts(repo.path_str(), "1".to_string())
+            .await
+            .unwrap();
+        assert_eq!(labels, vec!["Tag name".to_string(), "Message".to_string()]);
+    }
+
+    #[test]
+    fn test_custom_action_without_scope_deserializes() {
+        let json = r#"{"id":"1","name":"Old","command":"echo","arguments":null,
+            "workingDirectory":null,"shortcut":null,"showInToolbar":false,
+            "openInTerminal":false,"confirmBeforeRun":false}"#;
+        let action: CustomAction = serde_json::from_str(json).unwrap();
+        assert_eq!(action.scope, ActionScope::Repository);
+    }
+
+    fn prepared(command: &str, working_dir: &str) -> PreparedRun {
+        PreparedRun {
+            action: make_action("1", "Test", command),
+            full_command: command.to_string(),
+            working_dir: working_dir.to_string(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_action_streams_lines() {
+        let repo = TestRepo::with_initial_commit();
+        let lines = std::sync::Mutex::new(Vec::new());
+        let outcome = execute_action(
+            &prepared("echo one; echo two 1>&2; printf three", &repo.path_str()),
+            None,
+            &|stream, line| lines.lock().unwrap().push((stream, line.to_string())),
+        )
+        .unwrap();
+
+        assert_eq!(outcome.exit_code, Some(0));
+        assert_eq!(outcome.stdout, "one\nthree");
+        assert_eq!(outcome.stderr, "two\n");
+        let lines = lines.into_inner().unwrap();
+        assert!(lines.contains(&(ActionStream::Stdout, "one".to_string())));
+        assert!(lines.contains(&(ActionStream::Stderr, "two".to_string())));
+        assert!(lines.contains(&(ActionStream::Stdout, "three".to_string())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_action_cancel_kills_process_group() {
+        let repo = TestRepo::with_initial_commit();
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            canceller.cancel();
+        });
+
+        let timer = std::time::Instant::now();
+        // The backgrounded sleep holds stdout open after the shell is gone;
+        // only killing the whole group ends the run.
+        let outcome = execute_action(
+            &prepared("echo started; sleep 30 & sleep 30", &repo.path_str()),
+            Some(&token),
+            &|_, _| {},
+        )
+        .unwrap();
+
+        assert!(outcome.cancelled);
+        assert_eq!(outcome.exit_code, None);
+        assert!(outcome.stdout.contains("started"));
+        assert!(timer.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_run_custom_action_records_history() {
+        let repo = TestRepo::with_initial_commit();
+        save_custom_action(repo.path_str(), make_action("1", "Echo", "echo hello"))
+            .await
+            .unwrap();
+        save_custom_action(repo.path_str(), make_action("2", "Fail", "exit 3"))
+            .await
+            .unwrap();
+        run_custom_action(repo.path_str(), "1".to_string(), None)
+            .await
+            .unwrap();
+        run_custom_action(repo.path_str(), "2".to_string(), None)
+            .await
+            .unwrap();
+
+        let runs = get_custom_action_runs(repo.path_str(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 2);
+        // Newest first
+        assert_eq!(runs[0].action_id, "2");
+        assert_eq!(runs[0].exit_code, Some(3));
+        assert!(!runs[0].success);
+        assert_eq!(runs[1].action_name, "Echo");
+        assert!(runs[1].stdout.contains("hello"));
+        assert_ne!(runs[0].run_id, runs[1].run_id);
+
+        let only_echo = get_custom_action_runs(repo.path_str(), Some("1".to_string()), None)
+            .await
+            .unwrap();
+        assert_eq!(only_echo.len(), 1);
+
+        clear_custom_action_runs(repo.path_str()).await.unwrap();
+        assert!(get_custom_action_runs(repo.path_str(), None, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_run_history_is_capped() {
+        let repo = TestRepo::with_initial_commit();
+        let run = prepared("true", &repo.path_str());
+        let outcome = ActionOutcome {
+            exit_code: Some(0),
+            stdout: "ok\n".to_string(),
+            stderr: String::new(),
+            cancelled: false,
+        };
+        for i in 0..MAX_RUN_HISTORY + 5 {
+            let record = new_run_record(
+                format!("run-{}", i),
+                &run,
+                &outcome,
+                std::time::SystemTime::now(),
+                std::time::Duration::from_millis(1),
+            );
+            record_run(&repo.path, &record);
+        }
+
+        let runs = read_runs(&repo.path).unwrap();
+        assert_eq!(runs.len(), MAX_RUN_HISTORY);
+        assert_eq!(runs[0].run_id, "run-5");
+    }
+
+    #[test]
+    fn test_output_tail_keeps_end() {
+        let text = format!("{}end", "x".repeat(MAX_RECORDED_OUTPUT));
+        let (tail, truncated) = output_tail(&text);
+        assert!(truncated);
+        assert_eq!(tail.len(), MAX_RECORDED_OUTPUT);
+        assert!(tail.ends_with("end"));
+        assert_eq!(output_tail("short"), ("short".to_string(), false));
+    }
+
     #[test]
     fn test_get_current_branch_invalid_path() {
         let branch = get_current_branch(Path::new("/nonexistent/repo"));
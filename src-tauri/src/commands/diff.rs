@@ -4,6 +4,7 @@ use base64::{engine::general_purpose::STANDARD, Engine};
 use std::path::Path;
 use tauri::command;
 
+use super::partial_clone;
 use super::path_utils::validate_path_within_repo;
 use crate::error::Result;
 use crate::models::diff::{get_image_type, is_image_file};
@@ -549,9 +550,20 @@ pub struct CommitFileEntry {
 /// Get list of files changed in a commit
 #[command]
 pub async fn get_commit_files(path: String, commit_oid: String) -> Result<Vec<CommitFileEntry>> {
-    let repo = git2::Repository::open(Path::new(&path))?;
-    let commit = repo.find_commit(git2::Oid::from_str(&commit_oid)?)?;
+    let oid = git2::Oid::from_str(&commit_oid)?;
+
+    // Listing the files needs the trees, which a treeless clone may not have,
+    // but not the content of every changed file.
+    partial_clone::prefetch(&path, move |repo| {
+        let commit = repo.find_commit(oid)?;
+        Ok(std::iter::once(commit.tree_id())
+            .chain(commit.parent(0).ok().map(|p| p.tree_id()))
+            .collect())
+    })
+    .await?;
 
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let commit = repo.find_commit(oid)?;
     let parent = commit.parent(0).ok();
     let parent_tree = parent.as_ref().map(|p| p.tree()).transpose()?;
     let commit_tree = commit.tree()?;
@@ -559,8 +571,22 @@ pub async fn get_commit_files(path: String, commit_oid: String) -> Result<Vec<Co
     let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), None)?;
 
     // Detect renames/copies (git's default) so a moved file is a single
-    // "renamed" entry instead of a delete + add pair.
-    detect_renames(&mut diff)?;
+    // "renamed" entry instead of a delete + add pair. Where a partial clone
+    // does not have the content, only identical files are paired, and line
+    // counts below are left at zero.
+    let blobs: Vec<git2::Oid> = diff
+        .deltas()
+        .flat_map(|d| [d.old_file(), d.new_file()])
+        .filter(|f| f.mode() != git2::FileMode::Commit)
+        .map(|f| f.id())
+        .collect();
+    if partial_clone::missing_objects(&repo, &blobs)?.is_empty() {
+        detect_renames(&mut diff)?;
+    } else {
+        let mut find_opts = git2::DiffFindOptions::new();
+        find_opts.renames(true).exact_match_only(true);
+        diff.find_similar(Some(&mut find_opts))?;
+    }
 
     // First pass: collect file info
     let mut files: Vec<CommitFileEntry> = Vec::new();
@@ -600,16 +626,13 @@ pub async fn get_commit_files(path: String, commit_oid: String) -> Result<Vec<Co
         });
     }
 
-    // Second pass: count additions/deletions
-    let stats = diff.stats()?;
-    for i in 0..stats.files_changed() {
-        if i < files.len() {
-            // Get stats for this file from the diff
-            if let Some(patch) = git2::Patch::from_diff(&diff, i).ok().flatten() {
-                let (_, additions, deletions) = patch.line_stats()?;
-                files[i].additions = additions;
-                files[i].deletions = deletions;
-            }
+    // Second pass: count additions/deletions, for the files whose content
+    // is here
+    for (i, file) in files.iter_mut().enumerate() {
+        if let Some(patch) = git2::Patch::from_diff(&diff, i).ok().flatten() {
+            let (_, additions, deletions) = patch.line_stats()?;
+            file.additions = additions;
+            file.deletions = deletions;
         }
     }
 
@@ -734,9 +757,16 @@ pub async fn get_commit_file_diff(
     file_path: String,
     max_lines: Option<u32>,
 ) -> Result<DiffFile> {
-    let repo = git2::Repository::open(Path::new(&path))?;
-    let commit = repo.find_commit(git2::Oid::from_str(&commit_oid)?)?;
+    let oid = git2::Oid::from_str(&commit_oid)?;
+    let pathspec = file_path.clone();
+    partial_clone::prefetch(&path, move |repo| {
+        let commit = repo.find_commit(oid)?;
+        partial_clone::commit_diff_blobs(repo, &commit, Some(&pathspec))
+    })
+    .await?;
 
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let commit = repo.find_commit(oid)?;
     let parent = commit.parent(0).ok();
     let parent_tree = parent.as_ref().map(|p| p.tree()).transpose()?;
     let commit_tree = commit.tree()?;
@@ -799,6 +829,19 @@ pub async fn get_file_blame(
     start_line: Option<u32>,
     end_line: Option<u32>,
 ) -> Result<BlameResult> {
+    // Blame reads every version of the file; fetch them in one batch rather
+    // than failing on the first one a partial clone does not have.
+    let start = commit_oid.as_deref().map(git2::Oid::from_str).transpose()?;
+    let history_path = file_path.clone();
+    partial_clone::prefetch(&path, move |repo| {
+        let start = match start {
+            Some(oid) => oid,
+            None => repo.head()?.peel_to_commit()?.id(),
+        };
+        partial_clone::file_history_blobs(repo, start, &history_path, None)
+    })
+    .await?;
+
     let repo = git2::Repository::open(Path::new(&path))?;
 
     let mut blame_opts = git2::BlameOptions::new();
@@ -0,0 +1,1036 @@
+//! Repository health doctor
+//!
+//! One diagnostics pass over a repository that turns the raw numbers from
+//! the maintenance commands (`get_repo_size_info`, `get_pack_info`,
+//! `verify_repository`) and a walk of history into findings: what is wrong,
+//! how much it matters, and what to run about it. Findings are ordered
+//! critical first, so the top of the list is always what to fix first.
+//!
+//! Every check is read-only. Nothing here repacks, rewrites or fetches; the
+//! suggested fixes are for the user (or the UI) to run.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+use std::time::Instant;
+
+use tauri::command;
+
+use crate::commands::lfs_analysis::{cached_objects, inspect_blob, scan_history};
+use crate::commands::maintenance::{get_pack_info, get_repo_size_info, verify_repository};
+use crate::error::{LeviathanError, Result};
+
+/// Blobs at least this large are reported as huge
+const HUGE_BLOB_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Blobs at least this large are rejected outright by GitHub, so pushing
+/// any branch that contains one fails.
+const REJECTED_BLOB_BYTES: u64 = 100 * 1024 * 1024;
+
+/// `gc.auto` and `gc.autoPackLimit` defaults: the points at which git itself
+/// considers the repository due for a `gc`.
+const DEFAULT_GC_AUTO: i64 = 6700;
+const DEFAULT_GC_AUTO_PACK_LIMIT: i64 = 50;
+
+/// Below this many commits, history walks are fast enough without a
+/// commit-graph that suggesting one would be noise.
+const COMMIT_GRAPH_MIN_COMMITS: usize = 1000;
+
+/// Reflog entries per ref before the log is reported as very long
+const LONG_REFLOG_ENTRIES: usize = 10_000;
+
+/// Blobs larger than this are not read when checking line endings
+const MAX_EOL_CHECK_BYTES: u64 = 1024 * 1024;
+
+/// Affected items listed per finding; `item_count` has the full number.
+const MAX_ITEMS: usize = 20;
+
+/// Placeholder addresses that show up in copied setup instructions
+const PLACEHOLDER_EMAILS: &[&str] = &[
+    "you@example.com",
+    "your@email.com",
+    "email@example.com",
+    "user@example.com",
+];
+
+/// How urgent a finding is. Ordered most urgent first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthSeverity {
+    /// Data is at risk, or pushing/cloning will fail
+    Critical,
+    /// Slower or surprising behaviour that should be fixed
+    Warning,
+    /// Worth knowing; fixing it is optional
+    Info,
+}
+
+/// One problem found by the doctor
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthFinding {
+    /// Stable identifier of the check that produced this finding
+    pub check: String,
+    pub severity: HealthSeverity,
+    pub title: String,
+    pub detail: String,
+    /// What to do about it, in words
+    pub suggestion: String,
+    /// A command that fixes it, when a single command does
+    pub fix_command: Option<String>,
+    /// Affected paths, refs or objects, capped at [`MAX_ITEMS`]
+    pub items: Vec<String>,
+    pub item_count: usize,
+}
+
+impl HealthFinding {
+    fn new(check: &str, severity: HealthSeverity, title: String, detail: String) -> Self {
+        Self {
+            check: check.to_string(),
+            severity,
+            title,
+            detail,
+            suggestion: String::new(),
+            fix_command: None,
+            items: Vec::new(),
+            item_count: 0,
+        }
+    }
+
+    fn suggest(mut self, suggestion: &str, fix_command: Option<&str>) -> Self {
+        self.suggestion = suggestion.to_string();
+        self.fix_command = fix_command.map(|c| c.to_string());
+        self
+    }
+
+    fn with_items(mut self, items: Vec<String>) -> Self {
+        self.item_count = items.len();
+        self.items = items.into_iter().take(MAX_ITEMS).collect();
+        self
+    }
+}
+
+/// Result of a doctor run
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// Findings, most urgent first
+    pub findings: Vec<HealthFinding>,
+    /// Identifiers of every check that ran, including those that found
+    /// nothing, so the UI can show them as passed
+    pub checks_run: Vec<String>,
+    pub commits_scanned: usize,
+    pub duration_ms: u64,
+}
+
+fn format_size(bytes: u64) -> String {
+    const MIB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= 1024.0 * MIB {
+        format!("{:.1} GiB", bytes as f64 / (1024.0 * MIB))
+    } else if bytes as f64 >= MIB {
+        format!("{:.1} MiB", bytes as f64 / MIB)
+    } else {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    }
+}
+
+fn config_i64(repo: &git2::Repository, key: &str, default: i64) -> i64 {
+    repo.config()
+        .ok()
+        .and_then(|c| c.get_i64(key).ok())
+        .unwrap_or(default)
+}
+
+/// Huge blobs anywhere in history. Every clone downloads every one of them,
+/// forever, even after the file is deleted.
+fn check_huge_blobs(
+    blobs: &[(String, u64)],
+    threshold: u64,
+    rejected: u64,
+) -> Option<HealthFinding> {
+    let mut huge: Vec<&(String, u64)> = blobs.iter().filter(|(_, s)| *s >= threshold).collect();
+    if huge.is_empty() {
+        return None;
+    }
+    huge.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let total: u64 = huge.iter().map(|(_, s)| s).sum();
+    let severity = if huge[0].1 >= rejected {
+        HealthSeverity::Critical
+    } else {
+        HealthSeverity::Warning
+    };
+    let mut detail = format!(
+        "{} blob(s) of {} or more, {} in total, are in history. Every clone downloads them, \
+         even if the files were deleted since.",
+        huge.len(),
+        format_size(threshold),
+        format_size(total)
+    );
+    if severity == HealthSeverity::Critical {
+        detail.push_str(&format!(
+            " Blobs over {} are rejected by GitHub, so pushes containing them fail.",
+            format_size(rejected)
+        ));
+    }
+    let above = format!("{}mb", threshold / (1024 * 1024));
+    Some(
+        HealthFinding::new(
+            "huge-blobs",
+            severity,
+            "Huge files in history".to_string(),
+            detail,
+        )
+        .suggest(
+            "Move these files to Git LFS, rewriting the history that contains them.",
+            Some(&format!(
+                "git lfs migrate import --everything --above={}",
+                above
+            )),
+        )
+        .with_items(
+            huge.iter()
+                .map(|(path, size)| format!("{} ({})", path, format_size(*size)))
+                .collect(),
+        ),
+    )
+}
+
+/// Too many loose objects or packs: every object lookup gets slower, and
+/// git will start an automatic gc in the middle of some unrelated command.
+fn check_object_storage(
+    repo: &git2::Repository,
+    loose_objects: u32,
+    packs: u32,
+    pack_size_kb: usize,
+) -> Vec<HealthFinding> {
+    let mut findings = Vec::new();
+
+    let gc_auto = config_i64(repo, "gc.auto", DEFAULT_GC_AUTO);
+    let gc_auto = if gc_auto > 0 {
+        gc_auto
+    } else {
+        DEFAULT_GC_AUTO
+    };
+    if loose_objects as i64 > gc_auto {
+        findings.push(
+            HealthFinding::new(
+                "loose-objects",
+                HealthSeverity::Warning,
+                "Too many loose objects".to_string(),
+                format!(
+                    "{} loose objects (git packs them automatically above {}). Each is a \
+                     separate file, which slows object lookups and wastes disk space.",
+                    loose_objects, gc_auto
+                ),
+            )
+            .suggest("Pack them with a garbage collection.", Some("git gc")),
+        );
+    }
+
+    let pack_limit = config_i64(repo, "gc.autoPackLimit", DEFAULT_GC_AUTO_PACK_LIMIT);
+    let pack_limit = if pack_limit > 0 {
+        pack_limit
+    } else {
+        DEFAULT_GC_AUTO_PACK_LIMIT
+    };
+    if packs as i64 > pack_limit {
+        findings.push(
+            HealthFinding::new(
+                "too-many-packs",
+                HealthSeverity::Warning,
+                "Too many pack files".to_string(),
+                format!(
+                    "{} pack files ({} KB) (git consolidates them above {}). Every object \
+                     lookup searches each pack in turn.",
+                    packs, pack_size_kb, pack_limit
+                ),
+            )
+            .suggest(
+                "Consolidate the packs, or enable scheduled maintenance so incremental \
+                 repacks keep the count down.",
+                Some("git repack -d"),
+            ),
+        );
+    }
+
+    findings
+}
+
+/// No commit-graph: log, blame, ahead/behind and merge-base all parse
+/// every commit object they touch instead of reading a compact index.
+fn check_commit_graph(repo: &git2::Repository, commits: usize) -> Option<HealthFinding> {
+    if commits < COMMIT_GRAPH_MIN_COMMITS {
+        return None;
+    }
+    let info = repo.commondir().join("objects").join("info");
+    if info.join("commit-graph").exists()
+        || info
+            .join("commit-graphs")
+            .join("commit-graph-chain")
+            .exists()
+    {
+        return None;
+    }
+    Some(
+        HealthFinding::new(
+            "commit-graph",
+            HealthSeverity::Warning,
+            "No commit-graph".to_string(),
+            format!(
+                "History has {} commits but no commit-graph, so history walks read every \
+                 commit object from the object database.",
+                commits
+            ),
+        )
+        .suggest(
+            "Write a commit-graph, and enable scheduled maintenance to keep it current.",
+            Some("git commit-graph write --reachable --changed-paths"),
+        ),
+    )
+}
+
+fn count_lines(path: &Path) -> usize {
+    std::fs::read(path)
+        .map(|data| data.iter().filter(|&&b| b == b'\n').count())
+        .unwrap_or(0)
+}
+
+fn collect_reflogs(dir: &Path, prefix: &str, out: &mut Vec<(String, usize)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        let refname = format!("{}{}", prefix, name);
+        if path.is_dir() {
+            collect_reflogs(&path, &format!("{}/", refname), out);
+        } else {
+            out.push((refname, count_lines(&path)));
+        }
+    }
+}
+
+/// Reflogs that have grown very long, usually because `gc.reflogExpire` is
+/// set to `never` or gc never runs. Every reflog read loads the whole file.
+fn check_reflogs(repo: &git2::Repository, threshold: usize) -> Option<HealthFinding> {
+    let mut logs = Vec::new();
+    collect_reflogs(&repo.commondir().join("logs"), "", &mut logs);
+    if repo.path() != repo.commondir() {
+        let head = repo.path().join("logs").join("HEAD");
+        logs.retain(|(name, _)| name != "HEAD");
+        logs.push(("HEAD".to_string(), count_lines(&head)));
+    }
+    let mut long: Vec<(String, usize)> = logs.into_iter().filter(|(_, n)| *n > threshold).collect();
+    if long.is_empty() {
+        return None;
+    }
+    long.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Some(
+        HealthFinding::new(
+            "long-reflogs",
+            HealthSeverity::Info,
+            "Very long reflogs".to_string(),
+            format!(
+                "{} reflog(s) have more than {} entries. They keep otherwise unreachable \
+                 commits alive and are read in full whenever the reflog is shown.",
+                long.len(),
+                threshold
+            ),
+        )
+        .suggest(
+            "Expire old reflog entries.",
+            Some("git reflog expire --expire=90.days.ago --all"),
+        )
+        .with_items(
+            long.iter()
+                .map(|(name, n)| format!("{} ({} entries)", name, n))
+                .collect(),
+        ),
+    )
+}
+
+/// Refs pointing at objects that don't exist, and symbolic refs pointing
+/// at refs that don't exist (a stale `refs/remotes/origin/HEAD` after the
+/// remote's default branch was renamed is the common one).
+fn check_refs(repo: &git2::Repository) -> Result<Vec<HealthFinding>> {
+    let mut broken = Vec::new();
+    let mut dangling = Vec::new();
+    for reference in repo.references()? {
+        let reference = match reference {
+            Ok(r) => r,
+            Err(e) => {
+                broken.push(e.message().to_string());
+                continue;
+            }
+        };
+        let Ok(name) = reference.name() else {
+            continue;
+        };
+        let name = name.to_string();
+        match reference.kind() {
+            Some(git2::ReferenceType::Symbolic) => {
+                if reference.resolve().is_err() {
+                    let target = reference
+                        .symbolic_target()
+                        .ok()
+                        .flatten()
+                        .unwrap_or("?")
+                        .to_string();
+                    dangling.push(format!("{} -> {}", name, target));
+                }
+            }
+            _ => {
+                if let Some(oid) = reference.target() {
+                    if repo.find_object(oid, None).is_err() {
+                        broken.push(format!("{} -> {}", name, oid));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut findings = Vec::new();
+    if !broken.is_empty() {
+        findings.push(
+            HealthFinding::new(
+                "broken-refs",
+                HealthSeverity::Critical,
+                "Refs pointing at missing objects".to_string(),
+                format!(
+                    "{} ref(s) point at objects that are not in the repository. Commands \
+                     that walk all refs (fetch, gc, push --all) fail on them.",
+                    broken.len()
+                ),
+            )
+            .suggest(
+                "Delete each broken ref with `git update-ref -d <ref>`, or restore the \
+                 missing objects by fetching from a remote that has them.",
+                None,
+            )
+            .with_items(broken),
+        );
+    }
+    if !dangling.is_empty() {
+        findings.push(
+            HealthFinding::new(
+                "dangling-refs",
+                HealthSeverity::Warning,
+                "Symbolic refs pointing nowhere".to_string(),
+                format!(
+                    "{} symbolic ref(s) point at refs that don't exist.",
+                    dangling.len()
+                ),
+            )
+            .suggest(
+                "For a remote's HEAD, re-detect it with `git remote set-head <remote> \
+                 --auto`; otherwise delete it with `git symbolic-ref -d <ref>`.",
+                None,
+            )
+            .with_items(dangling),
+        );
+    }
+    Ok(findings)
+}
+
+/// Tracked paths that differ only in case. On the default case-insensitive
+/// filesystems of macOS and Windows only one of them can exist, so the
+/// checkout there is permanently "modified". Directories count too:
+/// `Docs/a` and `docs/b` land in one directory.
+fn check_case_collisions(index: &git2::Index) -> Option<HealthFinding> {
+    let mut paths: BTreeSet<String> = BTreeSet::new();
+    for entry in index.iter() {
+        let path = String::from_utf8_lossy(&entry.path).to_string();
+        let mut end = 0;
+        while let Some(pos) = path[end..].find('/') {
+            end += pos;
+            paths.insert(path[..end].to_string());
+            end += 1;
+        }
+        paths.insert(path);
+    }
+
+    let mut groups: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for path in &paths {
+        groups.entry(path.to_lowercase()).or_default().push(path);
+    }
+    let collisions: Vec<String> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|group| group.join(", "))
+        .collect();
+    if collisions.is_empty() {
+        return None;
+    }
+    Some(
+        HealthFinding::new(
+            "case-collisions",
+            HealthSeverity::Warning,
+            "Paths that differ only in case".to_string(),
+            format!(
+                "{} set(s) of tracked paths differ only in letter case. On macOS and \
+                 Windows only one of each set can be checked out.",
+                collisions.len()
+            ),
+        )
+        .suggest("Rename all but one path in each set with `git mv`.", None)
+        .with_items(collisions),
+    )
+}
+
+/// Whether the attributes for `path` ask git to normalize its line endings
+/// in the repository. `text=auto` only applies to content git detects as
+/// text, which the caller checks.
+fn eol_normalized(repo: &git2::Repository, path: &Path) -> (bool, bool) {
+    let flags = git2::AttrCheckFlags::FILE_THEN_INDEX;
+    let text = git2::AttrValue::from_string(repo.get_attr(path, "text", flags).ok().flatten());
+    let eol = git2::AttrValue::from_string(repo.get_attr(path, "eol", flags).ok().flatten());
+    match text {
+        git2::AttrValue::False => (false, false),
+        git2::AttrValue::True => (true, false),
+        git2::AttrValue::String("auto") => (true, true),
+        // An eol attribute on its own implies text (gitattributes(5)).
+        _ => (matches!(eol, git2::AttrValue::String(_)), false),
+    }
+}
+
+/// Staged files whose `.gitattributes` say "store with LF" but whose stored
+/// content has CRLF. Every collaborator with autocrlf or an eol rule sees
+/// them as modified without touching them.
+fn check_eol(repo: &git2::Repository, index: &git2::Index) -> Result<Option<HealthFinding>> {
+    let odb = repo.odb()?;
+    let mut violations = Vec::new();
+    for entry in index.iter() {
+        let path = String::from_utf8_lossy(&entry.path).to_string();
+        let (normalized, auto) = eol_normalized(repo, Path::new(&path));
+        if !normalized || (entry.file_size as u64) > MAX_EOL_CHECK_BYTES {
+            continue;
+        }
+        let Ok(blob) = odb.read(entry.id) else {
+            continue;
+        };
+        let data = blob.data();
+        if auto && data.contains(&0) {
+            continue;
+        }
+        if data.windows(2).any(|w| w == b"\r\n") {
+            violations.push(path);
+        }
+    }
+    if violations.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        HealthFinding::new(
+            "eol-violations",
+            HealthSeverity::Warning,
+            "Files with CRLF despite .gitattributes".to_string(),
+            format!(
+                "{} file(s) are stored with CRLF line endings although .gitattributes \
+                 marks them as text to normalize. They show as modified for anyone whose \
+                 checkout applies the rule.",
+                violations.len()
+            ),
+        )
+        .suggest(
+            "Renormalize the files and commit the result.",
+            Some("git add --renormalize ."),
+        )
+        .with_items(violations),
+    ))
+}
+
+/// A shallow clone: history, blame, merge-base and ahead/behind all stop at
+/// the graft point, and some merges can't find their base at all.
+fn check_shallow(repo: &git2::Repository) -> Option<HealthFinding> {
+    if !repo.is_shallow() {
+        return None;
+    }
+    Some(
+        HealthFinding::new(
+            "shallow-clone",
+            HealthSeverity::Warning,
+            "Shallow clone".to_string(),
+            "History is truncated, so log, blame, file history and ahead/behind counts are \
+             incomplete, and merges or rebases across the cut-off can't find their base."
+                .to_string(),
+        )
+        .suggest("Fetch the rest of history.", Some("git fetch --unshallow")),
+    )
+}
+
+/// `user.email` missing, malformed, a placeholder, or guessed by git from
+/// the hostname. Commits made with it are attributed to no one on the
+/// hosting service, and can't be fixed without rewriting them.
+fn check_user_email(repo: &git2::Repository) -> Option<HealthFinding> {
+    let email = repo
+        .config()
+        .ok()
+        .and_then(|c| c.get_string("user.email").ok());
+    let problem = match email.as_deref().map(str::trim) {
+        None | Some("") => "user.email is not set, so git refuses to commit or guesses an \
+                            address from the hostname."
+            .to_string(),
+        Some(email) => {
+            let lower = email.to_lowercase();
+            let domain = lower.rsplit('@').next().unwrap_or("");
+            if !lower.contains('@') || domain.is_empty() || lower.contains(' ') {
+                format!("user.email '{}' is not a valid address.", email)
+            } else if PLACEHOLDER_EMAILS.contains(&lower.as_str()) {
+                format!("user.email '{}' is a placeholder address.", email)
+            } else if domain.ends_with("(none)")
+                || domain.ends_with(".localdomain")
+                || domain.ends_with(".local")
+                || !domain.contains('.')
+            {
+                format!(
+                    "user.email '{}' looks machine-generated; hosting services can't link \
+                     commits to an account with it.",
+                    email
+                )
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(
+        HealthFinding::new(
+            "user-email",
+            HealthSeverity::Warning,
+            "Misconfigured commit email".to_string(),
+            problem,
+        )
+        .suggest(
+            "Set user.email to the address registered with your hosting service, with \
+             `git config --global user.email <address>` (or without --global for this \
+             repository only).",
+            None,
+        ),
+    )
+}
+
+/// LFS objects that can't be reached: pointers checked out at HEAD whose
+/// content isn't in the local cache (the file is a pointer stub on disk),
+/// and cached objects that no pointer in history refers to any more.
+fn check_lfs(
+    repo: &git2::Repository,
+    history_pointers: &HashSet<String>,
+) -> Result<Vec<HealthFinding>> {
+    let (_, cached) = cached_objects(repo);
+    let cached_oids: HashSet<&str> = cached.iter().map(|o| o.oid.as_str()).collect();
+
+    let mut missing = Vec::new();
+    if let Ok(tree) = repo.head().and_then(|h| h.peel_to_tree()) {
+        let odb = repo.odb()?;
+        tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                if let (_, Some(pointer)) = inspect_blob(&odb, entry.id()) {
+                    if !cached_oids.contains(pointer.oid.as_str()) {
+                        let name = entry.name().unwrap_or("");
+                        missing.push(format!("{}{}", dir, name));
+                    }
+                }
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+    }
+
+    let mut findings = Vec::new();
+    if !missing.is_empty() {
+        findings.push(
+            HealthFinding::new(
+                "lfs-missing",
+                HealthSeverity::Warning,
+                "LFS content missing locally".to_string(),
+                format!(
+                    "{} file(s) at HEAD are LFS pointers whose content is not in the local \
+                     LFS cache, so the working tree has pointer stubs instead of the files.",
+                    missing.len()
+                ),
+            )
+            .suggest("Download the missing content.", Some("git lfs pull"))
+            .with_items(missing),
+        );
+    }
+
+    let orphaned: Vec<_> = cached
+        .iter()
+        .filter(|o| !history_pointers.contains(&o.oid))
+        .collect();
+    if !orphaned.is_empty() {
+        let size: u64 = orphaned.iter().map(|o| o.size).sum();
+        findings.push(
+            HealthFinding::new(
+                "lfs-unreachable",
+                HealthSeverity::Info,
+                "Unreachable LFS objects".to_string(),
+                format!(
+                    "{} cached LFS object(s), {}, are not referenced by any branch or tag.",
+                    orphaned.len(),
+                    format_size(size)
+                ),
+            )
+            .suggest("Remove them from the local cache.", Some("git lfs prune"))
+            .with_items(orphaned.iter().map(|o| o.oid.clone()).collect()),
+        );
+    }
+    Ok(findings)
+}
+
+/// Object database errors from `git fsck`.
+fn check_fsck(errors: Vec<String>) -> Option<HealthFinding> {
+    if errors.is_empty() {
+        return None;
+    }
+    Some(
+        HealthFinding::new(
+            "fsck",
+            HealthSeverity::Critical,
+            "Object database errors".to_string(),
+            format!(
+                "git fsck reported {} problem(s): objects are missing or corrupt.",
+                errors.len()
+            ),
+        )
+        .suggest(
+            "Back up the repository, then fetch from a remote to restore missing objects. \
+             If fsck still fails, re-clone.",
+            Some("git fsck --full"),
+        )
+        .with_items(errors),
+    )
+}
+
+/// Everything that only needs the repository, run off the async runtime.
+fn run_local_checks(
+    path: &str,
+    checks_run: &mut Vec<String>,
+) -> Result<(usize, Vec<HealthFinding>)> {
+    let repo = git2::Repository::open(path)
+        .map_err(|_| LeviathanError::RepositoryNotFound(path.to_string()))?;
+    let mut findings = Vec::new();
+
+    let (commits, blobs) = if repo.head().is_ok() {
+        scan_history(&repo)?
+    } else {
+        (0, Default::default())
+    };
+    let sizes: Vec<(String, u64)> = blobs
+        .values()
+        .filter(|b| b.pointer.is_none())
+        .map(|b| (b.path.clone(), b.size))
+        .collect();
+    let pointers: HashSet<String> = blobs
+        .values()
+        .filter_map(|b| b.pointer.as_ref().map(|p| p.oid.clone()))
+        .collect();
+    drop(blobs);
+
+    checks_run.push("huge-blobs".to_string());
+    findings.extend(check_huge_blobs(
+        &sizes,
+        HUGE_BLOB_BYTES,
+        REJECTED_BLOB_BYTES,
+    ));
+
+    checks_run.push("commit-graph".to_string());
+    findings.extend(check_commit_graph(&repo, commits));
+
+    checks_run.push("long-reflogs".to_string());
+    findings.extend(check_reflogs(&repo, LONG_REFLOG_ENTRIES));
+
+    checks_run.extend(["broken-refs".to_string(), "dangling-refs".to_string()]);
+    findings.extend(check_refs(&repo)?);
+
+    if !repo.is_bare() {
+        let index = repo.index()?;
+        checks_run.push("case-collisions".to_string());
+        findings.extend(check_case_collisions(&index));
+        checks_run.push("eol-violations".to_string());
+        findings.extend(check_eol(&repo, &index)?);
+    }
+
+    checks_run.push("shallow-clone".to_string());
+    findings.extend(check_shallow(&repo));
+
+    checks_run.push("user-email".to_string());
+    findings.extend(check_user_email(&repo));
+
+    checks_run.extend(["lfs-missing".to_string(), "lfs-unreachable".to_string()]);
+    findings.extend(check_lfs(&repo, &pointers)?);
+
+    Ok((commits, findings))
+}
+
+/// Diagnose a repository
+///
+/// Checks for huge blobs in history, too many loose objects or packs, a
+/// missing commit-graph, very long reflogs, broken and dangling refs,
+/// case-colliding paths, files violating `.gitattributes` line-ending
+/// rules, shallow history, a misconfigured `user.email`, and LFS content
+/// that is missing locally or no longer referenced. `include_fsck` (default
+/// true) also runs `git fsck`, the slowest check.
+#[command]
+pub async fn run_repository_doctor(
+    path: String,
+    include_fsck: Option<bool>,
+) -> Result<HealthReport> {
+    let started = Instant::now();
+    let mut checks_run = Vec::new();
+    let mut findings = Vec::new();
+
+    let size_info = get_repo_size_info(path.clone()).await?;
+    let pack_info = get_pack_info(path.clone()).await?;
+    let fsck_errors = if include_fsck.unwrap_or(true) {
+        checks_run.push("fsck".to_string());
+        Some(verify_repository(path.clone(), false).await?.errors)
+    } else {
+        None
+    };
+
+    let (commits_scanned, local, local_checks) = tokio::task::spawn_blocking(move || {
+        let mut checks = Vec::new();
+        let repo = git2::Repository::open(&path)
+            .map_err(|_| LeviathanError::RepositoryNotFound(path.clone()))?;
+        checks.extend(["loose-objects".to_string(), "too-many-packs".to_string()]);
+        let mut found = check_object_storage(
+            &repo,
+            size_info.loose_objects_count,
+            size_info.pack_files_count,
+            pack_info.pack_size_kb,
+        );
+        let (commits, local) = run_local_checks(&path, &mut checks)?;
+        found.extend(local);
+        Ok::<_, LeviathanError>((commits, found, checks))
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("Task failed: {}", e)))??;
+
+    checks_run.extend(local_checks);
+    findings.extend(local);
+    if let Some(errors) = fsck_errors {
+        findings.extend(check_fsck(errors));
+    }
+    // Stable sort: within a severity, findings keep check order.
+    findings.sort_by_key(|f| f.severity);
+
+    Ok(HealthReport {
+        findings,
+        checks_run,
+        commits_scanned,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    fn find<'a>(report: &'a HealthReport, check: &str) -> Option<&'a HealthFinding> {
+        report.findings.iter().find(|f| f.check == check)
+    }
+
+    #[tokio::test]
+    async fn test_healthy_repository_has_no_findings() {
+        let repo = TestRepo::with_initial_commit();
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_str("user.email", "dev@example.org")
+            .unwrap();
+
+        let report = run_repository_doctor(repo.path_str(), None).await.unwrap();
+
+        assert!(report.findings.is_empty(), "{:?}", report.findings);
+        assert!(report.checks_run.contains(&"fsck".to_string()));
+        assert!(report.checks_run.contains(&"eol-violations".to_string()));
+        assert_eq!(report.commits_scanned, 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_path_is_an_error() {
+        assert!(
+            run_repository_doctor("/nonexistent/path".to_string(), Some(false))
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_huge_blobs_are_ranked_and_escalate_past_the_rejection_limit() {
+        let blobs = vec![
+            ("small.txt".to_string(), 10),
+            ("video.mp4".to_string(), 300),
+            ("data.bin".to_string(), 150),
+        ];
+
+        let finding = check_huge_blobs(&blobs, 100, 1000).unwrap();
+        assert_eq!(finding.severity, HealthSeverity::Warning);
+        assert_eq!(finding.item_count, 2);
+        assert!(finding.items[0].starts_with("video.mp4"));
+
+        let finding = check_huge_blobs(&blobs, 100, 200).unwrap();
+        assert_eq!(finding.severity, HealthSeverity::Critical);
+        assert!(check_huge_blobs(&blobs, 1000, 2000).is_none());
+    }
+
+    #[test]
+    fn test_missing_commit_graph_only_reported_for_large_histories() {
+        let repo = TestRepo::with_initial_commit();
+        let git = repo.repo();
+        assert!(check_commit_graph(&git, 10).is_none());
+        assert!(check_commit_graph(&git, COMMIT_GRAPH_MIN_COMMITS).is_some());
+
+        let output = crate::utils::create_command("git")
+            .current_dir(&repo.path)
+            .args(["commit-graph", "write", "--reachable"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(check_commit_graph(&git, COMMIT_GRAPH_MIN_COMMITS).is_none());
+    }
+
+    #[test]
+    fn test_broken_and_dangling_refs() {
+        let repo = TestRepo::with_initial_commit();
+        std::fs::write(
+            repo.path.join(".git/refs/heads/broken"),
+            "1111111111111111111111111111111111111111\n",
+        )
+        .unwrap();
+        repo.repo()
+            .reference_symbolic(
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/gone",
+                true,
+                "test",
+            )
+            .unwrap();
+
+        let findings = check_refs(&repo.repo()).unwrap();
+
+        let broken = findings.iter().find(|f| f.check == "broken-refs").unwrap();
+        assert_eq!(broken.severity, HealthSeverity::Critical);
+        assert!(broken.items[0].starts_with("refs/heads/broken"));
+        let dangling = findings
+            .iter()
+            .find(|f| f.check == "dangling-refs")
+            .unwrap();
+        assert_eq!(
+            dangling.items,
+            vec!["refs/remotes/origin/HEAD -> refs/remotes/origin/gone"]
+        );
+    }
+
+    #[test]
+    fn test_case_collisions_include_directories() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_commit(
+            "collide",
+            &[
+                ("Makefile", "a"),
+                ("makefile", "b"),
+                ("Docs/a.md", "a"),
+                ("docs/b.md", "b"),
+            ],
+        );
+
+        let finding = check_case_collisions(&repo.repo().index().unwrap()).unwrap();
+
+        assert_eq!(finding.item_count, 2);
+        assert!(finding.items.contains(&"Docs, docs".to_string()));
+        assert!(finding.items.contains(&"Makefile, makefile".to_string()));
+    }
+
+    #[test]
+    fn test_eol_violations_follow_gitattributes() {
+        let repo = TestRepo::with_initial_commit();
+        // The usual way into this state: files committed with CRLF before a
+        // rule was added. Staging after the rule would normalize them.
+        repo.create_commit(
+            "files",
+            &[
+                ("run.sh", "echo hi\r\n"),
+                ("run.bat", "echo hi\r\n"),
+                ("notes.txt", "plain\r\n"),
+            ],
+        );
+        repo.create_commit(
+            "attrs",
+            &[(".gitattributes", "*.sh text eol=lf\n*.bat -text\n")],
+        );
+
+        let git = repo.repo();
+        let finding = check_eol(&git, &git.index().unwrap()).unwrap().unwrap();
+
+        assert_eq!(finding.items, vec!["run.sh"]);
+        assert_eq!(
+            finding.fix_command.as_deref(),
+            Some("git add --renormalize .")
+        );
+    }
+
+    #[test]
+    fn test_user_email_validation() {
+        let repo = TestRepo::with_initial_commit();
+        let git = repo.repo();
+        let mut config = git.config().unwrap();
+
+        for bad in [
+            "not-an-email",
+            "you@example.com",
+            "dev@laptop.(none)",
+            "dev@host",
+        ] {
+            config.set_str("user.email", bad).unwrap();
+            assert!(check_user_email(&git).is_some(), "{bad} should be flagged");
+        }
+        config.set_str("user.email", "dev@company.io").unwrap();
+        assert!(check_user_email(&git).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lfs_missing_and_unreachable_objects() {
+        let repo = TestRepo::with_initial_commit();
+        let missing = "a".repeat(64);
+        repo.create_commit(
+            "pointer",
+            &[(
+                "asset.bin",
+                &format!(
+                    "version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize 42\n",
+                    missing
+                ),
+            )],
+        );
+        let orphan = "b".repeat(64);
+        let dir = repo.path.join(".git/lfs/objects/bb/bb");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(&orphan), "old content").unwrap();
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_str("user.email", "dev@example.org")
+            .unwrap();
+
+        let report = run_repository_doctor(repo.path_str(), Some(false))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            find(&report, "lfs-missing").unwrap().items,
+            vec!["asset.bin"]
+        );
+        let unreachable = find(&report, "lfs-unreachable").unwrap();
+        assert_eq!(unreachable.items, vec![orphan]);
+        // Warning-level findings sort ahead of info-level ones.
+        let first_info = report
+            .findings
+            .iter()
+            .position(|f| f.severity == HealthSeverity::Info)
+            .unwrap();
+        assert!(report.findings[..first_info]
+            .iter()
+            .all(|f| f.severity < HealthSeverity::Info));
+    }
+}
@@ -84,7 +84,7 @@ fn parse_attribute_token(token: &str) -> AttributeEntry {
 /// Quoted when the pattern contains whitespace or a character that would change
 /// how the line parses; left bare otherwise, so ordinary patterns keep the plain
 /// form a human would write.
-fn format_pattern(pattern: &str) -> String {
+pub(crate) fn format_pattern(pattern: &str) -> String {
     let needs_quotes = pattern.is_empty()
         || pattern
             .chars()
@@ -133,7 +133,7 @@ fn ensure_pattern_usable(pattern: &str) -> Result<()> {
 /// Per gitattributes(5), a pattern containing whitespace may be wrapped in
 /// double quotes, with C-style escapes (`\"`, `\\`, `\n`, `\t`, octal `\nnn`)
 /// inside. Otherwise the pattern is the first whitespace-delimited token.
-fn split_pattern_and_rest(trimmed: &str) -> (String, &str) {
+pub(crate) fn split_pattern_and_rest(trimmed: &str) -> (String, &str) {
     if let Some(after_quote) = trimmed.strip_prefix('"') {
         let bytes = after_quote.as_bytes();
         let mut out: Vec<u8> = Vec::new();
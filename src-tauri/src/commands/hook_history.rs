@@ -0,0 +1,396 @@
+//! Hook execution history
+//!
+//! Every hook the runner executes is appended to a per-repository log, so a
+//! failing or slow `pre-commit`/`commit-msg`/`pre-push` can be diagnosed after
+//! the error dialog is gone. The log is JSON lines under
+//! `<commondir>/leviathan/hook_history.jsonl` — the COMMON dir, so linked
+//! worktrees (which share the hooks) share one history too.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+use crate::error::Result;
+
+/// Bytes of stdout and of stderr kept per run. The tail is kept: a hook's
+/// verdict and the error that matters are at the end of its output.
+const MAX_OUTPUT_BYTES: usize = 8 * 1024;
+
+/// Once the log grows past this size it is compacted to the newest entries
+/// fitting in [`COMPACT_TO_BYTES`].
+const MAX_LOG_BYTES: u64 = 4 * 1024 * 1024;
+const COMPACT_TO_BYTES: usize = 2 * 1024 * 1024;
+
+/// Default threshold for [`get_slow_hooks`], in milliseconds.
+const DEFAULT_SLOW_THRESHOLD_MS: u64 = 2000;
+
+/// One recorded hook run
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRunRecord {
+    /// Hook name (`pre-commit`, `commit-msg`, ...). Checks run by the built-in
+    /// `.pre-commit-config.yaml` runner are recorded as `pre-commit:<id>`.
+    pub hook: String,
+    pub args: Vec<String>,
+    /// None when the hook could not be started or was killed by a signal
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Unix timestamp (seconds) the run started
+    pub started_at: i64,
+    pub stdout: String,
+    pub stderr: String,
+    /// True when stdout or stderr was cut to the last [`MAX_OUTPUT_BYTES`]
+    pub truncated: bool,
+}
+
+/// Timing summary for one hook
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookTimingSummary {
+    pub hook: String,
+    pub runs: usize,
+    pub failures: usize,
+    /// Runs at or above the slow threshold
+    pub slow_runs: usize,
+    pub average_ms: u64,
+    pub max_ms: u64,
+    pub last_run_at: i64,
+    pub last_duration_ms: u64,
+}
+
+fn history_path(repo: &git2::Repository) -> PathBuf {
+    repo.commondir()
+        .join("leviathan")
+        .join("hook_history.jsonl")
+}
+
+/// Keep the last `MAX_OUTPUT_BYTES` of `text`, cut on a char boundary.
+fn truncate_tail(text: &str) -> (String, bool) {
+    if text.len() <= MAX_OUTPUT_BYTES {
+        return (text.to_string(), false);
+    }
+    let mut start = text.len() - MAX_OUTPUT_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    (
+        format!("[... {} bytes truncated]\n{}", start, &text[start..]),
+        true,
+    )
+}
+
+/// Build a record for a finished (or failed-to-start) run.
+pub fn new_record(
+    hook: &str,
+    args: &[&str],
+    exit_code: Option<i32>,
+    started: std::time::SystemTime,
+    duration: std::time::Duration,
+    stdout: &str,
+    stderr: &str,
+) -> HookRunRecord {
+    let (stdout, out_cut) = truncate_tail(stdout);
+    let (stderr, err_cut) = truncate_tail(stderr);
+    HookRunRecord {
+        hook: hook.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        exit_code,
+        success: exit_code == Some(0),
+        duration_ms: duration.as_millis() as u64,
+        started_at: started
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0),
+        stdout,
+        stderr,
+        truncated: out_cut || err_cut,
+    }
+}
+
+/// Append a record to the repository's hook log.
+///
+/// Never fails the caller: a hook's verdict must not depend on whether its
+/// history could be written (read-only `.git`, full disk), so problems are
+/// only logged.
+pub fn record(repo: &git2::Repository, entry: &HookRunRecord) {
+    if let Err(e) = append(&history_path(repo), entry) {
+        tracing::warn!("failed to record {} hook run: {}", entry.hook, e);
+    }
+}
+
+fn append(path: &Path, entry: &HookRunRecord) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(line.as_bytes())?;
+    drop(file);
+
+    if std::fs::metadata(path)?.len() > MAX_LOG_BYTES {
+        compact(path)?;
+    }
+    Ok(())
+}
+
+/// Rewrite the log keeping only the newest lines that fit in
+/// [`COMPACT_TO_BYTES`].
+fn compact(path: &Path) -> Result<()> {
+    let content = std::fs::read_to_string(path)?;
+    let mut kept: Vec<&str> = Vec::new();
+    let mut size = 0;
+    for line in content.lines().rev() {
+        if size + line.len() + 1 > COMPACT_TO_BYTES {
+            break;
+        }
+        size += line.len() + 1;
+        kept.push(line);
+    }
+    kept.reverse();
+    let mut out = kept.join("\n");
+    out.push('\n');
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
+/// Read every record, oldest first. Lines that fail to parse (a run cut off
+/// mid-write) are skipped rather than hiding the rest of the history.
+fn read_all(repo: &git2::Repository) -> Result<Vec<HookRunRecord>> {
+    let path = history_path(repo);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    Ok(content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+/// Get recent hook runs, newest first
+#[command]
+pub async fn get_hook_history(
+    path: String,
+    limit: Option<usize>,
+    hook: Option<String>,
+    failed_only: Option<bool>,
+) -> Result<Vec<HookRunRecord>> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let failed_only = failed_only.unwrap_or(false);
+    let mut records: Vec<HookRunRecord> = read_all(&repo)?
+        .into_iter()
+        .rev()
+        .filter(|r| match hook.as_deref() {
+            Some(h) => r.hook == h,
+            None => true,
+        })
+        .filter(|r| !failed_only || !r.success)
+        .collect();
+    records.truncate(limit.unwrap_or(50));
+    Ok(records)
+}
+
+/// Get per-hook timing summaries, slowest (by average) first
+///
+/// Only hooks with at least one run at or above `threshold_ms` are returned
+/// (default 2000 ms); pass 0 to summarise every hook.
+#[command]
+pub async fn get_slow_hooks(
+    path: String,
+    threshold_ms: Option<u64>,
+) -> Result<Vec<HookTimingSummary>> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let threshold = threshold_ms.unwrap_or(DEFAULT_SLOW_THRESHOLD_MS);
+
+    let mut by_hook: HashMap<String, HookTimingSummary> = HashMap::new();
+    let mut totals: HashMap<String, u64> = HashMap::new();
+    for r in read_all(&repo)? {
+        let summary = by_hook
+            .entry(r.hook.clone())
+            .or_insert_with(|| HookTimingSummary {
+                hook: r.hook.clone(),
+                runs: 0,
+                failures: 0,
+                slow_runs: 0,
+                average_ms: 0,
+                max_ms: 0,
+                last_run_at: 0,
+                last_duration_ms: 0,
+            });
+        summary.runs += 1;
+        if !r.success {
+            summary.failures += 1;
+        }
+        if r.duration_ms >= threshold {
+            summary.slow_runs += 1;
+        }
+        summary.max_ms = summary.max_ms.max(r.duration_ms);
+        // Records are appended in run order, so the last one seen is newest.
+        summary.last_run_at = r.started_at;
+        summary.last_duration_ms = r.duration_ms;
+        *totals.entry(r.hook).or_insert(0) += r.duration_ms;
+    }
+
+    let mut summaries: Vec<HookTimingSummary> = by_hook
+        .into_values()
+        .filter(|s| s.slow_runs > 0)
+        .map(|mut s| {
+            s.average_ms = totals.get(&s.hook).copied().unwrap_or(0) / s.runs as u64;
+            s
+        })
+        .collect();
+    summaries.sort_by_key(|s| std::cmp::Reverse(s.average_ms));
+    Ok(summaries)
+}
+
+/// Delete the recorded hook history
+#[command]
+pub async fn clear_hook_history(path: String) -> Result<()> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let file = history_path(&repo);
+    if file.exists() {
+        std::fs::remove_file(file)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+    use std::time::{Duration, SystemTime};
+
+    fn sample(hook: &str, ms: u64, code: i32) -> HookRunRecord {
+        new_record(
+            hook,
+            &["arg"],
+            Some(code),
+            SystemTime::now(),
+            Duration::from_millis(ms),
+            "out",
+            "err",
+        )
+    }
+
+    #[test]
+    fn test_truncate_tail_keeps_the_end() {
+        let long = format!("{}END", "x".repeat(MAX_OUTPUT_BYTES * 2));
+        let (kept, cut) = truncate_tail(&long);
+        assert!(cut);
+        assert!(kept.ends_with("END"));
+        assert!(kept.len() < long.len());
+
+        let (kept, cut) = truncate_tail("short");
+        assert!(!cut);
+        assert_eq!(kept, "short");
+    }
+
+    #[test]
+    fn test_truncate_tail_respects_char_boundaries() {
+        let long = "é".repeat(MAX_OUTPUT_BYTES);
+        let (kept, cut) = truncate_tail(&long);
+        assert!(cut);
+        assert!(kept.ends_with('é'));
+    }
+
+    #[tokio::test]
+    async fn test_history_is_newest_first_and_filterable() {
+        let repo = TestRepo::with_initial_commit();
+        let git_repo = repo.repo();
+        record(&git_repo, &sample("pre-commit", 10, 0));
+        record(&git_repo, &sample("commit-msg", 20, 1));
+        record(&git_repo, &sample("pre-commit", 30, 0));
+
+        let all = get_hook_history(repo.path_str(), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].duration_ms, 30, "newest first");
+
+        let failed = get_hook_history(repo.path_str(), None, None, Some(true))
+            .await
+            .unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].hook, "commit-msg");
+
+        let limited = get_hook_history(repo.path_str(), Some(1), Some("pre-commit".into()), None)
+            .await
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+        assert_eq!(limited[0].duration_ms, 30);
+    }
+
+    #[tokio::test]
+    async fn test_slow_hooks_summary() {
+        let repo = TestRepo::with_initial_commit();
+        let git_repo = repo.repo();
+        record(&git_repo, &sample("pre-commit", 3000, 0));
+        record(&git_repo, &sample("pre-commit", 1000, 1));
+        record(&git_repo, &sample("commit-msg", 5, 0));
+
+        let slow = get_slow_hooks(repo.path_str(), None).await.unwrap();
+        assert_eq!(slow.len(), 1, "commit-msg never crossed the threshold");
+        let s = &slow[0];
+        assert_eq!(s.hook, "pre-commit");
+        assert_eq!(s.runs, 2);
+        assert_eq!(s.failures, 1);
+        assert_eq!(s.slow_runs, 1);
+        assert_eq!(s.average_ms, 2000);
+        assert_eq!(s.max_ms, 3000);
+        assert_eq!(s.last_duration_ms, 1000);
+
+        let all = get_slow_hooks(repo.path_str(), Some(0)).await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].hook, "pre-commit", "slowest average first");
+    }
+
+    #[tokio::test]
+    async fn test_clear_and_corrupt_lines() {
+        let repo = TestRepo::with_initial_commit();
+        let git_repo = repo.repo();
+        record(&git_repo, &sample("pre-push", 1, 0));
+        // A torn write must not hide the rest of the history.
+        let path = history_path(&git_repo);
+        let mut f = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        f.write_all(b"{\"hook\":\"pre-\n").unwrap();
+        record(&git_repo, &sample("pre-push", 2, 0));
+
+        let all = get_hook_history(repo.path_str(), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        clear_hook_history(repo.path_str()).await.unwrap();
+        assert!(get_hook_history(repo.path_str(), None, None, None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_compact_keeps_newest_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+        let line = "x".repeat(1024);
+        let mut content = String::new();
+        for i in 0..(COMPACT_TO_BYTES / 1024 + 100) {
+            content.push_str(&format!("{}{}\n", i, line));
+        }
+        std::fs::write(&path, &content).unwrap();
+        compact(&path).unwrap();
+        let after = std::fs::read_to_string(&path).unwrap();
+        assert!(after.len() <= COMPACT_TO_BYTES);
+        let last = content.lines().last().unwrap();
+        assert_eq!(after.lines().last().unwrap(), last, "newest line survives");
+    }
+}
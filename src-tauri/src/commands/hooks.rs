@@ -39,6 +39,111 @@ fn resolve_hooks_dir(repo: &git2::Repository) -> PathBuf {
     repo.commondir().join("hooks")
 }
 
+/// The raw `core.hooksPath` value and the config scope it came from
+/// (`worktree`, `local`, `global`, `system`, ...), if set and non-empty.
+///
+/// libgit2 already layers `config.worktree` over the repository config when
+/// `extensions.worktreeConfig` is on, so a per-worktree override wins here
+/// exactly as it does for git; the scope is reported so the UI can say which
+/// file to change.
+fn hooks_path_setting(repo: &git2::Repository) -> Option<(String, String)> {
+    let config = repo.config().ok()?;
+    let entry = config.get_entry("core.hooksPath").ok()?;
+    let value = entry.value().ok()?.to_string();
+    if value.is_empty() {
+        return None;
+    }
+    let scope = match entry.level() {
+        git2::ConfigLevel::Worktree => "worktree",
+        git2::ConfigLevel::Local => "local",
+        git2::ConfigLevel::Global | git2::ConfigLevel::XDG => "global",
+        git2::ConfigLevel::System | git2::ConfigLevel::ProgramData => "system",
+        _ => "other",
+    };
+    Some((value, scope.to_string()))
+}
+
+/// Where the effective hooks directory comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookSource {
+    /// `<commondir>/hooks`, git's default.
+    GitDir,
+    /// A `core.hooksPath` directory (e.g. a committed `.githooks/`).
+    HooksPath,
+    /// A Husky-managed `core.hooksPath` (`.husky/` or `.husky/_`).
+    Husky,
+}
+
+/// The hooks directory git runs from, and the directory the user's scripts
+/// actually live in. They differ only for Husky v9, whose `core.hooksPath`
+/// (`.husky/_`) holds generated wrappers that source `.husky/<hook>`.
+struct HookLocation {
+    run_dir: PathBuf,
+    script_dir: PathBuf,
+    source: HookSource,
+    /// Scripts are invoked through a wrapper (`sh -e`), so they run without
+    /// the executable bit.
+    wrapped: bool,
+}
+
+impl HookLocation {
+    /// Would the script at `path` run? See [`hook_exists_and_runs`].
+    fn runs(&self, path: &Path) -> bool {
+        if self.wrapped {
+            path.is_file()
+        } else {
+            hook_exists_and_runs(path)
+        }
+    }
+}
+
+fn hook_location(repo: &git2::Repository) -> HookLocation {
+    let run_dir = resolve_hooks_dir(repo);
+    if hooks_path_setting(repo).is_none() {
+        return HookLocation {
+            script_dir: run_dir.clone(),
+            run_dir,
+            source: HookSource::GitDir,
+            wrapped: false,
+        };
+    }
+
+    // Husky v9: hooksPath = .husky/_, every wrapper sources `_/h`, which runs
+    // `.husky/<hook>` with `sh -e` when that file exists. Editing the wrapper
+    // would be overwritten on the next `husky` install, so manage the parent.
+    let is_husky_wrappers = run_dir.file_name().is_some_and(|n| n == "_")
+        && (run_dir.join("h").is_file()
+            || run_dir
+                .parent()
+                .and_then(|p| p.file_name())
+                .is_some_and(|n| n == ".husky"));
+    if is_husky_wrappers {
+        if let Some(parent) = run_dir.parent() {
+            return HookLocation {
+                script_dir: parent.to_path_buf(),
+                run_dir,
+                source: HookSource::Husky,
+                wrapped: true,
+            };
+        }
+    }
+
+    // Husky v5-v8: hooksPath = .husky, scripts run directly by git and source
+    // `_/husky.sh`; only the label differs from a plain hooksPath.
+    let source = if run_dir.join("_").join("husky.sh").is_file() {
+        HookSource::Husky
+    } else {
+        HookSource::HooksPath
+    };
+    HookLocation {
+        script_dir: run_dir.clone(),
+        run_dir,
+        source,
+        wrapped: false,
+    }
+}
+
 /// Expand a leading `~` (or `~/`) to the user's home directory, like git.
 fn expand_tilde(path: &str) -> String {
     if path == "~" {
@@ -155,7 +260,27 @@ pub fn run_hook(
     // would misdirect them (and break linked worktrees). cwd = workdir is
     // exactly what git relies on.
 
+    // Every run that actually starts (or fails to) lands in the hook history,
+    // so slow or flaky hooks can be diagnosed after the fact.
+    let started = std::time::SystemTime::now();
+    let timer = std::time::Instant::now();
+    let record_failure = |e: &std::io::Error| {
+        crate::commands::hook_history::record(
+            repo,
+            &crate::commands::hook_history::new_record(
+                name,
+                args,
+                None,
+                started,
+                timer.elapsed(),
+                "",
+                &e.to_string(),
+            ),
+        );
+    };
+
     let mut child = cmd.spawn().map_err(|e| {
+        record_failure(&e);
         LeviathanError::OperationFailed(format!("Failed to run {} hook: {}", name, e))
     })?;
 
@@ -168,11 +293,24 @@ pub fn run_hook(
     }
 
     let output = child.wait_with_output().map_err(|e| {
+        record_failure(&e);
         LeviathanError::OperationFailed(format!("Failed to run {} hook: {}", name, e))
     })?;
 
     let mut combined = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr);
+    crate::commands::hook_history::record(
+        repo,
+        &crate::commands::hook_history::new_record(
+            name,
+            args,
+            output.status.code(),
+            started,
+            timer.elapsed(),
+            &combined,
+            &stderr,
+        ),
+    );
     if !stderr.is_empty() {
         if !combined.is_empty() && !combined.ends_with('\n') {
             combined.push('\n');
@@ -197,6 +335,47 @@ pub fn run_hook_blocking(
     stdin_data: Option<&str>,
 ) -> Result<()> {
     let outcome = run_hook(repo, name, args, stdin_data)?;
+    run_hook_blocking_outcome(name, outcome)
+}
+
+/// Run the pre-commit stage of a commit (blocking, git parity).
+///
+/// An installed `pre-commit` hook runs exactly as before. When there is none,
+/// the repository's `.pre-commit-config.yaml` can be run by the built-in
+/// runner in [`crate::commands::pre_commit`] instead (opt-in with
+/// `leviathan.precommit`) — without it, a repository that relies on the
+/// pre-commit framework has every check bypassed whenever the framework is
+/// not installed on this machine.
+///
+/// The built-in secret scan runs last, over what is staged once the hooks
+/// (which may restage fixes) are done; see [`crate::commands::secret_scan`].
+pub fn run_pre_commit(repo: &git2::Repository) -> Result<()> {
+    let outcome = run_hook(repo, "pre-commit", &[], None)?;
+    if outcome.ran {
+        run_hook_blocking_outcome("pre-commit", outcome)?;
+    } else {
+        crate::commands::pre_commit::run_checks_blocking(repo)?;
+    }
+    crate::commands::secret_scan::check_staged(repo)
+}
+
+/// The part of `run_pre_commit` that `git commit` itself knows nothing
+/// about: the `.pre-commit-config.yaml` checks (only when no native hook is
+/// installed, since git runs that one) and the staged secret scan.
+///
+/// The signed and allow-empty paths hand the commit to the git CLI, which
+/// runs the native hook on its own; they call this first so the Leviathan
+/// checks still gate them.
+pub fn run_pre_commit_for_cli(repo: &git2::Repository) -> Result<()> {
+    if repo.workdir().is_some() && !is_executable(&resolve_hooks_dir(repo).join("pre-commit")) {
+        crate::commands::pre_commit::run_checks_blocking(repo)?;
+    }
+    crate::commands::secret_scan::check_staged(repo)
+}
+
+/// Turn a hook outcome into the blocking-hook result: a non-zero exit aborts
+/// with the hook's output in the error.
+fn run_hook_blocking_outcome(name: &str, outcome: HookOutcome) -> Result<()> {
     if outcome.ran && !outcome.success {
         let detail = outcome.output.trim();
         return Err(LeviathanError::OperationFailed(if detail.is_empty() {
@@ -350,6 +529,9 @@ pub struct GitHook {
     pub enabled: bool,
     pub content: Option<String>,
     pub description: String,
+    /// Where the hook lives: git's own hooks dir, a `core.hooksPath`
+    /// directory, or a Husky setup.
+    pub source: HookSource,
 }
 
 /// Known hook names and their descriptions
@@ -406,8 +588,10 @@ pub async fn get_hooks(path: String) -> Result<Vec<GitHook>> {
     // itself honour core.hooksPath (husky sets it) and, in a linked worktree,
     // the COMMON dir. Managing a different directory than the one that runs
     // meant every hook read as "not configured" in those repos and every hook
-    // saved from this dialog was inert.
-    let hooks_dir = resolve_hooks_dir(&repo);
+    // saved from this dialog was inert. For Husky v9 the scripts live one
+    // level above the wrapper directory git runs; see hook_location.
+    let location = hook_location(&repo);
+    let hooks_dir = &location.script_dir;
 
     let mut hooks = Vec::new();
 
@@ -430,7 +614,7 @@ pub async fn get_hooks(path: String) -> Result<Vec<GitHook>> {
         // On Windows is_executable is always false, so fall back to existence
         // there. toggle_hook(true) chmods 0755, which makes the toggle the
         // repair.
-        let enabled = hook_exists_and_runs(&hook_path);
+        let enabled = location.runs(&hook_path);
         let exists = hook_path.exists() || disabled_path.exists();
         let content = if hook_path.exists() {
             std::fs::read_to_string(&hook_path).ok()
@@ -450,6 +634,7 @@ pub async fn get_hooks(path: String) -> Result<Vec<GitHook>> {
             enabled,
             content,
             description: description.to_string(),
+            source: location.source,
         });
     }
 
@@ -460,13 +645,13 @@ pub async fn get_hooks(path: String) -> Result<Vec<GitHook>> {
 #[command]
 pub async fn get_hook(path: String, name: String) -> Result<GitHook> {
     let repo = git2::Repository::open(Path::new(&path))?;
-    let hooks_dir = resolve_hooks_dir(&repo);
-    let hook_path = hooks_dir.join(&name);
-    let disabled_path = hooks_dir.join(format!("{}.disabled", name));
+    let location = hook_location(&repo);
+    let hook_path = location.script_dir.join(&name);
+    let disabled_path = location.script_dir.join(format!("{}.disabled", name));
 
     // A disabled hook still exists — see get_hooks, which also explains why
     // `enabled` tracks the executable bit rather than mere existence.
-    let enabled = hook_exists_and_runs(&hook_path);
+    let enabled = location.runs(&hook_path);
     let exists = hook_path.exists() || disabled_path.exists();
     let content = if hook_path.exists() {
         std::fs::read_to_string(&hook_path).ok()
@@ -489,6 +674,7 @@ pub async fn get_hook(path: String, name: String) -> Result<GitHook> {
         enabled,
         content,
         description,
+        source: location.source,
     })
 }
 
@@ -496,7 +682,7 @@ pub async fn get_hook(path: String, name: String) -> Result<GitHook> {
 #[command]
 pub async fn save_hook(path: String, name: String, content: String) -> Result<()> {
     let repo = git2::Repository::open(Path::new(&path))?;
-    let hooks_dir = resolve_hooks_dir(&repo);
+    let hooks_dir = hook_location(&repo).script_dir;
 
     // Ensure hooks directory exists
     std::fs::create_dir_all(&hooks_dir)?;
@@ -542,7 +728,7 @@ pub async fn save_hook(path: String, name: String, content: String) -> Result<()
 #[command]
 pub async fn delete_hook(path: String, name: String) -> Result<()> {
     let repo = git2::Repository::open(Path::new(&path))?;
-    let hooks_dir = resolve_hooks_dir(&repo);
+    let hooks_dir = hook_location(&repo).script_dir;
     let hook_path = hooks_dir.join(&name);
     // A disabled hook is still the user's script; Delete must remove it too,
     // or the entry reappears on the next load.
@@ -562,7 +748,7 @@ pub async fn delete_hook(path: String, name: String) -> Result<()> {
 #[command]
 pub async fn toggle_hook(path: String, name: String, enabled: bool) -> Result<()> {
     let repo = git2::Repository::open(Path::new(&path))?;
-    let hooks_dir = resolve_hooks_dir(&repo);
+    let hooks_dir = hook_location(&repo).script_dir;
     let hook_path = hooks_dir.join(&name);
     let disabled_path = hooks_dir.join(format!("{}.disabled", name));
 
@@ -592,6 +778,202 @@ pub async fn toggle_hook(path: String, name: String, enabled: bool) -> Result<()
     Ok(())
 }
 
+/// Where a repository's hooks are resolved from
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HooksLocation {
+    /// The directory git runs hooks from.
+    pub hooks_dir: String,
+    /// The directory the hook scripts are managed in (differs from
+    /// `hooks_dir` only for Husky v9's wrapper directory).
+    pub script_dir: String,
+    pub source: HookSource,
+    /// The raw `core.hooksPath` value, when set.
+    pub hooks_path: Option<String>,
+    /// Which config file `core.hooksPath` came from (`worktree`, `local`,
+    /// `global`, `system`).
+    pub config_scope: Option<String>,
+    /// The scripts live inside the working tree, i.e. can be committed and
+    /// shared with the team.
+    pub in_working_tree: bool,
+}
+
+/// Get where a repository's hooks are resolved from
+#[command]
+pub async fn get_hooks_location(path: String) -> Result<HooksLocation> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let location = hook_location(&repo);
+    let setting = hooks_path_setting(&repo);
+    let in_working_tree = repo
+        .workdir()
+        .is_some_and(|w| location.script_dir.starts_with(w));
+
+    Ok(HooksLocation {
+        hooks_dir: location.run_dir.to_string_lossy().to_string(),
+        script_dir: location.script_dir.to_string_lossy().to_string(),
+        source: location.source,
+        hooks_path: setting.as_ref().map(|(v, _)| v.clone()),
+        config_scope: setting.map(|(_, scope)| scope),
+        in_working_tree,
+    })
+}
+
+/// Result of installing a committed team hooks directory
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamHooksInstall {
+    /// The value written to `core.hooksPath`.
+    pub hooks_path: String,
+    /// The config scope it was written to (`local` or `worktree`).
+    pub config_scope: String,
+    /// The `core.hooksPath` value that was replaced, if any.
+    pub previous_hooks_path: Option<String>,
+    /// Known hooks found in the new directory.
+    pub hooks: Vec<String>,
+    /// Hooks that were checked in without the executable bit and were fixed.
+    pub made_executable: Vec<String>,
+    /// Active hooks in the previous directory that will no longer run.
+    pub shadowed_hooks: Vec<String>,
+}
+
+/// Point `core.hooksPath` at a directory committed in the repository (e.g.
+/// `.githooks`), so every clone runs the team's hooks.
+///
+/// Setting `core.hooksPath` silently stops every hook in the previous
+/// directory from running, so this refuses without `force` when it would
+/// replace a different `core.hooksPath` or strand active hooks; the error
+/// names what would be lost. The value is stored relative to the working
+/// tree so it stays valid in every worktree and clone.
+#[command]
+pub async fn install_team_hooks(
+    path: String,
+    dir: String,
+    force: Option<bool>,
+) -> Result<TeamHooksInstall> {
+    let force = force.unwrap_or(false);
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| {
+            LeviathanError::OperationFailed(
+                "Team hooks need a working tree; this repository is bare".to_string(),
+            )
+        })?
+        .to_path_buf();
+
+    let relative = validate_team_hooks_dir(&dir)?;
+    let hooks_dir = workdir.join(&relative);
+    if !hooks_dir.is_dir() {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Hooks directory '{}' does not exist in the working tree",
+            relative
+        )));
+    }
+
+    let previous = hooks_path_setting(&repo);
+    if let Some((value, scope)) = &previous {
+        if value.trim_end_matches('/') != relative && !force {
+            return Err(LeviathanError::OperationFailed(format!(
+                "core.hooksPath is already set to '{}' in the {} config; confirm to replace it",
+                value, scope
+            )));
+        }
+    }
+
+    let old_dir = resolve_hooks_dir(&repo);
+    let mut shadowed_hooks = Vec::new();
+    if old_dir != hooks_dir {
+        for (name, _) in HOOKS {
+            if hook_exists_and_runs(&old_dir.join(name)) {
+                shadowed_hooks.push(name.to_string());
+            }
+        }
+    }
+    if !shadowed_hooks.is_empty() && !force {
+        return Err(LeviathanError::OperationFailed(format!(
+            "These hooks in {} would stop running: {}; confirm to continue",
+            old_dir.display(),
+            shadowed_hooks.join(", ")
+        )));
+    }
+
+    // Committed hooks lose their executable bit through core.fileMode=false
+    // clones and some archive tools; git would then skip them silently.
+    let mut hooks = Vec::new();
+    let mut made_executable = Vec::new();
+    for (name, _) in HOOKS {
+        let hook_path = hooks_dir.join(name);
+        if !hook_path.is_file() {
+            continue;
+        }
+        hooks.push(name.to_string());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if !is_executable(&hook_path) {
+                let mut perms = std::fs::metadata(&hook_path)?.permissions();
+                perms.set_mode(perms.mode() | 0o755);
+                std::fs::set_permissions(&hook_path, perms)?;
+                made_executable.push(name.to_string());
+            }
+        }
+    }
+
+    // A worktree-scoped value would shadow a local one, so replace it where it
+    // lives; otherwise the repository-local config is the shared default.
+    let level = match &previous {
+        Some((_, scope)) if scope == "worktree" => git2::ConfigLevel::Worktree,
+        _ => git2::ConfigLevel::Local,
+    };
+    let mut config = repo.config()?.open_level(level)?;
+    config.set_str("core.hooksPath", &relative)?;
+
+    Ok(TeamHooksInstall {
+        hooks_path: relative,
+        config_scope: if level == git2::ConfigLevel::Worktree {
+            "worktree".to_string()
+        } else {
+            "local".to_string()
+        },
+        previous_hooks_path: previous.map(|(v, _)| v),
+        hooks,
+        made_executable,
+        shadowed_hooks,
+    })
+}
+
+/// Normalise a team hooks directory to a forward-slash path relative to the
+/// working tree, rejecting anything that could escape it or point into `.git`.
+fn validate_team_hooks_dir(dir: &str) -> Result<String> {
+    let trimmed = dir.trim().trim_end_matches(['/', '\\']);
+    let invalid = |why: &str| {
+        LeviathanError::OperationFailed(format!("Invalid hooks directory '{}': {}", dir, why))
+    };
+    if trimmed.is_empty() {
+        return Err(invalid("empty path"));
+    }
+    let path = Path::new(trimmed);
+    if path.is_absolute() || trimmed.starts_with('/') || trimmed.starts_with('~') {
+        return Err(invalid("must be relative to the working tree"));
+    }
+
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            std::path::Component::CurDir => {}
+            _ => return Err(invalid("must stay inside the working tree")),
+        }
+    }
+    if parts.is_empty() {
+        return Err(invalid("must name a directory"));
+    }
+    if parts[0] == ".git" {
+        return Err(invalid("must not be inside .git"));
+    }
+    Ok(parts.join("/"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1008,6 +1390,28 @@ mod tests {
         );
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hook_run_is_recorded_in_history() {
+        let repo = TestRepo::with_initial_commit();
+        repo.install_hook("pre-commit", "#!/bin/sh\necho nope 1>&2\nexit 1\n");
+        let git_repo = repo.repo();
+        let _ = run_hook_blocking(&git_repo, "pre-commit", &[], None);
+
+        let history = crate::commands::hook_history::get_hook_history(
+            repo.path_str(),
+            None,
+            Some("pre-commit".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].exit_code, Some(1));
+        assert!(!history[0].success);
+        assert!(history[0].stderr.contains("nope"));
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_run_hook_noblock_ignores_failure() {
@@ -1071,6 +1475,179 @@ mod tests {
         assert!(!outcome.success, "hook exited 7");
     }
 
+    #[cfg(unix)]
+    fn write_script(path: &Path, script: &str, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, script).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_get_hooks_reports_hookspath_location() {
+        let repo = TestRepo::with_initial_commit();
+        write_script(
+            &repo.path.join(".githooks/pre-commit"),
+            "#!/bin/sh\nexit 0\n",
+            0o755,
+        );
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_str("core.hooksPath", ".githooks")
+            .unwrap();
+
+        let hooks = get_hooks(repo.path_str()).await.unwrap();
+        let pre_commit = hooks.iter().find(|h| h.name == "pre-commit").unwrap();
+        assert!(pre_commit.enabled);
+        assert_eq!(pre_commit.source, HookSource::HooksPath);
+        assert!(pre_commit.path.contains(".githooks"));
+
+        let location = get_hooks_location(repo.path_str()).await.unwrap();
+        assert_eq!(location.hooks_path.as_deref(), Some(".githooks"));
+        assert_eq!(location.config_scope.as_deref(), Some("local"));
+        assert!(location.in_working_tree);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_worktree_config_hookspath_wins() {
+        let repo = TestRepo::with_initial_commit();
+        let wt_parent = tempfile::tempdir().unwrap();
+        let wt_path = wt_parent.path().join("wt");
+        {
+            let git_repo = repo.repo();
+            git_repo.worktree("wt", &wt_path, None).unwrap();
+            let mut cfg = git_repo.config().unwrap();
+            cfg.set_bool("extensions.worktreeConfig", true).unwrap();
+            cfg.set_str("core.hooksPath", "shared-hooks").unwrap();
+        }
+        let wt_repo = git2::Repository::open(&wt_path).unwrap();
+        std::fs::write(
+            wt_repo.path().join("config.worktree"),
+            "[core]\n\thooksPath = wt-hooks\n",
+        )
+        .unwrap();
+        let wt_repo = git2::Repository::open(&wt_path).unwrap();
+
+        let location = get_hooks_location(wt_path.to_string_lossy().to_string())
+            .await
+            .unwrap();
+        assert_eq!(location.hooks_path.as_deref(), Some("wt-hooks"));
+        assert_eq!(location.config_scope.as_deref(), Some("worktree"));
+        assert_eq!(
+            resolve_hooks_dir(&wt_repo),
+            wt_repo.workdir().unwrap().join("wt-hooks")
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_husky_v9_manages_scripts_above_wrappers() {
+        let repo = TestRepo::with_initial_commit();
+        write_script(&repo.path.join(".husky/_/h"), "#!/usr/bin/env sh\n", 0o644);
+        write_script(
+            &repo.path.join(".husky/_/pre-commit"),
+            "#!/usr/bin/env sh\n. \"$(dirname \"$0\")/h\"\n",
+            0o755,
+        );
+        // Husky runs user scripts with `sh -e`, so they need no +x.
+        write_script(&repo.path.join(".husky/pre-commit"), "npm test\n", 0o644);
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_str("core.hooksPath", ".husky/_")
+            .unwrap();
+
+        let hook = get_hook(repo.path_str(), "pre-commit".to_string())
+            .await
+            .unwrap();
+        assert_eq!(hook.source, HookSource::Husky);
+        assert!(hook.enabled);
+        assert_eq!(hook.content.as_deref(), Some("npm test\n"));
+        assert!(!hook.path.contains("/_/"), "must point at the user script");
+
+        toggle_hook(repo.path_str(), "pre-commit".to_string(), false)
+            .await
+            .unwrap();
+        assert!(repo.path.join(".husky/pre-commit.disabled").exists());
+        assert!(repo.path.join(".husky/_/pre-commit").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_team_hooks_sets_relative_hookspath() {
+        let repo = TestRepo::with_initial_commit();
+        write_script(
+            &repo.path.join(".githooks/pre-push"),
+            "#!/bin/sh\nexit 0\n",
+            0o644,
+        );
+        write_script(&repo.path.join(".githooks/README.md"), "docs\n", 0o644);
+
+        let result = install_team_hooks(repo.path_str(), "./.githooks/".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.hooks_path, ".githooks");
+        assert_eq!(result.config_scope, "local");
+        assert_eq!(result.hooks, vec!["pre-push".to_string()]);
+        assert_eq!(result.made_executable, vec!["pre-push".to_string()]);
+        assert!(is_executable(&repo.path.join(".githooks/pre-push")));
+        assert!(!is_executable(&repo.path.join(".githooks/README.md")));
+        assert_eq!(
+            repo.repo()
+                .config()
+                .unwrap()
+                .get_string("core.hooksPath")
+                .unwrap(),
+            ".githooks"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_team_hooks_refuses_to_strand_hooks() {
+        let repo = TestRepo::with_initial_commit();
+        repo.install_hook("pre-commit", "#!/bin/sh\nexit 0\n");
+        std::fs::create_dir_all(repo.path.join(".githooks")).unwrap();
+
+        let err = install_team_hooks(repo.path_str(), ".githooks".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("pre-commit"), "{err}");
+        assert!(repo
+            .repo()
+            .config()
+            .unwrap()
+            .get_string("core.hooksPath")
+            .is_err());
+
+        let result = install_team_hooks(repo.path_str(), ".githooks".to_string(), Some(true))
+            .await
+            .unwrap();
+        assert_eq!(result.shadowed_hooks, vec!["pre-commit".to_string()]);
+
+        // A different existing hooksPath also needs confirmation.
+        std::fs::create_dir_all(repo.path.join("other")).unwrap();
+        let err = install_team_hooks(repo.path_str(), "other".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(".githooks"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_team_hooks_dir() {
+        assert_eq!(
+            validate_team_hooks_dir("tools/hooks/").unwrap(),
+            "tools/hooks"
+        );
+        assert!(validate_team_hooks_dir("../hooks").is_err());
+        assert!(validate_team_hooks_dir("/etc/hooks").is_err());
+        assert!(validate_team_hooks_dir(".git/hooks").is_err());
+        assert!(validate_team_hooks_dir("  ").is_err());
+    }
+
     #[cfg(unix)]
     #[test]
     fn test_commit_msg_hook_rewrites_message() {
@@ -0,0 +1,933 @@
+//! Git LFS storage analysis
+//!
+//! Answers "what would `lfs_migrate` and `lfs_prune` do?" before running
+//! them. Everything here reads the object database and the local LFS cache
+//! directly with libgit2 rather than through `git lfs migrate info` /
+//! `git lfs prune --dry-run`, so the report is available whether or not
+//! git-lfs is installed, and is read-only either way.
+//!
+//! Sizes are uncompressed object sizes. They overstate what a pack actually
+//! takes on disk (text compresses well), but binary assets, the ones worth
+//! moving to LFS, barely compress, so the estimates are close where it
+//! matters.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use tauri::command;
+
+use crate::error::{LeviathanError, Result};
+
+/// git-lfs ignores anything larger than this when looking for pointers.
+pub(crate) const MAX_POINTER_SIZE: u64 = 1024;
+
+/// Typical size of a pointer file, used when estimating the size of a
+/// migrated history.
+const POINTER_SIZE_ESTIMATE: u64 = 130;
+
+/// Candidate patterns suggested when the caller gives none
+const SUGGESTED_PATTERNS: usize = 5;
+
+/// git-lfs defaults for the prune retention settings
+const DEFAULT_RECENT_REFS_DAYS: i64 = 7;
+const DEFAULT_RECENT_COMMITS_DAYS: i64 = 0;
+const DEFAULT_PRUNE_OFFSET_DAYS: i64 = 3;
+
+/// An LFS pointer file's contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LfsPointer {
+    /// SHA-256 of the real content
+    pub oid: String,
+    /// Size of the real content
+    pub size: u64,
+}
+
+/// Parse a blob as an LFS pointer file.
+pub(crate) fn parse_lfs_pointer(data: &[u8]) -> Option<LfsPointer> {
+    if data.len() as u64 > MAX_POINTER_SIZE {
+        return None;
+    }
+    let text = std::str::from_utf8(data).ok()?;
+    let mut lines = text.lines();
+    let version = lines.next()?;
+    if !version.starts_with("version https://git-lfs.github.com/spec/")
+        && !version.starts_with("version https://hawser.github.com/spec/")
+    {
+        return None;
+    }
+    let mut oid = None;
+    let mut size = None;
+    for line in lines {
+        if let Some(hash) = line.strip_prefix("oid sha256:") {
+            if hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+                oid = Some(hash.to_ascii_lowercase());
+            }
+        } else if let Some(s) = line.strip_prefix("size ") {
+            size = s.trim().parse().ok();
+        }
+    }
+    Some(LfsPointer {
+        oid: oid?,
+        size: size?,
+    })
+}
+
+/// One large blob in history
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsBlobEntry {
+    pub oid: String,
+    /// The first path the blob was found at
+    pub path: String,
+    pub extension: String,
+    pub size: u64,
+}
+
+/// History size by file extension
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsExtensionUsage {
+    /// Lowercased with its dot (".psd"), or "(no extension)"
+    pub extension: String,
+    pub blob_count: usize,
+    pub total_size: u64,
+    pub largest_size: u64,
+}
+
+/// History size by path: every version of one file
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsPathUsage {
+    pub path: String,
+    pub version_count: usize,
+    pub total_size: u64,
+    pub largest_size: u64,
+}
+
+/// What migrating one pattern (or a set of them) would do to history size
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsMigrationEstimate {
+    /// The pattern, or the patterns joined with ", " for the combined
+    /// estimate
+    pub pattern: String,
+    /// Non-pointer blobs the pattern matches
+    pub blob_count: usize,
+    /// Distinct paths among them
+    pub path_count: usize,
+    /// Bytes that would move into LFS storage
+    pub matched_size: u64,
+    /// History size today
+    pub size_without_lfs: u64,
+    /// History size with the matched blobs replaced by pointers
+    pub size_with_lfs: u64,
+}
+
+/// A blob at a path `.gitattributes` routes through LFS that was committed
+/// as regular content, typically because git-lfs was not installed on the
+/// committer's machine. `git lfs migrate import --fixup` repairs these.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsUnconvertedFile {
+    pub path: String,
+    pub oid: String,
+    pub size: u64,
+    /// Whether the blob is in HEAD's tree, as opposed to only in history
+    pub in_head: bool,
+}
+
+/// The local LFS object cache (`.git/lfs/objects`)
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsCacheUsage {
+    pub path: String,
+    pub object_count: usize,
+    pub total_size: u64,
+}
+
+/// History analysis for planning an LFS migration
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsStorageReport {
+    pub commits_scanned: usize,
+    /// Distinct blobs in history, pointers included
+    pub blob_count: usize,
+    /// Their combined size
+    pub total_size: u64,
+    /// Blobs that are LFS pointers
+    pub pointer_count: usize,
+    /// Size of the distinct LFS objects those pointers reference
+    pub lfs_referenced_size: u64,
+    pub largest_blobs: Vec<LfsBlobEntry>,
+    pub by_extension: Vec<LfsExtensionUsage>,
+    pub by_path: Vec<LfsPathUsage>,
+    /// One estimate per candidate pattern
+    pub estimates: Vec<LfsMigrationEstimate>,
+    /// All candidate patterns migrated together (blobs matched by several
+    /// patterns are counted once); None with fewer than two patterns
+    pub combined_estimate: Option<LfsMigrationEstimate>,
+    pub unconverted: Vec<LfsUnconvertedFile>,
+    pub cache: LfsCacheUsage,
+}
+
+/// An object `lfs_prune` would delete from the local cache
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsCacheObject {
+    pub oid: String,
+    pub size: u64,
+}
+
+/// What `lfs_prune` would remove
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsPrunePreview {
+    pub cache: LfsCacheUsage,
+    /// Cached objects that are kept
+    pub retained_count: usize,
+    pub prunable: Vec<LfsCacheObject>,
+    pub prunable_size: u64,
+}
+
+/// A distinct blob seen while walking history.
+pub(crate) struct HistoryBlob {
+    pub size: u64,
+    pub path: String,
+    pub pointer: Option<LfsPointer>,
+}
+
+/// Read a blob's size from the object header, and its pointer contents if
+/// it is small enough to be one; large blobs are never loaded.
+pub(crate) fn inspect_blob(odb: &git2::Odb, oid: git2::Oid) -> (u64, Option<LfsPointer>) {
+    let size = odb.read_header(oid).map(|(s, _)| s as u64).unwrap_or(0);
+    let pointer = if size <= MAX_POINTER_SIZE {
+        odb.read(oid).ok().and_then(|o| parse_lfs_pointer(o.data()))
+    } else {
+        None
+    };
+    (size, pointer)
+}
+
+/// Every distinct blob reachable from a branch, a tag or HEAD, each
+/// attributed to the first path it was found at.
+///
+/// Trees are visited once each, so a subtree that reappears unchanged in
+/// later commits costs nothing.
+pub(crate) fn scan_history(
+    repo: &git2::Repository,
+) -> Result<(usize, HashMap<git2::Oid, HistoryBlob>)> {
+    let odb = repo.odb()?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_glob("refs/heads")?;
+    revwalk.push_glob("refs/tags")?;
+    if repo.head().is_ok() {
+        let _ = revwalk.push_head();
+    }
+
+    let mut blobs: HashMap<git2::Oid, HistoryBlob> = HashMap::new();
+    let mut seen_trees: HashSet<git2::Oid> = HashSet::new();
+    let mut commits = 0;
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        commits += 1;
+        let mut pending = vec![(commit.tree_id(), String::new())];
+        while let Some((tree_oid, prefix)) = pending.pop() {
+            if !seen_trees.insert(tree_oid) {
+                continue;
+            }
+            let tree = repo.find_tree(tree_oid)?;
+            for entry in tree.iter() {
+                let Ok(name) = entry.name() else { continue };
+                let entry_path = format!("{}{}", prefix, name);
+                match entry.kind() {
+                    Some(git2::ObjectType::Tree) => {
+                        pending.push((entry.id(), format!("{}/", entry_path)));
+                    }
+                    Some(git2::ObjectType::Blob) => {
+                        blobs.entry(entry.id()).or_insert_with(|| {
+                            let (size, pointer) = inspect_blob(&odb, entry.id());
+                            HistoryBlob {
+                                size,
+                                path: entry_path,
+                                pointer,
+                            }
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    Ok((commits, blobs))
+}
+
+/// The extension the report groups by, matching the statistics view.
+fn extension_of(path: &str) -> String {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match name.rfind('.') {
+        Some(pos) if pos > 0 => name[pos..].to_lowercase(),
+        _ => "(no extension)".to_string(),
+    }
+}
+
+/// Match a path against a `.gitattributes`-style pattern: a pattern without
+/// a slash matches the file name at any depth, one with a slash matches
+/// from the repository root.
+fn attribute_pattern_matches(pattern: &glob::Pattern, anchored: bool, path: &str) -> bool {
+    let options = glob::MatchOptions {
+        case_sensitive: true,
+        require_literal_separator: true,
+        require_literal_leading_dot: false,
+    };
+    if anchored {
+        pattern.matches_with(path, options)
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        pattern.matches_with(name, options)
+    }
+}
+
+/// Compile candidate patterns, rejecting invalid ones by name.
+fn compile_patterns(patterns: &[String]) -> Result<Vec<(glob::Pattern, bool)>> {
+    patterns
+        .iter()
+        .map(|p| {
+            let trimmed = p.trim();
+            let anchored = trimmed.trim_end_matches('/').contains('/');
+            glob::Pattern::new(trimmed.trim_start_matches('/'))
+                .map(|g| (g, anchored))
+                .map_err(|e| {
+                    LeviathanError::OperationFailed(format!("Invalid pattern '{}': {}", p, e))
+                })
+        })
+        .collect()
+}
+
+fn estimate(
+    pattern: String,
+    blobs: &HashMap<git2::Oid, HistoryBlob>,
+    total_size: u64,
+    matches: &dyn Fn(&str) -> bool,
+) -> LfsMigrationEstimate {
+    let mut blob_count = 0;
+    let mut matched_size = 0;
+    let mut paths = HashSet::new();
+    for blob in blobs.values() {
+        if blob.pointer.is_none() && matches(&blob.path) {
+            blob_count += 1;
+            matched_size += blob.size;
+            paths.insert(blob.path.as_str());
+        }
+    }
+    LfsMigrationEstimate {
+        pattern,
+        blob_count,
+        path_count: paths.len(),
+        matched_size,
+        size_without_lfs: total_size,
+        size_with_lfs: total_size - matched_size + blob_count as u64 * POINTER_SIZE_ESTIMATE,
+    }
+}
+
+/// Where git-lfs keeps objects: `lfs.storage` when set (relative paths are
+/// relative to the git directory), else `.git/lfs`. Shared by worktrees.
+pub(crate) fn lfs_storage_dir(repo: &git2::Repository) -> PathBuf {
+    let common = repo.commondir().to_path_buf();
+    match repo
+        .config()
+        .ok()
+        .and_then(|c| c.get_path("lfs.storage").ok())
+    {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => common.join(dir),
+        None => common.join("lfs"),
+    }
+}
+
+/// Every object in the local cache, keyed by oid.
+pub(crate) fn cached_objects(repo: &git2::Repository) -> (PathBuf, Vec<LfsCacheObject>) {
+    let objects_dir = lfs_storage_dir(repo).join("objects");
+    let mut objects = Vec::new();
+    let level = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default()
+    };
+    // objects/<2 hex>/<2 hex>/<64 hex>
+    for first in level(&objects_dir).into_iter().filter(|p| p.is_dir()) {
+        for second in level(&first).into_iter().filter(|p| p.is_dir()) {
+            for file in level(&second) {
+                let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                if name.len() != 64 || !name.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    continue;
+                }
+                let size = std::fs::metadata(&file).map(|m| m.len()).unwrap_or(0);
+                objects.push(LfsCacheObject {
+                    oid: name.to_string(),
+                    size,
+                });
+            }
+        }
+    }
+    objects.sort_by(|a, b| a.oid.cmp(&b.oid));
+    (objects_dir, objects)
+}
+
+fn cache_usage(dir: &Path, objects: &[LfsCacheObject]) -> LfsCacheUsage {
+    LfsCacheUsage {
+        path: dir.to_string_lossy().to_string(),
+        object_count: objects.len(),
+        total_size: objects.iter().map(|o| o.size).sum(),
+    }
+}
+
+/// Analyse history for an LFS migration
+///
+/// Reports the largest blobs and the heaviest extensions and paths across
+/// every branch and tag, estimates history size with each candidate pattern
+/// moved to LFS (the five heaviest extensions when none are given), lists
+/// blobs committed as regular content at paths `.gitattributes` routes
+/// through LFS, and sizes the local LFS cache. `limit` caps each top list
+/// (default 20).
+#[command]
+pub async fn get_lfs_storage_report(
+    path: String,
+    patterns: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<LfsStorageReport> {
+    // The full-history scan can take minutes; keep it off the async runtime.
+    tokio::task::spawn_blocking(move || storage_report(&path, patterns, limit.unwrap_or(20)))
+        .await
+        .map_err(|e| LeviathanError::OperationFailed(format!("Task failed: {}", e)))?
+}
+
+fn storage_report(
+    path: &str,
+    patterns: Option<Vec<String>>,
+    limit: usize,
+) -> Result<LfsStorageReport> {
+    let repo = git2::Repository::open(path)?;
+    let limit = limit.max(1);
+    let (commits_scanned, blobs) = scan_history(&repo)?;
+
+    let total_size: u64 = blobs.values().map(|b| b.size).sum();
+    let mut lfs_objects: HashMap<&str, u64> = HashMap::new();
+    for pointer in blobs.values().filter_map(|b| b.pointer.as_ref()) {
+        lfs_objects.insert(pointer.oid.as_str(), pointer.size);
+    }
+
+    let mut largest_blobs: Vec<LfsBlobEntry> = blobs
+        .iter()
+        .filter(|(_, b)| b.pointer.is_none())
+        .map(|(oid, b)| LfsBlobEntry {
+            oid: oid.to_string(),
+            path: b.path.clone(),
+            extension: extension_of(&b.path),
+            size: b.size,
+        })
+        .collect();
+    largest_blobs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    largest_blobs.truncate(limit);
+
+    let mut extensions: HashMap<String, LfsExtensionUsage> = HashMap::new();
+    let mut paths: HashMap<&str, LfsPathUsage> = HashMap::new();
+    for blob in blobs.values().filter(|b| b.pointer.is_none()) {
+        let ext = extension_of(&blob.path);
+        let usage = extensions
+            .entry(ext.clone())
+            .or_insert_with(|| LfsExtensionUsage {
+                extension: ext,
+                blob_count: 0,
+                total_size: 0,
+                largest_size: 0,
+            });
+        usage.blob_count += 1;
+        usage.total_size += blob.size;
+        usage.largest_size = usage.largest_size.max(blob.size);
+
+        let usage = paths
+            .entry(blob.path.as_str())
+            .or_insert_with(|| LfsPathUsage {
+                path: blob.path.clone(),
+                version_count: 0,
+                total_size: 0,
+                largest_size: 0,
+            });
+        usage.version_count += 1;
+        usage.total_size += blob.size;
+        usage.largest_size = usage.largest_size.max(blob.size);
+    }
+    let mut by_extension: Vec<LfsExtensionUsage> = extensions.into_values().collect();
+    by_extension.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.extension.cmp(&b.extension))
+    });
+    let mut by_path: Vec<LfsPathUsage> = paths.into_values().collect();
+    by_path.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    by_path.truncate(limit);
+
+    let patterns = match patterns.filter(|p| !p.is_empty()) {
+        Some(patterns) => patterns,
+        None => by_extension
+            .iter()
+            .filter(|e| e.extension.starts_with('.'))
+            .take(SUGGESTED_PATTERNS)
+            .map(|e| format!("*{}", e.extension))
+            .collect(),
+    };
+    let compiled = compile_patterns(&patterns)?;
+    let estimates = patterns
+        .iter()
+        .zip(&compiled)
+        .map(|(pattern, (glob, anchored))| {
+            estimate(pattern.clone(), &blobs, total_size, &|p| {
+                attribute_pattern_matches(glob, *anchored, p)
+            })
+        })
+        .collect();
+    let combined_estimate = (compiled.len() > 1).then(|| {
+        estimate(patterns.join(", "), &blobs, total_size, &|p| {
+            compiled
+                .iter()
+                .any(|(glob, anchored)| attribute_pattern_matches(glob, *anchored, p))
+        })
+    });
+    by_extension.truncate(limit);
+
+    let head_blobs: HashSet<git2::Oid> = match repo.head().and_then(|h| h.peel_to_tree()) {
+        Ok(tree) => {
+            let mut set = HashSet::new();
+            tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+                if entry.kind() == Some(git2::ObjectType::Blob) {
+                    set.insert(entry.id());
+                }
+                git2::TreeWalkResult::Ok
+            })?;
+            set
+        }
+        Err(_) => HashSet::new(),
+    };
+    let mut unconverted: Vec<LfsUnconvertedFile> = blobs
+        .iter()
+        .filter(|(_, b)| b.pointer.is_none() && b.size > 0)
+        .filter(|(_, b)| {
+            repo.get_attr(
+                Path::new(&b.path),
+                "filter",
+                git2::AttrCheckFlags::FILE_THEN_INDEX,
+            )
+            .ok()
+            .flatten()
+                == Some("lfs")
+        })
+        .map(|(oid, b)| LfsUnconvertedFile {
+            path: b.path.clone(),
+            oid: oid.to_string(),
+            size: b.size,
+            in_head: head_blobs.contains(oid),
+        })
+        .collect();
+    unconverted.sort_by(|a, b| {
+        b.in_head
+            .cmp(&a.in_head)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.oid.cmp(&b.oid))
+    });
+
+    let (cache_dir, cached) = cached_objects(&repo);
+
+    Ok(LfsStorageReport {
+        commits_scanned,
+        blob_count: blobs.len(),
+        total_size,
+        pointer_count: blobs.values().filter(|b| b.pointer.is_some()).count(),
+        lfs_referenced_size: lfs_objects.values().sum(),
+        largest_blobs,
+        by_extension,
+        by_path,
+        estimates,
+        combined_estimate,
+        unconverted,
+        cache: cache_usage(&cache_dir, &cached),
+    })
+}
+
+/// Collects the LFS objects referenced by trees, reading each tree and
+/// small blob once.
+struct PointerCollector<'r> {
+    repo: &'r git2::Repository,
+    odb: git2::Odb<'r>,
+    seen_trees: HashSet<git2::Oid>,
+    seen_blobs: HashSet<git2::Oid>,
+    oids: HashSet<String>,
+}
+
+impl<'r> PointerCollector<'r> {
+    fn new(repo: &'r git2::Repository) -> Result<Self> {
+        Ok(Self {
+            repo,
+            odb: repo.odb()?,
+            seen_trees: HashSet::new(),
+            seen_blobs: HashSet::new(),
+            oids: HashSet::new(),
+        })
+    }
+
+    fn add_blob(&mut self, oid: git2::Oid) {
+        if self.seen_blobs.insert(oid) {
+            if let (_, Some(pointer)) = inspect_blob(&self.odb, oid) {
+                self.oids.insert(pointer.oid);
+            }
+        }
+    }
+
+    fn add_tree(&mut self, oid: git2::Oid) -> Result<()> {
+        let mut pending = vec![oid];
+        while let Some(tree_oid) = pending.pop() {
+            if !self.seen_trees.insert(tree_oid) {
+                continue;
+            }
+            let tree = self.repo.find_tree(tree_oid)?;
+            for entry in tree.iter() {
+                match entry.kind() {
+                    Some(git2::ObjectType::Tree) => pending.push(entry.id()),
+                    Some(git2::ObjectType::Blob) => self.add_blob(entry.id()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_commit(&mut self, oid: git2::Oid) -> Result<()> {
+        let tree = self.repo.find_commit(oid)?.tree_id();
+        self.add_tree(tree)
+    }
+}
+
+/// The LFS objects `git lfs prune` keeps, following its rules:
+///
+/// - everything HEAD, the index and the stash reference;
+/// - the tips of refs with a commit in the last `lfs.fetchrecentrefsdays`
+///   (+ `lfs.pruneoffsetdays`) days, remote refs too unless
+///   `lfs.fetchrecentremoterefs` is off, and with
+///   `lfs.fetchrecentcommitsdays` set, the commits that far behind each tip;
+/// - everything in commits not yet on `lfs.pruneremotetocheck` (origin).
+///
+/// Unpushed commits are retained whole rather than diffed against their
+/// parents, so the preview can only err towards keeping an object.
+fn retained_lfs_objects(repo: &git2::Repository) -> Result<HashSet<String>> {
+    let config = repo.config()?;
+    let days = |key: &str, default: i64| config.get_i64(key).unwrap_or(default).max(0);
+    let recent_refs = days("lfs.fetchrecentrefsdays", DEFAULT_RECENT_REFS_DAYS);
+    let recent_commits = days("lfs.fetchrecentcommitsdays", DEFAULT_RECENT_COMMITS_DAYS);
+    let offset = days("lfs.pruneoffsetdays", DEFAULT_PRUNE_OFFSET_DAYS);
+    let remote_refs = config.get_bool("lfs.fetchrecentremoterefs").unwrap_or(true);
+    let prune_remote = config
+        .get_string("lfs.pruneremotetocheck")
+        .unwrap_or_else(|_| "origin".to_string());
+
+    let mut collector = PointerCollector::new(repo)?;
+
+    if let Ok(tree) = repo.head().and_then(|h| h.peel_to_tree()) {
+        collector.add_tree(tree.id())?;
+    }
+    for entry in repo.index()?.iter() {
+        collector.add_blob(entry.id);
+    }
+    if let Ok(reflog) = repo.reflog("refs/stash") {
+        for entry in reflog.iter() {
+            // The stash commit, its index commit and its untracked commit
+            if let Ok(stash) = repo.find_commit(entry.id_new()) {
+                collector.add_tree(stash.tree_id())?;
+                for parent in stash.parents().skip(1) {
+                    collector.add_tree(parent.tree_id())?;
+                }
+            }
+        }
+    }
+
+    if recent_refs > 0 {
+        let now = chrono::Utc::now().timestamp();
+        let cutoff = now - (recent_refs + offset) * 86_400;
+        let mut globs = vec!["refs/heads/*", "refs/tags/*"];
+        if remote_refs {
+            globs.push("refs/remotes/*");
+        }
+        for glob in globs {
+            for reference in repo.references_glob(glob)?.flatten() {
+                let Ok(tip) = reference.peel_to_commit() else {
+                    continue;
+                };
+                if tip.time().seconds() < cutoff {
+                    continue;
+                }
+                collector.add_tree(tip.tree_id())?;
+                if recent_commits > 0 {
+                    let since = tip.time().seconds() - (recent_commits + offset) * 86_400;
+                    let mut commit = tip;
+                    while let Ok(parent) = commit.parent(0) {
+                        if parent.time().seconds() < since {
+                            break;
+                        }
+                        collector.add_tree(parent.tree_id())?;
+                        commit = parent;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut unpushed = repo.revwalk()?;
+    unpushed.push_glob("refs/heads")?;
+    unpushed.hide_glob(&format!("refs/remotes/{}", prune_remote))?;
+    for oid in unpushed {
+        collector.add_commit(oid?)?;
+    }
+
+    Ok(collector.oids)
+}
+
+/// Preview `lfs_prune`
+///
+/// Lists the cached LFS objects prune would delete and the space that
+/// frees, applying git-lfs's retention rules (see the retained set above)
+/// without git-lfs itself.
+#[command]
+pub async fn preview_lfs_prune(path: String) -> Result<LfsPrunePreview> {
+    // Finding the retained set walks history and trees; keep it off the
+    // async runtime like the storage report.
+    tokio::task::spawn_blocking(move || prune_preview(&path))
+        .await
+        .map_err(|e| LeviathanError::OperationFailed(format!("Task failed: {}", e)))?
+}
+
+fn prune_preview(path: &str) -> Result<LfsPrunePreview> {
+    let repo = git2::Repository::open(path)?;
+    let (cache_dir, cached) = cached_objects(&repo);
+    let cache = cache_usage(&cache_dir, &cached);
+    if cached.is_empty() {
+        return Ok(LfsPrunePreview {
+            cache,
+            retained_count: 0,
+            prunable: Vec::new(),
+            prunable_size: 0,
+        });
+    }
+
+    let retained = retained_lfs_objects(&repo)?;
+    let (kept, prunable): (Vec<_>, Vec<_>) =
+        cached.into_iter().partition(|o| retained.contains(&o.oid));
+    Ok(LfsPrunePreview {
+        cache,
+        retained_count: kept.len(),
+        prunable_size: prunable.iter().map(|o| o.size).sum(),
+        prunable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    fn pointer_text(oid: &str, size: u64) -> String {
+        format!(
+            "version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize {}\n",
+            oid, size
+        )
+    }
+
+    /// Put an object into the repository's LFS cache.
+    fn cache_object(repo: &TestRepo, oid: &str, size: usize) {
+        let dir = repo
+            .path
+            .join(".git/lfs/objects")
+            .join(&oid[0..2])
+            .join(&oid[2..4]);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(oid), vec![b'x'; size]).unwrap();
+    }
+
+    #[test]
+    fn test_parse_lfs_pointer() {
+        let oid = "a".repeat(64);
+        let pointer = parse_lfs_pointer(pointer_text(&oid, 12345).as_bytes()).unwrap();
+        assert_eq!(pointer.oid, oid);
+        assert_eq!(pointer.size, 12345);
+
+        assert!(parse_lfs_pointer(b"just some text\n").is_none());
+        assert!(parse_lfs_pointer(
+            b"version https://git-lfs.github.com/spec/v1\noid sha256:short\nsize 1\n"
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_attribute_pattern_matching() {
+        let compiled = compile_patterns(&[
+            "*.psd".to_string(),
+            "assets/*.bin".to_string(),
+            "/top.zip".to_string(),
+        ])
+        .unwrap();
+        let matches = |i: usize, p: &str| {
+            let (glob, anchored) = &compiled[i];
+            attribute_pattern_matches(glob, *anchored, p)
+        };
+        assert!(matches(0, "art/deep/hero.psd"));
+        assert!(matches(1, "assets/a.bin"));
+        assert!(!matches(1, "assets/sub/a.bin"));
+        assert!(!matches(1, "other/assets/a.bin"));
+        assert!(matches(2, "top.zip"));
+        assert!(!matches(2, "sub/top.zip"));
+    }
+
+    #[tokio::test]
+    async fn test_storage_report_ranks_blobs_and_estimates_patterns() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_commit(
+            "assets",
+            &[
+                ("art/hero.psd", &"p".repeat(5000)),
+                ("docs/guide.md", &"m".repeat(300)),
+            ],
+        );
+        repo.create_commit("new hero", &[("art/hero.psd", &"q".repeat(6000))]);
+
+        let report = get_lfs_storage_report(repo.path_str(), Some(vec!["*.psd".to_string()]), None)
+            .await
+            .unwrap();
+        assert_eq!(report.commits_scanned, 3);
+        assert_eq!(report.largest_blobs[0].path, "art/hero.psd");
+        assert_eq!(report.largest_blobs[0].size, 6000);
+        assert_eq!(report.by_extension[0].extension, ".psd");
+        assert_eq!(report.by_extension[0].blob_count, 2);
+        assert_eq!(report.by_path[0].path, "art/hero.psd");
+        assert_eq!(report.by_path[0].version_count, 2);
+        assert_eq!(report.by_path[0].total_size, 11_000);
+
+        let estimate = &report.estimates[0];
+        assert_eq!(estimate.blob_count, 2);
+        assert_eq!(estimate.path_count, 1);
+        assert_eq!(estimate.matched_size, 11_000);
+        assert_eq!(
+            estimate.size_with_lfs,
+            report.total_size - 11_000 + 2 * POINTER_SIZE_ESTIMATE
+        );
+        assert!(report.combined_estimate.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_storage_report_suggests_patterns_and_combines_them() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_commit(
+            "assets",
+            &[("a.psd", &"p".repeat(4000)), ("b.wav", &"w".repeat(2000))],
+        );
+
+        let report = get_lfs_storage_report(repo.path_str(), None, None)
+            .await
+            .unwrap();
+        let patterns: Vec<&str> = report
+            .estimates
+            .iter()
+            .map(|e| e.pattern.as_str())
+            .collect();
+        assert_eq!(&patterns[..2], &["*.psd", "*.wav"]);
+        let combined = report.combined_estimate.unwrap();
+        assert!(combined.matched_size >= 6000);
+    }
+
+    #[tokio::test]
+    async fn test_storage_report_finds_unconverted_files_and_counts_pointers() {
+        let repo = TestRepo::with_initial_commit();
+        let oid = "b".repeat(64);
+        repo.create_commit(
+            "lfs",
+            &[
+                (
+                    ".gitattributes",
+                    "*.psd filter=lfs diff=lfs merge=lfs -text\n",
+                ),
+                ("tracked.psd", &pointer_text(&oid, 9_000_000)),
+                ("raw.psd", "not a pointer"),
+            ],
+        );
+
+        let report = get_lfs_storage_report(repo.path_str(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(report.pointer_count, 1);
+        assert_eq!(report.lfs_referenced_size, 9_000_000);
+        assert_eq!(report.unconverted.len(), 1);
+        assert_eq!(report.unconverted[0].path, "raw.psd");
+        assert!(report.unconverted[0].in_head);
+        assert!(report.largest_blobs.iter().all(|b| b.path != "tracked.psd"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_prune_keeps_referenced_objects() {
+        let repo = TestRepo::with_initial_commit();
+        let old = "1".repeat(64);
+        let current = "2".repeat(64);
+        let orphan = "3".repeat(64);
+        repo.create_commit("v1", &[("art.psd", &pointer_text(&old, 10))]);
+        repo.create_commit("v2", &[("art.psd", &pointer_text(&current, 20))]);
+        cache_object(&repo, &old, 10);
+        cache_object(&repo, &current, 20);
+        cache_object(&repo, &orphan, 30);
+
+        // Pretend everything is pushed so only HEAD and recent refs count.
+        let head = repo.head_oid();
+        repo.create_remote_branch("main", head);
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_i64("lfs.fetchrecentrefsdays", 0)
+            .unwrap();
+
+        let preview = preview_lfs_prune(repo.path_str()).await.unwrap();
+        assert_eq!(preview.cache.object_count, 3);
+        assert_eq!(preview.cache.total_size, 60);
+        assert_eq!(preview.retained_count, 1);
+        let prunable: Vec<&str> = preview.prunable.iter().map(|o| o.oid.as_str()).collect();
+        assert_eq!(prunable, vec![old.as_str(), orphan.as_str()]);
+        assert_eq!(preview.prunable_size, 40);
+    }
+
+    #[tokio::test]
+    async fn test_preview_prune_keeps_unpushed_history() {
+        let repo = TestRepo::with_initial_commit();
+        let old = "4".repeat(64);
+        let current = "5".repeat(64);
+        repo.create_commit("v1", &[("art.psd", &pointer_text(&old, 10))]);
+        repo.create_commit("v2", &[("art.psd", &pointer_text(&current, 20))]);
+        cache_object(&repo, &old, 10);
+        cache_object(&repo, &current, 20);
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_i64("lfs.fetchrecentrefsdays", 0)
+            .unwrap();
+
+        let preview = preview_lfs_prune(repo.path_str()).await.unwrap();
+        assert_eq!(preview.retained_count, 2);
+        assert!(preview.prunable.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_preview_prune_empty_cache() {
+        let repo = TestRepo::with_initial_commit();
+        let preview = preview_lfs_prune(repo.path_str()).await.unwrap();
+        assert_eq!(preview.cache.object_count, 0);
+        assert!(preview.prunable.is_empty());
+    }
+}
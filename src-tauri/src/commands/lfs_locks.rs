@@ -0,0 +1,1170 @@
+//! Git LFS file locking command handlers
+//!
+//! Talks to the LFS server's locks API directly (see git-lfs's
+//! `docs/api/locking.md`) instead of shelling out to `git lfs lock`, so
+//! locking works without git-lfs installed and can be exercised against a
+//! local stand-in server. The endpoint is resolved the way git-lfs does it:
+//! `remote.<name>.lfsurl`, then `lfs.url` (repository config, then the
+//! committed `.lfsconfig`), then `<remote url>.git/info/lfs`.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::command;
+
+use crate::error::{LeviathanError, Result};
+use crate::utils::create_command;
+
+/// Media type the LFS API speaks
+const LFS_MEDIA_TYPE: &str = "application/vnd.git-lfs+json";
+
+/// Upper bound on pages followed when listing locks, so a server that keeps
+/// returning a cursor cannot spin forever.
+const MAX_LOCK_PAGES: usize = 100;
+
+/// A file lock held on the LFS server
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LfsLock {
+    pub id: String,
+    /// Repository-relative path, with forward slashes
+    pub path: String,
+    /// When the lock was taken (RFC 3339, as the server reports it)
+    pub locked_at: String,
+    /// Display name of the lock's owner
+    pub owner: Option<String>,
+    /// Whether the lock belongs to the current user. Only known when the
+    /// server supports lock verification; false otherwise.
+    pub is_ours: bool,
+}
+
+/// Result of [`apply_lfs_lockable_permissions`]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockablePermissions {
+    /// Lockable files made read-only because the user holds no lock on them
+    pub read_only: Vec<String>,
+    /// Lockable files left writable because the user holds their lock
+    pub writable: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiOwner {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ApiLock {
+    id: String,
+    path: String,
+    #[serde(default)]
+    locked_at: String,
+    owner: Option<ApiOwner>,
+}
+
+impl ApiLock {
+    fn into_lock(self, is_ours: bool) -> LfsLock {
+        LfsLock {
+            id: self.id,
+            path: self.path,
+            locked_at: self.locked_at,
+            owner: self.owner.map(|o| o.name),
+            is_ours,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiLockResponse {
+    lock: Option<ApiLock>,
+    message: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiLockList {
+    #[serde(default)]
+    locks: Vec<ApiLock>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiVerifyList {
+    #[serde(default)]
+    ours: Vec<ApiLock>,
+    #[serde(default)]
+    theirs: Vec<ApiLock>,
+    next_cursor: Option<String>,
+}
+
+/// Turn a git remote URL into its default LFS endpoint.
+///
+/// SSH remotes map to HTTPS on the same host, which is what the common
+/// hosts serve the API on; git-lfs's `git-lfs-authenticate` SSH handshake
+/// is not attempted.
+fn endpoint_from_remote_url(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let https = if url.starts_with("https://") || url.starts_with("http://") {
+        url.to_string()
+    } else if let Some(rest) = url.strip_prefix("ssh://") {
+        let rest = rest.split_once('@').map(|(_, r)| r).unwrap_or(rest);
+        let (host, path) = rest.split_once('/')?;
+        let host = host.split(':').next()?;
+        format!("https://{}/{}", host, path)
+    } else if let Some((user_host, path)) = url.split_once(':') {
+        // scp-like `git@host:owner/repo.git`
+        if user_host.contains('/') {
+            return None;
+        }
+        let host = user_host.rsplit('@').next()?;
+        format!("https://{}/{}", host, path.trim_start_matches('/'))
+    } else {
+        return None;
+    };
+    let base = if https.ends_with(".git") {
+        https
+    } else {
+        format!("{}.git", https)
+    };
+    Some(format!("{}/info/lfs", base))
+}
+
+/// Resolve the LFS endpoint for `remote`.
+fn lfs_endpoint(repo: &git2::Repository, remote: &str) -> Result<String> {
+    let lfsurl_key = format!("remote.{}.lfsurl", remote);
+    let from = |config: &git2::Config| {
+        config
+            .get_string(&lfsurl_key)
+            .or_else(|_| config.get_string("lfs.url"))
+            .ok()
+            .filter(|u| !u.trim().is_empty())
+    };
+
+    if let Some(url) = repo.config().ok().as_ref().and_then(from) {
+        return Ok(url.trim_end_matches('/').to_string());
+    }
+    if let Some(workdir) = repo.workdir() {
+        let lfsconfig = workdir.join(".lfsconfig");
+        if lfsconfig.exists() {
+            if let Some(url) = git2::Config::open(&lfsconfig).ok().as_ref().and_then(from) {
+                return Ok(url.trim_end_matches('/').to_string());
+            }
+        }
+    }
+
+    let git_remote = repo
+        .find_remote(remote)
+        .map_err(|_| LeviathanError::RemoteNotFound(remote.to_string()))?;
+    let url = git_remote
+        .url()
+        .map_err(|_| LeviathanError::RemoteNotFound(remote.to_string()))?;
+    endpoint_from_remote_url(url).ok_or_else(|| {
+        LeviathanError::OperationFailed(format!(
+            "Cannot determine the LFS server for remote '{}' ({}); set lfs.url",
+            remote, url
+        ))
+    })
+}
+
+/// The ref locks are scoped to: the current branch's upstream branch, as
+/// git-lfs sends it. None on a detached HEAD, which the API allows.
+fn lock_ref(repo: &git2::Repository) -> Option<String> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    let branch = head.shorthand().ok()?.to_string();
+    let merge = repo
+        .config()
+        .ok()
+        .and_then(|c| c.get_string(&format!("branch.{}.merge", branch)).ok());
+    Some(merge.unwrap_or_else(|| format!("refs/heads/{}", branch)))
+}
+
+/// Ask git's credential helpers for a username and password for `url`,
+/// without ever prompting.
+fn credential_fill(repo_path: &Path, url: &str) -> Option<(String, String)> {
+    use std::io::Write;
+
+    let mut child = create_command("git")
+        .current_dir(repo_path)
+        .args(["credential", "fill"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(format!("url={}\n\n", url).as_bytes());
+    }
+    let output = child.wait_with_output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut username = None;
+    let mut password = None;
+    for line in stdout.lines() {
+        if let Some(u) = line.strip_prefix("username=") {
+            username = Some(u.to_string());
+        } else if let Some(p) = line.strip_prefix("password=") {
+            password = Some(p.to_string());
+        }
+    }
+    Some((username?, password?))
+}
+
+/// A connection to one repository's locks API.
+struct LocksApi {
+    client: reqwest::Client,
+    endpoint: String,
+    repo_path: PathBuf,
+    lock_ref: Option<String>,
+    /// Filled from the credential helpers after the first 401
+    auth: Option<(String, String)>,
+}
+
+impl LocksApi {
+    fn open(repo_path: &Path, remote: Option<&str>) -> Result<Self> {
+        let repo = git2::Repository::open(repo_path)?;
+        let endpoint = lfs_endpoint(&repo, remote.unwrap_or("origin"))?;
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| {
+                LeviathanError::OperationFailed(format!("Failed to create HTTP client: {}", e))
+            })?;
+        Ok(Self {
+            client,
+            endpoint,
+            repo_path: repo_path.to_path_buf(),
+            lock_ref: lock_ref(&repo),
+            auth: None,
+        })
+    }
+
+    /// The `ref` member the write endpoints take.
+    fn ref_json(&self) -> Option<serde_json::Value> {
+        self.lock_ref
+            .as_ref()
+            .map(|name| serde_json::json!({ "name": name }))
+    }
+
+    /// Send a request, retrying once with stored credentials on a 401 the way
+    /// git-lfs does: servers that allow anonymous reads never see a prompt.
+    async fn request(
+        &mut self,
+        method: reqwest::Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&serde_json::Value>,
+    ) -> Result<(reqwest::StatusCode, String)> {
+        let url = format!("{}{}", self.endpoint, path);
+        for attempt in 0..2 {
+            let mut request = self
+                .client
+                .request(method.clone(), &url)
+                .header("Accept", LFS_MEDIA_TYPE)
+                .header("User-Agent", "Leviathan-Git-Client")
+                .query(query);
+            if let Some(body) = body {
+                request = request
+                    .header("Content-Type", LFS_MEDIA_TYPE)
+                    .body(body.to_string());
+            }
+            if let Some((user, pass)) = &self.auth {
+                request = request.basic_auth(user, Some(pass));
+            }
+
+            let response = request.send().await.map_err(|e| {
+                LeviathanError::OperationFailed(format!("LFS locks request failed: {}", e))
+            })?;
+            let status = response.status();
+            if status == reqwest::StatusCode::UNAUTHORIZED && attempt == 0 {
+                match credential_fill(&self.repo_path, &self.endpoint) {
+                    Some(creds) => {
+                        self.auth = Some(creds);
+                        continue;
+                    }
+                    None => return Err(LeviathanError::AuthenticationRequired),
+                }
+            }
+            if status == reqwest::StatusCode::UNAUTHORIZED {
+                return Err(LeviathanError::AuthenticationRequired);
+            }
+            let text = response.text().await.unwrap_or_default();
+            return Ok((status, text));
+        }
+        Err(LeviathanError::AuthenticationRequired)
+    }
+
+    /// The server's explanation for a failed request.
+    fn error_for(status: reqwest::StatusCode, text: &str) -> LeviathanError {
+        let message = serde_json::from_str::<ApiLockResponse>(text)
+            .ok()
+            .and_then(|r| r.message)
+            .filter(|m| !m.is_empty());
+        let message = match (status.as_u16(), message) {
+            (_, Some(message)) => message,
+            (404, None) => "The LFS server does not support file locking".to_string(),
+            (403, None) => "Permission denied by the LFS server".to_string(),
+            (_, None) => format!("LFS server returned {}", status),
+        };
+        LeviathanError::OperationFailed(message)
+    }
+
+    fn parse<T: serde::de::DeserializeOwned>(text: &str) -> Result<T> {
+        serde_json::from_str(text).map_err(|e| {
+            LeviathanError::OperationFailed(format!("Invalid LFS locks response: {}", e))
+        })
+    }
+
+    /// Every lock, optionally only the one on `path` (`GET /locks`).
+    async fn list(&mut self, path: Option<&str>) -> Result<Vec<LfsLock>> {
+        let mut locks = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LOCK_PAGES {
+            let mut query = Vec::new();
+            if let Some(path) = path {
+                query.push(("path", path.to_string()));
+            }
+            if let Some(c) = &cursor {
+                query.push(("cursor", c.clone()));
+            }
+            if let Some(r) = &self.lock_ref {
+                query.push(("refspec", r.clone()));
+            }
+            let (status, text) = self
+                .request(reqwest::Method::GET, "/locks", &query, None)
+                .await?;
+            if !status.is_success() {
+                return Err(Self::error_for(status, &text));
+            }
+            let page: ApiLockList = Self::parse(&text)?;
+            locks.extend(page.locks.into_iter().map(|l| l.into_lock(false)));
+            cursor = page.next_cursor.filter(|c| !c.is_empty());
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(locks)
+    }
+
+    /// Locks split into ours and theirs (`POST /locks/verify`). Ok(None)
+    /// when the server does not offer verification.
+    async fn verify(&mut self) -> Result<Option<Vec<LfsLock>>> {
+        let mut locks = Vec::new();
+        let mut cursor: Option<String> = None;
+        for _ in 0..MAX_LOCK_PAGES {
+            let mut body = serde_json::json!({});
+            if let Some(r) = self.ref_json() {
+                body["ref"] = r;
+            }
+            if let Some(c) = &cursor {
+                body["cursor"] = serde_json::Value::String(c.clone());
+            }
+            let (status, text) = self
+                .request(reqwest::Method::POST, "/locks/verify", &[], Some(&body))
+                .await?;
+            if matches!(status.as_u16(), 403 | 404 | 405 | 501) {
+                return Ok(None);
+            }
+            if !status.is_success() {
+                return Err(Self::error_for(status, &text));
+            }
+            let page: ApiVerifyList = Self::parse(&text)?;
+            locks.extend(page.ours.into_iter().map(|l| l.into_lock(true)));
+            locks.extend(page.theirs.into_iter().map(|l| l.into_lock(false)));
+            cursor = page.next_cursor.filter(|c| !c.is_empty());
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(Some(locks))
+    }
+
+    /// Locks with ownership when the server can tell, plain otherwise.
+    async fn all_locks(&mut self) -> Result<Vec<LfsLock>> {
+        match self.verify().await? {
+            Some(locks) => Ok(locks),
+            None => self.list(None).await,
+        }
+    }
+}
+
+/// Normalise a user-supplied path to the repository-relative, forward-slash
+/// form the locks API keys on. An absolute path inside the work tree is
+/// accepted.
+fn lock_path(repo_path: &Path, file_path: &str) -> Result<String> {
+    let candidate = Path::new(file_path);
+    let relative = if candidate.is_absolute() {
+        let root = std::fs::canonicalize(repo_path).unwrap_or_else(|_| repo_path.to_path_buf());
+        let abs = std::fs::canonicalize(candidate).unwrap_or_else(|_| candidate.to_path_buf());
+        abs.strip_prefix(&root)
+            .map(Path::to_path_buf)
+            .map_err(|_| LeviathanError::InvalidPath(file_path.to_string()))?
+    } else {
+        candidate.to_path_buf()
+    };
+    let normalized = relative
+        .to_string_lossy()
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .to_string();
+    if normalized.is_empty() || normalized.split('/').any(|c| c == "..") {
+        return Err(LeviathanError::InvalidPath(file_path.to_string()));
+    }
+    Ok(normalized)
+}
+
+/// Whether `path` carries the `lockable` attribute.
+fn is_lockable(repo: &git2::Repository, path: &str) -> bool {
+    let value = repo
+        .get_attr(
+            Path::new(path),
+            "lockable",
+            git2::AttrCheckFlags::FILE_THEN_INDEX,
+        )
+        .ok()
+        .flatten();
+    matches!(git2::AttrValue::from_string(value), git2::AttrValue::True)
+}
+
+/// Make a file writable or read-only for its owner, as git-lfs does for
+/// lockable files. On unix only the write bits change, so the group/other
+/// permissions the user chose are kept.
+fn set_writable(path: &Path, writable: bool) -> std::io::Result<()> {
+    let mut perms = std::fs::metadata(path)?.permissions();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = perms.mode();
+        perms.set_mode(if writable {
+            mode | 0o200
+        } else {
+            mode & !0o222
+        });
+    }
+    #[cfg(not(unix))]
+    {
+        perms.set_readonly(!writable);
+    }
+    std::fs::set_permissions(path, perms)
+}
+
+/// List the locks on the LFS server
+///
+/// `is_ours` is filled in when the server supports lock verification (it
+/// needs push access); otherwise every lock is reported as someone else's.
+#[command]
+pub async fn list_lfs_locks(
+    path: String,
+    remote: Option<String>,
+    file_path: Option<String>,
+) -> Result<Vec<LfsLock>> {
+    let repo_path = Path::new(&path);
+    let filter = file_path
+        .as_deref()
+        .map(|f| lock_path(repo_path, f))
+        .transpose()?;
+    let mut api = LocksApi::open(repo_path, remote.as_deref())?;
+    let mut locks = api.all_locks().await?;
+    if let Some(filter) = filter {
+        locks.retain(|l| l.path == filter);
+    }
+    locks.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(locks)
+}
+
+/// Lock a file on the LFS server
+///
+/// Fails with the current owner's name when someone else holds the lock.
+/// A lockable file is made writable once the lock is ours.
+#[command]
+pub async fn lfs_lock(path: String, file_path: String, remote: Option<String>) -> Result<LfsLock> {
+    let repo_path = Path::new(&path);
+    let file = lock_path(repo_path, &file_path)?;
+    let mut api = LocksApi::open(repo_path, remote.as_deref())?;
+
+    let mut body = serde_json::json!({ "path": file });
+    if let Some(r) = api.ref_json() {
+        body["ref"] = r;
+    }
+    let (status, text) = api
+        .request(reqwest::Method::POST, "/locks", &[], Some(&body))
+        .await?;
+    if status == reqwest::StatusCode::CONFLICT {
+        let existing = serde_json::from_str::<ApiLockResponse>(&text)
+            .ok()
+            .and_then(|r| r.lock)
+            .and_then(|l| l.owner)
+            .map(|o| o.name);
+        return Err(LeviathanError::OperationFailed(match existing {
+            Some(owner) => format!("'{}' is already locked by {}", file, owner),
+            None => format!("'{}' is already locked", file),
+        }));
+    }
+    if !status.is_success() {
+        return Err(LocksApi::error_for(status, &text));
+    }
+    let lock = LocksApi::parse::<ApiLockResponse>(&text)?
+        .lock
+        .ok_or_else(|| LeviathanError::OperationFailed("LFS server returned no lock".to_string()))?
+        .into_lock(true);
+
+    let full = repo_path.join(&lock.path);
+    if full.exists() {
+        if let Err(e) = set_writable(&full, true) {
+            tracing::warn!("Failed to make {} writable: {}", lock.path, e);
+        }
+    }
+    Ok(lock)
+}
+
+/// Unlock a file, by path or lock id
+///
+/// Like `git lfs unlock`, a file with uncommitted changes is not unlocked
+/// unless `force` is set: releasing the lock would invite someone else to
+/// edit a file whose changes are about to conflict with theirs. `force` also
+/// breaks a lock held by someone else, where the server permits it. A
+/// lockable file is made read-only again afterwards.
+#[command]
+pub async fn lfs_unlock(
+    path: String,
+    file_path: Option<String>,
+    lock_id: Option<String>,
+    force: Option<bool>,
+    remote: Option<String>,
+) -> Result<LfsLock> {
+    let repo_path = Path::new(&path);
+    let force = force.unwrap_or(false);
+    let file = file_path
+        .as_deref()
+        .map(|f| lock_path(repo_path, f))
+        .transpose()?;
+    let mut api = LocksApi::open(repo_path, remote.as_deref())?;
+
+    let id = match (lock_id, &file) {
+        (Some(id), _) => id,
+        (None, Some(file)) => api
+            .list(Some(file))
+            .await?
+            .into_iter()
+            .find(|l| &l.path == file)
+            .map(|l| l.id)
+            .ok_or_else(|| LeviathanError::OperationFailed(format!("'{}' is not locked", file)))?,
+        (None, None) => {
+            return Err(LeviathanError::OperationFailed(
+                "A file path or lock id is required".to_string(),
+            ))
+        }
+    };
+
+    if !force {
+        if let Some(file) = &file {
+            let repo = git2::Repository::open(repo_path)?;
+            let modified = repo
+                .status_file(Path::new(file))
+                .map(|s| !s.is_empty() && !s.contains(git2::Status::IGNORED))
+                .unwrap_or(false);
+            if modified {
+                return Err(LeviathanError::OperationFailed(format!(
+                    "'{}' has uncommitted changes; commit or discard them first, or force the unlock",
+                    file
+                )));
+            }
+        }
+    }
+
+    let mut body = serde_json::json!({ "force": force });
+    if let Some(r) = api.ref_json() {
+        body["ref"] = r;
+    }
+    let (status, text) = api
+        .request(
+            reqwest::Method::POST,
+            &format!("/locks/{}/unlock", urlencoding::encode(&id)),
+            &[],
+            Some(&body),
+        )
+        .await?;
+    if status == reqwest::StatusCode::FORBIDDEN && !force {
+        return Err(LeviathanError::OperationFailed(
+            "The lock belongs to someone else; force the unlock to break it".to_string(),
+        ));
+    }
+    if !status.is_success() {
+        return Err(LocksApi::error_for(status, &text));
+    }
+    let lock = LocksApi::parse::<ApiLockResponse>(&text)?
+        .lock
+        .ok_or_else(|| LeviathanError::OperationFailed("LFS server returned no lock".to_string()))?
+        .into_lock(false);
+
+    let repo = git2::Repository::open(repo_path)?;
+    let full = repo_path.join(&lock.path);
+    if full.exists() && is_lockable(&repo, &lock.path) {
+        if let Err(e) = set_writable(&full, false) {
+            tracing::warn!("Failed to make {} read-only: {}", lock.path, e);
+        }
+    }
+    Ok(lock)
+}
+
+/// Find changes about to be committed to files someone else has locked
+///
+/// Meant to run before a commit: returns the other users' locks on staged
+/// files (and, with `include_unstaged`, on modified files too), so the UI can
+/// warn before work is committed that the lock holder will have to merge.
+/// Without lock verification on the server every lock counts as someone
+/// else's, erring towards a warning.
+#[command]
+pub async fn check_lfs_lock_conflicts(
+    path: String,
+    remote: Option<String>,
+    include_unstaged: Option<bool>,
+) -> Result<Vec<LfsLock>> {
+    let repo_path = Path::new(&path);
+    let changed: HashSet<String> = {
+        let repo = git2::Repository::open(repo_path)?;
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(false).exclude_submodules(true);
+        let mut wanted = git2::Status::INDEX_NEW
+            | git2::Status::INDEX_MODIFIED
+            | git2::Status::INDEX_DELETED
+            | git2::Status::INDEX_RENAMED
+            | git2::Status::INDEX_TYPECHANGE;
+        if include_unstaged.unwrap_or(false) {
+            wanted |= git2::Status::WT_MODIFIED
+                | git2::Status::WT_DELETED
+                | git2::Status::WT_RENAMED
+                | git2::Status::WT_TYPECHANGE;
+        }
+        let statuses = repo.statuses(Some(&mut opts))?;
+        let changed = statuses
+            .iter()
+            .filter(|e| e.status().intersects(wanted))
+            .filter_map(|e| e.path().ok().map(|p| p.to_string()))
+            .collect();
+        changed
+    };
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut api = LocksApi::open(repo_path, remote.as_deref())?;
+    let mut conflicts: Vec<LfsLock> = api
+        .all_locks()
+        .await?
+        .into_iter()
+        .filter(|l| !l.is_ours && changed.contains(&l.path))
+        .collect();
+    conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(conflicts)
+}
+
+/// Make every lockable file read-only unless the user holds its lock
+///
+/// The same protection `git lfs` applies on checkout: an editor opening a
+/// lockable file someone else may be changing gets a read-only file, a cue
+/// to lock it first. Needs lock verification on the server to know which
+/// locks are the user's.
+#[command]
+pub async fn apply_lfs_lockable_permissions(
+    path: String,
+    remote: Option<String>,
+) -> Result<LockablePermissions> {
+    let repo_path = Path::new(&path);
+    let lockable: Vec<String> = {
+        let repo = git2::Repository::open(repo_path)?;
+        let index = repo.index()?;
+        let lockable = index
+            .iter()
+            .filter_map(|e| String::from_utf8(e.path).ok())
+            .filter(|p| is_lockable(&repo, p))
+            .collect();
+        lockable
+    };
+    if lockable.is_empty() {
+        return Ok(LockablePermissions {
+            read_only: Vec::new(),
+            writable: Vec::new(),
+        });
+    }
+
+    let mut api = LocksApi::open(repo_path, remote.as_deref())?;
+    let ours: HashSet<String> = api
+        .verify()
+        .await?
+        .ok_or_else(|| {
+            LeviathanError::OperationFailed(
+                "The LFS server cannot tell which locks are yours (lock verification is unavailable)"
+                    .to_string(),
+            )
+        })?
+        .into_iter()
+        .filter(|l| l.is_ours)
+        .map(|l| l.path)
+        .collect();
+
+    let mut result = LockablePermissions {
+        read_only: Vec::new(),
+        writable: Vec::new(),
+    };
+    for file in lockable {
+        let full = repo_path.join(&file);
+        if !full.exists() {
+            continue;
+        }
+        let writable = ours.contains(&file);
+        set_writable(&full, writable)?;
+        if writable {
+            result.writable.push(file);
+        } else {
+            result.read_only.push(file);
+        }
+    }
+    Ok(result)
+}
+
+/// Mark files matching `pattern` as lockable (or not) in .gitattributes
+///
+/// Adds or removes the `lockable` attribute on the pattern's line, creating
+/// the line when the pattern has none, the same edit `git lfs track
+/// --lockable` makes.
+#[command]
+pub async fn set_lfs_lockable(path: String, pattern: String, lockable: bool) -> Result<()> {
+    use crate::commands::gitattributes::{format_pattern, split_pattern_and_rest};
+
+    if pattern.trim().is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "A .gitattributes pattern cannot be empty.".to_string(),
+        ));
+    }
+    let attrs_path = Path::new(&path).join(".gitattributes");
+    let content = if attrs_path.exists() {
+        std::fs::read_to_string(&attrs_path)?
+    } else {
+        String::new()
+    };
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut found = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            lines.push(line.to_string());
+            continue;
+        }
+        let (line_pattern, rest) = split_pattern_and_rest(trimmed);
+        if line_pattern != pattern {
+            lines.push(line.to_string());
+            continue;
+        }
+        found = true;
+        let mut attrs: Vec<&str> = rest
+            .split_whitespace()
+            .filter(|a| *a != "lockable")
+            .collect();
+        if lockable {
+            attrs.push("lockable");
+        }
+        // A line left with no attributes at all means nothing; drop it.
+        if !attrs.is_empty() {
+            lines.push(format!("{} {}", format_pattern(&pattern), attrs.join(" ")));
+        }
+    }
+    if !found && lockable {
+        lines.push(format!("{} lockable", format_pattern(&pattern)));
+    }
+
+    let mut result = lines.join("\n");
+    if !result.is_empty() {
+        result.push('\n');
+    }
+    std::fs::write(&attrs_path, result)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// A minimal stand-in for an LFS server's locks API. Locks created
+    /// through it belong to "me"; `preload` adds locks owned by "other".
+    struct LockServer {
+        base: String,
+        locks: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    impl LockServer {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base = format!("http://{}", listener.local_addr().unwrap());
+            let locks: Arc<Mutex<Vec<serde_json::Value>>> = Arc::default();
+            let state = locks.clone();
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    Self::handle(stream, &state);
+                }
+            });
+            Self { base, locks }
+        }
+
+        fn preload(&self, path: &str) {
+            let mut locks = self.locks.lock().unwrap();
+            let id = format!("{}", locks.len() + 100);
+            locks.push(serde_json::json!({
+                "id": id, "path": path, "locked_at": "2024-01-01T00:00:00Z",
+                "owner": { "name": "other" }
+            }));
+        }
+
+        fn handle(stream: std::net::TcpStream, state: &Mutex<Vec<serde_json::Value>>) {
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(v) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = v.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or("");
+            let target = parts.next().unwrap_or("");
+            let (route, query) = target.split_once('?').unwrap_or((target, ""));
+            let route = route.split("/info/lfs").nth(1).unwrap_or("");
+
+            let mut locks = state.lock().unwrap();
+            let owner = |l: &serde_json::Value| l["owner"]["name"].as_str() == Some("me");
+            let (status, reply) = match (method, route) {
+                ("POST", "/locks") => {
+                    let path = body["path"].as_str().unwrap_or("").to_string();
+                    if let Some(existing) = locks.iter().find(|l| l["path"] == path) {
+                        (
+                            409,
+                            serde_json::json!({ "lock": existing, "message": "already locked" }),
+                        )
+                    } else {
+                        let lock = serde_json::json!({
+                            "id": format!("{}", locks.len() + 1), "path": path,
+                            "locked_at": "2024-01-02T00:00:00Z", "owner": { "name": "me" }
+                        });
+                        locks.push(lock.clone());
+                        (201, serde_json::json!({ "lock": lock }))
+                    }
+                }
+                ("GET", "/locks") => {
+                    let wanted = query
+                        .split('&')
+                        .find_map(|kv| kv.strip_prefix("path="))
+                        .map(|p| urlencoding::decode(p).unwrap().to_string());
+                    let found: Vec<_> = locks
+                        .iter()
+                        .filter(|l| match &wanted {
+                            Some(w) => l["path"] == w.as_str(),
+                            None => true,
+                        })
+                        .cloned()
+                        .collect();
+                    (200, serde_json::json!({ "locks": found }))
+                }
+                ("POST", "/locks/verify") => {
+                    let ours: Vec<_> = locks.iter().filter(|l| owner(l)).cloned().collect();
+                    let theirs: Vec<_> = locks.iter().filter(|l| !owner(l)).cloned().collect();
+                    (200, serde_json::json!({ "ours": ours, "theirs": theirs }))
+                }
+                ("POST", r) if r.ends_with("/unlock") => {
+                    let id = r.trim_start_matches("/locks/").trim_end_matches("/unlock");
+                    match locks.iter().position(|l| l["id"] == id) {
+                        Some(i) if owner(&locks[i]) || body["force"] == true => {
+                            let lock = locks.remove(i);
+                            (200, serde_json::json!({ "lock": lock }))
+                        }
+                        Some(_) => (403, serde_json::json!({ "message": "not your lock" })),
+                        None => (404, serde_json::json!({ "message": "no such lock" })),
+                    }
+                }
+                _ => (404, serde_json::json!({ "message": "not found" })),
+            };
+            drop(locks);
+
+            let reply = reply.to_string();
+            let mut stream = stream;
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {} X\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                LFS_MEDIA_TYPE,
+                reply.len(),
+                reply
+            );
+        }
+    }
+
+    fn repo_with_server() -> (TestRepo, LockServer) {
+        let server = LockServer::start();
+        let repo = TestRepo::with_initial_commit();
+        repo.add_remote("origin", &format!("{}/team/art", server.base));
+        (repo, server)
+    }
+
+    #[test]
+    fn test_endpoint_from_remote_url() {
+        assert_eq!(
+            endpoint_from_remote_url("https://example.com/team/art.git").as_deref(),
+            Some("https://example.com/team/art.git/info/lfs")
+        );
+        assert_eq!(
+            endpoint_from_remote_url("https://example.com/team/art/").as_deref(),
+            Some("https://example.com/team/art.git/info/lfs")
+        );
+        assert_eq!(
+            endpoint_from_remote_url("git@example.com:team/art.git").as_deref(),
+            Some("https://example.com/team/art.git/info/lfs")
+        );
+        assert_eq!(
+            endpoint_from_remote_url("ssh://git@example.com:2222/team/art").as_deref(),
+            Some("https://example.com/team/art.git/info/lfs")
+        );
+        assert_eq!(endpoint_from_remote_url("/srv/repos/art"), None);
+    }
+
+    #[test]
+    fn test_lfs_endpoint_prefers_configured_urls() {
+        let repo = TestRepo::with_initial_commit();
+        repo.add_remote("origin", "https://example.com/team/art.git");
+        let git_repo = repo.repo();
+        assert_eq!(
+            lfs_endpoint(&git_repo, "origin").unwrap(),
+            "https://example.com/team/art.git/info/lfs"
+        );
+
+        std::fs::write(
+            repo.path.join(".lfsconfig"),
+            "[lfs]\n\turl = https://lfs.example.com/art\n",
+        )
+        .unwrap();
+        assert_eq!(
+            lfs_endpoint(&git_repo, "origin").unwrap(),
+            "https://lfs.example.com/art"
+        );
+
+        git_repo
+            .config()
+            .unwrap()
+            .set_str("remote.origin.lfsurl", "https://override.example.com/lfs/")
+            .unwrap();
+        assert_eq!(
+            lfs_endpoint(&git_repo, "origin").unwrap(),
+            "https://override.example.com/lfs"
+        );
+    }
+
+    #[test]
+    fn test_lock_path_normalizes() {
+        let repo = TestRepo::with_initial_commit();
+        assert_eq!(
+            lock_path(&repo.path, "./art/hero.psd").unwrap(),
+            "art/hero.psd"
+        );
+        let abs = repo.path.join("README.md");
+        assert_eq!(
+            lock_path(&repo.path, &abs.to_string_lossy()).unwrap(),
+            "README.md"
+        );
+        assert!(lock_path(&repo.path, "../outside").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_lock_list_and_unlock() {
+        let (repo, server) = repo_with_server();
+        server.preload("art/other.psd");
+
+        let lock = lfs_lock(repo.path_str(), "README.md".to_string(), None)
+            .await
+            .unwrap();
+        assert!(lock.is_ours);
+        assert_eq!(lock.owner.as_deref(), Some("me"));
+
+        let locks = list_lfs_locks(repo.path_str(), None, None).await.unwrap();
+        assert_eq!(locks.len(), 2);
+        let theirs = locks.iter().find(|l| l.path == "art/other.psd").unwrap();
+        assert!(!theirs.is_ours);
+        assert_eq!(theirs.owner.as_deref(), Some("other"));
+
+        let unlocked = lfs_unlock(
+            repo.path_str(),
+            Some("README.md".to_string()),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(unlocked.id, lock.id);
+        assert_eq!(server.locks.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_lock_held_by_someone_else_names_the_owner() {
+        let (repo, server) = repo_with_server();
+        server.preload("README.md");
+
+        let err = lfs_lock(repo.path_str(), "README.md".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("locked by other"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_unlock_of_someone_elses_lock_needs_force() {
+        let (repo, server) = repo_with_server();
+        server.preload("README.md");
+
+        let refused = lfs_unlock(
+            repo.path_str(),
+            Some("README.md".to_string()),
+            None,
+            None,
+            None,
+        )
+        .await;
+        assert!(refused.is_err());
+        assert_eq!(server.locks.lock().unwrap().len(), 1);
+
+        lfs_unlock(
+            repo.path_str(),
+            Some("README.md".to_string()),
+            None,
+            Some(true),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(server.locks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unlock_refuses_a_modified_file() {
+        let (repo, _server) = repo_with_server();
+        lfs_lock(repo.path_str(), "README.md".to_string(), None)
+            .await
+            .unwrap();
+        std::fs::write(repo.path.join("README.md"), "edited").unwrap();
+
+        let result = lfs_unlock(
+            repo.path_str(),
+            Some("README.md".to_string()),
+            None,
+            None,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_lock_conflicts_reports_only_others_locks_on_staged_files() {
+        let (repo, server) = repo_with_server();
+        server.preload("art/hero.psd");
+        server.preload("art/untouched.psd");
+        repo.create_commit(
+            "art",
+            &[
+                ("art/hero.psd", "v1"),
+                ("art/mine.psd", "v1"),
+                ("art/untouched.psd", "v1"),
+            ],
+        );
+        lfs_lock(repo.path_str(), "art/mine.psd".to_string(), None)
+            .await
+            .unwrap();
+
+        std::fs::write(repo.path.join("art/hero.psd"), "v2").unwrap();
+        std::fs::write(repo.path.join("art/mine.psd"), "v2").unwrap();
+        repo.stage_file("art/hero.psd");
+        repo.stage_file("art/mine.psd");
+
+        let conflicts = check_lfs_lock_conflicts(repo.path_str(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "art/hero.psd");
+        assert_eq!(conflicts[0].owner.as_deref(), Some("other"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_lockable_files_are_read_only_unless_locked_by_us() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (repo, _server) = repo_with_server();
+        set_lfs_lockable(repo.path_str(), "*.psd".to_string(), true)
+            .await
+            .unwrap();
+        repo.create_commit("art", &[("a.psd", "a"), ("b.psd", "b"), ("c.txt", "c")]);
+        lfs_lock(repo.path_str(), "a.psd".to_string(), None)
+            .await
+            .unwrap();
+
+        let result = apply_lfs_lockable_permissions(repo.path_str(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.writable, vec!["a.psd".to_string()]);
+        assert_eq!(result.read_only, vec!["b.psd".to_string()]);
+        let mode = |f: &str| {
+            std::fs::metadata(repo.path.join(f))
+                .unwrap()
+                .permissions()
+                .mode()
+        };
+        assert_eq!(mode("b.psd") & 0o222, 0);
+        assert_ne!(mode("a.psd") & 0o200, 0);
+        assert_ne!(mode("c.txt") & 0o200, 0, "non-lockable files are untouched");
+    }
+
+    #[tokio::test]
+    async fn test_set_lfs_lockable_edits_the_pattern_line() {
+        let repo = TestRepo::with_initial_commit();
+        std::fs::write(
+            repo.path.join(".gitattributes"),
+            "# art\n*.psd filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+
+        set_lfs_lockable(repo.path_str(), "*.psd".to_string(), true)
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(repo.path.join(".gitattributes")).unwrap();
+        assert_eq!(
+            content,
+            "# art\n*.psd filter=lfs diff=lfs merge=lfs -text lockable\n"
+        );
+
+        set_lfs_lockable(repo.path_str(), "*.psd".to_string(), false)
+            .await
+            .unwrap();
+        let content = std::fs::read_to_string(repo.path.join(".gitattributes")).unwrap();
+        assert_eq!(
+            content,
+            "# art\n*.psd filter=lfs diff=lfs merge=lfs -text\n"
+        );
+    }
+}
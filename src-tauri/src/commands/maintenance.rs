@@ -5,9 +5,13 @@
 
 use std::fs;
 use std::path::Path;
-use tauri::command;
+use tauri::{command, AppHandle, State};
 
 use crate::error::{LeviathanError, Result};
+use crate::services::maintenance_service::{
+    self, MaintenanceRunRecord, MaintenanceSchedule, MaintenanceState, MaintenanceTask,
+    MaintenanceTrigger, ScheduledTask,
+};
 use crate::utils::create_command;
 
 // ──────────────────────────────────────────────────────────────────────────────
@@ -751,6 +755,214 @@ pub async fn get_pack_info(path: String) -> Result<PackInfo> {
     })
 }
 
+// ──────────────────────────────────────────────────────────────────────────────
+// Scheduled maintenance
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// A scheduled task with when it last ran and when it is next due
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceTaskStatus {
+    #[serde(flatten)]
+    pub schedule: ScheduledTask,
+    pub last_run: Option<MaintenanceRunRecord>,
+    /// Unix timestamp the task is next due; None when disabled
+    pub next_due_at: Option<i64>,
+}
+
+/// A repository's maintenance schedule and current state
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceStatus {
+    /// Whether the background loop is running for this repository
+    pub running: bool,
+    /// Why maintenance would be paused right now, if it would be
+    pub busy_reason: Option<String>,
+    pub tasks: Vec<MaintenanceTaskStatus>,
+}
+
+fn open_for_maintenance(path: &str) -> Result<git2::Repository> {
+    git2::Repository::open(path).map_err(|_| LeviathanError::RepositoryNotFound(path.to_string()))
+}
+
+/// Start scheduled maintenance for a repository
+#[command]
+pub async fn start_scheduled_maintenance(
+    app: AppHandle,
+    state: State<'_, MaintenanceState>,
+    path: String,
+) -> Result<()> {
+    open_for_maintenance(&path)?;
+    let mut service = state.write().await;
+    service.start(path, app);
+    Ok(())
+}
+
+/// Stop scheduled maintenance for a repository
+#[command]
+pub async fn stop_scheduled_maintenance(
+    state: State<'_, MaintenanceState>,
+    path: String,
+) -> Result<()> {
+    let mut service = state.write().await;
+    service.stop(&path);
+    Ok(())
+}
+
+/// Start scheduled maintenance for every repository in a workspace.
+///
+/// Repositories that no longer exist on disk are skipped rather than failing
+/// the whole workspace; the paths actually started are returned.
+#[command]
+pub async fn start_workspace_maintenance(
+    app: AppHandle,
+    state: State<'_, MaintenanceState>,
+    workspace_id: String,
+) -> Result<Vec<String>> {
+    let workspace = crate::commands::workspace::get_workspace(workspace_id).await?;
+    let mut service = state.write().await;
+    let mut started = Vec::new();
+    for repo in workspace.repositories {
+        if git2::Repository::open(&repo.path).is_err() {
+            tracing::warn!("Skipping maintenance for missing repository {}", repo.path);
+            continue;
+        }
+        service.start(repo.path.clone(), app.clone());
+        started.push(repo.path);
+    }
+    Ok(started)
+}
+
+/// Stop scheduled maintenance for every repository in a workspace
+#[command]
+pub async fn stop_workspace_maintenance(
+    state: State<'_, MaintenanceState>,
+    workspace_id: String,
+) -> Result<()> {
+    let workspace = crate::commands::workspace::get_workspace(workspace_id).await?;
+    let mut service = state.write().await;
+    for repo in workspace.repositories {
+        service.stop(&repo.path);
+    }
+    Ok(())
+}
+
+/// Repositories with scheduled maintenance running
+#[command]
+pub async fn get_scheduled_maintenance_repos(
+    state: State<'_, MaintenanceState>,
+) -> Result<Vec<String>> {
+    let service = state.read().await;
+    Ok(service.running_repos())
+}
+
+/// Get a repository's maintenance schedule with last runs and due times
+#[command]
+pub async fn get_maintenance_status(
+    state: State<'_, MaintenanceState>,
+    path: String,
+) -> Result<MaintenanceStatus> {
+    let running = state.read().await.is_running(&path);
+    tokio::task::spawn_blocking(move || build_maintenance_status(&path, running))
+        .await
+        .map_err(|e| LeviathanError::OperationFailed(format!("Task failed: {}", e)))?
+}
+
+fn build_maintenance_status(path: &str, running: bool) -> Result<MaintenanceStatus> {
+    let repo = open_for_maintenance(path)?;
+    let schedule = maintenance_service::load_schedule(&repo)?;
+    let mut last_runs = maintenance_service::last_runs(&maintenance_service::read_log(&repo)?);
+    let now = chrono::Utc::now().timestamp();
+
+    let tasks = schedule
+        .tasks
+        .into_iter()
+        .map(|entry| {
+            let last_run = last_runs.remove(&entry.task);
+            let next_due_at = entry
+                .enabled
+                .then(|| maintenance_service::next_due_at(&entry, last_run.as_ref(), now));
+            MaintenanceTaskStatus {
+                schedule: entry,
+                last_run,
+                next_due_at,
+            }
+        })
+        .collect();
+
+    Ok(MaintenanceStatus {
+        running,
+        busy_reason: maintenance_service::busy_reason(&repo),
+        tasks,
+    })
+}
+
+/// Save a repository's maintenance schedule.
+///
+/// Takes effect on the background loop's next tick; no restart is needed.
+#[command]
+pub async fn set_maintenance_schedule(
+    path: String,
+    schedule: MaintenanceSchedule,
+) -> Result<MaintenanceSchedule> {
+    let repo = open_for_maintenance(&path)?;
+    maintenance_service::save_schedule(&repo, schedule)
+}
+
+/// Run maintenance tasks now, regardless of schedule.
+///
+/// Runs every task in `tasks`, or every enabled task when omitted. A busy
+/// repository is not an error: each task comes back (and is logged) as
+/// skipped with the reason.
+#[command]
+pub async fn run_maintenance_tasks(
+    path: String,
+    tasks: Option<Vec<MaintenanceTask>>,
+) -> Result<Vec<MaintenanceRunRecord>> {
+    tokio::task::spawn_blocking(move || {
+        let tasks = match tasks {
+            Some(tasks) => tasks,
+            None => {
+                let repo = open_for_maintenance(&path)?;
+                maintenance_service::load_schedule(&repo)?
+                    .tasks
+                    .into_iter()
+                    .filter(|entry| entry.enabled)
+                    .map(|entry| entry.task)
+                    .collect()
+            }
+        };
+        maintenance_service::run_tasks(&path, &tasks, MaintenanceTrigger::Manual)
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("Task failed: {}", e)))?
+}
+
+/// Get logged maintenance runs, newest first
+#[command]
+pub async fn get_maintenance_log(
+    path: String,
+    limit: Option<usize>,
+) -> Result<Vec<MaintenanceRunRecord>> {
+    let repo = open_for_maintenance(&path)?;
+    let mut log = maintenance_service::read_log(&repo)?;
+    log.reverse();
+    if let Some(limit) = limit {
+        log.truncate(limit);
+    }
+    Ok(log)
+}
+
+/// Clear a repository's maintenance log.
+///
+/// This also resets the schedule: with no recorded runs every enabled task
+/// is due on the next tick.
+#[command]
+pub async fn clear_maintenance_log(path: String) -> Result<()> {
+    let repo = open_for_maintenance(&path)?;
+    maintenance_service::clear_log(&repo)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1068,4 +1280,58 @@ mod tests {
         assert!(json.contains("\"packSizeKb\":2048"), "got: {json}");
         assert!(!json.contains("pack_count"), "got: {json}");
     }
+
+    #[tokio::test]
+    async fn test_maintenance_status_reports_due_tasks_and_last_runs() {
+        let repo = TestRepo::with_initial_commit();
+
+        let mut schedule = MaintenanceSchedule::default();
+        for entry in &mut schedule.tasks {
+            entry.enabled = entry.task == MaintenanceTask::CommitGraph;
+        }
+        set_maintenance_schedule(repo.path_str(), schedule)
+            .await
+            .unwrap();
+
+        let records = run_maintenance_tasks(repo.path_str(), None).await.unwrap();
+        assert_eq!(records.len(), 1, "only enabled tasks run by default");
+        assert_eq!(records[0].task, MaintenanceTask::CommitGraph);
+
+        let status = build_maintenance_status(&repo.path_str(), false).unwrap();
+        let commit_graph = &status.tasks[0];
+        assert!(commit_graph.last_run.is_some());
+        assert_eq!(
+            commit_graph.next_due_at,
+            Some(commit_graph.last_run.as_ref().unwrap().started_at + 60 * 60)
+        );
+        assert!(status.tasks[1..].iter().all(|t| t.next_due_at.is_none()));
+
+        let json = serde_json::to_string(commit_graph).unwrap();
+        assert!(json.contains("\"task\":\"commit-graph\""), "got: {json}");
+        assert!(json.contains("\"intervalMinutes\":60"), "got: {json}");
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_log_is_newest_first_and_clearable() {
+        let repo = TestRepo::with_initial_commit();
+        run_maintenance_tasks(
+            repo.path_str(),
+            Some(vec![
+                MaintenanceTask::CommitGraph,
+                MaintenanceTask::LooseObjects,
+            ]),
+        )
+        .await
+        .unwrap();
+
+        let log = get_maintenance_log(repo.path_str(), Some(1)).await.unwrap();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].task, MaintenanceTask::LooseObjects);
+
+        clear_maintenance_log(repo.path_str()).await.unwrap();
+        assert!(get_maintenance_log(repo.path_str(), None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -466,7 +466,7 @@ pub async fn commit_merge(
     // Concluding a (conflicted) merge via `git commit` runs pre-commit and
     // commit-msg; the git2 path otherwise bypasses them. pre-commit can veto;
     // commit-msg can veto or rewrite the message.
-    crate::commands::hooks::run_hook_blocking(&repo, "pre-commit", &[], None)?;
+    crate::commands::hooks::run_pre_commit(&repo)?;
     let commit_message = crate::commands::hooks::run_commit_msg_hook(&repo, &commit_message)?;
 
     let mut index = repo.index()?;
@@ -13,6 +13,7 @@ pub mod branch;
 pub mod branch_cleanup;
 pub mod branch_rules;
 pub mod bundle;
+pub mod bundle_sync;
 pub mod checkout_file;
 pub mod clean;
 pub mod clipboard;
@@ -24,6 +25,7 @@ pub mod custom_actions;
 pub mod describe;
 pub mod diff;
 pub mod difftool;
+pub mod doctor;
 pub mod embedding_index;
 pub mod encoding;
 pub mod file;
@@ -33,10 +35,13 @@ pub mod github;
 pub mod gitignore;
 pub mod gitlab;
 pub mod gpg;
+pub mod hook_history;
 pub mod hooks;
 pub mod issue_templates;
 pub mod jira;
 pub mod lfs;
+pub mod lfs_analysis;
+pub mod lfs_locks;
 pub mod local_ai;
 pub mod maintenance;
 pub mod mcp;
@@ -44,9 +49,12 @@ pub mod merge;
 pub mod merge_tool;
 pub mod notes;
 pub mod oauth;
+pub mod partial_clone;
 pub mod patch;
+pub mod patch_series;
 pub mod path_utils;
 pub mod pr_templates;
+pub mod pre_commit;
 pub mod profiles;
 pub mod reflog;
 pub mod refs;
@@ -55,6 +63,8 @@ pub mod repository;
 pub mod rewrite;
 pub mod search;
 pub mod search_index;
+pub mod secret_scan;
+pub mod send_email;
 pub mod shortcuts;
 pub mod shortlog;
 pub mod signature;
@@ -65,6 +75,7 @@ pub mod staging;
 pub mod stash;
 pub mod stats;
 pub mod submodule;
+pub mod subtree;
 pub mod tags;
 pub mod templates;
 pub mod terminal;
@@ -0,0 +1,970 @@
+//! Partial clone support
+//!
+//! A blobless (`--filter=blob:none`) or treeless (`--filter=tree:0`) clone
+//! leaves objects on the promisor remote until they are needed. The git CLI
+//! fetches them lazily, one round trip per object; libgit2 does not fetch
+//! them at all and fails with "object not found". So operations that read
+//! historical content (commit diffs, blame, file history, content search)
+//! first work out which objects they will read and fetch the missing ones in
+//! a single batch, the same `git fetch --stdin` git's own promisor code uses.
+//!
+//! Everything here is a no-op outside a partial clone.
+
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::OnceLock;
+
+use tauri::{command, AppHandle, Emitter, State};
+
+use crate::commands::repository::CloneFilterInfo;
+use crate::error::{LeviathanError, Result};
+use crate::services::cancellation::{CancellationRegistry, CancellationToken};
+use crate::utils::create_command;
+
+/// How many commits a history content search prefetches blobs for. git keeps
+/// fetching lazily past this point, so it bounds the batch, not the search.
+pub(crate) const SEARCH_PREFETCH_COMMITS: usize = 500;
+
+/// Progress of a promisor fetch, emitted as `partial-clone-progress`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialCloneProgress {
+    pub operation_id: Option<String>,
+    /// "prefetch" or "convert"
+    pub operation: String,
+    /// git's stage name, e.g. "Receiving objects"
+    pub stage: String,
+    pub percent: u8,
+    pub current: u64,
+    pub total: u64,
+}
+
+/// What a prefetch should cover
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum BlobPrefetchScope {
+    /// A commit's diff against its first parent, optionally one file only
+    #[serde(rename_all = "camelCase")]
+    CommitDiff {
+        commit_oid: String,
+        file_path: Option<String>,
+    },
+    /// Every version of a file, as blame and file history read it
+    #[serde(rename_all = "camelCase")]
+    FileHistory {
+        file_path: String,
+        rev: Option<String>,
+        max_commits: Option<usize>,
+    },
+    /// The content changed by the most recent commits, as a content search
+    /// over history reads it
+    #[serde(rename_all = "camelCase")]
+    History {
+        rev: Option<String>,
+        max_commits: Option<usize>,
+    },
+    /// Every file in a revision's tree, optionally below some paths
+    #[serde(rename_all = "camelCase")]
+    Tree {
+        rev: Option<String>,
+        #[serde(default)]
+        paths: Vec<String>,
+    },
+}
+
+/// Result of a prefetch
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobPrefetchResult {
+    /// Objects the scope reads
+    pub requested: usize,
+    /// Of those, objects that were not present locally
+    pub missing: usize,
+    /// Missing objects now present
+    pub fetched: usize,
+}
+
+/// The promisor remote of a partial clone: `extensions.partialClone`, else
+/// the first remote marked `promisor` (origin preferred).
+pub(crate) fn promisor_remote(repo: &git2::Repository) -> Option<String> {
+    let config = repo.config().ok()?;
+    if let Ok(name) = config.get_string("extensions.partialClone") {
+        if !name.is_empty() {
+            return Some(name);
+        }
+    }
+    let is_promisor = |name: &str| {
+        config
+            .get_bool(&format!("remote.{}.promisor", name))
+            .unwrap_or(false)
+    };
+    if is_promisor("origin") {
+        return Some("origin".to_string());
+    }
+    let remotes = repo.remotes().ok()?;
+    let found = remotes
+        .iter()
+        .filter_map(|r| r.ok().flatten())
+        .find(|name| is_promisor(name))
+        .map(|name| name.to_string());
+    found
+}
+
+/// Whether the repository is a partial clone.
+pub(crate) fn is_partial_clone(repo: &git2::Repository) -> bool {
+    promisor_remote(repo).is_some()
+}
+
+/// Partial-clone configuration of a repository.
+pub(crate) fn filter_info(repo: &git2::Repository) -> CloneFilterInfo {
+    let promisor_remote = promisor_remote(repo);
+    let filter = promisor_remote.as_ref().and_then(|name| {
+        repo.config()
+            .ok()?
+            .get_string(&format!("remote.{}.partialclonefilter", name))
+            .ok()
+    });
+    CloneFilterInfo {
+        is_partial_clone: promisor_remote.is_some(),
+        filter,
+        promisor_remote,
+    }
+}
+
+/// The objects among `oids` that are not in the local object database.
+pub(crate) fn missing_objects(
+    repo: &git2::Repository,
+    oids: &[git2::Oid],
+) -> Result<Vec<git2::Oid>> {
+    let odb = repo.odb()?;
+    let mut seen = HashSet::new();
+    Ok(oids
+        .iter()
+        .copied()
+        .filter(|oid| !oid.is_zero() && seen.insert(*oid) && !odb.exists(*oid))
+        .collect())
+}
+
+/// Parse a git progress line such as `Receiving objects:  45% (9/20)` into
+/// (stage, percent, current, total).
+fn parse_progress_line(line: &str) -> Option<(String, u8, u64, u64)> {
+    let line = line.trim().trim_start_matches("remote:").trim();
+    let (stage, rest) = line.split_once(':')?;
+    let (percent, rest) = rest.trim().split_once('%')?;
+    let percent: u8 = percent.trim().parse().ok()?;
+    let counts = rest.split_once('(')?.1.split_once(')')?.0;
+    let (current, total) = counts.split_once('/')?;
+    Some((
+        stage.trim().to_string(),
+        percent.min(100),
+        current.trim().parse().ok()?,
+        total.trim().parse().ok()?,
+    ))
+}
+
+/// Run git, feeding `stdin`, reporting the progress it prints and killing it
+/// when `token` is cancelled.
+fn run_git_with_progress(
+    repo_path: &Path,
+    args: &[&str],
+    stdin: Option<String>,
+    token: Option<&CancellationToken>,
+    on_progress: &(dyn Fn(String, u8, u64, u64) + Sync),
+) -> Result<()> {
+    let mut child = create_command("git")
+        .current_dir(repo_path)
+        .env("LC_ALL", "C")
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| LeviathanError::OperationFailed(format!("Failed to run git: {}", e)))?;
+
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())?;
+    }
+    let stderr = child.stderr.take();
+
+    std::thread::scope(|scope| {
+        // git redraws progress with '\r', so split on both line endings.
+        let reader = scope.spawn(move || {
+            let mut messages = String::new();
+            let Some(mut stderr) = stderr else {
+                return messages;
+            };
+            let mut buf = [0u8; 4096];
+            let mut line = Vec::new();
+            let mut flush = |line: &mut Vec<u8>| {
+                let text = String::from_utf8_lossy(line).to_string();
+                line.clear();
+                match parse_progress_line(&text) {
+                    Some((stage, percent, current, total)) => {
+                        on_progress(stage, percent, current, total)
+                    }
+                    None if !text.trim().is_empty() => {
+                        messages.push_str(text.trim());
+                        messages.push('\n');
+                    }
+                    None => {}
+                }
+            };
+            while let Ok(n) = stderr.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                for &byte in &buf[..n] {
+                    if byte == b'\r' || byte == b'\n' {
+                        flush(&mut line);
+                    } else {
+                        line.push(byte);
+                    }
+                }
+            }
+            flush(&mut line);
+            messages
+        });
+
+        let status = loop {
+            if token.is_some_and(|t| t.is_cancelled()) {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = reader.join();
+                return Err(LeviathanError::OperationCancelled);
+            }
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) => std::thread::sleep(std::time::Duration::from_millis(50)),
+                Err(e) => return Err(LeviathanError::Io(e)),
+            }
+        };
+        let messages = reader.join().unwrap_or_default();
+        if status.success() {
+            Ok(())
+        } else {
+            Err(LeviathanError::OperationFailed(format!(
+                "git {} failed: {}",
+                args.iter()
+                    .find(|a| !a.starts_with('-') && !a.contains('='))
+                    .unwrap_or(&""),
+                messages.trim()
+            )))
+        }
+    })
+}
+
+/// Fetch `oids` from the promisor remote in one batch.
+fn fetch_from_promisor(
+    repo_path: &Path,
+    remote: &str,
+    oids: &[git2::Oid],
+    token: Option<&CancellationToken>,
+    on_progress: &(dyn Fn(String, u8, u64, u64) + Sync),
+) -> Result<()> {
+    let input: String = oids.iter().map(|oid| format!("{}\n", oid)).collect();
+    // The invocation git's promisor-remote.c makes for a lazy fetch: no
+    // negotiation, no ref updates, and still blob-filtered so fetching a tree
+    // does not drag in its blobs.
+    run_git_with_progress(
+        repo_path,
+        &[
+            "-c",
+            "fetch.negotiationAlgorithm=noop",
+            "fetch",
+            remote,
+            "--no-tags",
+            "--no-write-fetch-head",
+            "--recurse-submodules=no",
+            "--filter=blob:none",
+            "--progress",
+            "--stdin",
+        ],
+        Some(input),
+        token,
+        on_progress,
+    )
+}
+
+/// The app that fetches made by [`ensure_objects`] report progress to.
+static PROGRESS_APP: OnceLock<AppHandle> = OnceLock::new();
+
+/// Register the app handle at startup, so the fetches operations make
+/// before reading history emit `partial-clone-progress` events just as an
+/// explicit [`prefetch_missing_objects`] does.
+pub fn set_progress_app(app: AppHandle) {
+    let _ = PROGRESS_APP.set(app);
+}
+
+/// Make sure every object in `oids` is present, batch-fetching the missing
+/// ones when this is a partial clone. Returns how many were fetched.
+///
+/// Called by operations that read object content through libgit2 before
+/// they start; outside a partial clone it only costs an existence check.
+/// Progress is emitted as a `prefetch` operation without an operation id.
+pub(crate) fn ensure_objects(repo: &git2::Repository, oids: &[git2::Oid]) -> Result<usize> {
+    match PROGRESS_APP.get() {
+        Some(app) => {
+            let progress = emit_progress(app, &None, "prefetch");
+            ensure_objects_with_progress(repo, oids, None, &progress)
+        }
+        None => ensure_objects_with_progress(repo, oids, None, &|_, _, _, _| {}),
+    }
+}
+
+fn ensure_objects_with_progress(
+    repo: &git2::Repository,
+    oids: &[git2::Oid],
+    token: Option<&CancellationToken>,
+    on_progress: &(dyn Fn(String, u8, u64, u64) + Sync),
+) -> Result<usize> {
+    let Some(remote) = promisor_remote(repo) else {
+        return Ok(0);
+    };
+    let missing = missing_objects(repo, oids)?;
+    if missing.is_empty() {
+        return Ok(0);
+    }
+    let repo_path = repo.workdir().unwrap_or_else(|| repo.path());
+    tracing::debug!(count = missing.len(), remote = %remote, "Fetching promisor objects");
+    fetch_from_promisor(repo_path, &remote, &missing, token, on_progress)?;
+    // libgit2 caches the pack list; make it look at the pack just written.
+    repo.odb()?.refresh()?;
+    Ok(missing.len() - missing_objects(repo, &missing)?.len())
+}
+
+/// Whether a tree entry refers to content that lives in this repository
+/// (submodule commits do not).
+fn is_local_object(mode: u32) -> bool {
+    mode != 0o160000
+}
+
+/// Blobs a diff between two trees reads: both sides of every changed file.
+/// In a treeless clone the trees themselves may be missing, so they are
+/// fetched first.
+pub(crate) fn tree_diff_blobs(
+    repo: &git2::Repository,
+    old_tree: Option<git2::Oid>,
+    new_tree: Option<git2::Oid>,
+    pathspec: Option<&str>,
+) -> Result<Vec<git2::Oid>> {
+    let trees: Vec<git2::Oid> = old_tree.iter().chain(new_tree.iter()).copied().collect();
+    ensure_trees(repo, &trees)?;
+    let old = old_tree.map(|oid| repo.find_tree(oid)).transpose()?;
+    let new = new_tree.map(|oid| repo.find_tree(oid)).transpose()?;
+    let mut opts = git2::DiffOptions::new();
+    if let Some(spec) = pathspec {
+        opts.pathspec(spec);
+    }
+    let diff = repo.diff_tree_to_tree(old.as_ref(), new.as_ref(), Some(&mut opts))?;
+    let mut oids = Vec::new();
+    for delta in diff.deltas() {
+        for file in [delta.old_file(), delta.new_file()] {
+            if is_local_object(u32::from(file.mode())) {
+                oids.push(file.id());
+            }
+        }
+    }
+    Ok(oids)
+}
+
+/// Fetch root trees missing from a treeless clone. The promisor fetch is
+/// blob-filtered, not tree-filtered, so fetching a root tree brings every
+/// tree below it. A no-op in blobless clones, where every tree is present.
+fn ensure_trees(repo: &git2::Repository, roots: &[git2::Oid]) -> Result<()> {
+    ensure_objects(repo, roots).map(|_| ())
+}
+
+/// Blobs a commit's diff against its first parent reads.
+pub(crate) fn commit_diff_blobs(
+    repo: &git2::Repository,
+    commit: &git2::Commit,
+    pathspec: Option<&str>,
+) -> Result<Vec<git2::Oid>> {
+    let parent_tree = commit.parent(0).ok().map(|p| p.tree_id());
+    tree_diff_blobs(repo, parent_tree, Some(commit.tree_id()), pathspec)
+}
+
+/// Commits whose trees are fetched together when a path-limited walk reaches
+/// one a treeless clone does not have.
+const HISTORY_TREE_BATCH: usize = 256;
+
+/// The versions of `file_path` in the history of `start`: the blob at that
+/// path in each commit that changed it, newest first.
+///
+/// Walks only the history of the path, the way `git log -- <path>` does: a
+/// commit whose parent has the same blob continues through that parent
+/// alone, and a line of history ends where the file does not exist. At most
+/// `max_commits` commits are visited.
+pub(crate) fn file_history_blobs(
+    repo: &git2::Repository,
+    start: git2::Oid,
+    file_path: &str,
+    max_commits: Option<usize>,
+) -> Result<Vec<git2::Oid>> {
+    let path = Path::new(file_path);
+    let mut queue = std::collections::VecDeque::from([start]);
+    let mut seen = HashSet::from([start]);
+    let mut versions = HashSet::new();
+    let mut oids = Vec::new();
+    let mut visited = 0;
+    while let Some(oid) = queue.pop_front() {
+        if max_commits.is_some_and(|max| visited >= max) {
+            break;
+        }
+        visited += 1;
+        let commit = repo.find_commit(oid)?;
+        let Some(blob) = blob_in_commit(repo, &commit, path)? else {
+            continue;
+        };
+        if versions.insert(blob) {
+            oids.push(blob);
+        }
+
+        let mut parents = Vec::new();
+        for parent in commit.parents() {
+            let blob = blob_in_commit(repo, &parent, path)?;
+            parents.push((parent.id(), blob));
+        }
+        let next: Vec<git2::Oid> = match parents.iter().find(|(_, b)| *b == Some(blob)) {
+            Some((unchanged, _)) => vec![*unchanged],
+            None => parents
+                .iter()
+                .filter(|(_, b)| b.is_some())
+                .map(|(id, _)| *id)
+                .collect(),
+        };
+        for parent in next {
+            if seen.insert(parent) {
+                queue.push_back(parent);
+            }
+        }
+    }
+    Ok(oids)
+}
+
+/// The blob at `path` in `commit`, if there is one. In a treeless clone a
+/// missing tree is fetched together with those of the commits below it, so
+/// a walk down history costs a round trip per batch rather than per commit.
+fn blob_in_commit(
+    repo: &git2::Repository,
+    commit: &git2::Commit,
+    path: &Path,
+) -> Result<Option<git2::Oid>> {
+    if !repo.odb()?.exists(commit.tree_id()) {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(commit.id())?;
+        let mut trees = Vec::new();
+        for oid in revwalk.take(HISTORY_TREE_BATCH) {
+            trees.push(repo.find_commit(oid?)?.tree_id());
+        }
+        ensure_trees(repo, &trees)?;
+    }
+    match commit.tree()?.get_path(path) {
+        Ok(entry) if entry.kind() == Some(git2::ObjectType::Blob) => Ok(Some(entry.id())),
+        _ => Ok(None),
+    }
+}
+
+/// Resolve `rev` (HEAD by default) to a commit.
+fn resolve_commit<'r>(repo: &'r git2::Repository, rev: Option<&str>) -> Result<git2::Commit<'r>> {
+    Ok(repo
+        .revparse_single(rev.unwrap_or("HEAD"))?
+        .peel_to_commit()?)
+}
+
+/// The objects a prefetch scope reads.
+fn scope_objects(repo: &git2::Repository, scope: &BlobPrefetchScope) -> Result<Vec<git2::Oid>> {
+    match scope {
+        BlobPrefetchScope::CommitDiff {
+            commit_oid,
+            file_path,
+        } => {
+            let commit = repo.find_commit(git2::Oid::from_str(commit_oid)?)?;
+            commit_diff_blobs(repo, &commit, file_path.as_deref())
+        }
+        BlobPrefetchScope::FileHistory {
+            file_path,
+            rev,
+            max_commits,
+        } => {
+            let start = resolve_commit(repo, rev.as_deref())?.id();
+            file_history_blobs(repo, start, file_path, *max_commits)
+        }
+        BlobPrefetchScope::History { rev, max_commits } => {
+            let start = resolve_commit(repo, rev.as_deref())?.id();
+            history_blobs(repo, start, max_commits.unwrap_or(SEARCH_PREFETCH_COMMITS))
+        }
+        BlobPrefetchScope::Tree { rev, paths } => {
+            let tree = resolve_commit(repo, rev.as_deref())?.tree_id();
+            ensure_trees(repo, &[tree])?;
+            let tree = repo.find_tree(tree)?;
+            let mut oids = Vec::new();
+            tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+                if entry.kind() == Some(git2::ObjectType::Blob) {
+                    let full = format!("{}{}", dir, entry.name().unwrap_or_default());
+                    let wanted = paths.is_empty()
+                        || paths.iter().any(|p| {
+                            let p = p.trim_matches('/');
+                            full == p || full.starts_with(&format!("{}/", p))
+                        });
+                    if wanted {
+                        oids.push(entry.id());
+                    }
+                }
+                git2::TreeWalkResult::Ok
+            })?;
+            Ok(oids)
+        }
+    }
+}
+
+/// Blobs changed by the `max_commits` most recent commits from `start`.
+pub(crate) fn history_blobs(
+    repo: &git2::Repository,
+    start: git2::Oid,
+    max_commits: usize,
+) -> Result<Vec<git2::Oid>> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(start)?;
+    let mut commits = Vec::new();
+    for oid in revwalk.take(max_commits) {
+        commits.push(repo.find_commit(oid?)?);
+    }
+    // Every tree first, in one batch, rather than a fetch per commit.
+    let trees: Vec<git2::Oid> = commits
+        .iter()
+        .flat_map(|c| std::iter::once(c.tree_id()).chain(c.parents().map(|p| p.tree_id())))
+        .collect();
+    ensure_trees(repo, &trees)?;
+
+    let mut oids = Vec::new();
+    for commit in &commits {
+        oids.extend(commit_diff_blobs(repo, commit, None)?);
+    }
+    Ok(oids)
+}
+
+/// Fetch the objects `objects` picks out, on a blocking thread, before an
+/// async command reads them. A no-op outside a partial clone.
+pub(crate) async fn prefetch<F>(repo_path: &str, objects: F) -> Result<()>
+where
+    F: FnOnce(&git2::Repository) -> Result<Vec<git2::Oid>> + Send + 'static,
+{
+    let repo_path = repo_path.to_string();
+    tokio::task::spawn_blocking(move || {
+        let repo = git2::Repository::open(Path::new(&repo_path))?;
+        if !is_partial_clone(&repo) {
+            return Ok(());
+        }
+        let oids = objects(&repo)?;
+        ensure_objects(&repo, &oids).map(|_| ())
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("Prefetch task failed: {}", e)))?
+}
+
+/// Prefetch content before a search of history in a partial clone, so git
+/// does not fall back to fetching one object per round trip. Failures are
+/// logged, not returned: the search still works, only slower.
+pub(crate) async fn prefetch_for_history_search(repo_path: &str) {
+    let result = prefetch(repo_path, |repo| {
+        let head = resolve_commit(repo, None)?.id();
+        history_blobs(repo, head, SEARCH_PREFETCH_COMMITS)
+    })
+    .await;
+    if let Err(e) = result {
+        tracing::warn!("Prefetching history for search failed: {}", e);
+    }
+}
+
+/// A progress callback that emits `partial-clone-progress` events.
+fn emit_progress<'a>(
+    app: &'a AppHandle,
+    operation_id: &Option<String>,
+    operation: &str,
+) -> impl Fn(String, u8, u64, u64) + Sync + 'a {
+    let operation_id = operation_id.clone();
+    let operation = operation.to_string();
+    move |stage, percent, current, total| {
+        let _ = app.emit(
+            "partial-clone-progress",
+            PartialCloneProgress {
+                operation_id: operation_id.clone(),
+                operation: operation.clone(),
+                stage,
+                percent,
+                current,
+                total,
+            },
+        );
+    }
+}
+
+/// Fetch the objects an operation is about to read
+///
+/// Batch-fetches, in one request, whichever objects of the scope are
+/// missing from a partial clone, emitting `partial-clone-progress` events.
+/// Can be stopped with `cancel_operation(operationId)`. Outside a partial
+/// clone nothing is fetched.
+#[command]
+pub async fn prefetch_missing_objects(
+    app: AppHandle,
+    registry: State<'_, CancellationRegistry>,
+    path: String,
+    scope: BlobPrefetchScope,
+    operation_id: Option<String>,
+) -> Result<BlobPrefetchResult> {
+    let token = operation_id
+        .as_ref()
+        .map(|id| registry.register(id.clone()));
+
+    let thread_operation_id = operation_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let repo = git2::Repository::open(Path::new(&path))?;
+        let objects = scope_objects(&repo, &scope)?;
+        let missing = missing_objects(&repo, &objects)?.len();
+        let progress = emit_progress(&app, &thread_operation_id, "prefetch");
+        let fetched = ensure_objects_with_progress(&repo, &objects, token.as_ref(), &progress)?;
+        Ok(BlobPrefetchResult {
+            requested: objects.iter().collect::<HashSet<_>>().len(),
+            missing,
+            fetched,
+        })
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("Prefetch task failed: {}", e)))?;
+
+    if let Some(id) = &operation_id {
+        registry.remove(id);
+    }
+    result
+}
+
+/// Turn a partial clone into a full clone.
+///
+/// Refetches everything without a filter, then drops the promisor
+/// configuration. The filter is removed before fetching (fetch would
+/// otherwise reapply it) and restored if the fetch fails or is cancelled, so
+/// an interrupted conversion leaves a working partial clone.
+fn convert_repository(
+    repo_path: &Path,
+    token: Option<&CancellationToken>,
+    on_progress: &(dyn Fn(String, u8, u64, u64) + Sync),
+) -> Result<CloneFilterInfo> {
+    let repo = git2::Repository::open(repo_path)?;
+    let info = filter_info(&repo);
+    let Some(remote) = info.promisor_remote.clone() else {
+        return Ok(info);
+    };
+    let filter_key = format!("remote.{}.partialclonefilter", remote);
+    let mut config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+    if info.filter.is_some() {
+        config.remove(&filter_key)?;
+    }
+
+    let fetched = run_git_with_progress(
+        repo_path,
+        &[
+            "fetch",
+            "--refetch",
+            "--progress",
+            "--no-write-fetch-head",
+            &remote,
+        ],
+        None,
+        token,
+        on_progress,
+    );
+    if let Err(e) = fetched {
+        if let Some(filter) = &info.filter {
+            let _ = config.set_str(&filter_key, filter);
+        }
+        return Err(e);
+    }
+
+    let _ = config.remove(&format!("remote.{}.promisor", remote));
+    let _ = config.remove("extensions.partialClone");
+    Ok(filter_info(&git2::Repository::open(repo_path)?))
+}
+
+/// Convert a partial clone into a full clone
+///
+/// Downloads every object the filter left out, emitting
+/// `partial-clone-progress` events, and removes the partial-clone settings.
+/// Can be stopped with `cancel_operation(operationId)`, which leaves the
+/// repository a partial clone as before.
+#[command]
+pub async fn convert_to_full_clone(
+    app: AppHandle,
+    registry: State<'_, CancellationRegistry>,
+    path: String,
+    operation_id: Option<String>,
+) -> Result<CloneFilterInfo> {
+    let token = operation_id
+        .as_ref()
+        .map(|id| registry.register(id.clone()));
+
+    let thread_operation_id = operation_id.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let progress = emit_progress(&app, &thread_operation_id, "convert");
+        convert_repository(Path::new(&path), token.as_ref(), &progress)
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("Conversion task failed: {}", e)))?;
+
+    if let Some(id) = &operation_id {
+        registry.remove(id);
+    }
+    result
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    /// A blobless clone of a three-commit history. Only HEAD's blobs are
+    /// present; the earlier versions of `file.txt` are on the promisor.
+    pub(crate) struct PartialClone {
+        pub source: TestRepo,
+        pub clone_dir: tempfile::TempDir,
+        pub first: git2::Oid,
+        pub second: git2::Oid,
+    }
+
+    impl PartialClone {
+        pub(crate) fn new() -> Self {
+            let source = TestRepo::with_initial_commit();
+            let first = source.create_commit("first", &[("file.txt", "one\n")]);
+            let second = source.create_commit("second", &[("file.txt", "one\ntwo\n")]);
+            source.create_commit("third", &[("file.txt", "one\ntwo\nthree\n")]);
+            let mut config = source.repo().config().unwrap();
+            config.set_bool("uploadpack.allowFilter", true).unwrap();
+            config
+                .set_bool("uploadpack.allowAnySHA1InWant", true)
+                .unwrap();
+
+            let clone_dir = tempfile::tempdir().unwrap();
+            let output = std::process::Command::new("git")
+                .args(["clone", "--quiet", "--filter=blob:none"])
+                .arg(format!("file://{}", source.path.display()))
+                .arg(clone_dir.path())
+                .output()
+                .unwrap();
+            assert!(
+                output.status.success(),
+                "{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+            Self {
+                source,
+                clone_dir,
+                first,
+                second,
+            }
+        }
+
+        pub(crate) fn path_str(&self) -> String {
+            self.clone_dir.path().to_string_lossy().to_string()
+        }
+
+        pub(crate) fn repo(&self) -> git2::Repository {
+            git2::Repository::open(self.clone_dir.path()).unwrap()
+        }
+
+        pub(crate) fn blob_at(&self, commit: git2::Oid) -> git2::Oid {
+            let repo = self.source.repo();
+            let tree = repo.find_commit(commit).unwrap().tree().unwrap();
+            let id = tree.get_path(Path::new("file.txt")).unwrap().id();
+            id
+        }
+    }
+
+    #[test]
+    fn test_parse_progress_line() {
+        assert_eq!(
+            parse_progress_line("Receiving objects:  45% (9/20)"),
+            Some(("Receiving objects".to_string(), 45, 9, 20))
+        );
+        assert_eq!(
+            parse_progress_line("remote: Counting objects: 100% (3/3), done."),
+            Some(("Counting objects".to_string(), 100, 3, 3))
+        );
+        assert_eq!(parse_progress_line("From file:///tmp/x"), None);
+    }
+
+    #[test]
+    fn test_filter_info_of_partial_clone() {
+        let clone = PartialClone::new();
+        let info = filter_info(&clone.repo());
+        assert!(info.is_partial_clone);
+        assert_eq!(info.promisor_remote.as_deref(), Some("origin"));
+        assert_eq!(info.filter.as_deref(), Some("blob:none"));
+
+        let full = TestRepo::with_initial_commit();
+        assert!(!filter_info(&full.repo()).is_partial_clone);
+    }
+
+    #[test]
+    fn test_ensure_objects_batch_fetches_missing_blobs() {
+        let clone = PartialClone::new();
+        let repo = clone.repo();
+        let wanted = vec![clone.blob_at(clone.first), clone.blob_at(clone.second)];
+        assert_eq!(missing_objects(&repo, &wanted).unwrap().len(), 2);
+
+        assert_eq!(ensure_objects(&repo, &wanted).unwrap(), 2);
+        assert!(missing_objects(&repo, &wanted).unwrap().is_empty());
+        assert!(
+            repo.find_blob(wanted[0]).is_ok(),
+            "libgit2 sees the new pack"
+        );
+        assert_eq!(ensure_objects(&repo, &wanted).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_scope_objects() {
+        let clone = PartialClone::new();
+        let repo = clone.repo();
+
+        let diff = scope_objects(
+            &repo,
+            &BlobPrefetchScope::CommitDiff {
+                commit_oid: clone.second.to_string(),
+                file_path: None,
+            },
+        )
+        .unwrap();
+        assert!(diff.contains(&clone.blob_at(clone.first)));
+        assert!(diff.contains(&clone.blob_at(clone.second)));
+
+        let history = scope_objects(
+            &repo,
+            &BlobPrefetchScope::FileHistory {
+                file_path: "file.txt".to_string(),
+                rev: None,
+                max_commits: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(missing_objects(&repo, &history).unwrap().len(), 2);
+
+        let tree = scope_objects(
+            &repo,
+            &BlobPrefetchScope::Tree {
+                rev: Some(clone.first.to_string()),
+                paths: vec!["file.txt".to_string()],
+            },
+        )
+        .unwrap();
+        assert_eq!(tree, vec![clone.blob_at(clone.first)]);
+    }
+
+    #[test]
+    fn test_file_history_blobs_follows_the_path() {
+        let repo = TestRepo::with_initial_commit();
+        let v1 = repo.create_commit("v1", &[("file.txt", "one\n")]);
+        repo.create_commit("other", &[("other.txt", "x\n")]);
+        let v2 = repo.create_commit("v2", &[("file.txt", "one\ntwo\n")]);
+        let head = repo.create_commit("other again", &[("other.txt", "y\n")]);
+        let git = repo.repo();
+        let blob = |commit: git2::Oid| {
+            let tree = git.find_commit(commit).unwrap().tree().unwrap();
+            let id = tree.get_path(Path::new("file.txt")).unwrap().id();
+            id
+        };
+
+        let history = file_history_blobs(&git, head, "file.txt", None).unwrap();
+        assert_eq!(history, vec![blob(v2), blob(v1)]);
+
+        let bounded = file_history_blobs(&git, head, "file.txt", Some(2)).unwrap();
+        assert_eq!(bounded, vec![blob(v2)]);
+
+        let absent = file_history_blobs(&git, head, "missing.txt", None).unwrap();
+        assert!(absent.is_empty());
+    }
+
+    #[test]
+    fn test_ensure_objects_is_a_noop_in_full_clones() {
+        let repo = TestRepo::with_initial_commit();
+        let missing = git2::Oid::from_str("1234567890123456789012345678901234567890").unwrap();
+        assert_eq!(ensure_objects(&repo.repo(), &[missing]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_convert_to_full_clone() {
+        let clone = PartialClone::new();
+        let stages = std::sync::Mutex::new(Vec::new());
+        let info = convert_repository(clone.clone_dir.path(), None, &|stage, _, _, _| {
+            stages.lock().unwrap().push(stage)
+        })
+        .unwrap();
+        assert!(!info.is_partial_clone);
+        assert!(info.filter.is_none());
+
+        let repo = clone.repo();
+        let history = vec![clone.blob_at(clone.first), clone.blob_at(clone.second)];
+        assert!(missing_objects(&repo, &history).unwrap().is_empty());
+        assert!(!stages.lock().unwrap().is_empty(), "progress was reported");
+    }
+
+    #[test]
+    fn test_convert_restores_filter_when_cancelled() {
+        let clone = PartialClone::new();
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = convert_repository(clone.clone_dir.path(), Some(&token), &|_, _, _, _| {});
+        assert!(matches!(result, Err(LeviathanError::OperationCancelled)));
+        let info = filter_info(&clone.repo());
+        assert!(info.is_partial_clone);
+        assert_eq!(info.filter.as_deref(), Some("blob:none"));
+    }
+
+    #[tokio::test]
+    async fn test_commit_files_are_listed_without_fetching_content() {
+        let clone = PartialClone::new();
+
+        let files =
+            crate::commands::diff::get_commit_files(clone.path_str(), clone.second.to_string())
+                .await
+                .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "file.txt");
+
+        let history = vec![clone.blob_at(clone.first), clone.blob_at(clone.second)];
+        assert_eq!(missing_objects(&clone.repo(), &history).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_commit_diff_and_blame_work_in_a_partial_clone() {
+        let clone = PartialClone::new();
+
+        let diff = crate::commands::diff::get_commit_file_diff(
+            clone.path_str(),
+            clone.second.to_string(),
+            "file.txt".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(diff.additions, 1);
+
+        let blame = crate::commands::diff::get_file_blame(
+            clone.path_str(),
+            "file.txt".to_string(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(blame.lines.len(), 3);
+        assert_eq!(blame.lines[0].commit_oid, clone.first.to_string());
+    }
+}
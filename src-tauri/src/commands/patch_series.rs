@@ -0,0 +1,909 @@
+//! Patch series for mailing-list workflows
+//!
+//! [`create_patch`](super::patch::create_patch) writes standalone patches;
+//! projects that take contributions by email want more: a numbered series
+//! with a cover letter, rerolled as `v2`, `v3`, ... with a range-diff
+//! showing reviewers what changed since the last round. On the receiving
+//! end, a whole mbox is applied as commits, stopping on the patch that
+//! conflicts.
+//!
+//! Both directions go through the git CLI. `git format-patch` already
+//! produces the exact format list tooling (b4, patchwork, `git am`)
+//! expects, including the shortlog/diffstat cover letter and range-diff
+//! notes; `git am` owns the `.git/rebase-apply` state that `--continue`,
+//! `--skip` and `--abort` resume from, and that other git tools recognize.
+//!
+//! Every exported version is recorded per series in
+//! `<commondir>/leviathan/patch_series.json`, so the next reroll knows its
+//! version number and what to range-diff against without being told.
+
+use std::path::{Path, PathBuf};
+
+use tauri::command;
+
+use crate::error::{LeviathanError, Result};
+use crate::utils::{create_command, reject_flag_like};
+
+/// Placeholders `git format-patch --cover-letter` leaves for the author
+const COVER_SUBJECT_PLACEHOLDER: &str = "*** SUBJECT HERE ***";
+const COVER_BLURB_PLACEHOLDER: &str = "*** BLURB HERE ***";
+
+/// Versions remembered per series
+const MAX_RECORDED_VERSIONS: usize = 20;
+
+/// Commits listed in an am status
+const MAX_APPLIED_COMMITS: usize = 500;
+
+/// Options for exporting a patch series
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchSeriesOptions {
+    /// Upstream commit-ish the series is based on; the series is
+    /// `base..head`
+    pub base: String,
+    /// Tip of the series; defaults to HEAD
+    #[serde(default)]
+    pub head: Option<String>,
+    pub output_dir: String,
+    /// Reroll number. Defaults to one more than the last exported version
+    /// of this series (1 for a new series).
+    #[serde(default)]
+    pub version: Option<u32>,
+    /// Write a cover letter. Defaults to true for series of more than one
+    /// patch, and is always written when a range-diff is included in a
+    /// multi-patch series, since that is where git puts it.
+    #[serde(default)]
+    pub cover_letter: Option<bool>,
+    #[serde(default)]
+    pub cover_subject: Option<String>,
+    #[serde(default)]
+    pub cover_body: Option<String>,
+    /// Replaces `PATCH` in the subject, e.g. `RFC PATCH` or `PATCH net-next`
+    #[serde(default)]
+    pub subject_prefix: Option<String>,
+    /// Range or commit to range-diff against. Defaults to the previous
+    /// recorded version of this series; an empty string disables it.
+    #[serde(default)]
+    pub range_diff_against: Option<String>,
+    /// Record the base commit (`base-commit:` trailer) for reviewers' tools
+    #[serde(default)]
+    pub include_base: Option<bool>,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    /// Name the series is remembered under; defaults to the head branch
+    #[serde(default)]
+    pub series_name: Option<String>,
+}
+
+/// A recorded version of a series
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchSeriesVersion {
+    pub series: String,
+    pub version: u32,
+    pub base_oid: String,
+    pub head_oid: String,
+    pub patch_count: usize,
+    pub output_dir: String,
+    /// Unix timestamp of the export
+    pub created_at: i64,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PatchSeriesStore {
+    #[serde(default)]
+    versions: Vec<PatchSeriesVersion>,
+}
+
+/// Result of exporting a patch series
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchSeriesResult {
+    pub series: String,
+    pub version: u32,
+    pub cover_letter: Option<String>,
+    pub patches: Vec<String>,
+    /// What the range-diff compares against, when one was included
+    pub range_diff_against: Option<String>,
+}
+
+/// Options for applying an mbox
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyMboxOptions {
+    /// Fall back to a 3-way merge when a patch doesn't apply as-is
+    /// (default true)
+    #[serde(default)]
+    pub three_way: Option<bool>,
+    /// Add a `Signed-off-by` trailer to each commit
+    #[serde(default)]
+    pub signoff: Option<bool>,
+    #[serde(default)]
+    pub ignore_whitespace: Option<bool>,
+}
+
+/// Where a patch of an in-progress `git am` stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AmPatchState {
+    /// Applied or skipped
+    Done,
+    /// The patch `git am` stopped on
+    Current,
+    Pending,
+}
+
+/// One patch of an in-progress `git am`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmPatchInfo {
+    pub number: u32,
+    pub subject: String,
+    pub state: AmPatchState,
+}
+
+/// A commit created by `git am`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmAppliedCommit {
+    pub oid: String,
+    pub summary: String,
+}
+
+/// State of a `git am` session
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmStatus {
+    /// True while stopped on a patch, waiting for continue/skip/abort
+    pub in_progress: bool,
+    pub current_patch: Option<u32>,
+    pub total_patches: Option<u32>,
+    pub current_subject: Option<String>,
+    pub current_author: Option<String>,
+    pub patches: Vec<AmPatchInfo>,
+    pub conflicted_files: Vec<String>,
+    /// Commits applied so far in this session (`ORIG_HEAD..HEAD`)
+    pub applied_commits: Vec<AmAppliedCommit>,
+    /// What git said when it stopped
+    pub message: Option<String>,
+}
+
+fn open_repo(path: &str) -> Result<git2::Repository> {
+    git2::Repository::open(path).map_err(|_| LeviathanError::RepositoryNotFound(path.to_string()))
+}
+
+fn store_path(repo: &git2::Repository) -> PathBuf {
+    repo.commondir().join("leviathan").join("patch_series.json")
+}
+
+fn load_store(repo: &git2::Repository) -> Result<PatchSeriesStore> {
+    let path = store_path(repo);
+    if !path.exists() {
+        return Ok(PatchSeriesStore::default());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|e| {
+        LeviathanError::OperationFailed(format!("Failed to parse patch series history: {}", e))
+    })
+}
+
+fn save_store(repo: &git2::Repository, store: &PatchSeriesStore) -> Result<()> {
+    let path = store_path(repo);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(store)?)?;
+    Ok(())
+}
+
+fn resolve_commit(repo: &git2::Repository, spec: &str) -> Result<git2::Oid> {
+    repo.revparse_single(spec)
+        .and_then(|o| o.peel_to_commit())
+        .map(|c| c.id())
+        .map_err(|_| LeviathanError::CommitNotFound(spec.to_string()))
+}
+
+/// The name a series is remembered under: the head branch's short name, or
+/// the head spec itself when it isn't a branch.
+fn series_name_for(repo: &git2::Repository, head: &str) -> String {
+    let reference = if head == "HEAD" {
+        repo.head().ok()
+    } else {
+        repo.resolve_reference_from_short_name(head).ok()
+    };
+    reference
+        .filter(|r| r.is_branch())
+        .and_then(|r| r.shorthand().ok().map(|s| s.to_string()))
+        .unwrap_or_else(|| head.to_string())
+}
+
+fn git_output(path: &str, args: &[String]) -> Result<std::process::Output> {
+    create_command("git")
+        .current_dir(path)
+        .env("GIT_EDITOR", "true")
+        .args(args)
+        .output()
+        .map_err(|e| LeviathanError::OperationFailed(format!("Failed to execute git: {}", e)))
+}
+
+fn stderr_tail(output: &std::process::Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let text = if stderr.trim().is_empty() {
+        stdout
+    } else {
+        stderr
+    };
+    text.trim().to_string()
+}
+
+/// Get the recorded versions of a repository's patch series, newest first
+#[command]
+pub async fn get_patch_series_versions(
+    path: String,
+    series_name: Option<String>,
+) -> Result<Vec<PatchSeriesVersion>> {
+    let repo = open_repo(&path)?;
+    let mut versions: Vec<PatchSeriesVersion> = load_store(&repo)?
+        .versions
+        .into_iter()
+        .filter(|v| match &series_name {
+            Some(name) => &v.series == name,
+            None => true,
+        })
+        .collect();
+    versions.reverse();
+    Ok(versions)
+}
+
+/// Export `base..head` as a numbered patch series
+///
+/// Runs `git format-patch` with a cover letter (shortlog and diffstat, with
+/// the subject and blurb filled in when given), the reroll number, and a
+/// range-diff against the previous version of the series. The version is
+/// recorded so the next reroll continues from it.
+#[command]
+pub async fn format_patch_series(
+    path: String,
+    options: PatchSeriesOptions,
+) -> Result<PatchSeriesResult> {
+    let repo = open_repo(&path)?;
+    let head = options.head.clone().unwrap_or_else(|| "HEAD".to_string());
+    for (value, label) in [
+        (options.base.as_str(), "Base"),
+        (head.as_str(), "Head"),
+        (options.output_dir.as_str(), "Output directory"),
+    ] {
+        reject_flag_like(value, label)?;
+    }
+    for address in options.to.iter().chain(&options.cc) {
+        reject_flag_like(address, "Recipient")?;
+    }
+
+    let base_oid = resolve_commit(&repo, &options.base)?;
+    let head_oid = resolve_commit(&repo, &head)?;
+    let patch_count = {
+        let mut walk = repo.revwalk()?;
+        walk.push(head_oid)?;
+        walk.hide(base_oid)?;
+        walk.count()
+    };
+    if patch_count == 0 {
+        return Err(LeviathanError::OperationFailed(format!(
+            "No commits between {} and {}",
+            options.base, head
+        )));
+    }
+
+    let series = options
+        .series_name
+        .clone()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| series_name_for(&repo, &head));
+    let mut store = load_store(&repo)?;
+    let previous = store
+        .versions
+        .iter()
+        .filter(|v| v.series == series)
+        .max_by_key(|v| v.version)
+        .cloned();
+    let version = options
+        .version
+        .unwrap_or_else(|| previous.as_ref().map_or(1, |p| p.version + 1))
+        .max(1);
+
+    // The previous version's range, as long as its commits still exist (a
+    // rebased branch keeps them only through the reflog until gc).
+    let range_diff_against = match options.range_diff_against.as_deref() {
+        Some("") => None,
+        Some(spec) => {
+            reject_flag_like(spec, "Range-diff base")?;
+            Some(spec.to_string())
+        }
+        None => previous
+            .as_ref()
+            .filter(|p| p.version < version)
+            .filter(|p| {
+                [&p.base_oid, &p.head_oid].iter().all(|oid| {
+                    git2::Oid::from_str(oid)
+                        .map(|o| repo.find_commit(o).is_ok())
+                        .unwrap_or(false)
+                })
+            })
+            .map(|p| format!("{}..{}", p.base_oid, p.head_oid)),
+    };
+
+    let cover_letter = options.cover_letter.unwrap_or(patch_count > 1)
+        || (range_diff_against.is_some() && patch_count > 1);
+
+    let mut args: Vec<String> = vec![
+        "format-patch".into(),
+        "--numbered".into(),
+        "--thread".into(),
+        "-o".into(),
+        options.output_dir.clone(),
+    ];
+    if cover_letter {
+        args.push("--cover-letter".into());
+    }
+    if version > 1 {
+        args.push(format!("--reroll-count={}", version));
+    }
+    if let Some(prefix) = options.subject_prefix.as_deref().filter(|p| !p.is_empty()) {
+        args.push(format!("--subject-prefix={}", prefix));
+    }
+    if let Some(against) = &range_diff_against {
+        args.push(format!("--range-diff={}", against));
+    }
+    if options.include_base.unwrap_or(false) {
+        args.push(format!("--base={}", base_oid));
+    }
+    for to in &options.to {
+        args.push(format!("--to={}", to));
+    }
+    for cc in &options.cc {
+        args.push(format!("--cc={}", cc));
+    }
+    args.push(format!("{}..{}", base_oid, head_oid));
+
+    std::fs::create_dir_all(&options.output_dir)?;
+    let output = git_output(&path, &args)?;
+    if !output.status.success() {
+        return Err(LeviathanError::OperationFailed(format!(
+            "git format-patch failed: {}",
+            stderr_tail(&output)
+        )));
+    }
+
+    // format-patch prints each file it wrote, relative to the working
+    // directory unless -o was absolute.
+    let mut cover = None;
+    let mut patches = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let file = Path::new(&path).join(line.trim());
+        let file = file.to_string_lossy().to_string();
+        if line.ends_with("0000-cover-letter.patch") {
+            cover = Some(file);
+        } else if !line.trim().is_empty() {
+            patches.push(file);
+        }
+    }
+
+    if let Some(cover_path) = &cover {
+        fill_cover_letter(
+            Path::new(cover_path),
+            options.cover_subject.as_deref(),
+            options.cover_body.as_deref(),
+        )?;
+    }
+
+    store.versions.push(PatchSeriesVersion {
+        series: series.clone(),
+        version,
+        base_oid: base_oid.to_string(),
+        head_oid: head_oid.to_string(),
+        patch_count,
+        output_dir: options.output_dir.clone(),
+        created_at: chrono::Utc::now().timestamp(),
+    });
+    let count = store.versions.iter().filter(|v| v.series == series).count();
+    if count > MAX_RECORDED_VERSIONS {
+        let mut excess = count - MAX_RECORDED_VERSIONS;
+        store.versions.retain(|v| {
+            if excess > 0 && v.series == series {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+    save_store(&repo, &store)?;
+
+    Ok(PatchSeriesResult {
+        series,
+        version,
+        cover_letter: cover,
+        patches,
+        range_diff_against,
+    })
+}
+
+/// Replace the cover letter's subject and blurb placeholders
+fn fill_cover_letter(path: &Path, subject: Option<&str>, body: Option<&str>) -> Result<()> {
+    if subject.is_none() && body.is_none() {
+        return Ok(());
+    }
+    let mut content = std::fs::read_to_string(path)?;
+    if let Some(subject) = subject {
+        // A subject is a single header line; a pasted newline would start
+        // the body early.
+        let subject = subject.lines().next().unwrap_or("").trim();
+        content = content.replacen(COVER_SUBJECT_PLACEHOLDER, subject, 1);
+    }
+    if let Some(body) = body {
+        content = content.replacen(COVER_BLURB_PLACEHOLDER, body.trim_end(), 1);
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+/// The `rebase-apply` directory, when it belongs to `git am` rather than to
+/// a rebase (git marks am sessions with an `applying` file).
+fn am_dir(repo: &git2::Repository) -> Option<PathBuf> {
+    let dir = repo.path().join("rebase-apply");
+    dir.join("applying").exists().then_some(dir)
+}
+
+fn read_number(dir: &Path, name: &str) -> Option<u32> {
+    std::fs::read_to_string(dir.join(name))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+}
+
+/// `Subject:` of a split-out mail, without its `[PATCH n/m]` prefix
+fn mail_subject(mail: &str) -> Option<String> {
+    let mut lines = mail.lines();
+    let mut subject = None;
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            break;
+        }
+        if let Some(rest) = line.strip_prefix("Subject:") {
+            let mut value = rest.trim().to_string();
+            // Folded continuation lines
+            for next in lines.clone() {
+                if next.starts_with(' ') || next.starts_with('\t') {
+                    value.push(' ');
+                    value.push_str(next.trim());
+                } else {
+                    break;
+                }
+            }
+            subject = Some(value);
+            break;
+        }
+    }
+    subject.map(|s| strip_patch_prefix(&s))
+}
+
+fn strip_patch_prefix(subject: &str) -> String {
+    let mut s = subject.trim();
+    while let Some(rest) = s.strip_prefix('[') {
+        match rest.find(']') {
+            Some(end) => s = rest[end + 1..].trim_start(),
+            None => break,
+        }
+    }
+    s.to_string()
+}
+
+/// `ORIG_HEAD..HEAD`, newest first: `git am` sets ORIG_HEAD when it starts.
+fn applied_commits(repo: &git2::Repository) -> Vec<AmAppliedCommit> {
+    let (Ok(orig), Ok(head)) = (
+        repo.revparse_single("ORIG_HEAD")
+            .and_then(|o| o.peel_to_commit()),
+        repo.head().and_then(|h| h.peel_to_commit()),
+    ) else {
+        return Vec::new();
+    };
+    let Ok(mut walk) = repo.revwalk() else {
+        return Vec::new();
+    };
+    if walk.push(head.id()).is_err() || walk.hide(orig.id()).is_err() {
+        return Vec::new();
+    }
+    walk.take(MAX_APPLIED_COMMITS)
+        .flatten()
+        .filter_map(|oid| repo.find_commit(oid).ok())
+        .map(|c| AmAppliedCommit {
+            oid: c.id().to_string(),
+            summary: c.summary().ok().flatten().unwrap_or("").to_string(),
+        })
+        .collect()
+}
+
+fn read_am_status(path: &str, include_applied: bool, message: Option<String>) -> Result<AmStatus> {
+    let repo = open_repo(path)?;
+    let Some(dir) = am_dir(&repo) else {
+        return Ok(AmStatus {
+            in_progress: false,
+            current_patch: None,
+            total_patches: None,
+            current_subject: None,
+            current_author: None,
+            patches: Vec::new(),
+            conflicted_files: Vec::new(),
+            applied_commits: if include_applied {
+                applied_commits(&repo)
+            } else {
+                Vec::new()
+            },
+            message,
+        });
+    };
+
+    let current = read_number(&dir, "next");
+    let total = read_number(&dir, "last");
+
+    // `info` holds mailinfo's parse of the current patch.
+    let info = std::fs::read_to_string(dir.join("info")).unwrap_or_default();
+    let field = |name: &str| {
+        info.lines()
+            .find_map(|l| l.strip_prefix(name))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    let current_author = match (field("Author:"), field("Email:")) {
+        (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+        (name, email) => name.or(email),
+    };
+
+    let mut patches = Vec::new();
+    if let (Some(current), Some(total)) = (current, total) {
+        for number in 1..=total {
+            let mail = std::fs::read_to_string(dir.join(format!("{:04}", number)));
+            let subject = mail.ok().and_then(|m| mail_subject(&m)).unwrap_or_default();
+            patches.push(AmPatchInfo {
+                number,
+                subject,
+                state: match number.cmp(&current) {
+                    std::cmp::Ordering::Less => AmPatchState::Done,
+                    std::cmp::Ordering::Equal => AmPatchState::Current,
+                    std::cmp::Ordering::Greater => AmPatchState::Pending,
+                },
+            });
+        }
+    }
+
+    let mut conflicted_files = Vec::new();
+    let index = repo.index()?;
+    if index.has_conflicts() {
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                conflicted_files.push(String::from_utf8_lossy(&entry.path).to_string());
+            }
+        }
+    }
+
+    Ok(AmStatus {
+        in_progress: true,
+        current_patch: current,
+        total_patches: total,
+        current_subject: field("Subject:"),
+        current_author,
+        patches,
+        conflicted_files,
+        applied_commits: applied_commits(&repo),
+        message,
+    })
+}
+
+/// Run a `git am` step and report where it left the session.
+///
+/// A non-zero exit that leaves an am session behind is a stop on a patch,
+/// reported through the status; one that doesn't is a real error.
+fn run_am(path: &str, args: Vec<String>) -> Result<AmStatus> {
+    let output = git_output(path, &args)?;
+    let message = (!output.status.success()).then(|| stderr_tail(&output));
+    let status = read_am_status(path, true, message)?;
+    if !output.status.success() && !status.in_progress {
+        return Err(LeviathanError::OperationFailed(format!(
+            "git am failed: {}",
+            status.message.unwrap_or_default()
+        )));
+    }
+    Ok(status)
+}
+
+/// Apply an mbox of patches as commits, `git am` style
+///
+/// Falls back to a 3-way merge for patches that don't apply as-is (unless
+/// disabled). When a patch still conflicts, git stops on it: the status
+/// names the patch and the conflicted files, and the session is resumed
+/// with [`am_continue`], [`am_skip`] or [`am_abort`].
+#[command]
+pub async fn apply_mbox(
+    path: String,
+    mbox_path: String,
+    options: Option<ApplyMboxOptions>,
+) -> Result<AmStatus> {
+    let options = options.unwrap_or_default();
+    if !Path::new(&mbox_path).exists() {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Mailbox not found: {}",
+            mbox_path
+        )));
+    }
+    let repo = open_repo(&path)?;
+    if am_dir(&repo).is_some() {
+        return Err(LeviathanError::OperationFailed(
+            "A patch series is already being applied; continue, skip or abort it first".to_string(),
+        ));
+    }
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Cannot apply patches while a {:?} is in progress",
+            repo.state()
+        )));
+    }
+    drop(repo);
+
+    let mut args = vec!["am".to_string()];
+    if options.three_way.unwrap_or(true) {
+        args.push("--3way".into());
+    }
+    if options.signoff.unwrap_or(false) {
+        args.push("--signoff".into());
+    }
+    if options.ignore_whitespace.unwrap_or(false) {
+        args.push("--ignore-whitespace".into());
+    }
+    args.push("--".into());
+    args.push(mbox_path);
+    run_am(&path, args)
+}
+
+/// Get the state of the current `git am` session, if any
+#[command]
+pub async fn get_am_status(path: String) -> Result<AmStatus> {
+    read_am_status(&path, false, None)
+}
+
+fn require_am(path: &str) -> Result<()> {
+    if am_dir(&open_repo(path)?).is_none() {
+        return Err(LeviathanError::OperationFailed(
+            "No patch series is being applied".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Commit the resolved current patch and carry on with the rest
+///
+/// Resolutions must be staged first, as with `git am --continue`.
+#[command]
+pub async fn am_continue(path: String) -> Result<AmStatus> {
+    require_am(&path)?;
+    run_am(&path, vec!["am".into(), "--continue".into()])
+}
+
+/// Drop the current patch and carry on with the rest
+#[command]
+pub async fn am_skip(path: String) -> Result<AmStatus> {
+    require_am(&path)?;
+    run_am(&path, vec!["am".into(), "--skip".into()])
+}
+
+/// Stop applying and restore the branch to where it was before
+#[command]
+pub async fn am_abort(path: String) -> Result<AmStatus> {
+    require_am(&path)?;
+    let output = git_output(&path, &["am".into(), "--abort".into()])?;
+    if !output.status.success() {
+        return Err(LeviathanError::OperationFailed(format!(
+            "git am --abort failed: {}",
+            stderr_tail(&output)
+        )));
+    }
+    read_am_status(&path, false, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    fn options(base: &str, output_dir: &Path) -> PatchSeriesOptions {
+        PatchSeriesOptions {
+            base: base.to_string(),
+            output_dir: output_dir.to_string_lossy().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Concatenate a series' patches into one mbox, as a mail client's
+    /// "save thread" would.
+    fn mbox(patches: &[String], dest: &Path) -> String {
+        let mut content = Vec::new();
+        for patch in patches {
+            content.extend(std::fs::read(patch).unwrap());
+        }
+        std::fs::write(dest, content).unwrap();
+        dest.to_string_lossy().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_series_has_numbered_patches_and_filled_cover_letter() {
+        let repo = TestRepo::with_initial_commit();
+        let base = repo.head_oid().to_string();
+        repo.create_commit("Add parser", &[("parser.rs", "fn parse() {}\n")]);
+        repo.create_commit("Add lexer", &[("lexer.rs", "fn lex() {}\n")]);
+        let out = repo.path.join("out");
+
+        let mut opts = options(&base, &out);
+        opts.cover_subject = Some("Parsing support".to_string());
+        opts.cover_body = Some("This adds a parser and a lexer.".to_string());
+        let result = format_patch_series(repo.path_str(), opts).await.unwrap();
+
+        assert_eq!(result.version, 1);
+        assert_eq!(result.series, "main");
+        assert_eq!(result.patches.len(), 2);
+        let first = std::fs::read_to_string(&result.patches[0]).unwrap();
+        assert!(first.contains("Subject: [PATCH 1/2] Add parser"), "{first}");
+
+        let cover = std::fs::read_to_string(result.cover_letter.unwrap()).unwrap();
+        assert!(cover.contains("[PATCH 0/2] Parsing support"), "{cover}");
+        assert!(cover.contains("This adds a parser and a lexer."));
+        assert!(cover.contains("Add lexer"), "shortlog: {cover}");
+        assert!(cover.contains("2 files changed"), "diffstat: {cover}");
+    }
+
+    #[tokio::test]
+    async fn test_reroll_bumps_version_and_adds_range_diff() {
+        let repo = TestRepo::with_initial_commit();
+        let base = repo.head_oid().to_string();
+        repo.create_commit("Add parser", &[("parser.rs", "fn parse() {}\n")]);
+        repo.create_commit("Add lexer", &[("lexer.rs", "fn lex() {}\n")]);
+        format_patch_series(repo.path_str(), options(&base, &repo.path.join("v1")))
+            .await
+            .unwrap();
+
+        // Address review: amend the tip.
+        repo.create_commit("Fix lexer", &[("lexer.rs", "fn lex() { todo!() }\n")]);
+        let v2 = format_patch_series(repo.path_str(), options(&base, &repo.path.join("v2")))
+            .await
+            .unwrap();
+
+        assert_eq!(v2.version, 2);
+        assert!(v2.range_diff_against.is_some());
+        let cover = std::fs::read_to_string(v2.cover_letter.unwrap()).unwrap();
+        assert!(cover.contains("[PATCH v2 0/3]"), "{cover}");
+        assert!(cover.contains("Range-diff against v1"), "{cover}");
+        assert!(v2.patches[0].contains("v2-0001"));
+
+        let versions = get_patch_series_versions(repo.path_str(), Some("main".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(
+            versions.iter().map(|v| v.version).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_range_is_an_error() {
+        let repo = TestRepo::with_initial_commit();
+        let head = repo.head_oid().to_string();
+        let result =
+            format_patch_series(repo.path_str(), options(&head, &repo.path.join("out"))).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_mbox_creates_commits() {
+        let source = TestRepo::with_initial_commit();
+        let base = source.head_oid().to_string();
+        source.create_commit("One", &[("one.txt", "1\n")]);
+        source.create_commit("Two", &[("two.txt", "2\n")]);
+        let series = format_patch_series(source.path_str(), options(&base, &source.path.join("o")))
+            .await
+            .unwrap();
+
+        let target = TestRepo::with_initial_commit();
+        let mailbox = mbox(&series.patches, &source.path.join("series.mbox"));
+        let status = apply_mbox(target.path_str(), mailbox, None).await.unwrap();
+
+        assert!(!status.in_progress);
+        assert_eq!(
+            status
+                .applied_commits
+                .iter()
+                .map(|c| c.summary.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Two", "One"]
+        );
+        assert!(target.path.join("two.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_conflicting_patch_stops_and_can_be_continued() {
+        let source = TestRepo::with_initial_commit();
+        let base = source.head_oid().to_string();
+        source.create_commit("Edit readme", &[("README.md", "# Upstream\n")]);
+        source.create_commit("Add file", &[("new.txt", "new\n")]);
+        let series = format_patch_series(source.path_str(), options(&base, &source.path.join("o")))
+            .await
+            .unwrap();
+        let mailbox = mbox(&series.patches, &source.path.join("series.mbox"));
+
+        let target = TestRepo::with_initial_commit();
+        target.create_commit("Local readme", &[("README.md", "# Local\n")]);
+
+        let status = apply_mbox(target.path_str(), mailbox, None).await.unwrap();
+        assert!(status.in_progress);
+        assert_eq!(status.current_patch, Some(1));
+        assert_eq!(status.total_patches, Some(2));
+        assert_eq!(status.current_subject.as_deref(), Some("Edit readme"));
+        assert_eq!(status.conflicted_files, vec!["README.md"]);
+        assert_eq!(status.patches[1].subject, "Add file");
+        assert_eq!(status.patches[1].state, AmPatchState::Pending);
+        assert!(status.message.is_some());
+
+        target.create_file("README.md", "# Merged\n");
+        target.stage_file("README.md");
+        let status = am_continue(target.path_str()).await.unwrap();
+
+        assert!(!status.in_progress, "{:?}", status);
+        assert_eq!(status.applied_commits.len(), 2);
+        assert_eq!(
+            std::fs::read_to_string(target.path.join("README.md")).unwrap(),
+            "# Merged\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_skip_and_abort() {
+        let source = TestRepo::with_initial_commit();
+        let base = source.head_oid().to_string();
+        source.create_commit("Edit readme", &[("README.md", "# Upstream\n")]);
+        source.create_commit("Add file", &[("new.txt", "new\n")]);
+        let series = format_patch_series(source.path_str(), options(&base, &source.path.join("o")))
+            .await
+            .unwrap();
+        let mailbox = mbox(&series.patches, &source.path.join("series.mbox"));
+
+        let target = TestRepo::with_initial_commit();
+        let local = target.create_commit("Local readme", &[("README.md", "# Local\n")]);
+
+        apply_mbox(target.path_str(), mailbox.clone(), None)
+            .await
+            .unwrap();
+        let status = am_skip(target.path_str()).await.unwrap();
+        assert!(!status.in_progress);
+        assert_eq!(status.applied_commits.len(), 1);
+        assert_eq!(status.applied_commits[0].summary, "Add file");
+
+        {
+            let git = target.repo();
+            let commit = git.find_object(local, None).unwrap();
+            git.reset(&commit, git2::ResetType::Hard, None).unwrap();
+        }
+        apply_mbox(target.path_str(), mailbox, None).await.unwrap();
+        let status = am_abort(target.path_str()).await.unwrap();
+        assert!(!status.in_progress);
+        assert_eq!(target.head_oid(), local);
+        assert!(am_continue(target.path_str()).await.is_err());
+    }
+
+    #[test]
+    fn test_strip_patch_prefix() {
+        assert_eq!(strip_patch_prefix("[PATCH v2 3/7] Fix it"), "Fix it");
+        assert_eq!(strip_patch_prefix("[RFC] [PATCH] Try"), "Try");
+        assert_eq!(strip_patch_prefix("Plain"), "Plain");
+    }
+}
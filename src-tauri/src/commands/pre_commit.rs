@@ -0,0 +1,1348 @@
+//! Built-in runner for `.pre-commit-config.yaml`
+//!
+//! Many repositories declare their checks for the pre-commit framework. The
+//! framework installs a `pre-commit` hook that reads the config; when it is not
+//! installed there is no hook at all, so `hooks::run_hook` has nothing to run
+//! and every check is silently bypassed. This module reads the config itself
+//! and runs the hooks it can run without the framework — `repo: local` hooks
+//! whose `language` is `system`, `script` or `fail` — against the staged
+//! files, with the framework's `files`/`exclude`/`types` filtering.
+//!
+//! Hooks from remote repositories, and local hooks in a managed language
+//! (python, node, ...), need the framework to build their environment. They are
+//! reported as skipped with the reason, never run half-configured.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::command;
+
+use crate::error::{LeviathanError, Result};
+
+/// Config file name, at the root of the working tree.
+pub const CONFIG_FILE: &str = ".pre-commit-config.yaml";
+
+/// Languages the built-in runner can execute without the framework.
+const SUPPORTED_LANGUAGES: &[&str] = &["system", "script", "fail"];
+
+/// Upper bound on the filename bytes passed to one hook invocation. Larger
+/// file sets are split across several invocations, as the framework does, so a
+/// big commit cannot hit the OS argument-length limit.
+const MAX_FILENAME_BYTES: usize = 32 * 1024;
+
+/// A hook declared in `.pre-commit-config.yaml`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreCommitHook {
+    /// `repo:` of the enclosing entry (`local`, `meta` or a URL)
+    pub repo: String,
+    pub id: String,
+    pub name: String,
+    pub entry: String,
+    pub language: String,
+    pub files: String,
+    pub exclude: String,
+    pub types: Vec<String>,
+    pub types_or: Vec<String>,
+    pub exclude_types: Vec<String>,
+    pub args: Vec<String>,
+    pub pass_filenames: bool,
+    pub always_run: bool,
+    pub fail_fast: bool,
+    pub stages: Vec<String>,
+    /// Whether the built-in runner can execute this hook
+    pub supported: bool,
+}
+
+/// Parsed `.pre-commit-config.yaml`
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreCommitConfig {
+    /// Global `files` filter applied before each hook's own
+    pub files: String,
+    /// Global `exclude` filter applied before each hook's own
+    pub exclude: String,
+    pub fail_fast: bool,
+    pub hooks: Vec<PreCommitHook>,
+}
+
+/// Outcome of one hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreCommitHookStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Result of running one hook
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreCommitHookResult {
+    pub id: String,
+    pub name: String,
+    pub status: PreCommitHookStatus,
+    /// Exit code of the last invocation (None when the hook did not run)
+    pub exit_code: Option<i32>,
+    pub files_checked: usize,
+    /// Files whose working-tree content the hook changed
+    pub files_modified: Vec<String>,
+    /// Modified files that were re-staged (only with `restage_fixed`)
+    pub files_restaged: Vec<String>,
+    /// Combined stdout+stderr of every invocation
+    pub output: String,
+    pub skip_reason: Option<String>,
+}
+
+/// Result of a full run
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreCommitRunResult {
+    /// False when the repository has no `.pre-commit-config.yaml`
+    pub config_found: bool,
+    /// True when no hook failed
+    pub success: bool,
+    pub hooks: Vec<PreCommitHookResult>,
+}
+
+// ============================================================================
+// YAML values
+// ============================================================================
+
+/// A scalar as the framework sees it: numbers and booleans read as their
+/// text, so `args: [--line-length, 100]` yields `"100"`.
+fn yaml_str(value: &serde_norway::Value) -> Option<String> {
+    match value {
+        serde_norway::Value::String(s) => Some(s.clone()),
+        serde_norway::Value::Number(n) => Some(n.to_string()),
+        serde_norway::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A boolean, also accepting the YAML 1.1 spellings the framework's parser
+/// understands (`yes`, `on`, ...).
+fn yaml_bool(value: &serde_norway::Value) -> Option<bool> {
+    if let serde_norway::Value::Bool(b) = value {
+        return Some(*b);
+    }
+    match value.as_str()? {
+        "true" | "True" | "TRUE" | "yes" | "Yes" | "on" | "On" => Some(true),
+        "false" | "False" | "FALSE" | "no" | "No" | "off" | "Off" => Some(false),
+        _ => None,
+    }
+}
+
+/// A sequence of scalars; a lone scalar is read as a one-element list.
+fn yaml_str_list(value: &serde_norway::Value) -> Vec<String> {
+    match value {
+        serde_norway::Value::Sequence(items) => items.iter().filter_map(yaml_str).collect(),
+        other => yaml_str(other).into_iter().collect(),
+    }
+}
+
+// ============================================================================
+// Config loading
+// ============================================================================
+
+/// Parse `.pre-commit-config.yaml` content.
+pub fn parse_config(content: &str) -> Result<PreCommitConfig> {
+    let root: serde_norway::Value = if content.trim().is_empty() {
+        serde_norway::Value::Null
+    } else {
+        serde_norway::from_str(content).map_err(|e| {
+            LeviathanError::OperationFailed(format!("Invalid {}: {}", CONFIG_FILE, e))
+        })?
+    };
+    let default_stages = root
+        .get("default_stages")
+        .map(yaml_str_list)
+        .unwrap_or_default();
+
+    let mut hooks = Vec::new();
+    if let Some(repos) = root.get("repos").and_then(|v| v.as_sequence()) {
+        for repo in repos {
+            let repo_name = repo.get("repo").and_then(yaml_str).unwrap_or_default();
+            let Some(repo_hooks) = repo.get("hooks").and_then(|v| v.as_sequence()) else {
+                continue;
+            };
+            for hook in repo_hooks {
+                let str_field = |key: &str| hook.get(key).and_then(yaml_str);
+                let list_field = |key: &str| hook.get(key).map(yaml_str_list);
+                let bool_field = |key: &str| hook.get(key).and_then(yaml_bool);
+
+                let Some(id) = str_field("id") else {
+                    continue;
+                };
+                let language = str_field("language").unwrap_or_default();
+                let supported =
+                    repo_name == "local" && SUPPORTED_LANGUAGES.contains(&language.as_str());
+                hooks.push(PreCommitHook {
+                    repo: repo_name.clone(),
+                    name: str_field("name").unwrap_or_else(|| id.clone()),
+                    entry: str_field("entry").unwrap_or_default(),
+                    language,
+                    files: str_field("files").unwrap_or_default(),
+                    exclude: str_field("exclude").unwrap_or_else(|| "^$".to_string()),
+                    types: list_field("types").unwrap_or_else(|| vec!["file".to_string()]),
+                    types_or: list_field("types_or").unwrap_or_default(),
+                    exclude_types: list_field("exclude_types").unwrap_or_default(),
+                    args: list_field("args").unwrap_or_default(),
+                    pass_filenames: bool_field("pass_filenames").unwrap_or(true),
+                    always_run: bool_field("always_run").unwrap_or(false),
+                    fail_fast: bool_field("fail_fast").unwrap_or(false),
+                    stages: list_field("stages").unwrap_or_else(|| default_stages.clone()),
+                    supported,
+                    id,
+                });
+            }
+        }
+    }
+
+    Ok(PreCommitConfig {
+        files: root.get("files").and_then(yaml_str).unwrap_or_default(),
+        exclude: root
+            .get("exclude")
+            .and_then(yaml_str)
+            .unwrap_or_else(|| "^$".to_string()),
+        fail_fast: root.get("fail_fast").and_then(yaml_bool).unwrap_or(false),
+        hooks,
+    })
+}
+
+/// Read the config from the working tree, or None when there is none.
+pub fn load_config(workdir: &Path) -> Result<Option<PreCommitConfig>> {
+    let config_path = workdir.join(CONFIG_FILE);
+    if !config_path.is_file() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(&config_path)?;
+    parse_config(&content).map(Some)
+}
+
+/// Whether a hook takes part in the commit stage. No `stages` means every
+/// stage; `commit` is the framework's legacy name for `pre-commit`.
+fn runs_at_commit(hook: &PreCommitHook) -> bool {
+    hook.stages.is_empty()
+        || hook
+            .stages
+            .iter()
+            .any(|s| s == "pre-commit" || s == "commit")
+}
+
+// ============================================================================
+// File selection
+// ============================================================================
+
+/// Paths staged for commit (added, copied, modified, renamed or type-changed),
+/// matching the framework's `git diff --staged --diff-filter=ACMRT`.
+fn staged_files(repo: &git2::Repository) -> Result<Vec<String>> {
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let index = repo.index()?;
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), Some(&index), None)?;
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        if matches!(
+            delta.status(),
+            git2::Delta::Added
+                | git2::Delta::Copied
+                | git2::Delta::Modified
+                | git2::Delta::Renamed
+                | git2::Delta::Typechange
+        ) {
+            if let Some(path) = delta.new_file().path() {
+                files.push(path.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Every path in the index, for `--all-files` style runs.
+fn all_index_files(repo: &git2::Repository) -> Result<Vec<String>> {
+    let index = repo.index()?;
+    Ok(index
+        .iter()
+        .map(|e| String::from_utf8_lossy(&e.path).replace('\\', "/"))
+        .collect())
+}
+
+/// Extension and filename to identify-style tags. Covers the tags configs
+/// commonly filter on; anything else is just `file` + `text`/`binary`.
+fn language_tags(path: &str) -> &'static [&'static str] {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    match file_name {
+        "Dockerfile" => return &["dockerfile"],
+        "Makefile" | "makefile" | "GNUmakefile" => return &["makefile"],
+        _ => {}
+    }
+    let ext = match file_name.rsplit_once('.') {
+        Some((_, ext)) => ext.to_ascii_lowercase(),
+        None => return &[],
+    };
+    match ext.as_str() {
+        "py" => &["python"],
+        "pyi" => &["python", "pyi"],
+        "js" | "mjs" | "cjs" => &["javascript"],
+        "jsx" => &["javascript", "jsx"],
+        "ts" | "mts" | "cts" => &["ts"],
+        "tsx" => &["tsx"],
+        "rs" => &["rust"],
+        "go" => &["go"],
+        "rb" => &["ruby"],
+        "java" => &["java"],
+        "kt" | "kts" => &["kotlin"],
+        "c" => &["c"],
+        "h" => &["c", "header"],
+        "cc" | "cpp" | "cxx" => &["c++"],
+        "hpp" | "hh" | "hxx" => &["c++", "header"],
+        "cs" => &["c#"],
+        "swift" => &["swift"],
+        "php" => &["php"],
+        "lua" => &["lua"],
+        "sh" => &["shell", "sh"],
+        "bash" => &["shell", "bash"],
+        "zsh" => &["shell", "zsh"],
+        "json" => &["json"],
+        "yaml" | "yml" => &["yaml"],
+        "toml" => &["toml"],
+        "md" | "markdown" => &["markdown"],
+        "rst" => &["rst"],
+        "txt" => &["plain-text"],
+        "html" | "htm" => &["html"],
+        "css" => &["css"],
+        "scss" => &["scss"],
+        "xml" => &["xml"],
+        "sql" => &["sql"],
+        "proto" => &["proto"],
+        "png" => &["image", "png"],
+        "jpg" | "jpeg" => &["image", "jpeg"],
+        "gif" => &["image", "gif"],
+        "svg" => &["image", "svg", "xml"],
+        _ => &[],
+    }
+}
+
+/// Tags for a working-tree path, mirroring the identify library the
+/// framework uses for `types` filtering.
+fn file_tags(workdir: &Path, path: &str) -> HashSet<String> {
+    let mut tags: HashSet<String> = language_tags(path).iter().map(|t| t.to_string()).collect();
+    let full = workdir.join(path);
+    let Ok(meta) = full.symlink_metadata() else {
+        return tags;
+    };
+    if meta.file_type().is_symlink() {
+        tags.insert("symlink".to_string());
+        return tags;
+    }
+    tags.insert("file".to_string());
+
+    #[cfg(unix)]
+    let executable = {
+        use std::os::unix::fs::PermissionsExt;
+        meta.permissions().mode() & 0o111 != 0
+    };
+    #[cfg(not(unix))]
+    let executable = false;
+    tags.insert(
+        if executable {
+            "executable"
+        } else {
+            "non-executable"
+        }
+        .to_string(),
+    );
+
+    let is_binary = std::fs::read(&full)
+        .map(|bytes| bytes.iter().take(8000).any(|b| *b == 0))
+        .unwrap_or(false);
+    tags.insert(if is_binary { "binary" } else { "text" }.to_string());
+    tags
+}
+
+/// Compile a `files`/`exclude` pattern. The framework uses Python's `re`, so
+/// a config can hold syntax the `regex` crate lacks (lookarounds,
+/// backreferences); the error is returned as a skip reason rather than
+/// failing the whole run.
+fn compile(pattern: &str, what: &str, scope: &str) -> std::result::Result<regex::Regex, String> {
+    regex::Regex::new(pattern).map_err(|e| {
+        format!(
+            "{} `{}` pattern `{}` is not supported: {}",
+            scope, what, pattern, e
+        )
+    })
+}
+
+/// Apply the global and per-hook filters to `files`.
+///
+/// Returns the reason as the error when a pattern cannot be compiled; the
+/// caller reports the hook as skipped.
+fn filter_files(
+    workdir: &Path,
+    config: &PreCommitConfig,
+    hook: &PreCommitHook,
+    files: &[String],
+) -> std::result::Result<Vec<String>, String> {
+    let global_files = compile(&config.files, "files", "global")?;
+    let global_exclude = compile(&config.exclude, "exclude", "global")?;
+    let files_re = compile(&hook.files, "files", "hook")?;
+    let exclude_re = compile(&hook.exclude, "exclude", "hook")?;
+
+    let mut selected = Vec::new();
+    for file in files {
+        if !global_files.is_match(file) || global_exclude.is_match(file) {
+            continue;
+        }
+        if !files_re.is_match(file) || exclude_re.is_match(file) {
+            continue;
+        }
+        let tags = file_tags(workdir, file);
+        if !hook.types.iter().all(|t| tags.contains(t)) {
+            continue;
+        }
+        if !hook.types_or.is_empty() && !hook.types_or.iter().any(|t| tags.contains(t)) {
+            continue;
+        }
+        if hook.exclude_types.iter().any(|t| tags.contains(t)) {
+            continue;
+        }
+        selected.push(file.clone());
+    }
+    Ok(selected)
+}
+
+// ============================================================================
+// Execution
+// ============================================================================
+
+/// Split an `entry` into words with POSIX shell quoting rules (the framework
+/// uses `shlex.split`). No expansion of any kind is performed.
+fn split_entry(entry: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = entry.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                for q in chars.by_ref() {
+                    if q == '\'' {
+                        break;
+                    }
+                    current.push(q);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(q) = chars.next() {
+                    match q {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                current.push(escaped);
+                            }
+                        }
+                        _ => current.push(q),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            _ => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+/// Split `files` into batches whose total length stays under
+/// [`MAX_FILENAME_BYTES`]. Always yields at least one (possibly empty) batch.
+fn batch_files(files: &[String]) -> Vec<Vec<String>> {
+    let mut batches = vec![Vec::new()];
+    let mut size = 0;
+    for file in files {
+        let last = batches.last_mut().expect("batches is never empty");
+        if !last.is_empty() && size + file.len() + 1 > MAX_FILENAME_BYTES {
+            batches.push(Vec::new());
+            size = 0;
+        }
+        size += file.len() + 1;
+        batches
+            .last_mut()
+            .expect("batches is never empty")
+            .push(file.clone());
+    }
+    batches
+}
+
+/// Content hashes of `files` in the working tree, for detecting what a hook
+/// rewrote. A missing file hashes to None.
+fn snapshot(workdir: &Path, files: &[String]) -> Vec<Option<git2::Oid>> {
+    files
+        .iter()
+        .map(|f| git2::Oid::hash_file(git2::ObjectType::Blob, workdir.join(f)).ok())
+        .collect()
+}
+
+/// Run one invocation; returns (exit code, stdout, stderr).
+fn invoke(workdir: &Path, program: &Path, args: &[String]) -> Result<(i32, String, String)> {
+    let program_str = program.to_string_lossy().to_string();
+    let output = crate::utils::create_command(&program_str)
+        .current_dir(workdir)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| {
+            LeviathanError::OperationFailed(format!("Failed to run {}: {}", program_str, e))
+        })?;
+
+    Ok((
+        output.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+/// Run every batch of one hook; returns (last non-zero exit code or 0, output).
+///
+/// Each invocation is recorded in the hook history as `pre-commit:<id>`, the
+/// same log the installed hooks are recorded in.
+fn execute_hook(
+    repo: &git2::Repository,
+    workdir: &Path,
+    hook: &PreCommitHook,
+    files: &[String],
+) -> Result<(i32, String)> {
+    let words = split_entry(&hook.entry);
+    let Some((program, entry_args)) = words.split_first() else {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Hook {} has an empty entry",
+            hook.id
+        )));
+    };
+    // `script` entries are paths relative to the repository root; `system`
+    // entries are looked up on PATH like any command.
+    let program_path = if hook.language == "script" {
+        workdir.join(program)
+    } else {
+        PathBuf::from(program)
+    };
+
+    let batches = if hook.pass_filenames {
+        batch_files(files)
+    } else {
+        vec![Vec::new()]
+    };
+
+    let mut exit_code = 0;
+    let mut output = String::new();
+    for batch in batches {
+        let mut args: Vec<String> = entry_args.to_vec();
+        args.extend(hook.args.iter().cloned());
+        // The history records the file COUNT, not thousands of paths.
+        let mut logged: Vec<String> = args.clone();
+        if hook.pass_filenames {
+            logged.push(format!("<{} files>", batch.len()));
+        }
+        let logged_args: Vec<&str> = logged.iter().map(String::as_str).collect();
+
+        let started = std::time::SystemTime::now();
+        let timer = std::time::Instant::now();
+        args.extend(batch);
+        let (code, stdout, stderr) = match invoke(workdir, &program_path, &args) {
+            Ok(out) => out,
+            Err(e) => {
+                crate::commands::hook_history::record(
+                    repo,
+                    &crate::commands::hook_history::new_record(
+                        &format!("pre-commit:{}", hook.id),
+                        &logged_args,
+                        None,
+                        started,
+                        timer.elapsed(),
+                        "",
+                        &e.to_string(),
+                    ),
+                );
+                return Err(e);
+            }
+        };
+        crate::commands::hook_history::record(
+            repo,
+            &crate::commands::hook_history::new_record(
+                &format!("pre-commit:{}", hook.id),
+                &logged_args,
+                Some(code),
+                started,
+                timer.elapsed(),
+                &stdout,
+                &stderr,
+            ),
+        );
+
+        if code != 0 {
+            exit_code = code;
+        }
+        output.push_str(&stdout);
+        if !stderr.is_empty() {
+            if !output.is_empty() && !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output.push_str(&stderr);
+        }
+    }
+    Ok((exit_code, output))
+}
+
+/// Working-tree files that also differ from the index before any hook ran.
+/// Re-staging one of these after a fixer would sweep the user's unstaged edits
+/// into the commit, so such files are never re-staged.
+fn partially_staged(repo: &git2::Repository, files: &[String]) -> HashSet<String> {
+    files
+        .iter()
+        .filter(|f| {
+            repo.status_file(Path::new(f.as_str()))
+                .map(|s| {
+                    s.intersects(
+                        git2::Status::WT_MODIFIED
+                            | git2::Status::WT_DELETED
+                            | git2::Status::WT_TYPECHANGE,
+                    )
+                })
+                .unwrap_or(false)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Puts the staged content of partially staged files into the working tree
+/// while the hooks run, so they check what is about to be committed rather
+/// than the user's unstaged edits — the framework stashes unstaged changes
+/// for the same reason. The working-tree copies are restored on drop, on
+/// every exit path.
+struct StagedContentSwap {
+    workdir: PathBuf,
+    /// Swapped paths with their working-tree bytes (None when the file was
+    /// deleted in the working tree)
+    saved: Vec<(String, Option<Vec<u8>>)>,
+}
+
+impl StagedContentSwap {
+    fn apply(repo: &git2::Repository, workdir: &Path, files: &HashSet<String>) -> Result<Self> {
+        let index = repo.index()?;
+        let mut swap = Self {
+            workdir: workdir.to_path_buf(),
+            saved: Vec::new(),
+        };
+        let mut files: Vec<&String> = files.iter().collect();
+        files.sort();
+        for file in files {
+            let Some(entry) = index.get_path(Path::new(file), 0) else {
+                continue;
+            };
+            // Symlinks and submodules are left alone.
+            if entry.mode != 0o100644 && entry.mode != 0o100755 {
+                continue;
+            }
+            let full = workdir.join(file);
+            let original = match std::fs::read(&full) {
+                Ok(bytes) => Some(bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            };
+            let blob = repo.find_blob(entry.id)?;
+            let existed = original.is_some();
+            // Recorded before writing so a failed write is still undone.
+            swap.saved.push((file.clone(), original));
+            if let Some(parent) = full.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&full, blob.content())?;
+            #[cfg(unix)]
+            if !existed && entry.mode == 0o100755 {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&full, std::fs::Permissions::from_mode(0o755))?;
+            }
+        }
+        Ok(swap)
+    }
+}
+
+impl Drop for StagedContentSwap {
+    fn drop(&mut self) {
+        for (file, original) in self.saved.iter().rev() {
+            let full = self.workdir.join(file);
+            let restored = match original {
+                Some(bytes) => std::fs::write(&full, bytes),
+                None => std::fs::remove_file(&full),
+            };
+            if let Err(e) = restored {
+                tracing::warn!("failed to restore unstaged changes to {}: {}", file, e);
+            }
+        }
+    }
+}
+
+/// Run one hook against `candidates`, re-staging and re-running once when
+/// `restage_fixed` is set and the hook rewrote files.
+fn run_one(
+    repo: &git2::Repository,
+    workdir: &Path,
+    config: &PreCommitConfig,
+    hook: &PreCommitHook,
+    candidates: &[String],
+    restage_fixed: bool,
+    unstaged: &HashSet<String>,
+) -> Result<PreCommitHookResult> {
+    let mut result = PreCommitHookResult {
+        id: hook.id.clone(),
+        name: hook.name.clone(),
+        status: PreCommitHookStatus::Skipped,
+        exit_code: None,
+        files_checked: 0,
+        files_modified: Vec::new(),
+        files_restaged: Vec::new(),
+        output: String::new(),
+        skip_reason: None,
+    };
+
+    if !hook.supported {
+        result.skip_reason = Some(if hook.repo != "local" {
+            format!(
+                "hooks from `{}` need the pre-commit framework installed",
+                hook.repo
+            )
+        } else {
+            format!(
+                "`{}` hooks need the pre-commit framework to build their environment",
+                hook.language
+            )
+        });
+        return Ok(result);
+    }
+
+    let files = match filter_files(workdir, config, hook, candidates) {
+        Ok(files) => files,
+        Err(reason) => {
+            tracing::warn!("pre-commit hook {} skipped: {}", hook.id, reason);
+            result.skip_reason = Some(reason);
+            return Ok(result);
+        }
+    };
+    if files.is_empty() && !hook.always_run {
+        result.skip_reason = Some("no files to check".to_string());
+        return Ok(result);
+    }
+    result.files_checked = files.len();
+
+    if hook.language == "fail" {
+        result.status = PreCommitHookStatus::Failed;
+        result.exit_code = Some(1);
+        result.output = format!("{}\n\n{}\n", hook.entry, files.join("\n"));
+        return Ok(result);
+    }
+
+    let before = snapshot(workdir, &files);
+    let (code, output) = execute_hook(repo, workdir, hook, &files)?;
+    let after = snapshot(workdir, &files);
+    let modified: Vec<String> = files
+        .iter()
+        .zip(before.iter().zip(after.iter()))
+        .filter(|(_, (b, a))| b != a)
+        .map(|(f, _)| f.clone())
+        .collect();
+
+    result.exit_code = Some(code);
+    result.output = output;
+    result.files_modified = modified.clone();
+
+    // The framework fails a hook that rewrote files even when it exited 0:
+    // the fix is not in the commit the user is about to make.
+    let clean = code == 0 && modified.is_empty();
+    if clean || !restage_fixed || modified.is_empty() {
+        result.status = if clean {
+            PreCommitHookStatus::Passed
+        } else {
+            PreCommitHookStatus::Failed
+        };
+        return Ok(result);
+    }
+
+    if let Some(blocked) = modified.iter().find(|f| unstaged.contains(*f)) {
+        result.status = PreCommitHookStatus::Failed;
+        result.output.push_str(&format!(
+            "\n{} has unstaged changes, so the fix was not re-staged\n",
+            blocked
+        ));
+        return Ok(result);
+    }
+
+    // Re-stage through the caller's repository handle so its cached index —
+    // which the commit paths write their tree from — sees the fixes.
+    let mut index = repo.index()?;
+    for file in &modified {
+        index.add_path(Path::new(file))?;
+    }
+    index.write()?;
+    result.files_restaged = modified;
+
+    // Many fixers exit non-zero when they change something. Re-run once on
+    // the fixed files: a fixer that is now satisfied passes.
+    let (code, output) = execute_hook(repo, workdir, hook, &files)?;
+    let after_rerun = snapshot(workdir, &files);
+    result.exit_code = Some(code);
+    result.output.push_str(&output);
+    result.status = if code == 0 && after_rerun == after {
+        PreCommitHookStatus::Passed
+    } else {
+        PreCommitHookStatus::Failed
+    };
+    Ok(result)
+}
+
+/// Run the configured commit-stage hooks.
+///
+/// `hook_ids` restricts the run to those ids; `all_files` checks every tracked
+/// file instead of only the staged ones.
+pub fn run_checks(
+    repo: &git2::Repository,
+    restage_fixed: bool,
+    hook_ids: Option<&[String]>,
+    all_files: bool,
+) -> Result<PreCommitRunResult> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| {
+            LeviathanError::OperationFailed("Bare repositories have no working tree".to_string())
+        })?
+        .to_path_buf();
+
+    let Some(config) = load_config(&workdir)? else {
+        return Ok(PreCommitRunResult {
+            config_found: false,
+            success: true,
+            hooks: Vec::new(),
+        });
+    };
+
+    let candidates = if all_files {
+        all_index_files(repo)?
+    } else {
+        staged_files(repo)?
+    };
+    let unstaged = partially_staged(repo, &candidates);
+    let _swap = StagedContentSwap::apply(repo, &workdir, &unstaged)?;
+
+    let mut results = Vec::new();
+    let mut success = true;
+    for hook in &config.hooks {
+        if !runs_at_commit(hook) {
+            continue;
+        }
+        if let Some(ids) = hook_ids {
+            if !ids.iter().any(|id| id == &hook.id) {
+                continue;
+            }
+        }
+        let result = run_one(
+            repo,
+            &workdir,
+            &config,
+            hook,
+            &candidates,
+            restage_fixed,
+            &unstaged,
+        )?;
+        let failed = result.status == PreCommitHookStatus::Failed;
+        results.push(result);
+        if failed {
+            success = false;
+            if config.fail_fast || hook.fail_fast {
+                break;
+            }
+        }
+    }
+
+    Ok(PreCommitRunResult {
+        config_found: true,
+        success,
+        hooks: results,
+    })
+}
+
+/// The commit-path entry point: run the configured checks and abort the commit
+/// when one fails, like the framework's own hook would.
+///
+/// Controlled by two repository config keys: `leviathan.precommit` (default
+/// false) turns the built-in runner on, and `leviathan.precommitRestage`
+/// (default false) re-stages files that fixers rewrote. The runner is opt-in
+/// because it changes what a commit does in every repository that carries a
+/// config, including ones whose team never installs the framework.
+pub fn run_checks_blocking(repo: &git2::Repository) -> Result<()> {
+    if repo.workdir().is_none() {
+        return Ok(());
+    }
+    let config = repo.config()?;
+    if !config.get_bool("leviathan.precommit").unwrap_or(false) {
+        return Ok(());
+    }
+    let restage = config
+        .get_bool("leviathan.precommitRestage")
+        .unwrap_or(false);
+
+    let run = run_checks(repo, restage, None, false)?;
+    if run.success {
+        return Ok(());
+    }
+
+    let mut detail = String::new();
+    for hook in run
+        .hooks
+        .iter()
+        .filter(|h| h.status == PreCommitHookStatus::Failed)
+    {
+        detail.push_str(&format!("\n{} ({}) failed", hook.name, hook.id));
+        if !hook.files_modified.is_empty() && hook.files_restaged.is_empty() {
+            detail.push_str(&format!(
+                "; files were modified by this hook: {}",
+                hook.files_modified.join(", ")
+            ));
+        }
+        let output = hook.output.trim();
+        if !output.is_empty() {
+            detail.push_str(":\n");
+            detail.push_str(output);
+        }
+    }
+    Err(LeviathanError::OperationFailed(format!(
+        "pre-commit checks failed:{}",
+        detail
+    )))
+}
+
+/// Get the parsed `.pre-commit-config.yaml`, or None when the repository has
+/// none
+#[command]
+pub async fn get_pre_commit_config(path: String) -> Result<Option<PreCommitConfig>> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git2::Repository::open(Path::new(&path))?;
+        match repo.workdir() {
+            Some(workdir) => load_config(workdir),
+            None => Ok(None),
+        }
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("pre-commit config task failed: {}", e)))?
+}
+
+/// Run the `.pre-commit-config.yaml` checks against the staged files
+///
+/// The hooks are separate processes that can run for a long time, so this
+/// runs off the async runtime.
+#[command]
+pub async fn run_pre_commit_checks(
+    path: String,
+    restage_fixed: Option<bool>,
+    hook_ids: Option<Vec<String>>,
+    all_files: Option<bool>,
+) -> Result<PreCommitRunResult> {
+    tokio::task::spawn_blocking(move || {
+        let repo = git2::Repository::open(Path::new(&path))?;
+        run_checks(
+            &repo,
+            restage_fixed.unwrap_or(false),
+            hook_ids.as_deref(),
+            all_files.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("pre-commit task failed: {}", e)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    const SAMPLE: &str = r#"
+# Checks for this repo
+default_stages: [pre-commit]
+exclude: '^vendor/'
+repos:
+  - repo: https://github.com/pre-commit/pre-commit-hooks
+    rev: v4.5.0
+    hooks:
+      - id: trailing-whitespace
+  - repo: local
+    hooks:
+      - id: no-todo
+        name: "No TODO markers"
+        entry: grep -n TODO
+        language: system
+        types: [text]
+        files: \.(rs|md)$
+      - id: black
+        name: black
+        entry: black
+        language: python
+        types_or:
+          - python
+          - pyi
+        args: ["--line-length", "100"]
+        pass_filenames: false
+"#;
+
+    #[test]
+    fn test_parse_config_reads_hooks_and_filters() {
+        let config = parse_config(SAMPLE).unwrap();
+        assert_eq!(config.exclude, "^vendor/");
+        assert_eq!(config.hooks.len(), 3);
+
+        let remote = &config.hooks[0];
+        assert_eq!(remote.id, "trailing-whitespace");
+        assert!(!remote.supported, "remote hooks need the framework");
+
+        let todo = &config.hooks[1];
+        assert_eq!(todo.name, "No TODO markers");
+        assert_eq!(todo.entry, "grep -n TODO");
+        assert_eq!(todo.files, r"\.(rs|md)$");
+        assert_eq!(todo.types, vec!["text"]);
+        assert_eq!(todo.stages, vec!["pre-commit"], "default_stages applies");
+        assert!(todo.supported);
+
+        let black = &config.hooks[2];
+        assert_eq!(black.types_or, vec!["python", "pyi"]);
+        assert_eq!(black.args, vec!["--line-length", "100"]);
+        assert!(!black.pass_filenames);
+        assert!(!black.supported, "python hooks need the framework");
+    }
+
+    #[test]
+    fn test_parse_config_defaults() {
+        let config = parse_config(
+            "repos:\n- repo: local\n  hooks:\n  - id: x\n    entry: true\n    language: system\n",
+        )
+        .unwrap();
+        let hook = &config.hooks[0];
+        assert_eq!(hook.name, "x");
+        assert_eq!(hook.exclude, "^$");
+        assert_eq!(hook.types, vec!["file"]);
+        assert!(hook.pass_filenames);
+        assert!(hook.stages.is_empty());
+    }
+
+    #[test]
+    fn test_parse_config_quoted_and_multiline_scalars() {
+        let config = parse_config(
+            r#"
+exclude: "^docs/ # not a comment"
+repos:
+- repo: local
+  hooks:
+  - id: quotes
+    name: 'Don''t commit TODOs' # trailing comment
+    entry: "sh -c 'grep -n \"TODO #\" \"$@\"' --"
+    language: system
+  - id: folded
+    name: Don't ship it # the apostrophe is part of a plain scalar
+    entry: >
+      sh -c
+      'exit 0'
+    language: system
+    args:
+      - "first
+        second"
+  - id: literal
+    entry: |
+      one
+      two
+    language: system
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.exclude, "^docs/ # not a comment");
+        assert_eq!(config.hooks[0].name, "Don't commit TODOs");
+        assert_eq!(config.hooks[0].entry, r#"sh -c 'grep -n "TODO #" "$@"' --"#);
+        assert_eq!(config.hooks[1].name, "Don't ship it");
+        assert_eq!(config.hooks[1].entry, "sh -c 'exit 0'\n");
+        assert_eq!(config.hooks[1].args, vec!["first second"]);
+        assert_eq!(config.hooks[2].entry, "one\ntwo\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_partially_staged_files_are_checked_as_staged() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_file(
+            CONFIG_FILE,
+            "repos:\n- repo: local\n  hooks:\n  - id: no-todo\n    entry: sh -c '! grep -n TODO \"$@\"' --\n    language: system\n    files: \\.txt$\n",
+        );
+
+        // Clean staged content, TODO only in the unstaged edit: passes, and
+        // the edit is still there afterwards.
+        repo.create_file("a.txt", "clean\n");
+        repo.stage_file("a.txt");
+        repo.create_file("a.txt", "clean\nTODO later\n");
+        let run = run_checks(&repo.repo(), false, None, false).unwrap();
+        assert!(run.success, "{:?}", run.hooks);
+        assert_eq!(
+            std::fs::read_to_string(repo.path.join("a.txt")).unwrap(),
+            "clean\nTODO later\n"
+        );
+
+        // TODO staged, unstaged edit removes it: still fails.
+        repo.create_file("a.txt", "TODO now\n");
+        repo.stage_file("a.txt");
+        repo.create_file("a.txt", "fixed\n");
+        let run = run_checks(&repo.repo(), false, None, false).unwrap();
+        assert!(!run.success);
+        assert_eq!(
+            std::fs::read_to_string(repo.path.join("a.txt")).unwrap(),
+            "fixed\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unsupported_pattern_skips_hook_with_reason() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_file(
+            CONFIG_FILE,
+            "repos:\n- repo: local\n  hooks:\n  - id: lookahead\n    entry: false\n    language: system\n    files: ^(?!vendor/).*\\.rs$\n  - id: plain\n    entry: 'true'\n    language: system\n",
+        );
+        repo.create_file("lib.rs", "fn main() {}\n");
+        repo.stage_file("lib.rs");
+
+        let run = run_checks(&repo.repo(), false, None, false).unwrap();
+        assert!(run.success, "a pattern we cannot compile must not block");
+        let skipped = &run.hooks[0];
+        assert_eq!(skipped.status, PreCommitHookStatus::Skipped);
+        let reason = skipped.skip_reason.as_deref().unwrap();
+        assert!(reason.contains("^(?!vendor/)"), "got: {reason}");
+        assert_eq!(run.hooks[1].status, PreCommitHookStatus::Passed);
+    }
+
+    #[test]
+    fn test_parse_config_reports_invalid_yaml() {
+        let err = parse_config("repos:\n  - repo: local\n   hooks: [\n").unwrap_err();
+        assert!(err.to_string().contains(CONFIG_FILE), "got: {err}");
+        assert!(parse_config("").unwrap().hooks.is_empty());
+    }
+
+    #[test]
+    fn test_split_entry_follows_shell_quoting() {
+        assert_eq!(
+            split_entry(r#"sh -c 'echo "hi there"' x\ y"#),
+            vec!["sh", "-c", "echo \"hi there\"", "x y"]
+        );
+        assert!(split_entry("   ").is_empty());
+    }
+
+    #[test]
+    fn test_batch_files_splits_long_lists() {
+        let files: Vec<String> = (0..5000).map(|i| format!("src/file_{:05}.rs", i)).collect();
+        let batches = batch_files(&files);
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(|b| b.len()).sum::<usize>(), files.len());
+        assert_eq!(batch_files(&[]).len(), 1, "one empty batch for no files");
+    }
+
+    #[test]
+    fn test_run_checks_without_config() {
+        let repo = TestRepo::with_initial_commit();
+        let run = run_checks(&repo.repo(), false, None, false).unwrap();
+        assert!(!run.config_found);
+        assert!(run.success);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_hook_filters_staged_files() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_file(
+            CONFIG_FILE,
+            "repos:\n  - repo: local\n    hooks:\n      - id: no-todo\n        name: no todo\n        entry: sh -c '! grep -n TODO \"$@\"' --\n        language: system\n        files: \\.rs$\n",
+        );
+        repo.create_file("lib.rs", "// TODO remove\n");
+        repo.create_file("notes.md", "TODO is fine here\n");
+        repo.stage_file("lib.rs");
+        repo.stage_file("notes.md");
+
+        let run = run_checks(&repo.repo(), false, None, false).unwrap();
+        assert!(run.config_found);
+        assert!(!run.success);
+        let hook = &run.hooks[0];
+        assert_eq!(hook.status, PreCommitHookStatus::Failed);
+        assert_eq!(hook.files_checked, 1, "only the .rs file matches");
+        assert!(hook.output.contains("TODO remove"), "got: {}", hook.output);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unsupported_hooks_are_skipped_with_reason() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_file(CONFIG_FILE, SAMPLE);
+        repo.create_file("README.md", "# Changed\n");
+        repo.stage_file("README.md");
+
+        let run = run_checks(&repo.repo(), false, None, false).unwrap();
+        let remote = run
+            .hooks
+            .iter()
+            .find(|h| h.id == "trailing-whitespace")
+            .unwrap();
+        assert_eq!(remote.status, PreCommitHookStatus::Skipped);
+        assert!(remote.skip_reason.as_deref().unwrap().contains("framework"));
+        let black = run.hooks.iter().find(|h| h.id == "black").unwrap();
+        assert_eq!(black.status, PreCommitHookStatus::Skipped);
+        assert!(black.skip_reason.as_deref().unwrap().contains("`python`"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fixer_fails_without_restage_and_passes_with_it() {
+        let repo = TestRepo::with_initial_commit();
+        // A fixer that appends a newline when missing and exits 1 if it fixed
+        // anything, like end-of-file-fixer.
+        repo.create_file(
+            "fix-eof.sh",
+            "#!/bin/sh\nrc=0\nfor f in \"$@\"; do\n  if [ -n \"$(tail -c1 \"$f\")\" ]; then echo >> \"$f\"; rc=1; fi\ndone\nexit $rc\n",
+        );
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(
+                repo.path.join("fix-eof.sh"),
+                std::fs::Permissions::from_mode(0o755),
+            )
+            .unwrap();
+        }
+        repo.create_file(
+            CONFIG_FILE,
+            "repos:\n- repo: local\n  hooks:\n  - id: eof\n    name: eof\n    entry: fix-eof.sh\n    language: script\n    types: [text]\n    exclude: ^fix-eof\\.sh$\n",
+        );
+        repo.create_file("a.txt", "no newline");
+        repo.stage_file("a.txt");
+
+        let run = run_checks(&repo.repo(), false, None, false).unwrap();
+        let hook = &run.hooks[0];
+        assert_eq!(hook.status, PreCommitHookStatus::Failed);
+        assert_eq!(hook.files_modified, vec!["a.txt"]);
+        assert!(hook.files_restaged.is_empty());
+
+        // Undo the fix so the restage run has something to do.
+        repo.create_file("a.txt", "no newline");
+        let git_repo = repo.repo();
+        let run = run_checks(&git_repo, true, None, false).unwrap();
+        let hook = &run.hooks[0];
+        assert_eq!(hook.status, PreCommitHookStatus::Passed, "{}", hook.output);
+        assert_eq!(hook.files_restaged, vec!["a.txt"]);
+
+        let index = git_repo.index().unwrap();
+        let entry = index.get_path(Path::new("a.txt"), 0).unwrap();
+        let blob = git_repo.find_blob(entry.id).unwrap();
+        assert_eq!(blob.content(), b"no newline\n", "the fix is staged");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fix_is_not_restaged_over_unstaged_edits() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_file(
+            CONFIG_FILE,
+            "repos:\n- repo: local\n  hooks:\n  - id: touch\n    name: touch\n    entry: sh -c 'for f; do echo fixed >> \"$f\"; done' --\n    language: system\n",
+        );
+        repo.create_file("b.txt", "staged\n");
+        repo.stage_file("b.txt");
+        repo.create_file("b.txt", "staged\nunstaged edit\n");
+
+        let run = run_checks(&repo.repo(), true, Some(&["touch".to_string()][..]), false).unwrap();
+        let hook = &run.hooks[0];
+        assert_eq!(hook.status, PreCommitHookStatus::Failed);
+        assert!(hook.files_restaged.is_empty());
+        assert!(hook.output.contains("unstaged changes"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_commit_is_blocked_by_failing_config_hook() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_file(
+            CONFIG_FILE,
+            "repos:\n- repo: local\n  hooks:\n  - id: never\n    name: never\n    entry: must not commit\n    language: fail\n    files: \\.txt$\n",
+        );
+        repo.create_file("c.txt", "x\n");
+        repo.stage_file("c.txt");
+
+        // The runner is opt-in.
+        assert!(run_checks_blocking(&repo.repo()).is_ok());
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_bool("leviathan.precommit", true)
+            .unwrap();
+
+        let err = crate::commands::commit::create_commit(
+            repo.path_str(),
+            "msg".to_string(),
+            None,
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("must not commit"), "got: {err}");
+
+        // The allow-empty path commits through the git CLI, which knows
+        // nothing about the config; it must be gated all the same.
+        let err = crate::commands::commit::create_commit(
+            repo.path_str(),
+            "msg".to_string(),
+            None,
+            Some(false),
+            Some(true),
+            None,
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("must not commit"), "got: {err}");
+
+        // Turning the runner off restores the old behaviour.
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_bool("leviathan.precommit", false)
+            .unwrap();
+        assert!(crate::commands::commit::create_commit(
+            repo.path_str(),
+            "msg".to_string(),
+            None,
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await
+        .is_ok());
+    }
+}
@@ -785,6 +785,11 @@ pub(crate) fn push_branch(
         }
     }
 
+    // Refuse to publish commits that add credentials. Both push routes
+    // below go through here; the scan covers only commits no
+    // remote-tracking ref already has.
+    crate::commands::secret_scan::check_push(&repo, &branch_name)?;
+
     // Give a branch that has no upstream one from the push that
     // published it, the way `git push -u` and every mainstream
     // client do on a first push.
@@ -1083,6 +1088,12 @@ fn push_single_remote(
         }
     }
 
+    // The same secret gate as push_branch.
+    {
+        let repo = git2::Repository::open(Path::new(path)).map_err(|e| e.message().to_string())?;
+        crate::commands::secret_scan::check_push(&repo, branch_name).map_err(|e| e.to_string())?;
+    }
+
     if force_with_lease || push_tags {
         push_via_cli(
             path,
@@ -614,47 +614,11 @@ pub async fn get_clone_filter_info(path: String) -> Result<CloneFilterInfo> {
             LeviathanError::RepositoryNotFound(format!("Failed to open repository: {}", e))
         })?;
 
-        let config = repo.config().map_err(|e| {
-            LeviathanError::Custom(format!("Failed to read repository config: {}", e))
-        })?;
-
-        // Check for remote.<name>.promisor = true and remote.<name>.partialclonefilter
         // Git stores partial clone info in the config as:
         //   remote.<name>.promisor = true
         //   remote.<name>.partialclonefilter = <filter-spec>
-        // Also check extensions.partialClone for the promisor remote name
-
-        let promisor_remote = config
-            .get_string("extensions.partialClone")
-            .ok()
-            .or_else(|| {
-                // Fall back to checking if origin is a promisor remote
-                config
-                    .get_bool("remote.origin.promisor")
-                    .ok()
-                    .and_then(|is_promisor| {
-                        if is_promisor {
-                            Some("origin".to_string())
-                        } else {
-                            None
-                        }
-                    })
-            });
-
-        let filter = if let Some(ref remote_name) = promisor_remote {
-            let key = format!("remote.{}.partialclonefilter", remote_name);
-            config.get_string(&key).ok()
-        } else {
-            None
-        };
-
-        let is_partial_clone = promisor_remote.is_some();
-
-        Ok(CloneFilterInfo {
-            is_partial_clone,
-            filter,
-            promisor_remote,
-        })
+        // plus extensions.partialClone naming the promisor remote.
+        Ok(crate::commands::partial_clone::filter_info(&repo))
     })
     .await
     .map_err(|e| LeviathanError::Custom(format!("Task failed: {}", e)))?
@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use std::process::Command;
 use tauri::command;
 
+use super::partial_clone;
 use crate::error::{LeviathanError, Result};
 
 /// A single search match within a file
@@ -289,6 +290,9 @@ pub async fn search_in_commits(
 ) -> Result<Vec<DiffSearchResult>> {
     let max_commits = max_commits.unwrap_or(100);
 
+    // Pickaxe reads both sides of every change it walks past.
+    partial_clone::prefetch_for_history_search(&path).await;
+
     let mut cmd = Command::new("git");
     cmd.arg("-C")
         .arg(&path)
@@ -448,6 +452,8 @@ pub async fn search_commits_by_content(
     let use_regex = regex.unwrap_or(false);
     let case_insensitive = ignore_case.unwrap_or(false);
 
+    partial_clone::prefetch_for_history_search(&path).await;
+
     let mut cmd = Command::new("git");
     cmd.arg("-C")
         .arg(&path)
@@ -0,0 +1,1573 @@
+//! Sending patch series by email
+//!
+//! The other half of the mailing-list workflow in
+//! [`patch_series`](super::patch_series): a prepared series goes out over
+//! SMTP as one thread, the way `git send-email` sends it. The first message
+//! (normally the cover letter) starts the thread, or replies to an earlier
+//! one, and every following patch replies to it through `In-Reply-To` and
+//! `References`.
+//!
+//! Server settings live in the same `sendemail.*` git config keys
+//! `git send-email` reads, so an existing setup carries over. The password
+//! is kept out of git config, in the system keyring through
+//! [`credentials_service`](crate::services::credentials_service).
+//!
+//! Recipients come from the To/Cc lists. Each patch can also copy the people
+//! named in its `Signed-off-by`/`Cc` trailers and the maintainers
+//! responsible for the files it touches, read from a kernel-style
+//! `MAINTAINERS` file. The cover letter copies everyone the patches do, so
+//! the whole thread reaches each of them.
+//!
+//! Building the messages is separate from sending them: a dry run returns
+//! the exact messages that would be sent, byte for byte, without touching
+//! the network. Dates and generated Message-IDs derive from the send time
+//! and the series itself, so passing a dry run's `timestamp` back to the
+//! send reproduces the previewed messages and their threading.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::TimeZone;
+use lettre::address::Envelope;
+use lettre::transport::smtp::authentication::{Credentials, Mechanism};
+use lettre::transport::smtp::client::{SmtpConnection, TlsParameters};
+use lettre::transport::smtp::extension::ClientId;
+use sha2::{Digest, Sha256};
+use tauri::command;
+
+use crate::error::{LeviathanError, Result};
+use crate::services::credentials_service;
+
+/// Connect, read and write timeout for the SMTP conversation
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest line a message may contain, without its line ending (RFC 5322)
+const MAX_MESSAGE_LINE: usize = 998;
+
+/// Headers the sender rewrites; everything else in a patch file is kept
+const REWRITTEN_HEADERS: &[&str] = &[
+    "from",
+    "to",
+    "cc",
+    "bcc",
+    "subject",
+    "date",
+    "message-id",
+    "in-reply-to",
+    "references",
+];
+
+/// How the SMTP connection is secured, as in `sendemail.smtpEncryption`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpEncryption {
+    /// Plain text; only sensible for a local relay
+    #[default]
+    None,
+    /// TLS from the first byte (SMTPS, port 465)
+    Ssl,
+    /// Upgrade a plain connection with STARTTLS (submission, port 587)
+    Tls,
+}
+
+impl SmtpEncryption {
+    fn from_config(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ssl" => Self::Ssl,
+            "tls" | "starttls" => Self::Tls,
+            _ => Self::None,
+        }
+    }
+
+    fn config_value(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Ssl => Some("ssl"),
+            Self::Tls => Some("tls"),
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            Self::None => 25,
+            Self::Ssl => 465,
+            Self::Tls => 587,
+        }
+    }
+}
+
+/// `sendemail.*` settings
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SendEmailConfig {
+    pub smtp_server: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_encryption: SmtpEncryption,
+    pub smtp_user: Option<String>,
+    /// Name given in `EHLO`
+    pub smtp_domain: Option<String>,
+    /// Sender; defaults to the committer identity
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    /// Whether a password is stored in the keyring for the configured
+    /// server and user (read-only)
+    pub has_password: bool,
+}
+
+impl SendEmailConfig {
+    fn port(&self) -> u16 {
+        self.smtp_port
+            .unwrap_or_else(|| self.smtp_encryption.default_port())
+    }
+}
+
+/// Options for sending a patch series
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendEmailOptions {
+    /// Patch files in sending order; the first one starts the thread
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub cc: Vec<String>,
+    /// Receives every message without appearing in any header
+    #[serde(default)]
+    pub bcc: Vec<String>,
+    /// Message-ID the series replies to, e.g. the previous version's cover
+    /// letter
+    #[serde(default)]
+    pub in_reply_to: Option<String>,
+    /// Copy the people in each patch's `Signed-off-by`/`Cc` trailers
+    /// (default true)
+    #[serde(default)]
+    pub cc_from_trailers: Option<bool>,
+    /// Copy the maintainers of the touched files (default false)
+    #[serde(default)]
+    pub cc_maintainers: Option<bool>,
+    /// Maintainers file, relative to the working tree (default
+    /// `MAINTAINERS`)
+    #[serde(default)]
+    pub maintainers_file: Option<String>,
+    /// Build the messages without sending them
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    /// Send time to build the messages for, in Unix seconds (default now).
+    /// The `timestamp` of a dry run sends exactly the previewed messages.
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    /// Password for this send only, instead of the stored one
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// One message of a series, exactly as it is (or would be) sent
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutgoingEmail {
+    pub file: String,
+    pub subject: String,
+    pub message_id: String,
+    pub in_reply_to: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub envelope_from: String,
+    /// Every address the message is delivered to, including Bcc
+    pub envelope_recipients: Vec<String>,
+    /// The complete message, headers and body
+    pub raw: String,
+}
+
+/// Result of sending a patch series
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendEmailResult {
+    pub dry_run: bool,
+    /// Send time the messages were built for, in Unix seconds
+    pub timestamp: i64,
+    pub messages: Vec<OutgoingEmail>,
+    pub sent: usize,
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+fn open_repo(path: &str) -> Result<git2::Repository> {
+    git2::Repository::open(path).map_err(|_| LeviathanError::RepositoryNotFound(path.to_string()))
+}
+
+fn config_values(config: &git2::Config, name: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    if let Ok(mut entries) = config.multivar(name, None) {
+        while let Some(Ok(entry)) = entries.next() {
+            if let Ok(value) = entry.value() {
+                values.push(value.to_string());
+            }
+        }
+    }
+    values
+}
+
+fn read_config(repo: &git2::Repository) -> Result<SendEmailConfig> {
+    let config = repo.config()?.snapshot()?;
+    let string = |name: &str| {
+        config
+            .get_string(name)
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+    };
+    let mut result = SendEmailConfig {
+        smtp_server: string("sendemail.smtpServer"),
+        smtp_port: config
+            .get_i32("sendemail.smtpServerPort")
+            .ok()
+            .and_then(|p| u16::try_from(p).ok()),
+        smtp_encryption: string("sendemail.smtpEncryption")
+            .map(|v| SmtpEncryption::from_config(&v))
+            .unwrap_or_default(),
+        smtp_user: string("sendemail.smtpUser"),
+        smtp_domain: string("sendemail.smtpDomain"),
+        from: string("sendemail.from"),
+        to: config_values(&config, "sendemail.to"),
+        cc: config_values(&config, "sendemail.cc"),
+        has_password: false,
+    };
+    if let (Some(server), Some(user)) = (&result.smtp_server, &result.smtp_user) {
+        result.has_password =
+            credentials_service::get_smtp_password(server, result.port(), user).is_some();
+    }
+    Ok(result)
+}
+
+/// Get the `sendemail.*` settings in effect for a repository
+#[command]
+pub async fn get_send_email_config(path: String) -> Result<SendEmailConfig> {
+    read_config(&open_repo(&path)?)
+}
+
+/// Save `sendemail.*` settings to the repository, or to the global config
+/// when `global` is set. Unset fields are removed.
+#[command]
+pub async fn set_send_email_config(
+    path: String,
+    config: SendEmailConfig,
+    global: Option<bool>,
+) -> Result<()> {
+    let repo = open_repo(&path)?;
+    let level = if global.unwrap_or(false) {
+        git2::ConfigLevel::Global
+    } else {
+        git2::ConfigLevel::Local
+    };
+    let mut target = repo.config()?.open_level(level)?;
+
+    let mut set = |name: &str, value: Option<String>| -> Result<()> {
+        match value.filter(|v| !v.trim().is_empty()) {
+            Some(value) => target.set_str(name, value.trim())?,
+            None => match target.remove(name) {
+                Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+                _ => {}
+            },
+        }
+        Ok(())
+    };
+    set("sendemail.smtpServer", config.smtp_server.clone())?;
+    set(
+        "sendemail.smtpServerPort",
+        config.smtp_port.map(|p| p.to_string()),
+    )?;
+    set(
+        "sendemail.smtpEncryption",
+        config.smtp_encryption.config_value().map(str::to_string),
+    )?;
+    set("sendemail.smtpUser", config.smtp_user.clone())?;
+    set("sendemail.smtpDomain", config.smtp_domain.clone())?;
+    set("sendemail.from", config.from.clone())?;
+
+    for (name, values) in [("sendemail.to", &config.to), ("sendemail.cc", &config.cc)] {
+        match target.remove_multivar(name, ".*") {
+            Err(e) if e.code() != git2::ErrorCode::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        for value in values.iter().filter(|v| !v.trim().is_empty()) {
+            // A pattern that matches no existing value appends a new one.
+            target.set_multivar(name, "^$", value.trim())?;
+        }
+    }
+    Ok(())
+}
+
+/// Store the SMTP password for a server and user in the system keyring
+#[command]
+pub async fn store_smtp_password(
+    server: String,
+    port: u16,
+    username: String,
+    password: String,
+) -> Result<()> {
+    credentials_service::store_smtp_password(&server, port, &username, &password)
+        .map_err(LeviathanError::OperationFailed)
+}
+
+/// Remove a stored SMTP password
+#[command]
+pub async fn delete_smtp_password(server: String, port: u16, username: String) -> Result<()> {
+    credentials_service::delete_smtp_password(&server, port, &username);
+    Ok(())
+}
+
+// ============================================================================
+// Addresses and headers
+// ============================================================================
+
+/// A mailbox: `Name <address>` or a bare address
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Mailbox {
+    name: Option<String>,
+    address: String,
+}
+
+impl Mailbox {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (name, address) = match (value.rfind('<'), value.rfind('>')) {
+            (Some(start), Some(end)) if start < end => {
+                let name = value[..start].trim().trim_matches('"').trim();
+                (
+                    (!name.is_empty()).then(|| name.replace("\\\"", "\"")),
+                    value[start + 1..end].trim(),
+                )
+            }
+            _ => (None, value),
+        };
+        // A line break in either part would end the header it is written
+        // into and start a new one.
+        let valid = address.contains('@')
+            && !address.starts_with('@')
+            && !address.ends_with('@')
+            && !address
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control() || c == '<' || c == '>')
+            && !name.as_deref().unwrap_or("").chars().any(char::is_control);
+        valid.then(|| Mailbox {
+            name,
+            address: address.to_string(),
+        })
+    }
+
+    fn key(&self) -> String {
+        self.address.to_ascii_lowercase()
+    }
+
+    /// Human-readable form, for previews
+    fn display(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} <{}>", name, self.address),
+            None => self.address.clone(),
+        }
+    }
+
+    /// RFC 5322 form for a header: non-ASCII names become an encoded word,
+    /// names with specials are quoted.
+    fn header(&self) -> String {
+        match &self.name {
+            None => self.address.clone(),
+            Some(name) if !name.is_ascii() => {
+                format!(
+                    "=?UTF-8?B?{}?= <{}>",
+                    BASE64.encode(name.as_bytes()),
+                    self.address
+                )
+            }
+            Some(name) if name.chars().any(|c| "()<>[]:;@\\,.\"".contains(c)) => {
+                format!(
+                    "\"{}\" <{}>",
+                    name.replace('\\', "\\\\").replace('"', "\\\""),
+                    self.address
+                )
+            }
+            Some(name) => format!("{} <{}>", name, self.address),
+        }
+    }
+}
+
+/// Split an address list on the commas that separate mailboxes
+fn split_addresses(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut angle = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '<' if !quoted => angle = true,
+            '>' if !quoted => angle = false,
+            ',' if !quoted && !angle => {
+                parts.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn parse_mailboxes(values: &[String]) -> Result<Vec<Mailbox>> {
+    let mut mailboxes = Vec::new();
+    for value in values {
+        for part in split_addresses(value) {
+            mailboxes.push(Mailbox::parse(&part).ok_or_else(|| {
+                LeviathanError::OperationFailed(format!("Invalid email address: {}", part))
+            })?);
+        }
+    }
+    Ok(mailboxes)
+}
+
+/// Decode RFC 2047 encoded words (`=?UTF-8?q?...?=`), as `git format-patch`
+/// writes non-ASCII author names. Words in other charsets are left as-is.
+fn decode_header(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    let mut last_was_word = false;
+    while let Some(start) = rest.find("=?") {
+        let decoded = decode_word(&rest[start + 2..]).map(|(text, len)| (text, start + 2 + len));
+        match decoded {
+            Some((text, end)) => {
+                // Whitespace between adjacent encoded words is not part of
+                // the text.
+                let between = &rest[..start];
+                if !(last_was_word && between.trim().is_empty()) {
+                    out.push_str(between);
+                }
+                out.push_str(&text);
+                rest = &rest[end..];
+                last_was_word = true;
+            }
+            None => {
+                out.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                last_was_word = false;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Decode one encoded word, given the text after its `=?`; returns the text
+/// and how much of the input it spanned. The text's end is only searched
+/// for after the encoding, since `?=` can also open Q-encoded text.
+fn decode_word(word: &str) -> Option<(String, usize)> {
+    let (charset, after) = word.split_once('?')?;
+    let (encoding, after) = after.split_once('?')?;
+    let len = after.find("?=")?;
+    let text = &after[..len];
+    let charset = charset.to_ascii_lowercase();
+    if charset != "utf-8" && charset != "us-ascii" {
+        return None;
+    }
+    let bytes = match encoding {
+        "B" | "b" => BASE64.decode(text).ok()?,
+        "Q" | "q" => decode_q(text)?,
+        _ => return None,
+    };
+    let spanned = charset.len() + encoding.len() + len + 4;
+    Some((String::from_utf8(bytes).ok()?, spanned))
+}
+
+fn decode_q(text: &str) -> Option<Vec<u8>> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => out.push(b' '),
+            b'=' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                out.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 2;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    Some(out)
+}
+
+/// A message file split into unfolded headers and body
+struct ParsedMail {
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl ParsedMail {
+    fn parse(content: &str) -> Self {
+        let content = content.replace("\r\n", "\n");
+        let mut lines = content.split_inclusive('\n').peekable();
+        // The mbox separator `git format-patch` starts each file with
+        if lines.peek().is_some_and(|l| l.starts_with("From ")) {
+            lines.next();
+        }
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in lines.by_ref() {
+            let line = line.trim_end_matches('\n');
+            if line.is_empty() {
+                break;
+            }
+            if line.starts_with(' ') || line.starts_with('\t') {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            } else if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        ParsedMail {
+            headers,
+            body: lines.collect(),
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn header_values(&self, name: &str) -> Vec<String> {
+        self.headers
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| decode_header(v))
+            .collect()
+    }
+
+    /// Whether this carries a diff; a cover letter doesn't
+    fn is_patch(&self) -> bool {
+        self.body.lines().any(|l| l.starts_with("diff --git "))
+    }
+
+    /// Paths on either side of each `diff --git` line
+    fn touched_files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        for line in self.body.lines() {
+            let Some(paths) = line.strip_prefix("diff --git a/") else {
+                continue;
+            };
+            if let Some(split) = paths.rfind(" b/") {
+                for file in [&paths[..split], &paths[split + 3..]] {
+                    if !files.iter().any(|f| f == file) {
+                        files.push(file.to_string());
+                    }
+                }
+            }
+        }
+        files
+    }
+
+    /// People named in `Signed-off-by:` and `Cc:` trailers of the commit
+    /// message (everything before the `---` separator)
+    fn trailer_addresses(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        for line in self.body.lines() {
+            if line == "---" {
+                break;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim();
+            if key.eq_ignore_ascii_case("signed-off-by") || key.eq_ignore_ascii_case("cc") {
+                found.push(value.trim().to_string());
+            }
+        }
+        found
+    }
+}
+
+fn bracketed(id: &str) -> String {
+    let id = id.trim().trim_start_matches('<').trim_end_matches('>');
+    format!("<{}>", id)
+}
+
+/// A Message-ID given by the caller, checked so it cannot break out of the
+/// header it is written into
+fn reply_id(id: &str) -> Result<String> {
+    let id = bracketed(id);
+    if id.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Invalid Message-ID: {}",
+            id.escape_debug()
+        )));
+    }
+    Ok(id)
+}
+
+/// Write an address header, one mailbox per folded line
+fn push_address_header(out: &mut String, name: &str, mailboxes: &[Mailbox]) {
+    if mailboxes.is_empty() {
+        return;
+    }
+    let list: Vec<String> = mailboxes.iter().map(Mailbox::header).collect();
+    out.push_str(&format!("{}: {}\n", name, list.join(",\n\t")));
+}
+
+// ============================================================================
+// Maintainers file
+// ============================================================================
+
+/// A section of a kernel-style `MAINTAINERS` file
+#[derive(Debug, Default)]
+struct MaintainerSection {
+    /// `M:` maintainers, `R:` reviewers and `L:` lists
+    contacts: Vec<String>,
+    /// `F:` patterns
+    files: Vec<String>,
+    /// `X:` patterns
+    excludes: Vec<String>,
+}
+
+fn parse_maintainers(content: &str) -> Vec<MaintainerSection> {
+    let mut sections = Vec::new();
+    let mut current = MaintainerSection::default();
+    for line in content.lines() {
+        let field = line
+            .split_once(':')
+            .filter(|(tag, _)| tag.len() == 1 && tag.chars().all(|c| c.is_ascii_uppercase()));
+        match field {
+            Some((tag, value)) => {
+                let value = value.trim();
+                match tag {
+                    "M" | "R" => current.contacts.push(value.to_string()),
+                    // Lists carry notes like "(moderated for non-subscribers)"
+                    "L" => current.contacts.push(
+                        value
+                            .split_whitespace()
+                            .next()
+                            .unwrap_or_default()
+                            .to_string(),
+                    ),
+                    "F" => current.files.push(value.to_string()),
+                    "X" => current.excludes.push(value.to_string()),
+                    _ => {}
+                }
+            }
+            // A title or blank line starts the next section.
+            None => {
+                if !current.files.is_empty() {
+                    sections.push(std::mem::take(&mut current));
+                } else {
+                    current = MaintainerSection::default();
+                }
+            }
+        }
+    }
+    if !current.files.is_empty() {
+        sections.push(current);
+    }
+    sections
+}
+
+/// `F:`/`X:` matching: a trailing slash (or a bare directory name) covers
+/// everything below it; wildcards match within one path level.
+fn maintainer_pattern_matches(pattern: &str, file: &str) -> bool {
+    if let Some(dir) = pattern.strip_suffix('/') {
+        return file.starts_with(&format!("{}/", dir));
+    }
+    if pattern.contains(['*', '?', '[']) {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        return glob::Pattern::new(pattern)
+            .map(|p| p.matches_with(file, options))
+            .unwrap_or(false);
+    }
+    file == pattern || file.starts_with(&format!("{}/", pattern))
+}
+
+fn maintainers_for(sections: &[MaintainerSection], files: &[String]) -> Vec<String> {
+    let mut contacts = Vec::new();
+    for section in sections {
+        let covers = files.iter().any(|file| {
+            section
+                .files
+                .iter()
+                .any(|p| maintainer_pattern_matches(p, file))
+                && !section
+                    .excludes
+                    .iter()
+                    .any(|p| maintainer_pattern_matches(p, file))
+        });
+        if covers {
+            contacts.extend(section.contacts.iter().cloned());
+        }
+    }
+    contacts
+}
+
+// ============================================================================
+// Building the series
+// ============================================================================
+
+/// The sender: `sendemail.from`, or the committer identity
+fn sender(repo: &git2::Repository, config: &SendEmailConfig) -> Result<Mailbox> {
+    if let Some(from) = &config.from {
+        return Mailbox::parse(from).ok_or_else(|| {
+            LeviathanError::OperationFailed(format!("Invalid sendemail.from: {}", from))
+        });
+    }
+    let signature = repo.signature().map_err(|_| {
+        LeviathanError::OperationFailed(
+            "No sender configured: set sendemail.from or user.name and user.email".to_string(),
+        )
+    })?;
+    Mailbox::parse(&format!(
+        "{} <{}>",
+        signature.name().unwrap_or_default(),
+        signature.email().unwrap_or_default()
+    ))
+    .ok_or_else(|| LeviathanError::OperationFailed("Invalid user.email".to_string()))
+}
+
+/// Accumulates one header's recipients, dropping duplicates
+struct RecipientList<'a> {
+    seen: &'a mut HashSet<String>,
+    list: Vec<Mailbox>,
+}
+
+impl RecipientList<'_> {
+    fn extend(&mut self, mailboxes: impl IntoIterator<Item = Mailbox>) {
+        for mailbox in mailboxes {
+            if self.seen.insert(mailbox.key()) {
+                self.list.push(mailbox);
+            }
+        }
+    }
+}
+
+fn build_messages(
+    repo: &git2::Repository,
+    config: &SendEmailConfig,
+    options: &SendEmailOptions,
+    timestamp: i64,
+) -> Result<Vec<OutgoingEmail>> {
+    if options.files.is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "No patches to send".to_string(),
+        ));
+    }
+    let sender = sender(repo, config)?;
+    let domain = sender
+        .address
+        .rsplit('@')
+        .next()
+        .unwrap_or("localhost")
+        .to_string();
+
+    let to_all = parse_mailboxes(&[options.to.clone(), config.to.clone()].concat())?;
+    let cc_all = parse_mailboxes(&[options.cc.clone(), config.cc.clone()].concat())?;
+    let bcc = parse_mailboxes(&options.bcc)?;
+
+    let maintainers = if options.cc_maintainers.unwrap_or(false) {
+        let workdir = repo.workdir().ok_or_else(|| {
+            LeviathanError::OperationFailed("Maintainers lookup needs a working tree".to_string())
+        })?;
+        let file = options.maintainers_file.as_deref().unwrap_or("MAINTAINERS");
+        let content = std::fs::read_to_string(workdir.join(file))
+            .map_err(|e| LeviathanError::OperationFailed(format!("Cannot read {}: {}", file, e)))?;
+        parse_maintainers(&content)
+    } else {
+        Vec::new()
+    };
+
+    // Generated Message-IDs are unique to this sender, send time and
+    // series content, and the same each time the series is built from them.
+    let mut seed = Sha256::new();
+    seed.update(format!("{}\0{}\0", sender.address, timestamp).as_bytes());
+    let mut mails = Vec::new();
+    for file in &options.files {
+        let content = std::fs::read_to_string(file)
+            .map_err(|e| LeviathanError::OperationFailed(format!("Cannot read {}: {}", file, e)))?;
+        seed.update(content.as_bytes());
+        seed.update([0u8]);
+        mails.push(ParsedMail::parse(&content));
+    }
+    let seed: String = seed.finalize()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+
+    // People a patch itself calls for: its trailers and its files'
+    // maintainers. Addresses that don't parse (a trailer naming someone
+    // without an email) are simply not copied.
+    let derived: Vec<Vec<Mailbox>> = mails
+        .iter()
+        .map(|mail| {
+            let mut found = Vec::new();
+            if options.cc_from_trailers.unwrap_or(true) {
+                found.extend(mail.trailer_addresses());
+            }
+            found.extend(maintainers_for(&maintainers, &mail.touched_files()));
+            found
+                .iter()
+                .flat_map(|v| split_addresses(v))
+                .filter_map(|v| Mailbox::parse(&v))
+                .collect()
+        })
+        .collect();
+
+    let reply_to = options
+        .in_reply_to
+        .as_deref()
+        .filter(|id| !id.trim().is_empty())
+        .map(reply_id)
+        .transpose()?;
+    let now = chrono::Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .ok_or_else(|| {
+            LeviathanError::OperationFailed(format!("Invalid timestamp: {}", timestamp))
+        })?;
+    let mut thread_root: Option<String> = None;
+    let mut messages = Vec::new();
+
+    for (index, (mail, file)) in mails.iter().zip(&options.files).enumerate() {
+        let message_id = mail
+            .header("Message-Id")
+            .map(bracketed)
+            .unwrap_or_else(|| format!("<{}.{}-{}@{}>", timestamp, seed, index + 1, domain));
+        let (in_reply_to, references) = match &thread_root {
+            None => (reply_to.clone(), reply_to.iter().cloned().collect()),
+            Some(root) => (
+                Some(root.clone()),
+                reply_to
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::once(root.clone()))
+                    .collect::<Vec<_>>(),
+            ),
+        };
+
+        let mut seen = HashSet::new();
+        let mut to = RecipientList {
+            seen: &mut seen,
+            list: Vec::new(),
+        };
+        to.extend(to_all.iter().cloned());
+        to.extend(parse_mailboxes(&mail.header_values("To"))?);
+        let to = to.list;
+        // The sender doesn't copy themselves, but can still address
+        // themselves explicitly.
+        seen.insert(sender.key());
+        let mut cc = RecipientList {
+            seen: &mut seen,
+            list: Vec::new(),
+        };
+        cc.extend(cc_all.iter().cloned());
+        cc.extend(parse_mailboxes(&mail.header_values("Cc"))?);
+        if mail.is_patch() {
+            cc.extend(derived[index].iter().cloned());
+        } else {
+            // A cover letter reaches everyone its patches do.
+            cc.extend(derived.iter().flatten().cloned());
+        }
+        let cc = cc.list;
+
+        let mut envelope: Vec<String> = Vec::new();
+        for mailbox in to.iter().chain(&cc).chain(&bcc) {
+            if !envelope
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(&mailbox.address))
+            {
+                envelope.push(mailbox.address.clone());
+            }
+        }
+        if envelope.is_empty() {
+            return Err(LeviathanError::OperationFailed(format!(
+                "No recipients for {}",
+                file
+            )));
+        }
+
+        // Sending someone else's patch: the mail comes from the sender, and
+        // an in-body From line keeps the authorship for `git am`.
+        let author = mail
+            .header("From")
+            .map(decode_header)
+            .and_then(|a| Mailbox::parse(&a));
+        let mut body = mail.body.clone();
+        if let Some(author) = author.filter(|a| a != &sender) {
+            body = format!("From: {}\n\n{}", author.display(), body);
+        }
+
+        let subject_raw = mail.header("Subject").unwrap_or("").to_string();
+        let mut raw = String::new();
+        raw.push_str(&format!("From: {}\n", sender.header()));
+        push_address_header(&mut raw, "To", &to);
+        push_address_header(&mut raw, "Cc", &cc);
+        raw.push_str(&format!("Subject: {}\n", subject_raw));
+        // One second apart, so mail clients sort the series in order
+        let date = now + chrono::Duration::seconds(index as i64);
+        raw.push_str(&format!("Date: {}\n", date.to_rfc2822()));
+        raw.push_str(&format!("Message-ID: {}\n", message_id));
+        if let Some(parent) = &in_reply_to {
+            raw.push_str(&format!("In-Reply-To: {}\n", parent));
+            raw.push_str(&format!("References: {}\n", references.join(" ")));
+        }
+        for (name, value) in &mail.headers {
+            if !REWRITTEN_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                raw.push_str(&format!("{}: {}\n", name, value));
+            }
+        }
+        if !body.is_ascii() && mail.header("Content-Type").is_none() {
+            raw.push_str("MIME-Version: 1.0\n");
+            raw.push_str("Content-Type: text/plain; charset=UTF-8\n");
+            raw.push_str("Content-Transfer-Encoding: 8bit\n");
+        }
+        raw.push('\n');
+        raw.push_str(&body);
+
+        check_line_lengths(file, &raw)?;
+
+        if thread_root.is_none() {
+            thread_root = Some(message_id.clone());
+        }
+        messages.push(OutgoingEmail {
+            file: file.clone(),
+            subject: decode_header(&subject_raw),
+            message_id,
+            in_reply_to,
+            from: sender.display(),
+            to: to.iter().map(Mailbox::display).collect(),
+            cc: cc.iter().map(Mailbox::display).collect(),
+            envelope_from: sender.address.clone(),
+            envelope_recipients: envelope,
+            raw,
+        });
+    }
+    Ok(messages)
+}
+
+/// Refuse a message with a line longer than mail allows. Servers truncate
+/// or bounce such lines, and a patch that arrives cut short no longer
+/// applies.
+fn check_line_lengths(file: &str, raw: &str) -> Result<()> {
+    match raw.lines().position(|l| l.len() > MAX_MESSAGE_LINE) {
+        Some(index) => Err(LeviathanError::OperationFailed(format!(
+            "{}: line {} is longer than {} bytes, which email cannot carry",
+            file,
+            index + 1,
+            MAX_MESSAGE_LINE
+        ))),
+        None => Ok(()),
+    }
+}
+
+// ============================================================================
+// SMTP
+// ============================================================================
+
+fn smtp_error(message: impl std::fmt::Display) -> LeviathanError {
+    LeviathanError::OperationFailed(format!("SMTP: {}", message))
+}
+
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<std::net::IpAddr>()
+            .map(|ip| ip.is_loopback())
+            .unwrap_or(false)
+}
+
+/// Open one session for the whole series: connect, secure it as
+/// configured and log in. STARTTLS is required, not opportunistic — a
+/// server that doesn't offer it ends the session before anything is sent.
+fn connect(
+    config: &SendEmailConfig,
+    server: &str,
+    password: Option<&str>,
+) -> Result<SmtpConnection> {
+    let port = config.port();
+    let hello = ClientId::Domain(
+        config
+            .smtp_domain
+            .clone()
+            .unwrap_or_else(|| "localhost.localdomain".to_string()),
+    );
+    let tls = || TlsParameters::new(server.to_string()).map_err(smtp_error);
+    let wrapper = match config.smtp_encryption {
+        SmtpEncryption::Ssl => Some(tls()?),
+        _ => None,
+    };
+    let mut connection = SmtpConnection::connect(
+        (server, port),
+        Some(SMTP_TIMEOUT),
+        &hello,
+        wrapper.as_ref(),
+        None,
+    )
+    .map_err(|e| smtp_error(format!("Cannot connect to {}:{}: {}", server, port, e)))?;
+
+    if config.smtp_encryption == SmtpEncryption::Tls {
+        connection
+            .starttls(&tls()?, &hello)
+            .map_err(|e| smtp_error(format!("STARTTLS with {} failed: {}", server, e)))?;
+    }
+
+    if let Some(user) = &config.smtp_user {
+        let password = password
+            .ok_or_else(|| smtp_error(format!("No password stored for {} on {}", user, server)))?;
+        connection
+            .auth(
+                &[Mechanism::Plain, Mechanism::Login],
+                &Credentials::new(user.clone(), password.to_string()),
+            )
+            .map_err(|e| smtp_error(format!("Authentication failed: {}", e)))?;
+    }
+    Ok(connection)
+}
+
+/// Send one message. lettre dot-stuffs the data, and asks for
+/// `BODY=8BITMIME` only when the message isn't plain ASCII — refusing it
+/// outright when the server doesn't offer the extension.
+fn send(connection: &mut SmtpConnection, message: &OutgoingEmail) -> Result<()> {
+    check_line_lengths(&message.file, &message.raw)?;
+    let address = |value: &str| {
+        value
+            .parse::<lettre::Address>()
+            .map_err(|e| smtp_error(format!("Invalid address {}: {}", value, e)))
+    };
+    let recipients = message
+        .envelope_recipients
+        .iter()
+        .map(|r| address(r))
+        .collect::<Result<Vec<_>>>()?;
+    let envelope =
+        Envelope::new(Some(address(&message.envelope_from)?), recipients).map_err(smtp_error)?;
+    let data = message.raw.lines().collect::<Vec<_>>().join("\r\n");
+    connection
+        .send(&envelope, data.as_bytes())
+        .map_err(smtp_error)?;
+    Ok(())
+}
+
+fn deliver(
+    config: &SendEmailConfig,
+    password: Option<&str>,
+    messages: &[OutgoingEmail],
+) -> Result<()> {
+    let server = config.smtp_server.as_deref().ok_or_else(|| {
+        LeviathanError::OperationFailed(
+            "No SMTP server configured (sendemail.smtpServer)".to_string(),
+        )
+    })?;
+    let mut connection = connect(config, server, password)?;
+    for (index, message) in messages.iter().enumerate() {
+        send(&mut connection, message).map_err(|e| {
+            LeviathanError::OperationFailed(format!(
+                "Sent {} of {} messages; {} failed: {}",
+                index,
+                messages.len(),
+                message.file,
+                e
+            ))
+        })?;
+    }
+    let _ = connection.quit();
+    Ok(())
+}
+
+/// Send a patch series over SMTP as one thread
+///
+/// With `dry_run`, returns the messages exactly as they would be sent
+/// without connecting to the server. Passing the result's `timestamp` to the
+/// send then sends those same messages.
+#[command]
+pub async fn send_patch_series(path: String, options: SendEmailOptions) -> Result<SendEmailResult> {
+    let repo = open_repo(&path)?;
+    let config = read_config(&repo)?;
+    let timestamp = options
+        .timestamp
+        .unwrap_or_else(|| chrono::Local::now().timestamp());
+    let messages = build_messages(&repo, &config, &options, timestamp)?;
+    drop(repo);
+
+    if options.dry_run.unwrap_or(false) {
+        return Ok(SendEmailResult {
+            dry_run: true,
+            timestamp,
+            messages,
+            sent: 0,
+        });
+    }
+
+    let server = config.smtp_server.clone().ok_or_else(|| {
+        LeviathanError::OperationFailed(
+            "No SMTP server configured (sendemail.smtpServer)".to_string(),
+        )
+    })?;
+    // Credentials never cross the wire in the clear, except to a relay on
+    // this machine.
+    if config.smtp_user.is_some()
+        && config.smtp_encryption == SmtpEncryption::None
+        && !is_loopback(&server)
+    {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Refusing to send the SMTP password to {} unencrypted; set sendemail.smtpEncryption to ssl or tls",
+            server
+        )));
+    }
+    let password = match (&options.password, &config.smtp_user) {
+        (Some(password), _) => Some(password.clone()),
+        (None, Some(user)) => credentials_service::get_smtp_password(&server, config.port(), user),
+        (None, None) => None,
+    };
+
+    let messages = tokio::task::spawn_blocking(move || {
+        deliver(&config, password.as_deref(), &messages).map(|_| messages)
+    })
+    .await
+    .map_err(|e| LeviathanError::OperationFailed(format!("Send task failed: {}", e)))??;
+
+    let sent = messages.len();
+    Ok(SendEmailResult {
+        dry_run: false,
+        timestamp,
+        messages,
+        sent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::patch_series::{format_patch_series, PatchSeriesOptions};
+    use crate::test_utils::TestRepo;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// A mail as the sink received it
+    #[derive(Debug, Clone)]
+    struct Received {
+        auth: Option<String>,
+        from: String,
+        body_8bit: bool,
+        recipients: Vec<String>,
+        data: String,
+    }
+
+    /// A minimal SMTP server on a loopback port that accepts everything and
+    /// records what it gets, for one connection. `eight_bit` says whether it
+    /// offers 8BITMIME.
+    fn smtp_sink(eight_bit: bool) -> (u16, Arc<Mutex<Vec<Received>>>, std::thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let store = received.clone();
+        let handle = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut reply = |text: &str| writer.write_all(text.as_bytes()).unwrap();
+            reply("220 sink ESMTP\r\n");
+            let mut auth = None;
+            let mut current: Option<Received> = None;
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                let command = line.trim_end().to_string();
+                line.clear();
+                let upper = command.to_ascii_uppercase();
+                if upper.starts_with("EHLO") {
+                    if eight_bit {
+                        reply("250-sink\r\n250-8BITMIME\r\n250 AUTH PLAIN LOGIN\r\n");
+                    } else {
+                        reply("250-sink\r\n250 AUTH PLAIN LOGIN\r\n");
+                    }
+                } else if upper.starts_with("AUTH PLAIN ") {
+                    auth = Some(command[11..].to_string());
+                    reply("235 ok\r\n");
+                } else if let Some(from) = command.strip_prefix("MAIL FROM:") {
+                    current = Some(Received {
+                        auth: auth.clone(),
+                        from: from.split_whitespace().next().unwrap().to_string(),
+                        body_8bit: from.contains("BODY=8BITMIME"),
+                        recipients: Vec::new(),
+                        data: String::new(),
+                    });
+                    reply("250 ok\r\n");
+                } else if let Some(to) = command.strip_prefix("RCPT TO:") {
+                    current.as_mut().unwrap().recipients.push(to.to_string());
+                    reply("250 ok\r\n");
+                } else if upper == "DATA" {
+                    reply("354 go\r\n");
+                    let mut data = String::new();
+                    loop {
+                        let mut l = String::new();
+                        reader.read_line(&mut l).unwrap();
+                        if l == ".\r\n" {
+                            break;
+                        }
+                        data.push_str(&l);
+                    }
+                    let mut mail = current.take().unwrap();
+                    mail.data = data;
+                    store.lock().unwrap().push(mail);
+                    reply("250 queued\r\n");
+                } else if upper == "QUIT" {
+                    reply("221 bye\r\n");
+                    break;
+                } else {
+                    reply("502 unknown\r\n");
+                }
+            }
+        });
+        (port, received, handle)
+    }
+
+    /// A two-patch series with a cover letter; the first patch is signed
+    /// off by a co-author and touches `net/`.
+    async fn series(repo: &TestRepo) -> Vec<String> {
+        let base = repo.head_oid().to_string();
+        repo.create_commit(
+            "Add socket\n\nSigned-off-by: Co Author <co@example.org>",
+            &[("net/socket.c", "int s;\n")],
+        );
+        repo.create_commit(
+            "Add docs\n\n.dotfiles are covered too",
+            &[("docs/net.md", "# Net\n")],
+        );
+        let result = format_patch_series(
+            repo.path_str(),
+            PatchSeriesOptions {
+                base,
+                output_dir: repo.path.join("out").to_string_lossy().to_string(),
+                cover_subject: Some("Networking".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        std::iter::once(result.cover_letter.unwrap())
+            .chain(result.patches)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_threads_and_collects_recipients() {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_commit(
+            "Add maintainers",
+            &[(
+                "MAINTAINERS",
+                "NETWORKING\nM:\tNet Maintainer <net@example.org>\nL:\tnetdev@example.org (open list)\nF:\tnet/\n\nDOCS\nM:\tDoc Person <docs@example.org>\nF:\tdocs/*.md\nX:\tdocs/internal/\n",
+            )],
+        );
+        let files = series(&repo).await;
+
+        let result = send_patch_series(
+            repo.path_str(),
+            SendEmailOptions {
+                files,
+                to: vec!["list@example.org".to_string()],
+                cc_maintainers: Some(true),
+                in_reply_to: Some("v1-cover@example.org".to_string()),
+                dry_run: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(result.dry_run);
+        assert_eq!(result.sent, 0);
+        let [cover, first, second] = &result.messages[..] else {
+            panic!("expected three messages");
+        };
+        assert_eq!(cover.subject, "[PATCH 0/2] Networking");
+        assert_eq!(cover.in_reply_to.as_deref(), Some("<v1-cover@example.org>"));
+        for patch in [first, second] {
+            assert_eq!(patch.in_reply_to.as_ref(), Some(&cover.message_id));
+            assert!(patch.raw.contains(&format!(
+                "References: <v1-cover@example.org> {}\n",
+                cover.message_id
+            )));
+        }
+
+        assert_eq!(first.to, vec!["list@example.org"]);
+        assert_eq!(
+            first.cc,
+            vec![
+                "Co Author <co@example.org>",
+                "Net Maintainer <net@example.org>",
+                "netdev@example.org"
+            ]
+        );
+        assert_eq!(second.cc, vec!["Doc Person <docs@example.org>"]);
+        // The cover letter copies everyone the patches do.
+        assert_eq!(cover.cc.len(), 4);
+        // The sender isn't copied on their own Signed-off-by.
+        assert!(!cover.cc.iter().any(|c| c.contains("test@example.com")));
+        assert!(cover
+            .raw
+            .starts_with("From: Test User <test@example.com>\n"));
+    }
+
+    #[tokio::test]
+    async fn test_sends_series_to_smtp_sink() {
+        let repo = TestRepo::with_initial_commit();
+        let files = series(&repo).await;
+        let (port, received, handle) = smtp_sink(true);
+        {
+            let git = repo.repo();
+            let mut config = git.config().unwrap();
+            config.set_str("sendemail.smtpServer", "127.0.0.1").unwrap();
+            config
+                .set_i32("sendemail.smtpServerPort", port as i32)
+                .unwrap();
+            config.set_str("sendemail.smtpUser", "sender").unwrap();
+        }
+        let options = SendEmailOptions {
+            files,
+            to: vec!["list@example.org".to_string()],
+            bcc: vec!["archive@example.org".to_string()],
+            password: Some("secret".to_string()),
+            ..Default::default()
+        };
+
+        let preview = send_patch_series(
+            repo.path_str(),
+            SendEmailOptions {
+                dry_run: Some(true),
+                timestamp: Some(1_700_000_000),
+                ..options.clone()
+            },
+        )
+        .await
+        .unwrap();
+        let result = send_patch_series(
+            repo.path_str(),
+            SendEmailOptions {
+                timestamp: Some(preview.timestamp),
+                ..options
+            },
+        )
+        .await
+        .unwrap();
+        handle.join().unwrap();
+
+        assert_eq!(result.sent, 3);
+        let received = received.lock().unwrap();
+        // What went out is what was previewed, threading included.
+        for (sent, previewed) in received.iter().zip(&preview.messages) {
+            assert_eq!(
+                sent.data.replace("\r\n..", "\r\n."),
+                previewed.raw.replace('\n', "\r\n")
+            );
+        }
+        assert_eq!(received.len(), 3);
+        assert_eq!(
+            received[0].auth.as_deref(),
+            Some(BASE64.encode("\0sender\0secret").as_str())
+        );
+        assert_eq!(received[0].from, "<test@example.com>");
+        // Plain ASCII mail doesn't ask for 8BITMIME.
+        assert!(received.iter().all(|r| !r.body_8bit));
+        assert!(received[1]
+            .recipients
+            .contains(&"<archive@example.org>".to_string()));
+        assert!(!received[1].data.contains("archive@example.org"));
+        assert!(received[1]
+            .data
+            .contains("Cc: Co Author <co@example.org>\r\n"));
+        // Dot-stuffed on the wire
+        assert!(received[2]
+            .data
+            .contains("\r\n..dotfiles are covered too\r\n"));
+        assert!(received[2].data.contains(&format!(
+            "In-Reply-To: {}\r\n",
+            result.messages[0].message_id
+        )));
+    }
+
+    /// Send one patch with non-ASCII content to a sink.
+    async fn send_8bit_patch(eight_bit: bool) -> (Result<SendEmailResult>, Vec<Received>) {
+        let repo = TestRepo::with_initial_commit();
+        let base = repo.head_oid().to_string();
+        repo.create_commit("Add menu", &[("menu.txt", "Café crème\n")]);
+        let files = format_patch_series(
+            repo.path_str(),
+            PatchSeriesOptions {
+                base,
+                output_dir: repo.path.join("out").to_string_lossy().to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .patches;
+        let (port, received, handle) = smtp_sink(eight_bit);
+        {
+            let git = repo.repo();
+            let mut config = git.config().unwrap();
+            config.set_str("sendemail.smtpServer", "127.0.0.1").unwrap();
+            config
+                .set_i32("sendemail.smtpServerPort", port as i32)
+                .unwrap();
+        }
+        let result = send_patch_series(
+            repo.path_str(),
+            SendEmailOptions {
+                files,
+                to: vec!["list@example.org".to_string()],
+                ..Default::default()
+            },
+        )
+        .await;
+        handle.join().unwrap();
+        let received = received.lock().unwrap().clone();
+        (result, received)
+    }
+
+    #[tokio::test]
+    async fn test_8bit_body_needs_8bitmime() {
+        let (result, received) = send_8bit_patch(true).await;
+        assert_eq!(result.unwrap().sent, 1);
+        assert!(received[0].body_8bit);
+        assert!(received[0].data.contains("Café crème"));
+
+        // Without 8BITMIME the message is refused, not sent anyway.
+        let (result, received) = send_8bit_patch(false).await;
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("8BITMIME"), "{err}");
+        assert!(received.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_refuses_lines_too_long_for_email() {
+        let repo = TestRepo::with_initial_commit();
+        let file = repo.path.join("long.patch");
+        std::fs::write(
+            &file,
+            format!(
+                "From: Test User <test@example.com>\nSubject: [PATCH] Long\n\n{}\n",
+                "x".repeat(MAX_MESSAGE_LINE + 1)
+            ),
+        )
+        .unwrap();
+        let result = send_patch_series(
+            repo.path_str(),
+            SendEmailOptions {
+                files: vec![file.to_string_lossy().to_string()],
+                to: vec!["list@example.org".to_string()],
+                dry_run: Some(true),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("line 7 is longer than 998 bytes"));
+    }
+
+    #[tokio::test]
+    async fn test_refuses_plaintext_password_to_remote_server() {
+        let repo = TestRepo::with_initial_commit();
+        let files = series(&repo).await;
+        {
+            let git = repo.repo();
+            let mut config = git.config().unwrap();
+            config
+                .set_str("sendemail.smtpServer", "smtp.example.org")
+                .unwrap();
+            config.set_str("sendemail.smtpUser", "sender").unwrap();
+        }
+        let result = send_patch_series(
+            repo.path_str(),
+            SendEmailOptions {
+                files,
+                to: vec!["list@example.org".to_string()],
+                password: Some("secret".to_string()),
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("unencrypted"));
+    }
+
+    #[tokio::test]
+    async fn test_config_round_trip() {
+        let repo = TestRepo::new();
+        let config = SendEmailConfig {
+            smtp_server: Some("smtp.example.org".to_string()),
+            smtp_encryption: SmtpEncryption::Tls,
+            smtp_user: Some("me".to_string()),
+            to: vec!["a@example.org".to_string(), "b@example.org".to_string()],
+            ..Default::default()
+        };
+        set_send_email_config(repo.path_str(), config, None)
+            .await
+            .unwrap();
+
+        let read = get_send_email_config(repo.path_str()).await.unwrap();
+        assert_eq!(read.smtp_server.as_deref(), Some("smtp.example.org"));
+        assert_eq!(read.smtp_encryption, SmtpEncryption::Tls);
+        assert_eq!(read.port(), 587);
+        assert_eq!(read.to, vec!["a@example.org", "b@example.org"]);
+
+        set_send_email_config(repo.path_str(), SendEmailConfig::default(), None)
+            .await
+            .unwrap();
+        let read = get_send_email_config(repo.path_str()).await.unwrap();
+        assert!(read.smtp_server.is_none());
+        assert!(read.to.is_empty());
+    }
+
+    #[test]
+    fn test_address_handling() {
+        assert_eq!(
+            split_addresses("\"Doe, Jane\" <jane@x.org>, bob@x.org"),
+            vec!["\"Doe, Jane\" <jane@x.org>", "bob@x.org"]
+        );
+        let jane = Mailbox::parse("\"Doe, Jane\" <jane@x.org>").unwrap();
+        assert_eq!(jane.header(), "\"Doe, Jane\" <jane@x.org>");
+        assert_eq!(
+            Mailbox::parse("Ünï <u@x.org>").unwrap().header(),
+            "=?UTF-8?B?w5xuw68=?= <u@x.org>"
+        );
+        assert!(Mailbox::parse("Just A Name").is_none());
+        assert!(Mailbox::parse("Eve\r\nBcc: x@evil.org <eve@x.org>").is_none());
+        assert!(Mailbox::parse("eve@x.org\u{0}").is_none());
+        assert!(reply_id("<a@x.org>\r\nBcc: x@evil.org").is_err());
+        assert_eq!(
+            decode_header("=?UTF-8?q?=C3=9Cn=C3=AF=20Name?= <me@x.org>"),
+            "Ünï Name <me@x.org>"
+        );
+    }
+
+    #[test]
+    fn test_maintainer_patterns() {
+        assert!(maintainer_pattern_matches("net/", "net/core/sock.c"));
+        assert!(maintainer_pattern_matches("net", "net/sock.c"));
+        assert!(maintainer_pattern_matches("docs/*.md", "docs/a.md"));
+        assert!(!maintainer_pattern_matches("docs/*.md", "docs/sub/a.md"));
+        assert!(!maintainer_pattern_matches("net/", "network.c"));
+    }
+}
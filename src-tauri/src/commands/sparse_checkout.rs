@@ -146,6 +146,540 @@ pub async fn add_sparse_checkout_patterns(
     Ok(build_config(&path))
 }
 
+/// Committed presets file at the repository root, shared with the team
+pub const PRESETS_FILE: &str = ".leviathan-sparse.json";
+
+/// Cap on the paths a preview lists; the counts and sizes always cover
+/// everything.
+const PREVIEW_PATH_LIMIT: usize = 1000;
+
+/// A named set of sparse-checkout patterns, e.g. "backend" or "mobile"
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparsePreset {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Cone mode takes directories; non-cone mode takes gitignore-style
+    /// patterns
+    #[serde(default = "default_cone_mode")]
+    pub cone_mode: bool,
+    pub patterns: Vec<String>,
+}
+
+fn default_cone_mode() -> bool {
+    true
+}
+
+/// Where a preset is stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SparsePresetSource {
+    /// `.git/leviathan/sparse_presets.json`, this clone only
+    Local,
+    /// The committed `.leviathan-sparse.json`
+    Committed,
+}
+
+/// A preset as listed in the UI
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparsePresetInfo {
+    #[serde(flatten)]
+    pub preset: SparsePreset,
+    pub source: SparsePresetSource,
+}
+
+/// The committed presets file
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SparsePresetsFile {
+    presets: Vec<SparsePreset>,
+}
+
+/// File count and blob size of one directory, read from a tree
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparseDirectorySize {
+    pub path: String,
+    /// Files anywhere below the directory
+    pub file_count: usize,
+    pub total_size: u64,
+}
+
+/// Sizes for a directory and each of its subdirectories
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparseDirectoryListing {
+    /// The directory listed ("" for the root)
+    pub directory: String,
+    pub file_count: usize,
+    pub total_size: u64,
+    /// Files directly in the directory. Cone mode always checks these out
+    /// once any directory below is selected.
+    pub direct_file_count: usize,
+    pub direct_size: u64,
+    pub children: Vec<SparseDirectorySize>,
+}
+
+/// What applying a preset would change in the working tree
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparseCheckoutPreview {
+    /// Files that would be checked out (at most 1000 listed)
+    pub added: Vec<String>,
+    /// Files that would be removed from the working tree (at most 1000
+    /// listed)
+    pub removed: Vec<String>,
+    pub added_count: usize,
+    pub added_size: u64,
+    pub removed_count: usize,
+    pub removed_size: u64,
+    /// The working tree afterwards
+    pub resulting_file_count: usize,
+    pub resulting_size: u64,
+    /// Files with local changes among those to be removed; git keeps them
+    /// on disk and warns instead
+    pub modified_removed: Vec<String>,
+}
+
+fn local_presets_path(repo: &git2::Repository) -> std::path::PathBuf {
+    // The common dir, so every worktree of the clone sees the same presets.
+    repo.commondir()
+        .join("leviathan")
+        .join("sparse_presets.json")
+}
+
+fn parse_presets(content: &str, what: &str) -> Result<Vec<SparsePreset>> {
+    serde_json::from_str::<SparsePresetsFile>(content)
+        .map(|f| f.presets)
+        .map_err(|e| LeviathanError::OperationFailed(format!("Failed to parse {}: {}", what, e)))
+}
+
+fn load_local_presets(repo: &git2::Repository) -> Result<Vec<SparsePreset>> {
+    let file = local_presets_path(repo);
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+    parse_presets(&std::fs::read_to_string(file)?, "sparse presets")
+}
+
+/// Read the committed presets. The file itself may be outside the sparse
+/// checkout in non-cone mode, so HEAD's copy is used when the working tree
+/// has none.
+fn load_committed_presets(repo: &git2::Repository) -> Result<Vec<SparsePreset>> {
+    if let Some(file) = repo.workdir().map(|w| w.join(PRESETS_FILE)) {
+        if file.exists() {
+            return parse_presets(&std::fs::read_to_string(file)?, PRESETS_FILE);
+        }
+    }
+    let Some(tree) = repo.head().ok().and_then(|h| h.peel_to_tree().ok()) else {
+        return Ok(Vec::new());
+    };
+    let Ok(entry) = tree.get_path(std::path::Path::new(PRESETS_FILE)) else {
+        return Ok(Vec::new());
+    };
+    let blob = repo.find_blob(entry.id())?;
+    parse_presets(&String::from_utf8_lossy(blob.content()), PRESETS_FILE)
+}
+
+fn write_presets(file: &std::path::Path, presets: Vec<SparsePreset>) -> Result<()> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(&SparsePresetsFile { presets }).map_err(|e| {
+        LeviathanError::OperationFailed(format!("Failed to serialize sparse presets: {}", e))
+    })?;
+    std::fs::write(file, format!("{}\n", content))?;
+    Ok(())
+}
+
+/// Local presets first, then committed ones not shadowed by a local preset
+/// of the same name.
+fn all_presets(repo: &git2::Repository) -> Result<Vec<SparsePresetInfo>> {
+    let local = load_local_presets(repo)?;
+    let committed = load_committed_presets(repo)?;
+    let mut presets: Vec<SparsePresetInfo> = local
+        .into_iter()
+        .map(|preset| SparsePresetInfo {
+            preset,
+            source: SparsePresetSource::Local,
+        })
+        .collect();
+    for preset in committed {
+        if !presets.iter().any(|p| p.preset.name == preset.name) {
+            presets.push(SparsePresetInfo {
+                preset,
+                source: SparsePresetSource::Committed,
+            });
+        }
+    }
+    Ok(presets)
+}
+
+fn find_preset(repo: &git2::Repository, name: &str) -> Result<SparsePreset> {
+    all_presets(repo)?
+        .into_iter()
+        .find(|p| p.preset.name == name)
+        .map(|p| p.preset)
+        .ok_or_else(|| {
+            LeviathanError::OperationFailed(format!("Sparse checkout preset '{}' not found", name))
+        })
+}
+
+/// List the sparse-checkout presets, local and committed
+///
+/// A local preset shadows a committed one with the same name, so a
+/// teammate's definition can be adjusted without editing the shared file.
+#[command]
+pub async fn get_sparse_presets(path: String) -> Result<Vec<SparsePresetInfo>> {
+    let repo = git2::Repository::open(&path)?;
+    all_presets(&repo)
+}
+
+/// Save a preset, replacing one with the same name
+///
+/// With `committed` the preset goes into `.leviathan-sparse.json` in the
+/// working tree, to be committed like any other change; otherwise it is
+/// kept in this clone only.
+#[command]
+pub async fn save_sparse_preset(
+    path: String,
+    preset: SparsePreset,
+    committed: bool,
+) -> Result<Vec<SparsePresetInfo>> {
+    let preset = SparsePreset {
+        name: preset.name.trim().to_string(),
+        patterns: preset
+            .patterns
+            .iter()
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect(),
+        ..preset
+    };
+    if preset.name.is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "A preset name is required".to_string(),
+        ));
+    }
+    if preset.patterns.is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "At least one pattern is required".to_string(),
+        ));
+    }
+
+    let repo = git2::Repository::open(&path)?;
+    let (file, mut presets) = if committed {
+        let workdir = repo.workdir().ok_or_else(|| {
+            LeviathanError::OperationFailed("Repository has no working directory".to_string())
+        })?;
+        (workdir.join(PRESETS_FILE), load_committed_presets(&repo)?)
+    } else {
+        (local_presets_path(&repo), load_local_presets(&repo)?)
+    };
+    match presets.iter_mut().find(|p| p.name == preset.name) {
+        Some(existing) => *existing = preset,
+        None => presets.push(preset),
+    }
+    write_presets(&file, presets)?;
+    all_presets(&repo)
+}
+
+/// Delete a local or committed preset
+#[command]
+pub async fn delete_sparse_preset(
+    path: String,
+    name: String,
+    committed: bool,
+) -> Result<Vec<SparsePresetInfo>> {
+    let repo = git2::Repository::open(&path)?;
+    let (file, mut presets) = if committed {
+        let workdir = repo.workdir().ok_or_else(|| {
+            LeviathanError::OperationFailed("Repository has no working directory".to_string())
+        })?;
+        (workdir.join(PRESETS_FILE), load_committed_presets(&repo)?)
+    } else {
+        (local_presets_path(&repo), load_local_presets(&repo)?)
+    };
+    let before = presets.len();
+    presets.retain(|p| p.name != name);
+    if presets.len() == before {
+        return Err(LeviathanError::OperationFailed(format!(
+            "Sparse checkout preset '{}' not found",
+            name
+        )));
+    }
+    write_presets(&file, presets)?;
+    all_presets(&repo)
+}
+
+/// Size a directory and each of its subdirectories from a tree
+///
+/// Reads `rev`'s tree (HEAD by default) and blob headers only, so
+/// directories outside the current sparse checkout, which are not on disk,
+/// can be sized before choosing them.
+#[command]
+pub async fn get_sparse_directory_sizes(
+    path: String,
+    directory: Option<String>,
+    rev: Option<String>,
+) -> Result<SparseDirectoryListing> {
+    let repo = git2::Repository::open(&path)?;
+    let odb = repo.odb()?;
+    let root = repo
+        .revparse_single(rev.as_deref().unwrap_or("HEAD"))?
+        .peel_to_tree()?;
+    let directory = directory.unwrap_or_default().trim_matches('/').to_string();
+    let tree = if directory.is_empty() {
+        root
+    } else {
+        let entry = root
+            .get_path(std::path::Path::new(&directory))
+            .map_err(|_| LeviathanError::InvalidPath(directory.clone()))?;
+        repo.find_tree(entry.id())
+            .map_err(|_| LeviathanError::InvalidPath(directory.clone()))?
+    };
+
+    let blob_size = |oid: git2::Oid| odb.read_header(oid).map(|(s, _)| s as u64).unwrap_or(0);
+    let mut listing = SparseDirectoryListing {
+        directory: directory.clone(),
+        file_count: 0,
+        total_size: 0,
+        direct_file_count: 0,
+        direct_size: 0,
+        children: Vec::new(),
+    };
+    for entry in tree.iter() {
+        let name = entry.name().unwrap_or_default().to_string();
+        match entry.kind() {
+            Some(git2::ObjectType::Blob) => {
+                listing.direct_file_count += 1;
+                listing.direct_size += blob_size(entry.id());
+            }
+            Some(git2::ObjectType::Tree) => {
+                let subtree = repo.find_tree(entry.id())?;
+                let mut size = SparseDirectorySize {
+                    path: if directory.is_empty() {
+                        name
+                    } else {
+                        format!("{}/{}", directory, name)
+                    },
+                    file_count: 0,
+                    total_size: 0,
+                };
+                subtree.walk(git2::TreeWalkMode::PreOrder, |_, e| {
+                    if e.kind() == Some(git2::ObjectType::Blob) {
+                        size.file_count += 1;
+                        size.total_size += blob_size(e.id());
+                    }
+                    git2::TreeWalkResult::Ok
+                })?;
+                listing.children.push(size);
+            }
+            _ => {}
+        }
+    }
+    listing.file_count =
+        listing.direct_file_count + listing.children.iter().map(|c| c.file_count).sum::<usize>();
+    listing.total_size =
+        listing.direct_size + listing.children.iter().map(|c| c.total_size).sum::<u64>();
+    listing.children.sort_by(|a, b| {
+        b.total_size
+            .cmp(&a.total_size)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    Ok(listing)
+}
+
+/// Decides which paths a set of sparse-checkout patterns includes.
+enum SparseMatcher {
+    /// Cone mode: the listed directories recursively, plus the files
+    /// directly in the root and in every parent of a listed directory
+    Cone(Vec<String>),
+    /// Non-cone mode: gitignore-style patterns, last match wins
+    Patterns(Vec<(glob::Pattern, bool, bool, bool)>),
+}
+
+impl SparseMatcher {
+    fn new(preset: &SparsePreset) -> Result<Self> {
+        if preset.cone_mode {
+            return Ok(SparseMatcher::Cone(
+                preset
+                    .patterns
+                    .iter()
+                    .map(|p| p.trim_matches('/').to_string())
+                    .collect(),
+            ));
+        }
+        let mut compiled = Vec::new();
+        for pattern in &preset.patterns {
+            let (negated, body) = match pattern.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, pattern.as_str()),
+            };
+            let dir_only = body.ends_with('/');
+            let body = body.trim_end_matches('/');
+            let anchored = body.contains('/');
+            let glob = glob::Pattern::new(body.trim_start_matches('/')).map_err(|e| {
+                LeviathanError::OperationFailed(format!("Invalid pattern '{}': {}", pattern, e))
+            })?;
+            compiled.push((glob, negated, anchored, dir_only));
+        }
+        Ok(SparseMatcher::Patterns(compiled))
+    }
+
+    fn includes(&self, path: &str) -> bool {
+        let parent = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        match self {
+            SparseMatcher::Cone(dirs) => {
+                parent.is_empty()
+                    || dirs.iter().any(|dir| {
+                        parent == dir
+                            || parent.starts_with(&format!("{}/", dir))
+                            || dir.starts_with(&format!("{}/", parent))
+                    })
+            }
+            SparseMatcher::Patterns(patterns) => {
+                let options = glob::MatchOptions {
+                    case_sensitive: true,
+                    require_literal_separator: true,
+                    require_literal_leading_dot: false,
+                };
+                // Test the path and every leading directory, as a pattern
+                // naming a directory includes everything below it.
+                let components: Vec<&str> = path.split('/').collect();
+                let mut included = false;
+                for (glob, negated, anchored, dir_only) in patterns {
+                    let hit = (1..=components.len()).any(|n| {
+                        let is_dir = n < components.len();
+                        if *dir_only && !is_dir {
+                            return false;
+                        }
+                        let candidate = components[..n].join("/");
+                        if *anchored {
+                            glob.matches_with(&candidate, options)
+                        } else {
+                            glob.matches_with(components[n - 1], options)
+                        }
+                    });
+                    if hit {
+                        included = !negated;
+                    }
+                }
+                included
+            }
+        }
+    }
+}
+
+/// Compute what switching to a preset would change
+fn preview_preset(repo: &git2::Repository, preset: &SparsePreset) -> Result<SparseCheckoutPreview> {
+    let matcher = SparseMatcher::new(preset)?;
+    let odb = repo.odb()?;
+    let index = repo.index()?;
+    let sparse_enabled = repo
+        .config()?
+        .get_bool("core.sparseCheckout")
+        .unwrap_or(false);
+    let modified: std::collections::HashSet<String> = {
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(false);
+        repo.statuses(Some(&mut opts))?
+            .iter()
+            .filter(|e| !e.status().is_empty())
+            .filter_map(|e| e.path().ok().map(|p| p.to_string()))
+            .collect()
+    };
+
+    let mut preview = SparseCheckoutPreview {
+        added: Vec::new(),
+        removed: Vec::new(),
+        added_count: 0,
+        added_size: 0,
+        removed_count: 0,
+        removed_size: 0,
+        resulting_file_count: 0,
+        resulting_size: 0,
+        modified_removed: Vec::new(),
+    };
+    for entry in index.iter() {
+        // Conflict stages appear more than once; the file counts once.
+        if (entry.flags >> 12) & 0x3 > 1 {
+            continue;
+        }
+        let Ok(file) = String::from_utf8(entry.path) else {
+            continue;
+        };
+        let size = odb
+            .read_header(entry.id)
+            .map(|(s, _)| s as u64)
+            .unwrap_or(0);
+        let present = !sparse_enabled
+            || entry.flags_extended & git2::IndexEntryExtendedFlag::SKIP_WORKTREE.bits() == 0;
+        let wanted = matcher.includes(&file);
+        if wanted {
+            preview.resulting_file_count += 1;
+            preview.resulting_size += size;
+        }
+        match (present, wanted) {
+            (false, true) => {
+                preview.added_count += 1;
+                preview.added_size += size;
+                if preview.added.len() < PREVIEW_PATH_LIMIT {
+                    preview.added.push(file);
+                }
+            }
+            (true, false) => {
+                preview.removed_count += 1;
+                preview.removed_size += size;
+                if modified.contains(&file) {
+                    preview.modified_removed.push(file.clone());
+                }
+                if preview.removed.len() < PREVIEW_PATH_LIMIT {
+                    preview.removed.push(file);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(preview)
+}
+
+/// Preview switching to a preset
+///
+/// Lists the files that would appear in and disappear from the working
+/// tree, with their sizes, by evaluating the preset against the index.
+/// Nothing on disk changes.
+#[command]
+pub async fn preview_sparse_preset(path: String, name: String) -> Result<SparseCheckoutPreview> {
+    let repo = git2::Repository::open(&path)?;
+    let preset = find_preset(&repo, &name)?;
+    preview_preset(&repo, &preset)
+}
+
+/// Switch the sparse checkout to a preset
+///
+/// Enables sparse checkout if needed and replaces the patterns in the
+/// preset's mode.
+#[command]
+pub async fn apply_sparse_preset(path: String, name: String) -> Result<SparseCheckoutConfig> {
+    let repo = git2::Repository::open(&path)?;
+    let preset = find_preset(&repo, &name)?;
+    let mut args: Vec<&str> = vec!["sparse-checkout", "set"];
+    args.push(if preset.cone_mode {
+        "--cone"
+    } else {
+        "--no-cone"
+    });
+    args.push("--");
+    for p in &preset.patterns {
+        args.push(p.as_str());
+    }
+    run_git(&path, &args)?;
+    Ok(build_config(&path))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -361,4 +895,221 @@ mod tests {
         let result = run_git("/nonexistent/path/that/does/not/exist", &["status"]);
         assert!(result.is_err());
     }
+
+    fn monorepo() -> TestRepo {
+        let repo = TestRepo::with_initial_commit();
+        repo.create_commit(
+            "Add services",
+            &[
+                ("services/backend/api.rs", "fn api() {}"),
+                ("services/backend/db.rs", "fn db() {}"),
+                ("services/shared.txt", "shared"),
+                ("apps/mobile/app.kt", "class App"),
+                ("apps/mobile/assets/logo.png", &"x".repeat(1000)),
+                ("docs/guide.md", "# Guide"),
+            ],
+        );
+        repo
+    }
+
+    fn preset(name: &str, cone_mode: bool, patterns: &[&str]) -> SparsePreset {
+        SparsePreset {
+            name: name.to_string(),
+            description: None,
+            cone_mode,
+            patterns: patterns.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_presets_shadow_committed_ones() {
+        let repo = monorepo();
+        save_sparse_preset(
+            repo.path_str(),
+            preset("backend", true, &["services/backend"]),
+            true,
+        )
+        .await
+        .unwrap();
+        save_sparse_preset(
+            repo.path_str(),
+            preset("mobile", true, &["apps/mobile"]),
+            true,
+        )
+        .await
+        .unwrap();
+        save_sparse_preset(
+            repo.path_str(),
+            preset("backend", true, &["services"]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let presets = get_sparse_presets(repo.path_str()).await.unwrap();
+        assert_eq!(presets.len(), 2);
+        let backend = presets.iter().find(|p| p.preset.name == "backend").unwrap();
+        assert_eq!(backend.source, SparsePresetSource::Local);
+        assert_eq!(backend.preset.patterns, vec!["services".to_string()]);
+
+        let presets = delete_sparse_preset(repo.path_str(), "backend".to_string(), false)
+            .await
+            .unwrap();
+        let backend = presets.iter().find(|p| p.preset.name == "backend").unwrap();
+        assert_eq!(backend.source, SparsePresetSource::Committed);
+        assert!(
+            delete_sparse_preset(repo.path_str(), "nope".to_string(), false)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_committed_presets_are_read_from_head_when_not_checked_out() {
+        let repo = monorepo();
+        repo.create_commit(
+            "Add presets",
+            &[(
+                PRESETS_FILE,
+                r#"{"presets": [{"name": "docs", "patterns": ["docs"]}]}"#,
+            )],
+        );
+        std::fs::remove_file(repo.path.join(PRESETS_FILE)).unwrap();
+
+        let presets = get_sparse_presets(repo.path_str()).await.unwrap();
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].preset.name, "docs");
+        assert!(presets[0].preset.cone_mode, "cone mode is the default");
+        assert_eq!(presets[0].source, SparsePresetSource::Committed);
+    }
+
+    #[tokio::test]
+    async fn test_save_sparse_preset_requires_patterns() {
+        let repo = TestRepo::with_initial_commit();
+        let result =
+            save_sparse_preset(repo.path_str(), preset("empty", true, &[" "]), false).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_sparse_directory_sizes() {
+        let repo = monorepo();
+        let root = get_sparse_directory_sizes(repo.path_str(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(root.directory, "");
+        assert_eq!(root.direct_file_count, 1, "README.md");
+        assert_eq!(root.file_count, 7);
+        assert_eq!(root.children[0].path, "apps", "largest first");
+        assert_eq!(root.children[0].file_count, 2);
+        assert_eq!(root.children[0].total_size, 1000 + "class App".len() as u64);
+
+        let services =
+            get_sparse_directory_sizes(repo.path_str(), Some("services/".to_string()), None)
+                .await
+                .unwrap();
+        assert_eq!(services.direct_file_count, 1);
+        assert_eq!(services.children.len(), 1);
+        assert_eq!(services.children[0].path, "services/backend");
+        assert_eq!(services.children[0].file_count, 2);
+
+        assert!(
+            get_sparse_directory_sizes(repo.path_str(), Some("missing".to_string()), None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sparse_matcher_cone_includes_parent_files() {
+        let matcher = SparseMatcher::new(&preset("b", true, &["services/backend"])).unwrap();
+        assert!(matcher.includes("README.md"));
+        assert!(matcher.includes("services/shared.txt"));
+        assert!(matcher.includes("services/backend/api.rs"));
+        assert!(!matcher.includes("apps/mobile/app.kt"));
+        assert!(!matcher.includes("services/backend-old/x.rs"));
+    }
+
+    #[test]
+    fn test_sparse_matcher_patterns_last_match_wins() {
+        let matcher =
+            SparseMatcher::new(&preset("p", false, &["/*", "!/*/", "docs/", "*.kt"])).unwrap();
+        assert!(matcher.includes("README.md"));
+        assert!(matcher.includes("docs/guide.md"));
+        assert!(matcher.includes("apps/mobile/app.kt"));
+        assert!(!matcher.includes("apps/mobile/assets/logo.png"));
+        assert!(!matcher.includes("services/backend/api.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_preview_and_apply_sparse_preset() {
+        if !git_supports_sparse_checkout() {
+            eprintln!("Skipping: git sparse-checkout not supported");
+            return;
+        }
+
+        let repo = monorepo();
+        save_sparse_preset(
+            repo.path_str(),
+            preset("backend", true, &["services/backend"]),
+            false,
+        )
+        .await
+        .unwrap();
+        save_sparse_preset(
+            repo.path_str(),
+            preset("mobile", true, &["apps/mobile"]),
+            false,
+        )
+        .await
+        .unwrap();
+
+        // From a full checkout, only removals.
+        let preview = preview_sparse_preset(repo.path_str(), "backend".to_string())
+            .await
+            .unwrap();
+        assert_eq!(preview.added_count, 0);
+        assert_eq!(preview.removed_count, 3);
+        assert!(preview.removed.contains(&"docs/guide.md".to_string()));
+        assert_eq!(preview.resulting_file_count, 4);
+
+        let config = apply_sparse_preset(repo.path_str(), "backend".to_string())
+            .await
+            .unwrap();
+        assert!(config.enabled);
+        assert!(!repo.path.join("apps/mobile/app.kt").exists());
+
+        // Switching presets now adds the mobile app and drops the backend.
+        let preview = preview_sparse_preset(repo.path_str(), "mobile".to_string())
+            .await
+            .unwrap();
+        let mut added = preview.added.clone();
+        added.sort();
+        assert_eq!(
+            added,
+            vec![
+                "apps/mobile/app.kt".to_string(),
+                "apps/mobile/assets/logo.png".to_string()
+            ]
+        );
+        assert_eq!(preview.added_size, 1000 + "class App".len() as u64);
+        let mut removed = preview.removed.clone();
+        removed.sort();
+        assert_eq!(
+            removed,
+            vec![
+                "services/backend/api.rs".to_string(),
+                "services/backend/db.rs".to_string(),
+                "services/shared.txt".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preview_sparse_preset_unknown_name() {
+        let repo = TestRepo::with_initial_commit();
+        assert!(preview_sparse_preset(repo.path_str(), "nope".to_string())
+            .await
+            .is_err());
+    }
 }
@@ -1,4 +1,4 @@
-//! Squash and fixup commit command handlers
+//! Squash, fixup and absorb command handlers
 
 use std::path::Path;
 use tauri::command;
@@ -420,6 +420,390 @@ pub async fn fixup_commit(
     })
 }
 
+/// Default number of commits below HEAD absorb considers, as git-absorb.
+const ABSORB_MAX_STACK: usize = 10;
+
+/// Where one staged hunk goes
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbsorbHunk {
+    pub path: String,
+    /// Old-side line range (in HEAD) the hunk replaces
+    pub old_start: usize,
+    pub old_lines: usize,
+    /// Commit the hunk is absorbed into, None when it stays staged
+    pub target: Option<String>,
+    pub target_summary: Option<String>,
+    /// Why the hunk stays staged
+    pub reason: Option<String>,
+}
+
+/// Result of an absorb (or its preview)
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbsorbResult {
+    pub hunks: Vec<AbsorbHunk>,
+    /// `fixup!` commits created (fixup mode)
+    pub fixup_commits: Vec<String>,
+    /// HEAD after the absorb, None for a preview
+    pub new_head: Option<String>,
+    /// Hunks left staged
+    pub left_staged: usize,
+}
+
+/// The commits absorb may target: HEAD downwards, stopping at the upstream
+/// (published commits are never rewritten), a merge, or `max` commits.
+fn absorb_stack(repo: &git2::Repository, max: usize) -> Result<Vec<git2::Oid>> {
+    let head = repo.head()?;
+    let head_oid = head.peel_to_commit()?.id();
+    let mut walk = repo.revwalk()?;
+    walk.push(head_oid)?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL)?;
+    if head.is_branch() {
+        if let Ok(name) = head.shorthand() {
+            if let Ok(upstream) = repo
+                .find_branch(name, git2::BranchType::Local)
+                .and_then(|b| b.upstream())
+            {
+                if let Some(oid) = upstream.get().target() {
+                    walk.hide(oid)?;
+                }
+            }
+        }
+    }
+    let mut stack = Vec::new();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 || stack.len() >= max {
+            break;
+        }
+        stack.push(commit.id());
+    }
+    Ok(stack)
+}
+
+/// Staged hunks with their targets.
+struct AbsorbPlan {
+    hunks: Vec<AbsorbHunk>,
+    /// Per hunk, its index in its file's zero-context diff (None for whole
+    /// files that cannot be absorbed)
+    indexes: Vec<Option<usize>>,
+    /// The candidate commits, newest first
+    stack: Vec<git2::Oid>,
+}
+
+/// Attribute each staged hunk to the stack commit that last touched its
+/// lines.
+fn plan_absorb(repo: &git2::Repository, max_commits: Option<usize>) -> Result<AbsorbPlan> {
+    let head = repo.head()?.peel_to_commit()?;
+    let head_tree = head.tree()?;
+    let staged = repo.find_tree(repo.index()?.write_tree()?)?;
+    if staged.id() == head_tree.id() {
+        return Err(LeviathanError::OperationFailed(
+            "No staged changes to absorb".to_string(),
+        ));
+    }
+    let stack = absorb_stack(repo, max_commits.unwrap_or(ABSORB_MAX_STACK))?;
+    let files = crate::commands::rewrite::split_files(repo, &head_tree, &staged, 0)?;
+
+    let mut plan = Vec::new();
+    let mut indexes = Vec::new();
+    for file in &files {
+        let mut push = |index: Option<usize>,
+                        old_start,
+                        old_lines,
+                        target: std::result::Result<git2::Oid, &str>| {
+            indexes.push(index);
+            let (target, target_summary, reason) = match target {
+                Ok(oid) => (
+                    Some(oid.to_string()),
+                    repo.find_commit(oid)
+                        .ok()
+                        .and_then(|c| c.summary().ok().flatten().map(str::to_string)),
+                    None,
+                ),
+                Err(reason) => (None, None, Some(reason.to_string())),
+            };
+            plan.push(AbsorbHunk {
+                path: file.path.clone(),
+                old_start,
+                old_lines,
+                target,
+                target_summary,
+                reason,
+            });
+        };
+        if file.old_mode.is_none() {
+            push(None, 0, 0, Err("New file: no commit to absorb into"));
+            continue;
+        }
+        if file.hunks.is_empty() {
+            push(None, 0, 0, Err("Binary or submodule change"));
+            continue;
+        }
+
+        let mut opts = git2::BlameOptions::new();
+        opts.newest_commit(head.id());
+        let blame = match repo.blame_file(Path::new(&file.path), Some(&mut opts)) {
+            Ok(blame) => blame,
+            Err(_) => {
+                for (index, hunk) in file.hunks.iter().enumerate() {
+                    push(
+                        Some(index),
+                        hunk.old_start,
+                        hunk.old_lines,
+                        Err("Could not blame the file"),
+                    );
+                }
+                continue;
+            }
+        };
+        let line_count: usize = blame.iter().map(|h| h.lines_in_hunk()).sum();
+
+        for (index, hunk) in file.hunks.iter().enumerate() {
+            // A pure insertion has no old lines of its own; the lines either
+            // side of it decide, and must agree.
+            let lines: Vec<usize> = if hunk.old_lines == 0 {
+                [hunk.old_start, hunk.old_start + 1]
+                    .into_iter()
+                    .filter(|l| *l >= 1 && *l <= line_count)
+                    .collect()
+            } else {
+                (hunk.old_start..hunk.old_start + hunk.old_lines).collect()
+            };
+            let mut owners: Vec<git2::Oid> = lines
+                .iter()
+                .filter_map(|l| blame.get_line(*l).map(|h| h.final_commit_id()))
+                .collect();
+            owners.sort();
+            owners.dedup();
+
+            let target = match owners.as_slice() {
+                [] => Err("No surrounding lines to attribute the change by"),
+                [owner] if stack.contains(owner) => Ok(*owner),
+                [_] => Err("Lines come from a published or older commit"),
+                _ => Err("Lines come from more than one commit"),
+            };
+            push(Some(index), hunk.old_start, hunk.old_lines, target);
+        }
+    }
+    Ok(AbsorbPlan {
+        hunks: plan,
+        indexes,
+        stack,
+    })
+}
+
+/// Preview which commit each staged hunk would be absorbed into
+#[command]
+pub async fn preview_absorb(path: String, max_commits: Option<usize>) -> Result<AbsorbResult> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let hunks = plan_absorb(&repo, max_commits)?.hunks;
+    let left_staged = hunks.iter().filter(|h| h.target.is_none()).count();
+    Ok(AbsorbResult {
+        hunks,
+        fixup_commits: Vec::new(),
+        new_head: None,
+        left_staged,
+    })
+}
+
+/// Absorb staged hunks into the commits that introduced their lines
+///
+/// The `git absorb` equivalent. Each staged hunk is blamed against the
+/// branch's unpublished commits (HEAD down to the upstream, a merge, or
+/// `max_commits`, default 10); a hunk whose lines all come from one of them
+/// gets a `fixup! <summary>` commit for it. With `autosquash` the fixups
+/// are folded straight into their targets, as `fixup_commit` does for a
+/// hand-picked target, and the commits after them are replayed. Hunks that
+/// cannot be attributed to exactly one such commit (new files, lines from
+/// published commits, changes spanning commits) are reported and left
+/// staged. The working tree is never touched.
+///
+/// The fixup commits are made like any other commit: pre-commit hooks, the
+/// secret scan, commit-msg and `commit.gpgsign` signing all apply.
+#[command]
+pub async fn absorb_staged_changes(
+    path: String,
+    autosquash: Option<bool>,
+    max_commits: Option<usize>,
+) -> Result<AbsorbResult> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(LeviathanError::OperationFailed(
+            "Another operation is in progress".to_string(),
+        ));
+    }
+    let AbsorbPlan {
+        hunks,
+        indexes,
+        stack,
+    } = plan_absorb(&repo, max_commits)?;
+    let left_staged = hunks.iter().filter(|h| h.target.is_none()).count();
+
+    // One fixup per target, carrying its hunks.
+    let mut targets: Vec<String> = Vec::new();
+    let mut groups: Vec<crate::commands::rewrite::SplitGroup> = Vec::new();
+    for (hunk, index) in hunks.iter().zip(&indexes) {
+        let (Some(target), Some(index)) = (&hunk.target, *index) else {
+            continue;
+        };
+        let pos = match targets.iter().position(|t| t == target) {
+            Some(pos) => pos,
+            None => {
+                targets.push(target.clone());
+                groups.push(crate::commands::rewrite::SplitGroup {
+                    message: format!(
+                        "fixup! {}",
+                        hunk.target_summary.as_deref().unwrap_or(target)
+                    ),
+                    files: Vec::new(),
+                    hunks: Vec::new(),
+                });
+                targets.len() - 1
+            }
+        };
+        groups[pos].hunks.push(crate::commands::rewrite::SplitHunk {
+            path: hunk.path.clone(),
+            index,
+        });
+    }
+    if groups.is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "No staged hunk could be attributed to an unpublished commit".to_string(),
+        ));
+    }
+    // Oldest target first, so fixups read in history order.
+    let order = |t: &String| {
+        stack
+            .iter()
+            .position(|s| s.to_string() == *t)
+            .map(std::cmp::Reverse)
+    };
+    let mut paired: Vec<_> = targets.into_iter().zip(groups).collect();
+    paired.sort_by_key(|(t, _)| order(t));
+    let (targets, groups): (Vec<String>, Vec<_>) = paired.into_iter().unzip();
+
+    if !autosquash.unwrap_or(false) {
+        let split = crate::commands::rewrite::commit_staged_split(&path, &groups, 0).await?;
+        return Ok(AbsorbResult {
+            hunks,
+            new_head: Some(split.new_tip),
+            fixup_commits: split.commits,
+            left_staged,
+        });
+    }
+
+    let head = repo.head()?.peel_to_commit()?;
+    let staged = repo.find_tree(repo.index()?.write_tree()?)?;
+    let fixup_trees =
+        crate::commands::rewrite::plan_split(&repo, &head.tree()?, &staged, &groups, 0)?;
+
+    // Replay the stack oldest first, folding each target's fixup in right
+    // after it. Each fixup is applied as the change from the tree before it.
+    let signature = repo.signature()?;
+    let mut previous_tree = head.tree_id();
+    let mut fixups: Vec<(git2::Oid, git2::Oid, git2::Oid)> = Vec::new();
+    for (target, tree) in targets.iter().zip(&fixup_trees) {
+        fixups.push((git2::Oid::from_str(target)?, previous_tree, *tree));
+        previous_tree = *tree;
+    }
+    let oldest = *stack
+        .iter()
+        .rev()
+        .find(|s| fixups.iter().any(|f| f.0 == **s))
+        .expect("every target is on the stack");
+    let mut walk = repo.revwalk()?;
+    walk.push(head.id())?;
+    let oldest_commit = repo.find_commit(oldest)?;
+    for parent in oldest_commit.parent_ids() {
+        walk.hide(parent)?;
+    }
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+    let mut current: Option<git2::Commit> = oldest_commit.parent(0).ok();
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let mut tree = match &current {
+            Some(base) if commit.parent_id(0).ok() != Some(base.id()) => {
+                let mut merged = repo.merge_trees(
+                    &commit.parent(0)?.tree()?,
+                    &base.tree()?,
+                    &commit.tree()?,
+                    None,
+                )?;
+                if merged.has_conflicts() {
+                    return Err(LeviathanError::OperationFailed(format!(
+                        "Conflict while replaying commit {}. Use absorb without autosquash.",
+                        commit.id()
+                    )));
+                }
+                merged.write_tree_to(&repo)?
+            }
+            _ => commit.tree_id(),
+        };
+        for (_, before, after) in fixups.iter().filter(|f| f.0 == commit.id()) {
+            let mut merged = repo.merge_trees(
+                &repo.find_tree(*before)?,
+                &repo.find_tree(tree)?,
+                &repo.find_tree(*after)?,
+                None,
+            )?;
+            if merged.has_conflicts() {
+                return Err(LeviathanError::OperationFailed(format!(
+                    "Conflict while squashing into {}. Use absorb without autosquash.",
+                    commit.id()
+                )));
+            }
+            tree = merged.write_tree_to(&repo)?;
+        }
+        let unchanged = tree == commit.tree_id()
+            && commit.parent_id(0).ok() == current.as_ref().map(|c| c.id());
+        let new_oid = if unchanged {
+            commit.id()
+        } else {
+            let parents: Vec<&git2::Commit> = current.iter().collect();
+            repo.commit(
+                None,
+                &commit.author(),
+                &signature,
+                commit.message().unwrap_or(""),
+                &repo.find_tree(tree)?,
+                &parents,
+            )?
+        };
+        current = Some(repo.find_commit(new_oid)?);
+    }
+    let new_head = current.expect("the stack is not empty");
+
+    // Folding every fixup in must land on exactly what committing them would
+    // have; the index then still holds only the unattributed hunks as staged.
+    if Some(&new_head.tree_id()) != fixup_trees.last() {
+        return Err(LeviathanError::OperationFailed(
+            "Autosquash would change the result; use absorb without autosquash".to_string(),
+        ));
+    }
+
+    let head_ref = repo.head()?;
+    if head_ref.is_branch() {
+        repo.reference(
+            head_ref.name()?,
+            new_head.id(),
+            true,
+            "absorb: fold staged changes into earlier commits",
+        )?;
+    } else {
+        repo.set_head_detached(new_head.id())?;
+    }
+
+    Ok(AbsorbResult {
+        hunks,
+        fixup_commits: Vec::new(),
+        new_head: Some(new_head.id().to_string()),
+        left_staged,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -781,4 +1165,167 @@ mod tests {
         assert!(json.contains("\"squashedCount\":3"));
         assert!(json.contains("\"success\":true"));
     }
+
+    /// A base commit with two files, then one commit editing each, and
+    /// staged edits to the lines each of those commits introduced plus a
+    /// new file.
+    fn absorb_fixture() -> (TestRepo, git2::Oid, git2::Oid) {
+        let repo = TestRepo::with_initial_commit();
+        let lines = |tag: &str| -> String {
+            (1..=10)
+                .map(|i| {
+                    if i == 5 {
+                        format!("line {} {}\n", i, tag)
+                    } else {
+                        format!("line {}\n", i)
+                    }
+                })
+                .collect()
+        };
+        repo.create_commit("base", &[("a.txt", &lines("")), ("b.txt", &lines(""))]);
+        let first = repo.create_commit("edit a", &[("a.txt", &lines("from a"))]);
+        let second = repo.create_commit("edit b", &[("b.txt", &lines("from b"))]);
+        for (name, content) in [
+            ("a.txt", lines("from a, fixed")),
+            ("b.txt", lines("from b, fixed")),
+            ("new.txt", "new\n".to_string()),
+        ] {
+            repo.create_file(name, &content);
+            repo.stage_file(name);
+        }
+        (repo, first, second)
+    }
+
+    #[tokio::test]
+    async fn test_absorb_creates_fixups_and_leaves_unattributed_hunks_staged() {
+        let (repo, first, second) = absorb_fixture();
+
+        let preview = preview_absorb(repo.path_str(), None).await.unwrap();
+        assert_eq!(preview.left_staged, 1);
+        let target_of = |path: &str| {
+            preview
+                .hunks
+                .iter()
+                .find(|h| h.path == path)
+                .unwrap()
+                .target
+                .clone()
+        };
+        assert_eq!(target_of("a.txt"), Some(first.to_string()));
+        assert_eq!(target_of("b.txt"), Some(second.to_string()));
+        assert_eq!(target_of("new.txt"), None);
+
+        let result = absorb_staged_changes(repo.path_str(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(result.fixup_commits.len(), 2);
+
+        let git = repo.repo();
+        let head = git.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary().ok().flatten(), Some("fixup! edit b"));
+        assert_eq!(
+            head.parent(0).unwrap().summary().ok().flatten(),
+            Some("fixup! edit a")
+        );
+        assert!(head.tree().unwrap().get_path(Path::new("new.txt")).is_err());
+        let staged = git.index().unwrap().write_tree().unwrap();
+        assert!(git
+            .find_tree(staged)
+            .unwrap()
+            .get_path(Path::new("new.txt"))
+            .is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_absorb_fixups_go_through_commit_gate_and_signing() {
+        let (repo, _, second) = absorb_fixture();
+        let staged_before = repo.repo().index().unwrap().write_tree().unwrap();
+        {
+            let mut config = repo.repo().config().unwrap();
+            config.set_bool("commit.gpgsign", true).unwrap();
+            config
+                .set_str("gpg.program", "/nonexistent/definitely-not-real-gpg")
+                .unwrap();
+        }
+
+        // A bogus gpg.program can only fail the absorb if it tries to sign.
+        assert!(absorb_staged_changes(repo.path_str(), None, None)
+            .await
+            .is_err());
+        assert_eq!(repo.head_oid(), second);
+        assert_eq!(
+            repo.repo().index().unwrap().write_tree().unwrap(),
+            staged_before
+        );
+
+        repo.repo()
+            .config()
+            .unwrap()
+            .set_bool("commit.gpgsign", false)
+            .unwrap();
+        repo.install_hook("pre-commit", "#!/bin/sh\necho blocked 1>&2\nexit 1\n");
+        let err = absorb_staged_changes(repo.path_str(), None, None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("blocked"), "{err}");
+        assert_eq!(repo.head_oid(), second);
+    }
+
+    #[tokio::test]
+    async fn test_absorb_autosquash_folds_into_targets() {
+        let (repo, _, _) = absorb_fixture();
+
+        absorb_staged_changes(repo.path_str(), Some(true), None)
+            .await
+            .unwrap();
+
+        let git = repo.repo();
+        let head = git.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary().ok().flatten(), Some("edit b"));
+        let edit_a = head.parent(0).unwrap();
+        assert_eq!(edit_a.summary().ok().flatten(), Some("edit a"));
+        let blob = |commit: &git2::Commit, path: &str| {
+            let id = commit
+                .tree()
+                .unwrap()
+                .get_path(Path::new(path))
+                .unwrap()
+                .id();
+            String::from_utf8(git.find_blob(id).unwrap().content().to_vec()).unwrap()
+        };
+        assert!(blob(&edit_a, "a.txt").contains("from a, fixed"));
+        assert!(!blob(&edit_a, "b.txt").contains("from b"));
+        assert!(blob(&head, "b.txt").contains("from b, fixed"));
+
+        // Only the new file is still staged.
+        let index = git.index().unwrap();
+        let diff = git
+            .diff_tree_to_index(Some(&head.tree().unwrap()), Some(&index), None)
+            .unwrap();
+        let staged: Vec<_> = diff
+            .deltas()
+            .map(|d| d.new_file().path().unwrap().to_path_buf())
+            .collect();
+        assert_eq!(staged, vec![std::path::PathBuf::from("new.txt")]);
+    }
+
+    #[tokio::test]
+    async fn test_absorb_skips_published_commits() {
+        let (repo, _, second) = absorb_fixture();
+        let branch = repo.current_branch();
+        repo.add_remote("origin", "/nonexistent/origin.git");
+        repo.create_remote_branch(&branch, second);
+        repo.repo()
+            .find_branch(&branch, git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some(&format!("origin/{}", branch)))
+            .unwrap();
+
+        let preview = preview_absorb(repo.path_str(), None).await.unwrap();
+        assert!(preview.hunks.iter().all(|h| h.target.is_none()));
+        assert!(absorb_staged_changes(repo.path_str(), None, None)
+            .await
+            .is_err());
+    }
 }
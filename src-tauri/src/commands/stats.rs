@@ -120,6 +120,45 @@ pub struct ContributorStats {
     pub lines_deleted: usize,
 }
 
+/// One person as the contributor stats count them, with every raw
+/// identity that resolved to them
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityCluster {
+    /// Canonical identity: the resolved one with the most commits
+    pub name: String,
+    pub email: String,
+    /// Sum of the identities' counts (a commit authored and committed under
+    /// two of them counts twice)
+    pub commit_count: usize,
+    /// Identities exactly as recorded in commits (author or committer)
+    pub identities: Vec<RawIdentity>,
+    /// Other clusters under the same resolved name. Possibly the same person
+    /// at another address, possibly a namesake; they are never merged, and
+    /// only a `.mailmap` entry makes them one cluster.
+    pub possible_matches: Vec<PossibleIdentityMatch>,
+}
+
+/// Another cluster that shares a cluster's name but not its email
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PossibleIdentityMatch {
+    /// Canonical identity of the other cluster
+    pub name: String,
+    pub email: String,
+    pub commit_count: usize,
+}
+
+/// A name/email pair exactly as recorded in commits
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawIdentity {
+    pub name: String,
+    pub email: String,
+    /// Commits carrying it as author, committer or both
+    pub commit_count: usize,
+}
+
 /// Activity for a month
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -375,6 +414,128 @@ pub async fn get_contributor_stats(
     Ok(contributors)
 }
 
+/// Group the identities in HEAD's history into people
+///
+/// Identities are grouped by their mailmap-resolved email, as the
+/// contributor stats are. Clusters that only share a name are not merged —
+/// two people can have the same name — but listed as each other's
+/// `possible_matches`; a `.mailmap` entry joins them for real.
+#[command]
+pub async fn get_identity_clusters(
+    path: String,
+    max_commits: Option<usize>,
+) -> Result<Vec<IdentityCluster>> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    identity_clusters(&repo, max_commits.unwrap_or(5000))
+}
+
+pub(crate) fn identity_clusters(
+    repo: &git2::Repository,
+    max_commits: usize,
+) -> Result<Vec<IdentityCluster>> {
+    let mailmap = repo.mailmap().ok();
+    let mut revwalk = repo.revwalk()?;
+    if revwalk.push_head().is_err() {
+        return Ok(Vec::new());
+    }
+    revwalk.set_sorting(git2::Sort::TIME)?;
+
+    // Raw (name, email) -> (resolved name, resolved email, commits)
+    let mut raw: HashMap<(String, String), (String, String, usize)> = HashMap::new();
+    for oid in revwalk.take(max_commits) {
+        let commit = repo.find_commit(oid?)?;
+        let mut seen = HashSet::new();
+        for sig in [commit.author(), commit.committer()] {
+            let name = sig.name().unwrap_or("Unknown").to_string();
+            let email = sig.email().unwrap_or("unknown").to_string();
+            if !seen.insert((name.clone(), email.clone())) {
+                continue;
+            }
+            let resolved = mailmap
+                .as_ref()
+                .and_then(|mm| mm.resolve_signature(&sig).ok())
+                .map(|r| {
+                    (
+                        r.name().unwrap_or("Unknown").to_string(),
+                        r.email().unwrap_or("unknown").to_string(),
+                    )
+                })
+                .unwrap_or_else(|| (name.clone(), email.clone()));
+            raw.entry((name, email))
+                .or_insert((resolved.0, resolved.1, 0))
+                .2 += 1;
+        }
+    }
+
+    // One cluster per resolved email.
+    let mut identities: Vec<_> = raw.into_iter().collect();
+    identities.sort();
+    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, (_, (_, email, _))) in identities.iter().enumerate() {
+        groups.entry(email.to_lowercase()).or_default().push(i);
+    }
+
+    let mut clusters: Vec<IdentityCluster> = groups
+        .into_values()
+        .map(|members| {
+            let mut resolved: HashMap<(String, String), usize> = HashMap::new();
+            for &i in &members {
+                let (_, (name, email, count)) = &identities[i];
+                *resolved.entry((name.clone(), email.clone())).or_default() += count;
+            }
+            let ((name, email), _) = resolved
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+                .unwrap_or_default();
+            let mut raw: Vec<RawIdentity> = members
+                .iter()
+                .map(|&i| RawIdentity {
+                    name: identities[i].0 .0.clone(),
+                    email: identities[i].0 .1.clone(),
+                    commit_count: identities[i].1 .2,
+                })
+                .collect();
+            raw.sort_by_key(|r| std::cmp::Reverse(r.commit_count));
+            IdentityCluster {
+                name,
+                email,
+                commit_count: raw.iter().map(|r| r.commit_count).sum(),
+                identities: raw,
+                possible_matches: Vec::new(),
+            }
+        })
+        .collect();
+    clusters.sort_by(|a, b| {
+        b.commit_count
+            .cmp(&a.commit_count)
+            .then_with(|| a.email.cmp(&b.email))
+    });
+
+    // Name-only matches, reported but never merged.
+    let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, cluster) in clusters.iter().enumerate() {
+        let name = cluster.name.trim().to_lowercase();
+        if name != "unknown" && !name.is_empty() {
+            by_name.entry(name).or_default().push(i);
+        }
+    }
+    for indices in by_name.values().filter(|v| v.len() > 1) {
+        for &i in indices {
+            let matches = indices
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| PossibleIdentityMatch {
+                    name: clusters[j].name.clone(),
+                    email: clusters[j].email.clone(),
+                    commit_count: clusters[j].commit_count,
+                })
+                .collect();
+            clusters[i].possible_matches = matches;
+        }
+    }
+    Ok(clusters)
+}
+
 /// Simple epoch to year/month conversion
 fn epoch_to_year_month(epoch: i64) -> (i32, u32) {
     // Approximate calculation
@@ -1382,4 +1543,42 @@ mod tests {
             .unwrap();
         assert_eq!(h14.commit_count, 0);
     }
+
+    #[tokio::test]
+    async fn test_identity_clusters_keep_namesakes_apart() {
+        let repo = TestRepo::with_initial_commit();
+        let mut config = repo.repo().config().unwrap();
+        config
+            .set_str("user.email", "test@personal.example")
+            .unwrap();
+        repo.create_commit("personal", &[("p.txt", "p")]);
+        config.set_str("user.name", "Someone Else").unwrap();
+        config.set_str("user.email", "else@example.com").unwrap();
+        repo.create_commit("other", &[("o.txt", "o")]);
+
+        let clusters = get_identity_clusters(repo.path_str(), None).await.unwrap();
+        assert_eq!(clusters.len(), 3, "a shared name alone does not merge");
+        let personal = clusters
+            .iter()
+            .find(|c| c.email == "test@personal.example")
+            .unwrap();
+        assert_eq!(personal.identities.len(), 1);
+        assert_eq!(personal.possible_matches.len(), 1);
+        assert_eq!(personal.possible_matches[0].email, "test@example.com");
+        let other = clusters.iter().find(|c| c.name == "Someone Else").unwrap();
+        assert!(other.possible_matches.is_empty());
+
+        // A mailmap entry is what joins two addresses.
+        std::fs::write(
+            repo.path.join(".mailmap"),
+            "Test User <test@example.com> <test@personal.example>\n",
+        )
+        .unwrap();
+        let clusters = get_identity_clusters(repo.path_str(), None).await.unwrap();
+        assert_eq!(clusters.len(), 2);
+        let me = clusters.iter().find(|c| c.name == "Test User").unwrap();
+        assert_eq!(me.identities.len(), 2);
+        assert_eq!(me.commit_count, 2);
+        assert!(me.possible_matches.is_empty());
+    }
 }
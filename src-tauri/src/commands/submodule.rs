@@ -1,10 +1,16 @@
 //! Submodule command handlers
 //! Manage git submodules
+//!
+//! Besides the flat list and the plain git verbs, this provides a recursive
+//! tree of nested submodules with pointer/dirty/ahead-behind state, batch
+//! updates with one result per submodule, committing updated pointers in the
+//! superproject, and a foreach that reports each submodule separately.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::command;
 
 use crate::error::{LeviathanError, Result};
+use crate::models::AheadBehind;
 use crate::utils::cli_safety::reject_flag_like;
 use crate::utils::create_command;
 
@@ -66,6 +72,47 @@ fn run_git_command(repo_path: &Path, args: &[&str]) -> Result<String> {
     }
 }
 
+/// Classify a submodule the way the list and the tree both show it.
+fn submodule_state(repo_path: &Path, submodule: &git2::Submodule) -> SubmoduleStatus {
+    match submodule.open() {
+        Ok(sub_repo) => {
+            // Submodule is initialized
+            let head_id = sub_repo.head().ok().and_then(|h| h.target());
+            let index_id = submodule.index_id();
+
+            if head_id != index_id {
+                SubmoduleStatus::Modified
+            } else if is_worktree_dirty(&sub_repo) {
+                SubmoduleStatus::Dirty
+            } else {
+                SubmoduleStatus::Current
+            }
+        }
+        Err(_) => {
+            // Check if path exists
+            let full_path = repo_path.join(submodule.path());
+            if full_path.exists() {
+                SubmoduleStatus::Uninitialized
+            } else {
+                SubmoduleStatus::Missing
+            }
+        }
+    }
+}
+
+/// Whether a submodule checkout has local changes, untracked files included.
+fn is_worktree_dirty(sub_repo: &git2::Repository) -> bool {
+    let statuses = sub_repo.statuses(Some(
+        git2::StatusOptions::new()
+            .include_untracked(true)
+            .recurse_untracked_dirs(false),
+    ));
+    match statuses {
+        Ok(statuses) => statuses.iter().any(|s| !s.status().is_empty()),
+        Err(_) => false,
+    }
+}
+
 /// Get list of submodules in the repository
 #[command]
 pub async fn get_submodules(path: String) -> Result<Vec<Submodule>> {
@@ -81,44 +128,7 @@ pub async fn get_submodules(path: String) -> Result<Vec<Submodule>> {
         let url = submodule.url().ok().flatten().map(|s| s.to_string());
         let branch = submodule.branch().ok().flatten().map(|s| s.to_string());
 
-        // Determine status
-        let status = match submodule.open() {
-            Ok(sub_repo) => {
-                // Submodule is initialized
-                let head_id = sub_repo.head().ok().and_then(|h| h.target());
-                let index_id = submodule.index_id();
-
-                if head_id != index_id {
-                    SubmoduleStatus::Modified
-                } else {
-                    // Check for local changes
-                    let statuses = sub_repo.statuses(Some(
-                        git2::StatusOptions::new()
-                            .include_untracked(true)
-                            .recurse_untracked_dirs(false),
-                    ));
-
-                    if let Ok(statuses) = statuses {
-                        if statuses.iter().any(|s| !s.status().is_empty()) {
-                            SubmoduleStatus::Dirty
-                        } else {
-                            SubmoduleStatus::Current
-                        }
-                    } else {
-                        SubmoduleStatus::Current
-                    }
-                }
-            }
-            Err(_) => {
-                // Check if path exists
-                let full_path = repo_path.join(submodule.path());
-                if full_path.exists() {
-                    SubmoduleStatus::Uninitialized
-                } else {
-                    SubmoduleStatus::Missing
-                }
-            }
-        };
+        let status = submodule_state(repo_path, &submodule);
 
         let initialized = matches!(
             status,
@@ -388,6 +398,475 @@ pub async fn submodule_foreach(
     run_git_command(repo_path, &args)
 }
 
+/// A submodule in the recursive tree, with its nested submodules
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleNode {
+    pub name: String,
+    /// Path relative to its own superproject
+    pub path: String,
+    /// Path relative to the top-level repository, e.g. `deps/lib/vendor/x`
+    pub display_path: String,
+    pub url: Option<String>,
+    /// Branch configured in .gitmodules for `update --remote`
+    pub branch: Option<String>,
+    pub status: SubmoduleStatus,
+    /// Commit recorded in the superproject's HEAD
+    pub recorded_oid: Option<String>,
+    /// Commit checked out in the submodule
+    pub head_oid: Option<String>,
+    /// Checked-out commit relative to the recorded one (ahead = new commits
+    /// a pointer commit would record, behind = the checkout is older)
+    pub pointer_ahead_behind: Option<AheadBehind>,
+    /// Whether the submodule has uncommitted changes or untracked files
+    pub is_dirty: bool,
+    /// Whether HEAD is detached, as `git submodule update` leaves it
+    pub is_detached: bool,
+    /// Local branch checked out, when not detached
+    pub head_branch: Option<String>,
+    /// Remote-tracking ref the checkout is compared with, e.g. `origin/main`
+    pub remote_ref: Option<String>,
+    /// Ahead/behind relative to `remote_ref`
+    pub ahead_behind: Option<AheadBehind>,
+    pub children: Vec<SubmoduleNode>,
+}
+
+/// The remote-tracking ref a submodule checkout is measured against: the
+/// branch .gitmodules tracks, else the checked-out branch's upstream, else
+/// the remote's default branch.
+fn submodule_remote_ref(
+    sub_repo: &git2::Repository,
+    tracked: Option<&str>,
+    head_branch: Option<&str>,
+) -> Option<(String, git2::Oid)> {
+    let resolve = |refname: &str| {
+        sub_repo
+            .find_reference(refname)
+            .ok()
+            .and_then(|r| r.resolve().ok())
+            .and_then(|r| r.target())
+    };
+    // "." means "same name as the superproject's branch", which only makes
+    // sense during `update --remote`; fall through to the other sources.
+    if let Some(branch) = tracked.filter(|b| *b != ".") {
+        let name = format!("origin/{}", branch);
+        if let Some(oid) = resolve(&format!("refs/remotes/{}", name)) {
+            return Some((name, oid));
+        }
+    }
+    if let Some(branch) = head_branch {
+        if let Ok(local) = sub_repo.find_branch(branch, git2::BranchType::Local) {
+            if let Ok(upstream) = local.upstream() {
+                if let (Ok(Some(name)), Some(oid)) = (upstream.name(), upstream.get().target()) {
+                    return Some((name.to_string(), oid));
+                }
+            }
+        }
+    }
+    let head = sub_repo.find_reference("refs/remotes/origin/HEAD").ok()?;
+    let target = head.symbolic_target().ok().flatten()?.to_string();
+    let name = target
+        .strip_prefix("refs/remotes/")
+        .unwrap_or(&target)
+        .to_string();
+    resolve(&target).map(|oid| (name, oid))
+}
+
+/// Build the nodes for the submodules of `repo`, recursing into every
+/// initialized one. `prefix` is `repo`'s path relative to the top level.
+fn submodule_nodes(repo: &git2::Repository, prefix: &str) -> Result<Vec<SubmoduleNode>> {
+    let Some(workdir) = repo.workdir() else {
+        return Ok(Vec::new());
+    };
+    let mut nodes = Vec::new();
+    for submodule in repo.submodules()? {
+        let path = submodule.path().to_string_lossy().to_string();
+        let display_path = if prefix.is_empty() {
+            path.clone()
+        } else {
+            format!("{}/{}", prefix, path)
+        };
+        let branch = submodule.branch().ok().flatten().map(|s| s.to_string());
+        let recorded = submodule.head_id();
+        let mut node = SubmoduleNode {
+            name: submodule.name().ok().unwrap_or("").to_string(),
+            path,
+            display_path,
+            url: submodule.url().ok().flatten().map(|s| s.to_string()),
+            branch,
+            status: submodule_state(workdir, &submodule),
+            recorded_oid: recorded.map(|oid| oid.to_string()),
+            head_oid: None,
+            pointer_ahead_behind: None,
+            is_dirty: false,
+            is_detached: false,
+            head_branch: None,
+            remote_ref: None,
+            ahead_behind: None,
+            children: Vec::new(),
+        };
+
+        if let Ok(sub_repo) = submodule.open() {
+            let head_oid = sub_repo.head().ok().and_then(|h| h.target());
+            node.head_oid = head_oid.map(|oid| oid.to_string());
+            node.is_detached = sub_repo.head_detached().unwrap_or(false);
+            if !node.is_detached {
+                node.head_branch = sub_repo
+                    .head()
+                    .ok()
+                    .and_then(|h| h.shorthand().ok().map(|s| s.to_string()));
+            }
+            node.is_dirty = is_worktree_dirty(&sub_repo);
+
+            if let Some(head_oid) = head_oid {
+                // The recorded commit may not have been fetched into the
+                // submodule yet; then there is nothing to count against.
+                node.pointer_ahead_behind = recorded
+                    .and_then(|rec| sub_repo.graph_ahead_behind(head_oid, rec).ok())
+                    .map(|(ahead, behind)| AheadBehind { ahead, behind });
+                if let Some((name, remote_oid)) = submodule_remote_ref(
+                    &sub_repo,
+                    node.branch.as_deref(),
+                    node.head_branch.as_deref(),
+                ) {
+                    node.remote_ref = Some(name);
+                    node.ahead_behind = sub_repo
+                        .graph_ahead_behind(head_oid, remote_oid)
+                        .ok()
+                        .map(|(ahead, behind)| AheadBehind { ahead, behind });
+                }
+            }
+
+            node.children = submodule_nodes(&sub_repo, &node.display_path)?;
+        }
+        nodes.push(node);
+    }
+    Ok(nodes)
+}
+
+/// Get the recursive tree of submodules
+///
+/// Each node compares the checked-out commit with the one the superproject
+/// records and with its remote-tracking branch, and reports local changes
+/// and detached HEAD. Uninitialized submodules are listed without children,
+/// since their own .gitmodules is not on disk yet.
+#[command]
+pub async fn get_submodule_tree(path: String) -> Result<Vec<SubmoduleNode>> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    submodule_nodes(&repo, "")
+}
+
+/// Where an initialized or registered submodule lives, for running commands.
+struct SubmoduleLocation {
+    name: String,
+    /// Working directory of its superproject
+    parent: PathBuf,
+    /// Path relative to `parent`
+    path: String,
+    /// Path relative to the top level
+    display_path: String,
+}
+
+/// Flatten the tree into locations, parents before their children (the
+/// order `git submodule foreach --recursive` uses).
+fn submodule_locations(root: &Path, nodes: &[SubmoduleNode]) -> Vec<SubmoduleLocation> {
+    fn walk(parent: &Path, nodes: &[SubmoduleNode], out: &mut Vec<SubmoduleLocation>) {
+        for node in nodes {
+            out.push(SubmoduleLocation {
+                name: node.name.clone(),
+                parent: parent.to_path_buf(),
+                path: node.path.clone(),
+                display_path: node.display_path.clone(),
+            });
+            walk(&parent.join(&node.path), &node.children, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(root, nodes, &mut out);
+    out
+}
+
+/// Pick the locations named by `selection` (display paths), or every
+/// location when there is no selection. Unknown paths are an error rather
+/// than silently doing less than asked.
+fn select_locations(
+    all: Vec<SubmoduleLocation>,
+    selection: Option<&[String]>,
+) -> Result<Vec<SubmoduleLocation>> {
+    let Some(selection) = selection else {
+        return Ok(all);
+    };
+    for wanted in selection {
+        if !all.iter().any(|l| &l.display_path == wanted) {
+            return Err(LeviathanError::OperationFailed(format!(
+                "No submodule at '{}'",
+                wanted
+            )));
+        }
+    }
+    Ok(all
+        .into_iter()
+        .filter(|l| selection.contains(&l.display_path))
+        .collect())
+}
+
+/// What a batch update moves each submodule to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SubmoduleUpdateTarget {
+    /// The commit the superproject records (`git submodule update`)
+    Recorded,
+    /// The tip of the remote branch (`git submodule update --remote`)
+    Remote,
+}
+
+/// Outcome of a batch operation for one submodule
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleOperationResult {
+    /// Path relative to the top level
+    pub path: String,
+    pub success: bool,
+    /// git's output, or its error
+    pub message: String,
+    /// Commit checked out afterwards
+    pub head_oid: Option<String>,
+}
+
+/// Update several submodules, reporting each one separately
+///
+/// `submodule_paths` are top-level-relative paths from the tree, nested ones
+/// included; without them every top-level submodule is updated (and with
+/// `recursive`, everything below). Each submodule is updated on its own, so
+/// one that fails (an unreachable remote, a commit that was never pushed)
+/// does not stop the rest, and the UI can show exactly which ones need
+/// attention. Uninitialized submodules are initialized first.
+#[command]
+pub async fn batch_update_submodules(
+    path: String,
+    submodule_paths: Option<Vec<String>>,
+    target: SubmoduleUpdateTarget,
+    recursive: Option<bool>,
+) -> Result<Vec<SubmoduleOperationResult>> {
+    let root = Path::new(&path);
+    let recursive = recursive.unwrap_or(false);
+    if let Some(paths) = &submodule_paths {
+        for p in paths {
+            reject_flag_like(p, "Submodule path")?;
+        }
+    }
+
+    let repo = git2::Repository::open(root)?;
+    let tree = submodule_nodes(&repo, "")?;
+    let locations = match &submodule_paths {
+        Some(paths) => select_locations(submodule_locations(root, &tree), Some(paths))?,
+        // Top level only: `--recursive` on each reaches the rest, and listing
+        // nested ones as well would update them twice.
+        None => submodule_locations(root, &tree)
+            .into_iter()
+            .filter(|l| l.parent == root)
+            .collect(),
+    };
+
+    let mut results = Vec::new();
+    for location in locations {
+        let mut args = vec!["submodule", "update", "--init"];
+        if target == SubmoduleUpdateTarget::Remote {
+            args.push("--remote");
+        }
+        if recursive {
+            args.push("--recursive");
+        }
+        args.push("--");
+        args.push(&location.path);
+
+        let outcome = run_git_command(&location.parent, &args);
+        let head_oid = git2::Repository::open(location.parent.join(&location.path))
+            .ok()
+            .and_then(|r| r.head().ok().and_then(|h| h.target()))
+            .map(|oid| oid.to_string());
+        results.push(match outcome {
+            Ok(message) => SubmoduleOperationResult {
+                path: location.display_path,
+                success: true,
+                message,
+                head_oid,
+            },
+            Err(e) => SubmoduleOperationResult {
+                path: location.display_path,
+                success: false,
+                message: e.to_string(),
+                head_oid,
+            },
+        });
+    }
+    Ok(results)
+}
+
+/// Commit the submodules' checked-out commits as their new pointers
+///
+/// Only the gitlinks are committed (`git commit --only`), so anything else
+/// staged in the superproject stays staged. `submodule_paths` are relative
+/// to `path`, whose direct submodules are the only ones it can record; to
+/// commit a nested pointer, pass that submodule's superproject as `path`.
+/// Without a message, one is generated listing each pointer's old and new
+/// commit. Returns the new commit's id.
+#[command]
+pub async fn commit_submodule_pointers(
+    path: String,
+    submodule_paths: Option<Vec<String>>,
+    message: Option<String>,
+) -> Result<String> {
+    let repo_path = Path::new(&path);
+    let repo = git2::Repository::open(repo_path)?;
+    if let Some(selection) = &submodule_paths {
+        for wanted in selection {
+            reject_flag_like(wanted, "Submodule path")?;
+            if repo.find_submodule(wanted).is_err() {
+                return Err(LeviathanError::OperationFailed(format!(
+                    "No submodule at '{}'",
+                    wanted
+                )));
+            }
+        }
+    }
+
+    let mut changed: Vec<(String, Option<git2::Oid>, git2::Oid)> = Vec::new();
+    for submodule in repo.submodules()? {
+        let sm_path = submodule.path().to_string_lossy().to_string();
+        if let Some(selection) = &submodule_paths {
+            if !selection.contains(&sm_path) {
+                continue;
+            }
+        }
+        let Some(head) = submodule
+            .open()
+            .ok()
+            .and_then(|r| r.head().ok().and_then(|h| h.target()))
+        else {
+            continue;
+        };
+        if submodule.head_id() != Some(head) {
+            changed.push((sm_path, submodule.head_id(), head));
+        }
+    }
+    if changed.is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "No submodule pointers have changed".to_string(),
+        ));
+    }
+
+    let short = |oid: &git2::Oid| oid.to_string()[..7].to_string();
+    let message = message.filter(|m| !m.trim().is_empty()).unwrap_or_else(|| {
+        let subject = if changed.len() == 1 {
+            format!("Update submodule {}", changed[0].0)
+        } else {
+            format!("Update {} submodules", changed.len())
+        };
+        let body: Vec<String> = changed
+            .iter()
+            .map(|(p, old, new)| match old {
+                Some(old) => format!("- {}: {}..{}", p, short(old), short(new)),
+                None => format!("- {}: {}", p, short(new)),
+            })
+            .collect();
+        format!("{}\n\n{}", subject, body.join("\n"))
+    });
+
+    let mut args: Vec<&str> = vec!["commit", "-m", &message, "--only", "--"];
+    for (p, _, _) in &changed {
+        args.push(p);
+    }
+    run_git_command(repo_path, &args)?;
+    run_git_command(repo_path, &["rev-parse", "HEAD"])
+}
+
+/// Output of a foreach command in one submodule
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleForeachResult {
+    pub name: String,
+    /// Path relative to the top level
+    pub path: String,
+    /// None when the command could not be started or was killed by a signal
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Run a command in each initialized submodule, with one result per submodule
+///
+/// Unlike [`submodule_foreach`], a failure in one submodule does not stop the
+/// rest and the output is not interleaved. The command sees the same
+/// variables `git submodule foreach` provides: `name`, `sm_path`,
+/// `displaypath`, `sha1` and `toplevel`.
+#[command]
+pub async fn submodule_foreach_results(
+    path: String,
+    command: String,
+    recursive: Option<bool>,
+) -> Result<Vec<SubmoduleForeachResult>> {
+    let root = Path::new(&path);
+    let repo = git2::Repository::open(root)?;
+    let tree = submodule_nodes(&repo, "")?;
+    let recursive = recursive.unwrap_or(false);
+
+    let mut results = Vec::new();
+    for location in submodule_locations(root, &tree) {
+        if !recursive && location.parent != root {
+            continue;
+        }
+        let dir = location.parent.join(&location.path);
+        let Ok(sub_repo) = git2::Repository::open(&dir) else {
+            continue; // not initialized, as git foreach skips it
+        };
+        let sha1 = sub_repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .map(|oid| oid.to_string())
+            .unwrap_or_default();
+
+        let mut cmd = if cfg!(target_os = "windows") {
+            let mut cmd = create_command("cmd");
+            cmd.args(["/C", &command]);
+            cmd
+        } else {
+            let mut cmd = create_command("sh");
+            cmd.args(["-c", &command]);
+            cmd
+        };
+        cmd.current_dir(&dir)
+            .env("name", &location.name)
+            .env("sm_path", &location.path)
+            .env("displaypath", &location.display_path)
+            .env("sha1", &sha1)
+            .env("toplevel", &location.parent)
+            .stdin(std::process::Stdio::null());
+
+        results.push(match cmd.output() {
+            Ok(output) => SubmoduleForeachResult {
+                name: location.name,
+                path: location.display_path,
+                exit_code: output.status.code(),
+                success: output.status.success(),
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            },
+            Err(e) => SubmoduleForeachResult {
+                name: location.name,
+                path: location.display_path,
+                exit_code: None,
+                success: false,
+                stdout: String::new(),
+                stderr: format!("Failed to run command: {}", e),
+            },
+        });
+    }
+    Ok(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -735,4 +1214,201 @@ mod tests {
             err
         );
     }
+
+    /// leaf -> middle -> superproject, cloned recursively. The leaf's source
+    /// repo is returned so tests can move its branch.
+    struct NestedFixture {
+        leaf: TestRepo,
+        _middle: TestRepo,
+        top: TestRepo,
+    }
+
+    fn nested_fixture() -> NestedFixture {
+        let leaf = TestRepo::with_initial_commit();
+        let middle = TestRepo::with_initial_commit();
+        git_in(
+            &middle.path,
+            &["submodule", "add", &leaf.path_str(), "vendor/leaf"],
+        );
+        git_in(&middle.path, &["commit", "-m", "add leaf"]);
+
+        let top = TestRepo::with_initial_commit();
+        git_in(
+            &top.path,
+            &["submodule", "add", &middle.path_str(), "deps/middle"],
+        );
+        git_in(&top.path, &["commit", "-m", "add middle"]);
+        git_in(&top.path, &["submodule", "update", "--init", "--recursive"]);
+        NestedFixture {
+            leaf,
+            _middle: middle,
+            top,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_submodule_tree_is_recursive() {
+        let fx = nested_fixture();
+
+        let tree = get_submodule_tree(fx.top.path_str()).await.unwrap();
+        assert_eq!(tree.len(), 1);
+        let middle = &tree[0];
+        assert_eq!(middle.display_path, "deps/middle");
+        assert!(matches!(middle.status, SubmoduleStatus::Current));
+        assert_eq!(middle.head_oid, middle.recorded_oid);
+        assert!(!middle.is_dirty);
+
+        assert_eq!(middle.children.len(), 1);
+        let leaf = &middle.children[0];
+        assert_eq!(leaf.path, "vendor/leaf");
+        assert_eq!(leaf.display_path, "deps/middle/vendor/leaf");
+        assert!(leaf.is_detached, "submodule update leaves HEAD detached");
+        assert_eq!(leaf.remote_ref.as_deref(), Some("origin/main"));
+        assert_eq!(
+            leaf.ahead_behind.as_ref().map(|ab| (ab.ahead, ab.behind)),
+            Some((0, 0))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_submodule_tree_reports_moved_pointer_and_dirt() {
+        let fx = nested_fixture();
+        let middle_path = fx.top.path.join("deps/middle");
+        git_in(&middle_path, &["config", "user.email", "test@example.com"]);
+        git_in(&middle_path, &["config", "user.name", "Test User"]);
+        std::fs::write(middle_path.join("new.txt"), "x").unwrap();
+        git_in(&middle_path, &["add", "new.txt"]);
+        git_in(&middle_path, &["commit", "-m", "local"]);
+        std::fs::write(middle_path.join("loose.txt"), "x").unwrap();
+
+        let tree = get_submodule_tree(fx.top.path_str()).await.unwrap();
+        let middle = &tree[0];
+        assert!(matches!(middle.status, SubmoduleStatus::Modified));
+        assert!(middle.is_dirty);
+        assert_eq!(
+            middle
+                .pointer_ahead_behind
+                .as_ref()
+                .map(|ab| (ab.ahead, ab.behind)),
+            Some((1, 0))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_update_to_recorded_commit() {
+        let fx = nested_fixture();
+        let leaf_path = fx.top.path.join("deps/middle/vendor/leaf");
+        let recorded = git_in(&leaf_path, &["rev-parse", "HEAD"]);
+        fx.leaf.create_commit("upstream work", &[("u.txt", "u\n")]);
+        git_in(&leaf_path, &["fetch", "origin"]);
+        git_in(&leaf_path, &["checkout", "--detach", "origin/main"]);
+
+        let results = batch_update_submodules(
+            fx.top.path_str(),
+            Some(vec!["deps/middle/vendor/leaf".to_string()]),
+            SubmoduleUpdateTarget::Recorded,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].success, "{}", results[0].message);
+        assert_eq!(results[0].head_oid.as_deref(), Some(recorded.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_batch_update_to_remote_then_commit_pointer() {
+        let fx = nested_fixture();
+        let middle_path = fx.top.path.join("deps/middle");
+        let leaf_path = middle_path.join("vendor/leaf");
+        git_in(&leaf_path, &["config", "protocol.file.allow", "always"]);
+        let new_tip = fx
+            .leaf
+            .create_commit("upstream work", &[("u.txt", "u\n")])
+            .to_string();
+
+        let results = batch_update_submodules(
+            middle_path.to_string_lossy().to_string(),
+            None,
+            SubmoduleUpdateTarget::Remote,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].success, "{}", results[0].message);
+        assert_eq!(results[0].head_oid.as_deref(), Some(new_tip.as_str()));
+
+        // Something unrelated is staged; it must not ride along.
+        git_in(&middle_path, &["config", "user.email", "test@example.com"]);
+        git_in(&middle_path, &["config", "user.name", "Test User"]);
+        std::fs::write(middle_path.join("other.txt"), "x").unwrap();
+        git_in(&middle_path, &["add", "other.txt"]);
+
+        let commit =
+            commit_submodule_pointers(middle_path.to_string_lossy().to_string(), None, None)
+                .await
+                .unwrap();
+
+        let message = git_in(&middle_path, &["log", "-1", "--format=%B", &commit]);
+        assert!(
+            message.starts_with("Update submodule vendor/leaf"),
+            "{}",
+            message
+        );
+        let recorded = git_in(&middle_path, &["rev-parse", "HEAD:vendor/leaf"]);
+        assert_eq!(recorded, new_tip);
+        let staged = git_in(&middle_path, &["diff", "--cached", "--name-only"]);
+        assert_eq!(staged, "other.txt");
+    }
+
+    #[tokio::test]
+    async fn test_commit_submodule_pointers_without_changes_fails() {
+        let fx = nested_fixture();
+        let result = commit_submodule_pointers(fx.top.path_str(), None, None).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_update_rejects_unknown_path() {
+        let fx = nested_fixture();
+        let result = batch_update_submodules(
+            fx.top.path_str(),
+            Some(vec!["nope".to_string()]),
+            SubmoduleUpdateTarget::Recorded,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_submodule_foreach_results_per_submodule() {
+        let fx = nested_fixture();
+
+        let shallow =
+            submodule_foreach_results(fx.top.path_str(), "echo $displaypath".to_string(), None)
+                .await
+                .unwrap();
+        assert_eq!(shallow.len(), 1);
+
+        let results = submodule_foreach_results(
+            fx.top.path_str(),
+            "echo $displaypath; test \"$name\" != vendor/leaf".to_string(),
+            Some(true),
+        )
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].path, "deps/middle");
+        assert!(results[0].success);
+        assert_eq!(results[0].stdout.trim(), "deps/middle");
+        assert_eq!(results[1].path, "deps/middle/vendor/leaf");
+        assert!(
+            !results[1].success,
+            "a failure is reported for its submodule only"
+        );
+        assert_eq!(results[1].exit_code, Some(1));
+    }
 }
@@ -0,0 +1,640 @@
+//! Subtree command handlers
+//! Vendor other repositories into a directory with `git subtree`
+//!
+//! git keeps no registry of subtrees: the only record is the
+//! `git-subtree-dir`/`git-subtree-split` trailers `git subtree` writes into
+//! the commits it creates, so existing subtrees are detected from history.
+//! The upstream a subtree came from is not in those trailers at all; add and
+//! pull remember it in the repository config (`subtree.<prefix>.url` and
+//! `subtree.<prefix>.ref`) so later pulls and pushes can default to it.
+
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::command;
+
+use crate::error::{LeviathanError, Result};
+use crate::utils::cli_safety::reject_flag_like;
+use crate::utils::create_command;
+
+/// A subtree found in the repository
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtreeInfo {
+    /// Directory the subtree lives in, without a trailing slash
+    pub prefix: String,
+    /// Upstream repository, when Leviathan recorded it on add or pull
+    pub url: Option<String>,
+    /// Upstream branch or ref, when recorded
+    pub git_ref: Option<String>,
+    /// Upstream commit the subtree was last synced with (`git-subtree-split`)
+    pub last_split: Option<String>,
+    /// Local commit that recorded the last sync
+    pub last_sync_commit: Option<String>,
+    /// Commit time of `last_sync_commit` (unix seconds)
+    pub last_sync_time: Option<i64>,
+    /// Whether the last sync was squashed into a single commit
+    pub squashed: bool,
+    /// Whether the prefix directory exists at HEAD
+    pub exists: bool,
+}
+
+/// A commit a split would extract
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtreeSplitCommit {
+    pub oid: String,
+    pub summary: String,
+    pub author: String,
+    pub time: i64,
+    /// Files under the prefix this commit changes, relative to the prefix
+    pub files: Vec<String>,
+}
+
+/// What `git subtree split` (and so `push`) would extract for a prefix
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubtreeSplitPreview {
+    pub prefix: String,
+    /// The local commit of the last sync; commits before it are already
+    /// upstream. None when the subtree was never synced through git subtree.
+    pub since_commit: Option<String>,
+    /// Commits touching the prefix since the last sync, newest first
+    pub commits: Vec<SubtreeSplitCommit>,
+}
+
+/// Helper to run git commands
+fn run_git_command(repo_path: &Path, args: &[&str]) -> Result<String> {
+    let output = create_command("git")
+        .current_dir(repo_path)
+        // git subtree reports refusals ("Working tree has modifications",
+        // "prefix 'x' already exists") as plain text the UI shows as is.
+        .env("LC_ALL", "C")
+        .args(args)
+        .output()
+        .map_err(|e| LeviathanError::OperationFailed(format!("Failed to run git: {}", e)))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    if output.status.success() {
+        Ok(stdout.trim().to_string())
+    } else {
+        Err(LeviathanError::OperationFailed(
+            if stderr.trim().is_empty() {
+                stdout
+            } else {
+                stderr
+            }
+            .trim()
+            .to_string(),
+        ))
+    }
+}
+
+/// `vendor/lib/` and `./vendor/lib` name the same subtree as `vendor/lib`.
+fn normalize_prefix(prefix: &str) -> Result<String> {
+    let normalized = prefix
+        .trim()
+        .trim_start_matches("./")
+        .trim_end_matches('/')
+        .to_string();
+    if normalized.is_empty() {
+        return Err(LeviathanError::OperationFailed(
+            "Subtree prefix is required".to_string(),
+        ));
+    }
+    reject_flag_like(&normalized, "Subtree prefix")?;
+    Ok(normalized)
+}
+
+/// The newest trailer-carrying commit seen for one prefix.
+struct SubtreeSync {
+    commit: String,
+    time: i64,
+    split: Option<String>,
+    squashed: bool,
+}
+
+/// Scan HEAD's history for the commits `git subtree` wrote, keeping the newest
+/// per prefix.
+fn scan_subtree_trailers(repo_path: &Path) -> Result<HashMap<String, SubtreeSync>> {
+    // An unborn HEAD has no history to scan.
+    if run_git_command(repo_path, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+        return Ok(HashMap::new());
+    }
+    let log = run_git_command(
+        repo_path,
+        &[
+            "log",
+            "--grep=^git-subtree-dir:",
+            "--format=%H%x00%ct%x00%B%x1e",
+            "HEAD",
+        ],
+    )?;
+
+    let mut found: HashMap<String, SubtreeSync> = HashMap::new();
+    for record in log.split('\x1e') {
+        let mut fields = record.trim_start_matches('\n').splitn(3, '\0');
+        let (Some(oid), Some(time), Some(body)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let trailer = |key: &str| {
+            body.lines()
+                .rev()
+                .find_map(|l| l.strip_prefix(key).map(|v| v.trim().to_string()))
+        };
+        let Some(dir) = trailer("git-subtree-dir:") else {
+            continue;
+        };
+        let dir = dir.trim_end_matches('/').to_string();
+        // `git log` lists newest first; the first commit seen for a prefix is
+        // its last sync.
+        found.entry(dir).or_insert_with(|| SubtreeSync {
+            commit: oid.to_string(),
+            time: time.trim().parse().unwrap_or(0),
+            split: trailer("git-subtree-split:"),
+            squashed: body.starts_with("Squashed '"),
+        });
+    }
+    Ok(found)
+}
+
+/// The upstream recorded for `prefix` by an earlier add or pull.
+fn recorded_upstream(repo: &git2::Repository, prefix: &str) -> (Option<String>, Option<String>) {
+    let Ok(config) = repo.config() else {
+        return (None, None);
+    };
+    let get = |key: &str| {
+        config
+            .get_string(&format!("subtree.{}.{}", prefix, key))
+            .ok()
+            .filter(|v| !v.is_empty())
+    };
+    (get("url"), get("ref"))
+}
+
+/// Remember where a subtree comes from, so pull and push can default to it.
+fn record_upstream(repo_path: &Path, prefix: &str, url: &str, git_ref: &str) -> Result<()> {
+    let repo = git2::Repository::open(repo_path)?;
+    let mut config = repo.config()?.open_level(git2::ConfigLevel::Local)?;
+    config.set_str(&format!("subtree.{}.url", prefix), url)?;
+    config.set_str(&format!("subtree.{}.ref", prefix), git_ref)?;
+    Ok(())
+}
+
+/// Prefixes with a recorded upstream, from `subtree.<prefix>.url` keys.
+fn recorded_prefixes(repo: &git2::Repository) -> Vec<String> {
+    let mut prefixes = Vec::new();
+    let Ok(config) = repo.config() else {
+        return prefixes;
+    };
+    if let Ok(entries) = config.entries(Some(r"^subtree\..*\.url$")) {
+        let _ = entries.for_each(|entry| {
+            if let Some(prefix) = entry
+                .name()
+                .ok()
+                .and_then(|name| name.strip_prefix("subtree."))
+                .and_then(|rest| rest.strip_suffix(".url"))
+            {
+                prefixes.push(prefix.to_string());
+            }
+        });
+    }
+    prefixes
+}
+
+/// List the subtrees in the repository
+///
+/// Subtrees are found from the trailers in HEAD's history; ones Leviathan
+/// recorded an upstream for are listed even before their first sync shows up
+/// in history.
+#[command]
+pub async fn list_subtrees(path: String) -> Result<Vec<SubtreeInfo>> {
+    let repo_path = Path::new(&path);
+    let repo = git2::Repository::open(repo_path)?;
+    let mut syncs = scan_subtree_trailers(repo_path)?;
+
+    let mut prefixes: Vec<String> = syncs.keys().cloned().collect();
+    for prefix in recorded_prefixes(&repo) {
+        if !prefixes.contains(&prefix) {
+            prefixes.push(prefix);
+        }
+    }
+    prefixes.sort();
+
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    Ok(prefixes
+        .into_iter()
+        .map(|prefix| {
+            let sync = syncs.remove(&prefix);
+            let (url, git_ref) = recorded_upstream(&repo, &prefix);
+            let exists = head_tree
+                .as_ref()
+                .and_then(|t| t.get_path(Path::new(&prefix)).ok())
+                .is_some();
+            SubtreeInfo {
+                url,
+                git_ref,
+                last_split: sync.as_ref().and_then(|s| s.split.clone()),
+                last_sync_commit: sync.as_ref().map(|s| s.commit.clone()),
+                last_sync_time: sync.as_ref().map(|s| s.time),
+                squashed: sync.as_ref().map(|s| s.squashed).unwrap_or(false),
+                exists,
+                prefix,
+            }
+        })
+        .collect())
+}
+
+/// Look up one subtree after an operation changed it.
+async fn find_subtree(path: String, prefix: &str) -> Result<SubtreeInfo> {
+    list_subtrees(path)
+        .await?
+        .into_iter()
+        .find(|s| s.prefix == prefix)
+        .ok_or_else(|| LeviathanError::OperationFailed(format!("No subtree at '{}'", prefix)))
+}
+
+/// Resolve the repository and ref for pull/push: explicit values win, then
+/// what add or pull recorded.
+fn upstream_or_recorded(
+    repo_path: &Path,
+    prefix: &str,
+    repository: Option<String>,
+    git_ref: Option<String>,
+) -> Result<(String, String)> {
+    let repo = git2::Repository::open(repo_path)?;
+    let (recorded_url, recorded_ref) = recorded_upstream(&repo, prefix);
+    let url = repository.or(recorded_url).ok_or_else(|| {
+        LeviathanError::OperationFailed(format!(
+            "No upstream repository recorded for subtree '{}'",
+            prefix
+        ))
+    })?;
+    let git_ref = git_ref.or(recorded_ref).ok_or_else(|| {
+        LeviathanError::OperationFailed(format!(
+            "No upstream branch recorded for subtree '{}'",
+            prefix
+        ))
+    })?;
+    reject_flag_like(&url, "Subtree repository")?;
+    reject_flag_like(&git_ref, "Subtree ref")?;
+    Ok((url, git_ref))
+}
+
+/// Add a repository as a subtree under `prefix`
+#[command]
+pub async fn subtree_add(
+    path: String,
+    prefix: String,
+    repository: String,
+    git_ref: String,
+    squash: Option<bool>,
+    message: Option<String>,
+) -> Result<SubtreeInfo> {
+    let repo_path = Path::new(&path);
+    let prefix = normalize_prefix(&prefix)?;
+    reject_flag_like(&repository, "Subtree repository")?;
+    reject_flag_like(&git_ref, "Subtree ref")?;
+
+    let prefix_arg = format!("--prefix={}", prefix);
+    let mut args = vec!["subtree", "add", prefix_arg.as_str()];
+    if squash.unwrap_or(false) {
+        args.push("--squash");
+    }
+    if let Some(m) = message.as_deref().filter(|m| !m.trim().is_empty()) {
+        args.push("-m");
+        args.push(m);
+    }
+    args.push(&repository);
+    args.push(&git_ref);
+
+    run_git_command(repo_path, &args)?;
+    record_upstream(repo_path, &prefix, &repository, &git_ref)?;
+    find_subtree(path, &prefix).await
+}
+
+/// Pull upstream changes into a subtree
+///
+/// `repository` and `git_ref` default to what add or an earlier pull
+/// recorded; explicit values are recorded for next time.
+#[command]
+pub async fn subtree_pull(
+    path: String,
+    prefix: String,
+    repository: Option<String>,
+    git_ref: Option<String>,
+    squash: Option<bool>,
+    message: Option<String>,
+) -> Result<SubtreeInfo> {
+    let repo_path = Path::new(&path);
+    let prefix = normalize_prefix(&prefix)?;
+    let (url, git_ref) = upstream_or_recorded(repo_path, &prefix, repository, git_ref)?;
+
+    let prefix_arg = format!("--prefix={}", prefix);
+    let mut args = vec!["subtree", "pull", prefix_arg.as_str()];
+    if squash.unwrap_or(false) {
+        args.push("--squash");
+    }
+    if let Some(m) = message.as_deref().filter(|m| !m.trim().is_empty()) {
+        args.push("-m");
+        args.push(m);
+    }
+    args.push(&url);
+    args.push(&git_ref);
+
+    run_git_command(repo_path, &args)?;
+    record_upstream(repo_path, &prefix, &url, &git_ref)?;
+    find_subtree(path, &prefix).await
+}
+
+/// Push a subtree's local changes back to its upstream
+///
+/// Splits the prefix's history and pushes the result to `git_ref` on
+/// `repository`, both defaulting to the recorded upstream. Returns git's
+/// output.
+#[command]
+pub async fn subtree_push(
+    path: String,
+    prefix: String,
+    repository: Option<String>,
+    git_ref: Option<String>,
+) -> Result<String> {
+    let repo_path = Path::new(&path);
+    let prefix = normalize_prefix(&prefix)?;
+    let (url, git_ref) = upstream_or_recorded(repo_path, &prefix, repository, git_ref)?;
+
+    let prefix_arg = format!("--prefix={}", prefix);
+    run_git_command(
+        repo_path,
+        &["subtree", "push", prefix_arg.as_str(), &url, &git_ref],
+    )
+}
+
+/// Extract a prefix's history into a standalone commit chain
+///
+/// With `branch`, the split is also stored as that branch; `rejoin` merges
+/// the split back so later splits can start from it. Returns the split's
+/// tip commit.
+#[command]
+pub async fn subtree_split(
+    path: String,
+    prefix: String,
+    branch: Option<String>,
+    rejoin: Option<bool>,
+) -> Result<String> {
+    let repo_path = Path::new(&path);
+    let prefix = normalize_prefix(&prefix)?;
+    if let Some(b) = &branch {
+        reject_flag_like(b, "Branch")?;
+    }
+
+    let prefix_arg = format!("--prefix={}", prefix);
+    let mut args = vec!["subtree", "split", prefix_arg.as_str()];
+    if let Some(b) = branch.as_deref() {
+        args.push("-b");
+        args.push(b);
+    }
+    if rejoin.unwrap_or(false) {
+        args.push("--rejoin");
+    }
+
+    let output = run_git_command(repo_path, &args)?;
+    // The split id is printed last; --rejoin prints merge chatter before it.
+    output
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|l| l.len() == 40 && l.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_string)
+        .ok_or_else(|| {
+            LeviathanError::OperationFailed(format!("Unexpected git subtree output: {}", output))
+        })
+}
+
+/// Preview which local commits a split (and so a push) would extract
+///
+/// Lists the non-merge commits touching the prefix since the last sync; the
+/// merges are the syncs themselves, and upstream's own history is already
+/// upstream.
+#[command]
+pub async fn preview_subtree_split(
+    path: String,
+    prefix: String,
+    max_count: Option<usize>,
+) -> Result<SubtreeSplitPreview> {
+    let repo_path = Path::new(&path);
+    let prefix = normalize_prefix(&prefix)?;
+    let since = scan_subtree_trailers(repo_path)?
+        .remove(&prefix)
+        .map(|s| s.commit);
+
+    let repo = git2::Repository::open(repo_path)?;
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    if let Some(since) = &since {
+        revwalk.hide(git2::Oid::from_str(since)?)?;
+    }
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+
+    let prefix_path = Path::new(&prefix);
+    let limit = max_count.unwrap_or(usize::MAX);
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        if commits.len() >= limit {
+            break;
+        }
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let mut opts = git2::DiffOptions::new();
+        opts.pathspec(&prefix);
+        let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+        let files: Vec<String> = diff
+            .deltas()
+            .filter_map(|d| d.new_file().path().or_else(|| d.old_file().path()))
+            .filter_map(|p| p.strip_prefix(prefix_path).ok())
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        commits.push(SubtreeSplitCommit {
+            oid: commit.id().to_string(),
+            summary: commit.summary().ok().flatten().unwrap_or("").to_string(),
+            author: commit
+                .author()
+                .name()
+                .ok()
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            time: commit.time().seconds(),
+            files,
+        });
+    }
+
+    Ok(SubtreeSplitPreview {
+        prefix,
+        since_commit: since,
+        commits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    /// A repository with `vendor/up` added from a second repository.
+    async fn with_subtree(squash: bool) -> (TestRepo, TestRepo) {
+        let upstream = TestRepo::with_initial_commit();
+        upstream.create_commit("upstream lib", &[("lib.txt", "v1\n")]);
+        let repo = TestRepo::with_initial_commit();
+        subtree_add(
+            repo.path_str(),
+            "vendor/up/".to_string(),
+            upstream.path_str(),
+            "main".to_string(),
+            Some(squash),
+            None,
+        )
+        .await
+        .expect("subtree add");
+        (repo, upstream)
+    }
+
+    #[test]
+    fn test_normalize_prefix() {
+        assert_eq!(normalize_prefix("./vendor/lib/").unwrap(), "vendor/lib");
+        assert!(normalize_prefix("  ").is_err());
+        assert!(normalize_prefix("--all").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_subtrees_empty() {
+        let repo = TestRepo::with_initial_commit();
+        assert!(list_subtrees(repo.path_str()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_subtree_add_is_detected_with_upstream() {
+        let (repo, upstream) = with_subtree(false).await;
+        let subtrees = list_subtrees(repo.path_str()).await.unwrap();
+
+        assert_eq!(subtrees.len(), 1);
+        let s = &subtrees[0];
+        assert_eq!(s.prefix, "vendor/up");
+        assert!(s.exists);
+        assert!(!s.squashed);
+        assert_eq!(s.url.as_deref(), Some(upstream.path_str().as_str()));
+        assert_eq!(s.git_ref.as_deref(), Some("main"));
+        assert_eq!(s.last_split, Some(upstream.head_oid().to_string()));
+        assert!(repo.path.join("vendor/up/lib.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_subtree_pull_squashed_uses_recorded_upstream() {
+        let (repo, upstream) = with_subtree(true).await;
+        let new_tip = upstream.create_commit("upstream v2", &[("lib.txt", "v2\n")]);
+
+        let info = subtree_pull(
+            repo.path_str(),
+            "vendor/up".to_string(),
+            None,
+            None,
+            Some(true),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(info.squashed);
+        assert_eq!(info.last_split, Some(new_tip.to_string()));
+        assert_eq!(
+            std::fs::read_to_string(repo.path.join("vendor/up/lib.txt")).unwrap(),
+            "v2\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subtree_pull_without_upstream_fails() {
+        let repo = TestRepo::with_initial_commit();
+        let result = subtree_pull(
+            repo.path_str(),
+            "vendor/x".to_string(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_preview_split_lists_local_changes_since_sync() {
+        let (repo, _upstream) = with_subtree(false).await;
+        repo.create_commit("unrelated", &[("app.txt", "app\n")]);
+        let local = repo.create_commit("fix vendored lib", &[("vendor/up/lib.txt", "fixed\n")]);
+
+        let preview = preview_subtree_split(repo.path_str(), "vendor/up".to_string(), None)
+            .await
+            .unwrap();
+
+        assert!(preview.since_commit.is_some());
+        assert_eq!(preview.commits.len(), 1);
+        assert_eq!(preview.commits[0].oid, local.to_string());
+        assert_eq!(preview.commits[0].files, vec!["lib.txt".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_split_and_push_back_upstream() {
+        let (repo, upstream) = with_subtree(false).await;
+        repo.create_commit("fix vendored lib", &[("vendor/up/lib.txt", "fixed\n")]);
+
+        let split = subtree_split(
+            repo.path_str(),
+            "vendor/up".to_string(),
+            Some("split-up".to_string()),
+            None,
+        )
+        .await
+        .unwrap();
+        let git_repo = repo.repo();
+        let split_commit = git_repo
+            .find_commit(git2::Oid::from_str(&split).unwrap())
+            .unwrap();
+        assert!(split_commit.tree().unwrap().get_name("lib.txt").is_some());
+        assert_eq!(
+            git_repo
+                .find_branch("split-up", git2::BranchType::Local)
+                .unwrap()
+                .get()
+                .target(),
+            Some(split_commit.id())
+        );
+
+        subtree_push(
+            repo.path_str(),
+            "vendor/up".to_string(),
+            None,
+            Some("from-vendor".to_string()),
+        )
+        .await
+        .unwrap();
+        let pushed = upstream
+            .repo()
+            .find_branch("from-vendor", git2::BranchType::Local)
+            .unwrap()
+            .get()
+            .target();
+        assert_eq!(pushed, Some(split_commit.id()));
+    }
+}
@@ -1,10 +1,16 @@
 //! Worktree command handlers
 //! Manage git worktrees for working on multiple branches simultaneously
+//!
+//! Besides the plain git verbs this covers a status dashboard across all
+//! worktrees, creating a worktree for a branch or pull request under a
+//! conventional `<repo>.worktrees/` directory, and cleaning up worktrees
+//! whose branch has been merged or whose directory is gone.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tauri::command;
 
 use crate::error::{LeviathanError, Result};
+use crate::models::{AheadBehind, RepositoryState};
 use crate::utils::create_command;
 
 /// Information about a worktree
@@ -186,6 +192,12 @@ pub async fn add_worktree(
 
     run_git_command(repo_path, &args)?;
 
+    if let Some(ref nb) = new_branch {
+        if let Ok(repo) = git2::Repository::open(repo_path) {
+            record_fork_point(&repo, nb);
+        }
+    }
+
     // Get the newly added worktree info.
     //
     // Canonicalize against the REPO, not the process CWD. run_git_command runs
@@ -328,6 +340,488 @@ pub async fn repair_worktrees(path: String) -> Result<String> {
     run_git_command(repo_path, &["worktree", "repair"])
 }
 
+/// A worktree with the state the dashboard shows for it
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeStatus {
+    #[serde(flatten)]
+    pub worktree: Worktree,
+    /// Files with staged changes
+    pub staged: usize,
+    /// Tracked files with unstaged changes
+    pub unstaged: usize,
+    /// Untracked files (an untracked directory counts once, as in `git status`)
+    pub untracked: usize,
+    /// Files with unresolved conflicts
+    pub conflicted: usize,
+    /// Upstream of the checked-out branch, e.g. `origin/feature`
+    pub upstream: Option<String>,
+    /// Ahead/behind of the checked-out branch relative to its upstream
+    pub ahead_behind: Option<AheadBehind>,
+    /// Operation in progress in this worktree (rebase, merge, bisect, ...)
+    pub operation: RepositoryState,
+    /// Whether the checked-out branch is fully contained in the base branch
+    pub is_merged: bool,
+}
+
+/// The branch "merged" is measured against, resolved to a commit.
+struct MergeBase {
+    /// Ref name as given or detected, e.g. `origin/main`
+    name: String,
+    oid: git2::Oid,
+}
+
+/// Resolve the base branch for merge checks: `base` when given, otherwise the
+/// remote's default branch (`origin/HEAD`), then a local `main` or `master`.
+///
+/// Measuring against the main worktree's HEAD instead would report every
+/// branch as unmerged whenever the main checkout sits on a feature branch,
+/// and cleanup would then never find anything to do.
+fn resolve_merge_base(repo: &git2::Repository, base: Option<&str>) -> Option<MergeBase> {
+    let resolve = |name: &str| {
+        repo.revparse_single(name)
+            .ok()
+            .and_then(|obj| obj.peel_to_commit().ok())
+            .map(|commit| MergeBase {
+                name: name.to_string(),
+                oid: commit.id(),
+            })
+    };
+    if let Some(base) = base {
+        return resolve(base);
+    }
+    if let Ok(head) = repo.find_reference("refs/remotes/origin/HEAD") {
+        if let Ok(Some(target)) = head.symbolic_target() {
+            let name = target.strip_prefix("refs/remotes/").unwrap_or(target);
+            if let Some(found) = resolve(name) {
+                return Some(found);
+            }
+        }
+    }
+    ["main", "master"].iter().find_map(|name| {
+        repo.find_branch(name, git2::BranchType::Local)
+            .ok()
+            .and_then(|_| resolve(name))
+    })
+}
+
+/// Where `branch` was created, from the oldest entry of its reflog. When
+/// the creation entry has expired, the oldest position still on record.
+fn branch_creation_point(repo: &git2::Repository, branch: &str) -> Option<git2::Oid> {
+    let reflog = repo.reflog(&format!("refs/heads/{}", branch)).ok()?;
+    let oldest = reflog.get(reflog.len().checked_sub(1)?)?;
+    Some(if oldest.id_old().is_zero() {
+        oldest.id_new()
+    } else {
+        oldest.id_old()
+    })
+}
+
+/// Config key holding where a branch created for a worktree forked from the
+/// base branch
+fn fork_point_key(branch: &str) -> String {
+    format!("branch.{}.leviathanForkPoint", branch)
+}
+
+/// Record where a branch just created for a worktree forked from the base
+/// branch: its merge base with it. A branch created at a tip that already
+/// has commits of its own (a pull request head, a remote branch) starts its
+/// reflog at that tip, so the reflog alone cannot tell those commits apart.
+/// Best effort; git deletes the key along with the branch.
+fn record_fork_point(repo: &git2::Repository, branch: &str) {
+    let Some(tip) = repo
+        .find_branch(branch, git2::BranchType::Local)
+        .ok()
+        .and_then(|b| b.get().target())
+    else {
+        return;
+    };
+    let fork = resolve_merge_base(repo, None)
+        .and_then(|base| repo.merge_base(tip, base.oid).ok())
+        .unwrap_or(tip);
+    if let Ok(mut config) = repo.config() {
+        let _ = config.set_str(&fork_point_key(branch), &fork.to_string());
+    }
+}
+
+/// Where `branch` started: the fork point recorded when a worktree created
+/// it, else its creation point per its reflog.
+fn branch_start(repo: &git2::Repository, branch: &str) -> Option<git2::Oid> {
+    repo.config()
+        .ok()
+        .and_then(|config| config.get_string(&fork_point_key(branch)).ok())
+        .and_then(|oid| git2::Oid::from_str(&oid).ok())
+        .or_else(|| branch_creation_point(repo, branch))
+}
+
+/// Whether `branch` has been merged into the base: it has at least one
+/// commit of its own, and the base contains them all.
+///
+/// `git branch --merged` also lists a branch that has no commits yet, which
+/// for a worktree just set up would let cleanup delete it along with the
+/// branch. Its own commits are the ones made since it started (see
+/// [`branch_start`]); without a recorded fork point or a reflog nothing can
+/// be told apart, so the branch is not reported. The base's own local branch
+/// is never reported as merged into itself.
+fn is_branch_merged(repo: &git2::Repository, branch: &str, base: &MergeBase) -> bool {
+    if base.name == branch || base.name.ends_with(&format!("/{}", branch)) {
+        return false;
+    }
+    let Some(tip) = repo
+        .find_branch(branch, git2::BranchType::Local)
+        .ok()
+        .and_then(|b| b.get().target())
+    else {
+        return false;
+    };
+    let Some(created) = branch_start(repo, branch) else {
+        return false;
+    };
+    let has_own_commits =
+        tip != created && !repo.graph_descendant_of(created, tip).unwrap_or(false);
+    let base_contains_tip =
+        tip == base.oid || repo.graph_descendant_of(base.oid, tip).unwrap_or(false);
+    has_own_commits && base_contains_tip
+}
+
+/// Fill in the dashboard fields for one worktree.
+fn worktree_status(
+    repo: &git2::Repository,
+    worktree: Worktree,
+    base: Option<&MergeBase>,
+) -> WorktreeStatus {
+    let mut status = WorktreeStatus {
+        worktree,
+        staged: 0,
+        unstaged: 0,
+        untracked: 0,
+        conflicted: 0,
+        upstream: None,
+        ahead_behind: None,
+        operation: RepositoryState::Clean,
+        is_merged: false,
+    };
+
+    if let Some(branch) = status.worktree.branch.clone() {
+        if let Ok(local) = repo.find_branch(&branch, git2::BranchType::Local) {
+            if let Ok(upstream) = local.upstream() {
+                status.upstream = upstream.name().ok().flatten().map(|n| n.to_string());
+                if let (Some(local_oid), Some(upstream_oid)) =
+                    (local.get().target(), upstream.get().target())
+                {
+                    status.ahead_behind = repo
+                        .graph_ahead_behind(local_oid, upstream_oid)
+                        .ok()
+                        .map(|(ahead, behind)| AheadBehind { ahead, behind });
+                }
+            }
+        }
+        status.is_merged = base.is_some_and(|base| is_branch_merged(repo, &branch, base));
+    }
+
+    // A prunable worktree's directory is gone; there is nothing to open.
+    if status.worktree.is_prunable || status.worktree.is_bare {
+        return status;
+    }
+    let Ok(wt_repo) = git2::Repository::open(&status.worktree.path) else {
+        return status;
+    };
+    status.operation = wt_repo.state().into();
+
+    let mut opts = git2::StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .exclude_submodules(true);
+    if let Ok(entries) = wt_repo.statuses(Some(&mut opts)) {
+        for entry in entries.iter() {
+            let s = entry.status();
+            if s.is_conflicted() {
+                status.conflicted += 1;
+                continue;
+            }
+            if s.intersects(
+                git2::Status::INDEX_NEW
+                    | git2::Status::INDEX_MODIFIED
+                    | git2::Status::INDEX_DELETED
+                    | git2::Status::INDEX_RENAMED
+                    | git2::Status::INDEX_TYPECHANGE,
+            ) {
+                status.staged += 1;
+            }
+            if s.intersects(
+                git2::Status::WT_MODIFIED
+                    | git2::Status::WT_DELETED
+                    | git2::Status::WT_RENAMED
+                    | git2::Status::WT_TYPECHANGE,
+            ) {
+                status.unstaged += 1;
+            }
+            if s.contains(git2::Status::WT_NEW) {
+                status.untracked += 1;
+            }
+        }
+    }
+    status
+}
+
+/// Get every worktree with its branch, dirty counts, ahead/behind, operation
+/// in progress, lock state and whether its branch is merged
+///
+/// `base_branch` is what "merged" is measured against; by default the
+/// remote's default branch, then a local `main` or `master`.
+#[command]
+pub async fn get_worktree_dashboard(
+    path: String,
+    base_branch: Option<String>,
+) -> Result<Vec<WorktreeStatus>> {
+    let repo = git2::Repository::open(Path::new(&path))?;
+    let base = resolve_merge_base(&repo, base_branch.as_deref());
+    if base_branch.is_some() && base.is_none() {
+        return Err(LeviathanError::BranchNotFound(
+            base_branch.unwrap_or_default(),
+        ));
+    }
+
+    let worktrees = get_worktrees(path).await?;
+    Ok(worktrees
+        .into_iter()
+        .map(|wt| worktree_status(&repo, wt, base.as_ref()))
+        .collect())
+}
+
+/// Turn a branch name into a single directory name (`feature/login` becomes
+/// `feature-login`).
+fn worktree_dir_name(branch: &str) -> String {
+    branch
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_whitespace() => '-',
+            c => c,
+        })
+        .collect()
+}
+
+/// The conventional place for a new worktree: a `<repo>.worktrees` directory
+/// next to the main worktree, so worktrees never nest inside the checkout
+/// (where they would show up as untracked files) and stay easy to find.
+/// A suffix is added when the directory is already taken.
+async fn conventional_worktree_path(path: &str, branch: &str) -> Result<PathBuf> {
+    let worktrees = get_worktrees(path.to_string()).await?;
+    let main = worktrees
+        .iter()
+        .find(|wt| wt.is_main)
+        .map(|wt| PathBuf::from(&wt.path))
+        .unwrap_or_else(|| PathBuf::from(path));
+    let repo_name = main
+        .file_name()
+        .map(|n| n.to_string_lossy().trim_end_matches(".git").to_string())
+        .unwrap_or_else(|| "repo".to_string());
+    let parent = main.parent().map(Path::to_path_buf).unwrap_or(main.clone());
+    let root = parent.join(format!("{}.worktrees", repo_name));
+
+    let dir_name = worktree_dir_name(branch);
+    let mut candidate = root.join(&dir_name);
+    let mut n = 2;
+    while candidate.exists() {
+        candidate = root.join(format!("{}-{}", dir_name, n));
+        n += 1;
+    }
+    Ok(candidate)
+}
+
+/// The worktree that has `branch` checked out, if any.
+async fn worktree_with_branch(path: &str, branch: &str) -> Result<Option<Worktree>> {
+    Ok(get_worktrees(path.to_string())
+        .await?
+        .into_iter()
+        .find(|wt| wt.branch.as_deref() == Some(branch)))
+}
+
+/// Create a worktree for a branch in the conventional location
+///
+/// A local branch is checked out as is; a branch that exists only on `remote`
+/// (default `origin`) gets a local tracking branch. When the branch is
+/// already checked out in some worktree, that worktree is returned instead:
+/// git refuses a second checkout, and the user's intent ("let me work on this
+/// branch") is already met.
+#[command]
+pub async fn create_worktree_for_branch(
+    path: String,
+    branch: String,
+    remote: Option<String>,
+) -> Result<Worktree> {
+    crate::utils::reject_flag_like(&branch, "Branch")?;
+    if let Some(existing) = worktree_with_branch(&path, &branch).await? {
+        return Ok(existing);
+    }
+
+    let remote = remote.unwrap_or_else(|| "origin".to_string());
+    let (has_local, has_remote) = {
+        let repo = git2::Repository::open(Path::new(&path))?;
+        let has_local = repo.find_branch(&branch, git2::BranchType::Local).is_ok();
+        let has_remote = repo
+            .find_branch(&format!("{}/{}", remote, branch), git2::BranchType::Remote)
+            .is_ok();
+        (has_local, has_remote)
+    };
+
+    let dir = conventional_worktree_path(&path, &branch).await?;
+    let dir = dir.to_string_lossy().to_string();
+    if has_local {
+        add_worktree(path, dir, Some(branch), None, None, None, None).await
+    } else if has_remote {
+        // Created from the remote-tracking branch, so git's default
+        // branch.autoSetupMerge sets the upstream.
+        let start = format!("{}/{}", remote, branch);
+        add_worktree(path, dir, Some(start), Some(branch), None, None, None).await
+    } else {
+        Err(LeviathanError::BranchNotFound(branch))
+    }
+}
+
+/// Create a worktree for a pull request in the conventional location
+///
+/// Fetches the pull request head the way GitHub (and Gitea/Forgejo) publish
+/// it, `refs/pull/<n>/head`, into `refs/remotes/<remote>/pr/<n>` and checks
+/// it out as a local `pr/<n>` branch. An existing `pr/<n>` branch is reused
+/// as is rather than reset, so local work on it is never thrown away.
+#[command]
+pub async fn create_worktree_for_pull_request(
+    path: String,
+    number: u64,
+    remote: Option<String>,
+) -> Result<Worktree> {
+    let remote = remote.unwrap_or_else(|| "origin".to_string());
+    crate::utils::reject_flag_like(&remote, "Remote")?;
+    let branch = format!("pr/{}", number);
+    if let Some(existing) = worktree_with_branch(&path, &branch).await? {
+        return Ok(existing);
+    }
+
+    let tracking = format!("refs/remotes/{}/pr/{}", remote, number);
+    let refspec = format!("+refs/pull/{}/head:{}", number, tracking);
+    run_git_command(Path::new(&path), &["fetch", "--no-tags", &remote, &refspec])?;
+
+    {
+        let repo = git2::Repository::open(Path::new(&path))?;
+        if repo.find_branch(&branch, git2::BranchType::Local).is_err() {
+            let commit = repo.find_reference(&tracking)?.peel_to_commit()?;
+            repo.branch(&branch, &commit, false)?;
+            record_fork_point(&repo, &branch);
+        }
+    }
+
+    let dir = conventional_worktree_path(&path, &branch).await?;
+    let dir = dir.to_string_lossy().to_string();
+    add_worktree(path, dir, Some(branch), None, None, None, None).await
+}
+
+/// One worktree considered by [`cleanup_worktrees`]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeCleanupEntry {
+    pub path: String,
+    pub branch: Option<String>,
+    /// "merged" or "prunable" (directory gone)
+    pub reason: String,
+    /// Whether the worktree was removed (always false for a dry run)
+    pub removed: bool,
+    /// Whether its branch was deleted as well
+    pub branch_deleted: bool,
+    /// Why removal failed, if it did
+    pub error: Option<String>,
+}
+
+/// Remove worktrees that are no longer needed
+///
+/// Candidates are worktrees whose directory is gone, and clean worktrees with
+/// no operation in progress whose branch is merged into `base_branch` (see
+/// [`get_worktree_dashboard`]). The main worktree and locked worktrees are
+/// never touched. Removal goes through `git worktree remove` without
+/// `--force`, so git's own last check for local changes still applies. With
+/// `delete_branches`, the merged branches are deleted too, except those a
+/// branch rule protects. `dry_run` only lists the candidates.
+#[command]
+pub async fn cleanup_worktrees(
+    path: String,
+    base_branch: Option<String>,
+    dry_run: Option<bool>,
+    delete_branches: Option<bool>,
+) -> Result<Vec<WorktreeCleanupEntry>> {
+    let dry_run = dry_run.unwrap_or(false);
+    let dashboard = get_worktree_dashboard(path.clone(), base_branch).await?;
+    let repo_path = Path::new(&path);
+
+    let mut entries: Vec<WorktreeCleanupEntry> = dashboard
+        .iter()
+        .filter(|s| !s.worktree.is_main && !s.worktree.is_locked)
+        .filter_map(|s| {
+            let reason = if s.worktree.is_prunable {
+                "prunable"
+            } else if s.is_merged
+                && s.staged + s.unstaged + s.untracked + s.conflicted == 0
+                && matches!(s.operation, RepositoryState::Clean)
+            {
+                "merged"
+            } else {
+                return None;
+            };
+            Some(WorktreeCleanupEntry {
+                path: s.worktree.path.clone(),
+                branch: s.worktree.branch.clone(),
+                reason: reason.to_string(),
+                removed: false,
+                branch_deleted: false,
+                error: None,
+            })
+        })
+        .collect();
+    if dry_run || entries.is_empty() {
+        return Ok(entries);
+    }
+
+    for entry in entries.iter_mut().filter(|e| e.reason == "merged") {
+        match run_git_command(repo_path, &["worktree", "remove", &entry.path]) {
+            Ok(_) => entry.removed = true,
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+    }
+    if entries.iter().any(|e| e.reason == "prunable") {
+        let pruned = run_git_command(repo_path, &["worktree", "prune"]);
+        for entry in entries.iter_mut().filter(|e| e.reason == "prunable") {
+            match &pruned {
+                Ok(_) => entry.removed = true,
+                Err(e) => entry.error = Some(e.to_string()),
+            }
+        }
+    }
+
+    if delete_branches.unwrap_or(false) {
+        let repo = git2::Repository::open(repo_path)?;
+        let rules = super::branch_rules::load_rules(repo_path).unwrap_or_default();
+        for entry in entries
+            .iter_mut()
+            .filter(|e| e.removed && e.reason == "merged")
+        {
+            let Some(name) = entry.branch.as_deref() else {
+                continue;
+            };
+            if super::branch_rules::is_deletion_prevented(&rules, name) {
+                continue;
+            }
+            if let Ok(mut branch) = repo.find_branch(name, git2::BranchType::Local) {
+                match branch.delete() {
+                    Ok(()) => entry.branch_deleted = true,
+                    Err(e) => entry.error = Some(e.message().to_string()),
+                }
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -667,4 +1161,292 @@ mod tests {
         assert!(worktrees[0].head_oid.is_some());
         assert!(!worktrees[0].head_oid.as_ref().unwrap().is_empty());
     }
+
+    /// Deletes the `<repo>.worktrees` directory the conventional-location
+    /// commands create next to the (temporary) repository.
+    struct ConventionalRoot(std::path::PathBuf);
+
+    impl ConventionalRoot {
+        fn of(repo: &TestRepo) -> Self {
+            let canonical = std::fs::canonicalize(&repo.path).unwrap();
+            let name = canonical.file_name().unwrap().to_string_lossy().to_string();
+            Self(canonical.with_file_name(format!("{}.worktrees", name)))
+        }
+    }
+
+    impl Drop for ConventionalRoot {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_worktree_dashboard_reports_state() {
+        let repo = TestRepo::with_initial_commit();
+        let temp = TempDir::new().unwrap();
+        let wt_path = temp.path().join("feature-wt");
+        add_worktree_cli(&repo.path, &wt_path, "feature");
+
+        std::fs::write(wt_path.join("README.md"), "changed\n").unwrap();
+        std::fs::write(wt_path.join("staged.txt"), "new\n").unwrap();
+        std::fs::write(wt_path.join("loose.txt"), "new\n").unwrap();
+        let wt_repo = git2::Repository::open(&wt_path).unwrap();
+        let mut index = wt_repo.index().unwrap();
+        index.add_path(Path::new("staged.txt")).unwrap();
+        index.write().unwrap();
+
+        let dashboard = get_worktree_dashboard(repo.path_str(), None).await.unwrap();
+        assert_eq!(dashboard.len(), 2);
+
+        let main = dashboard.iter().find(|s| s.worktree.is_main).unwrap();
+        assert_eq!(main.staged + main.unstaged + main.untracked, 0);
+        assert!(!main.is_merged, "the base branch is not merged into itself");
+
+        let feature = dashboard
+            .iter()
+            .find(|s| s.worktree.branch.as_deref() == Some("feature"))
+            .unwrap();
+        assert_eq!(feature.staged, 1);
+        assert_eq!(feature.unstaged, 1);
+        assert_eq!(feature.untracked, 1);
+        assert!(matches!(feature.operation, RepositoryState::Clean));
+        assert!(
+            !feature.is_merged,
+            "a branch with no commits of its own is not merged"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_worktree_dashboard_reports_unmerged_and_operation() {
+        let repo = TestRepo::with_initial_commit();
+        let temp = TempDir::new().unwrap();
+        let wt_path = temp.path().join("feature-wt");
+        add_worktree_cli(&repo.path, &wt_path, "feature");
+
+        std::fs::write(wt_path.join("f.txt"), "f\n").unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .current_dir(&wt_path)
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["add", "f.txt"]);
+        git(&["commit", "-m", "feature work"]);
+        git(&["bisect", "start"]);
+
+        let dashboard = get_worktree_dashboard(repo.path_str(), Some("main".to_string()))
+            .await
+            .unwrap();
+        let feature = dashboard
+            .iter()
+            .find(|s| s.worktree.branch.as_deref() == Some("feature"))
+            .unwrap();
+        assert!(!feature.is_merged);
+        assert!(matches!(feature.operation, RepositoryState::Bisect));
+
+        git(&["bisect", "reset"]);
+    }
+
+    #[tokio::test]
+    async fn test_worktree_dashboard_unknown_base_fails() {
+        let repo = TestRepo::with_initial_commit();
+        let result = get_worktree_dashboard(repo.path_str(), Some("nope".to_string())).await;
+        assert!(matches!(result, Err(LeviathanError::BranchNotFound(_))));
+    }
+
+    #[test]
+    fn test_worktree_dir_name_flattens_branch_names() {
+        assert_eq!(worktree_dir_name("feature/login"), "feature-login");
+        assert_eq!(worktree_dir_name("fix it"), "fix-it");
+        assert_eq!(worktree_dir_name("plain"), "plain");
+    }
+
+    #[tokio::test]
+    async fn test_create_worktree_for_branch_uses_conventional_location() {
+        let repo = TestRepo::with_initial_commit();
+        let root = ConventionalRoot::of(&repo);
+        repo.create_branch("feature/login");
+
+        let wt = create_worktree_for_branch(repo.path_str(), "feature/login".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(wt.branch.as_deref(), Some("feature/login"));
+        assert_eq!(Path::new(&wt.path), root.0.join("feature-login"));
+
+        // Asking again returns the existing checkout instead of failing.
+        let again = create_worktree_for_branch(repo.path_str(), "feature/login".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(again.path, wt.path);
+    }
+
+    #[tokio::test]
+    async fn test_create_worktree_for_remote_only_branch_tracks_it() {
+        let repo = TestRepo::with_initial_commit();
+        let _root = ConventionalRoot::of(&repo);
+        repo.add_remote("origin", "https://example.com/repo.git");
+        repo.create_remote_branch("topic", repo.head_oid());
+
+        let wt = create_worktree_for_branch(repo.path_str(), "topic".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(wt.branch.as_deref(), Some("topic"));
+
+        let git_repo = repo.repo();
+        let local = git_repo
+            .find_branch("topic", git2::BranchType::Local)
+            .unwrap();
+        let upstream = local.upstream().unwrap();
+        assert_eq!(upstream.name().ok().flatten(), Some("origin/topic"));
+    }
+
+    #[tokio::test]
+    async fn test_create_worktree_for_missing_branch_fails() {
+        let repo = TestRepo::with_initial_commit();
+        let result = create_worktree_for_branch(repo.path_str(), "ghost".to_string(), None).await;
+        assert!(matches!(result, Err(LeviathanError::BranchNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_create_worktree_for_pull_request() {
+        let upstream = TestRepo::with_initial_commit();
+        let pr_oid = upstream.create_commit("pr work", &[("pr.txt", "pr\n")]);
+        upstream
+            .repo()
+            .reference("refs/pull/7/head", pr_oid, false, "test")
+            .unwrap();
+
+        let repo = TestRepo::with_initial_commit();
+        let root = ConventionalRoot::of(&repo);
+        repo.add_remote("origin", &upstream.path_str());
+
+        let wt = create_worktree_for_pull_request(repo.path_str(), 7, None)
+            .await
+            .unwrap();
+        assert_eq!(wt.branch.as_deref(), Some("pr/7"));
+        assert_eq!(wt.head_oid, Some(pr_oid.to_string()));
+        assert_eq!(Path::new(&wt.path), root.0.join("pr-7"));
+        assert!(Path::new(&wt.path).join("pr.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_worktrees_removes_only_clean_merged_ones() {
+        let repo = TestRepo::with_initial_commit();
+        let temp = TempDir::new().unwrap();
+        let merged = temp.path().join("merged");
+        let dirty = temp.path().join("dirty");
+        let locked = temp.path().join("locked");
+        let fresh = temp.path().join("fresh");
+        add_worktree_cli(&repo.path, &merged, "merged");
+        add_worktree_cli(&repo.path, &dirty, "dirty");
+        add_worktree_cli(&repo.path, &locked, "locked");
+        add_worktree_cli(&repo.path, &fresh, "fresh");
+        let git = |dir: &Path, args: &[&str]| {
+            let out = std::process::Command::new("git")
+                .current_dir(dir)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(out.status.success(), "{:?}", out);
+        };
+        // Work on three of the branches, all merged into main; `fresh` has
+        // none of its own.
+        for (dir, file) in [(&merged, "m.txt"), (&dirty, "d.txt"), (&locked, "l.txt")] {
+            std::fs::write(dir.join(file), "work\n").unwrap();
+            git(dir, &["add", file]);
+            git(dir, &["commit", "-m", "work"]);
+        }
+        git(
+            &repo.path,
+            &["merge", "--no-edit", "merged", "dirty", "locked"],
+        );
+        std::fs::write(dirty.join("README.md"), "local edit\n").unwrap();
+        lock_worktree(
+            repo.path_str(),
+            locked.to_string_lossy().to_string(),
+            Some("keep".to_string()),
+        )
+        .await
+        .unwrap();
+
+        let preview = cleanup_worktrees(repo.path_str(), None, Some(true), None)
+            .await
+            .unwrap();
+        assert_eq!(preview.len(), 1);
+        assert_eq!(preview[0].branch.as_deref(), Some("merged"));
+        assert!(!preview[0].removed);
+        assert!(merged.exists(), "a dry run must not remove anything");
+
+        let done = cleanup_worktrees(repo.path_str(), None, None, Some(true))
+            .await
+            .unwrap();
+        assert_eq!(done.len(), 1);
+        assert!(done[0].removed && done[0].branch_deleted, "{:?}", done[0]);
+        assert!(!merged.exists());
+        assert!(dirty.exists() && locked.exists());
+        assert!(fresh.exists(), "a brand-new worktree is not merged");
+        assert!(repo
+            .repo()
+            .find_branch("merged", git2::BranchType::Local)
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_worktrees_removes_merged_pull_request() {
+        let repo = TestRepo::with_initial_commit();
+        let _root = ConventionalRoot::of(&repo);
+        let git = |args: &[&str]| {
+            let out = std::process::Command::new("git")
+                .current_dir(&repo.path)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(out.status.success(), "{:?}", out);
+        };
+        // A pull request head with a commit main does not have yet
+        git(&["checkout", "-q", "-b", "contributor"]);
+        std::fs::write(repo.path.join("pr.txt"), "pr\n").unwrap();
+        git(&["add", "pr.txt"]);
+        git(&["commit", "-q", "-m", "pr work"]);
+        git(&["update-ref", "refs/pull/7/head", "contributor"]);
+        git(&["checkout", "-q", "main"]);
+        git(&["branch", "-q", "-D", "contributor"]);
+        repo.add_remote("origin", &repo.path_str());
+
+        let wt = create_worktree_for_pull_request(repo.path_str(), 7, None)
+            .await
+            .unwrap();
+        let preview = cleanup_worktrees(repo.path_str(), None, Some(true), None)
+            .await
+            .unwrap();
+        assert!(preview.is_empty(), "an unmerged pull request stays");
+
+        git(&["merge", "--no-edit", "--no-ff", "refs/pull/7/head"]);
+        let done = cleanup_worktrees(repo.path_str(), None, None, Some(true))
+            .await
+            .unwrap();
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].branch.as_deref(), Some("pr/7"));
+        assert!(done[0].removed && done[0].branch_deleted, "{:?}", done[0]);
+        assert!(!Path::new(&wt.path).exists());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_worktrees_prunes_missing_directories() {
+        let repo = TestRepo::with_initial_commit();
+        let temp = TempDir::new().unwrap();
+        let gone = temp.path().join("gone");
+        add_worktree_cli(&repo.path, &gone, "gone");
+        repo.create_commit("main moves on", &[("m.txt", "m\n")]);
+        std::fs::remove_dir_all(&gone).unwrap();
+
+        let done = cleanup_worktrees(repo.path_str(), None, None, None)
+            .await
+            .unwrap();
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0].reason, "prunable");
+        assert!(done[0].removed);
+        assert_eq!(get_worktrees(repo.path_str()).await.unwrap().len(), 1);
+    }
 }
@@ -23,7 +23,8 @@ use services::ai::mcp::server::create_mcp_state;
 use services::ai::AiState;
 use services::commit_index::SharedCommitIndex;
 use services::{
-    create_ai_state, create_autofetch_state, create_update_state, CancellationRegistry,
+    create_ai_state, create_autofetch_state, create_maintenance_state, create_update_state,
+    CancellationRegistry,
 };
 
 /// Initialize the application
@@ -120,10 +121,14 @@ pub fn run() {
     let app = builder
         .manage(WatcherState::default())
         .manage(create_autofetch_state())
+        .manage(create_maintenance_state())
         .manage(create_update_state())
         .manage(CancellationRegistry::default())
         .manage(SharedCommitIndex::default())
         .setup(|app| {
+            // Let prefetches made inside other commands report progress
+            commands::partial_clone::set_progress_app(app.handle().clone());
+
             // Initialize AI state with config directory
             let config_dir = app.path().app_config_dir().unwrap_or_default();
             app.manage(create_ai_state(config_dir));
@@ -276,6 +281,8 @@ pub fn run() {
             commands::repository::init_repository,
             commands::repository::get_repository_info,
             commands::repository::get_clone_filter_info,
+            commands::partial_clone::prefetch_missing_objects,
+            commands::partial_clone::convert_to_full_clone,
             commands::repository::list_tracked_files,
             commands::branch::get_branches,
             commands::branch::create_branch,
@@ -388,8 +395,22 @@ pub fn run() {
             commands::rewrite::reorder_commits,
             commands::rewrite::cherry_pick_from_branch,
             commands::rewrite::cherry_pick_range,
+            commands::rewrite::preview_history_purge,
+            commands::rewrite::purge_history,
+            commands::rewrite::list_purge_backups,
+            commands::rewrite::undo_history_purge,
+            commands::rewrite::delete_purge_backup,
+            commands::rewrite::suggest_identity_mappings,
+            commands::rewrite::preview_identity_rewrite,
+            commands::rewrite::rewrite_identities,
+            commands::rewrite::split_commit,
+            commands::rewrite::split_staged_changes,
+            commands::rewrite::apply_commit_split_suggestion,
+            commands::rewrite::move_commits,
             commands::squash::squash_commits,
             commands::squash::fixup_commit,
+            commands::squash::preview_absorb,
+            commands::squash::absorb_staged_changes,
             commands::reflog::get_reflog,
             commands::reflog::reset_to_reflog,
             // Undo/redo history
@@ -406,6 +427,9 @@ pub fn run() {
             commands::bisect::bisect_good,
             commands::bisect::bisect_skip,
             commands::bisect::bisect_reset,
+            commands::bisect::export_bisect_log,
+            commands::bisect::replay_bisect_log,
+            commands::bisect::start_bisect_run,
             commands::submodule::get_submodules,
             commands::submodule::add_submodule,
             commands::submodule::init_submodules,
@@ -415,6 +439,16 @@ pub fn run() {
             commands::submodule::remove_submodule,
             commands::submodule::get_submodule_status,
             commands::submodule::submodule_foreach,
+            commands::submodule::get_submodule_tree,
+            commands::submodule::batch_update_submodules,
+            commands::submodule::commit_submodule_pointers,
+            commands::submodule::submodule_foreach_results,
+            commands::subtree::list_subtrees,
+            commands::subtree::subtree_add,
+            commands::subtree::subtree_pull,
+            commands::subtree::subtree_push,
+            commands::subtree::subtree_split,
+            commands::subtree::preview_subtree_split,
             commands::worktree::get_worktrees,
             commands::worktree::add_worktree,
             commands::worktree::remove_worktree,
@@ -423,6 +457,10 @@ pub fn run() {
             commands::worktree::unlock_worktree,
             commands::worktree::move_worktree,
             commands::worktree::repair_worktrees,
+            commands::worktree::get_worktree_dashboard,
+            commands::worktree::create_worktree_for_branch,
+            commands::worktree::create_worktree_for_pull_request,
+            commands::worktree::cleanup_worktrees,
             commands::lfs::get_lfs_status,
             commands::lfs::init_lfs,
             commands::lfs::lfs_track,
@@ -432,6 +470,14 @@ pub fn run() {
             commands::lfs::lfs_fetch,
             commands::lfs::lfs_prune,
             commands::lfs::lfs_migrate,
+            commands::lfs_analysis::get_lfs_storage_report,
+            commands::lfs_analysis::preview_lfs_prune,
+            commands::lfs_locks::list_lfs_locks,
+            commands::lfs_locks::lfs_lock,
+            commands::lfs_locks::lfs_unlock,
+            commands::lfs_locks::check_lfs_lock_conflicts,
+            commands::lfs_locks::apply_lfs_lockable_permissions,
+            commands::lfs_locks::set_lfs_lockable,
             // Repository maintenance
             commands::maintenance::run_garbage_collection,
             commands::maintenance::prune_remote_tracking_branches,
@@ -442,6 +488,18 @@ pub fn run() {
             commands::maintenance::run_prune,
             commands::maintenance::get_repository_stats,
             commands::maintenance::get_pack_info,
+            commands::maintenance::start_scheduled_maintenance,
+            commands::maintenance::stop_scheduled_maintenance,
+            commands::maintenance::start_workspace_maintenance,
+            commands::maintenance::stop_workspace_maintenance,
+            commands::maintenance::get_scheduled_maintenance_repos,
+            commands::maintenance::get_maintenance_status,
+            commands::maintenance::set_maintenance_schedule,
+            commands::maintenance::run_maintenance_tasks,
+            commands::maintenance::get_maintenance_log,
+            commands::maintenance::clear_maintenance_log,
+            // Repository health doctor
+            commands::doctor::run_repository_doctor,
             commands::gpg::get_gpg_config,
             commands::gpg::get_gpg_keys,
             commands::gpg::set_signing_key,
@@ -682,14 +740,33 @@ pub fn run() {
             commands::patch::create_patch,
             commands::patch::apply_patch,
             commands::patch::apply_patch_to_index,
+            commands::patch_series::format_patch_series,
+            commands::patch_series::get_patch_series_versions,
+            commands::patch_series::apply_mbox,
+            commands::patch_series::get_am_status,
+            commands::patch_series::am_continue,
+            commands::patch_series::am_skip,
+            commands::patch_series::am_abort,
+            commands::send_email::get_send_email_config,
+            commands::send_email::set_send_email_config,
+            commands::send_email::store_smtp_password,
+            commands::send_email::delete_smtp_password,
+            commands::send_email::send_patch_series,
             // Archive
             commands::archive::create_archive,
             commands::archive::get_archive_files,
+            commands::archive::create_release_archive,
             // Bundle
             commands::bundle::bundle_create,
             commands::bundle::bundle_verify,
             commands::bundle::bundle_list_heads,
             commands::bundle::bundle_unbundle,
+            commands::bundle_sync::get_bundle_sync_profiles,
+            commands::bundle_sync::save_bundle_sync_profile,
+            commands::bundle_sync::delete_bundle_sync_profile,
+            commands::bundle_sync::reset_bundle_sync_profile,
+            commands::bundle_sync::bundle_sync_export,
+            commands::bundle_sync::bundle_sync_import,
             // Git Notes
             commands::notes::get_note,
             commands::notes::get_notes,
@@ -716,6 +793,20 @@ pub fn run() {
             commands::hooks::save_hook,
             commands::hooks::delete_hook,
             commands::hooks::toggle_hook,
+            commands::hooks::get_hooks_location,
+            commands::hooks::install_team_hooks,
+            // Hook execution history
+            commands::hook_history::get_hook_history,
+            commands::hook_history::get_slow_hooks,
+            commands::hook_history::clear_hook_history,
+            // Secret scanning
+            commands::secret_scan::scan_staged_secrets,
+            commands::secret_scan::scan_push_secrets,
+            commands::secret_scan::scan_history_secrets,
+            commands::secret_scan::get_secret_scan_rules,
+            // Built-in .pre-commit-config.yaml runner
+            commands::pre_commit::get_pre_commit_config,
+            commands::pre_commit::run_pre_commit_checks,
             // Terminal integration
             commands::terminal::open_terminal,
             commands::terminal::open_file_manager,
@@ -723,6 +814,7 @@ pub fn run() {
             // Repository statistics
             commands::stats::get_repo_stats,
             commands::stats::get_contributor_stats,
+            commands::stats::get_identity_clusters,
             commands::stats::get_repo_statistics,
             // Search / grep
             commands::search::search_in_files,
@@ -750,6 +842,12 @@ pub fn run() {
             commands::sparse_checkout::disable_sparse_checkout,
             commands::sparse_checkout::set_sparse_checkout_patterns,
             commands::sparse_checkout::add_sparse_checkout_patterns,
+            commands::sparse_checkout::get_sparse_presets,
+            commands::sparse_checkout::save_sparse_preset,
+            commands::sparse_checkout::delete_sparse_preset,
+            commands::sparse_checkout::get_sparse_directory_sizes,
+            commands::sparse_checkout::preview_sparse_preset,
+            commands::sparse_checkout::apply_sparse_preset,
             // Avatar / Gravatar
             commands::avatar::get_avatar_url,
             commands::avatar::get_avatar_urls,
@@ -820,6 +918,10 @@ pub fn run() {
             commands::custom_actions::save_custom_action,
             commands::custom_actions::delete_custom_action,
             commands::custom_actions::run_custom_action,
+            commands::custom_actions::get_custom_action_prompts,
+            commands::custom_actions::start_custom_action,
+            commands::custom_actions::get_custom_action_runs,
+            commands::custom_actions::clear_custom_action_runs,
             // Advanced search
             commands::advanced_search::filter_commits,
             commands::advanced_search::get_branch_diff_commits,
@@ -158,7 +158,7 @@ struct RemoteUpdatesEvent {
 /// Deterministic per-repo stagger within [0, interval/2), derived from the
 /// repo path. Keeps simultaneous fetches for many open repos spread out
 /// without needing shared state or randomness.
-fn stagger_offset(repo_path: &str, interval: Duration) -> Duration {
+pub(crate) fn stagger_offset(repo_path: &str, interval: Duration) -> Duration {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -393,6 +393,48 @@ pub fn delete_credentials(url: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Keyring account for an SMTP login.
+///
+/// Kept apart from the per-host git credentials above: a self-hosted forge
+/// and its mail server often share a hostname but not a password, and one
+/// must not silently be offered to the other.
+fn smtp_account(server: &str, port: u16, username: &str) -> String {
+    format!("smtp:{}:{}:{}", server.to_ascii_lowercase(), port, username)
+}
+
+/// Store an SMTP password in the keychain
+pub fn store_smtp_password(
+    server: &str,
+    port: u16,
+    username: &str,
+    password: &str,
+) -> Result<(), String> {
+    if keyring_set(
+        SERVICE_NAME,
+        &smtp_account(server, port, username),
+        password,
+    ) {
+        tracing::info!("Stored SMTP password in keyring for server: {}", server);
+        Ok(())
+    } else {
+        Err(format!(
+            "Failed to store SMTP password for {} in the keyring",
+            server
+        ))
+    }
+}
+
+/// Get a stored SMTP password from the keychain
+pub fn get_smtp_password(server: &str, port: u16, username: &str) -> Option<String> {
+    keyring_get(SERVICE_NAME, &smtp_account(server, port, username))
+}
+
+/// Delete a stored SMTP password from the keychain
+pub fn delete_smtp_password(server: &str, port: u16, username: &str) {
+    keyring_delete(SERVICE_NAME, &smtp_account(server, port, username));
+    tracing::debug!("Deleted SMTP password for server: {}", server);
+}
+
 /// Extract host from a git URL
 fn extract_host(url: &str) -> Option<String> {
     // Handle SSH URLs like git@github.com:user/repo.git
@@ -0,0 +1,930 @@
+//! Scheduled maintenance service
+//!
+//! Runs `git maintenance`-style tasks in the background for each open
+//! repository (or every repository of a workspace), the way
+//! [`autofetch_service`](super::autofetch_service) runs fetches. Each
+//! repository gets one polling loop; on every tick the loop works out which
+//! configured tasks are due and runs them one after another.
+//!
+//! The schedule is per repository and lives in
+//! `<commondir>/leviathan/maintenance.json`; every run is appended to
+//! `<commondir>/leviathan/maintenance_log.jsonl`. Both use the COMMON dir, so
+//! linked worktrees — which share the object store the tasks operate on —
+//! share one schedule and one history instead of repacking the same objects
+//! once per worktree.
+//!
+//! The last recorded run of each task is what the next due time is derived
+//! from, so schedules survive restarts: an app opened once a day still gets
+//! its daily repack instead of resetting the clock on every launch.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::Emitter;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::error::{LeviathanError, Result};
+use crate::utils::create_command;
+
+/// How often each repository's loop checks for due tasks. Task intervals are
+/// configured in minutes, so a one-minute tick is as fine as they can get.
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Runs kept in the log; older entries are dropped when it grows past this.
+const MAX_LOG_ENTRIES: usize = 500;
+
+/// Bytes of git output kept in a run's message. The tail is kept, since
+/// that is where git reports what went wrong.
+const MAX_MESSAGE_BYTES: usize = 4 * 1024;
+
+/// `gc.pid` older than this is left over from a crashed `git gc`. git itself
+/// ignores it after 12 hours, so maintenance must not pause forever on it.
+const STALE_GC_PID: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// A maintenance task. The names match `git maintenance run --task=<name>`
+/// where git has an equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MaintenanceTask {
+    /// Incrementally write the commit-graph so history walks stay fast
+    CommitGraph,
+    /// Fetch every remote into `refs/prefetch/` without touching
+    /// remote-tracking branches, so the user's next fetch is quick
+    Prefetch,
+    /// Pack loose objects into a new pack and drop the loose copies
+    LooseObjects,
+    /// Repack small pack-files together using the multi-pack-index
+    IncrementalRepack,
+    /// Rewrite and verify the multi-pack-index on its own
+    MultiPackIndex,
+}
+
+impl MaintenanceTask {
+    pub const ALL: [MaintenanceTask; 5] = [
+        MaintenanceTask::CommitGraph,
+        MaintenanceTask::Prefetch,
+        MaintenanceTask::LooseObjects,
+        MaintenanceTask::IncrementalRepack,
+        MaintenanceTask::MultiPackIndex,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MaintenanceTask::CommitGraph => "commit-graph",
+            MaintenanceTask::Prefetch => "prefetch",
+            MaintenanceTask::LooseObjects => "loose-objects",
+            MaintenanceTask::IncrementalRepack => "incremental-repack",
+            MaintenanceTask::MultiPackIndex => "multi-pack-index",
+        }
+    }
+
+    /// The cadence `git maintenance start` uses: the cheap, user-visible
+    /// tasks hourly, the repacking ones daily.
+    fn default_interval_minutes(self) -> u32 {
+        match self {
+            MaintenanceTask::CommitGraph | MaintenanceTask::Prefetch => 60,
+            MaintenanceTask::LooseObjects
+            | MaintenanceTask::IncrementalRepack
+            | MaintenanceTask::MultiPackIndex => 24 * 60,
+        }
+    }
+}
+
+/// One task's entry in a repository's schedule
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledTask {
+    pub task: MaintenanceTask,
+    pub enabled: bool,
+    pub interval_minutes: u32,
+}
+
+/// A repository's maintenance schedule
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceSchedule {
+    pub tasks: Vec<ScheduledTask>,
+}
+
+impl Default for MaintenanceSchedule {
+    fn default() -> Self {
+        Self {
+            tasks: MaintenanceTask::ALL
+                .iter()
+                .map(|&task| ScheduledTask {
+                    task,
+                    enabled: true,
+                    interval_minutes: task.default_interval_minutes(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MaintenanceSchedule {
+    /// Every task exactly once, in [`MaintenanceTask::ALL`] order.
+    ///
+    /// A schedule written by an older version lacks tasks added since, and a
+    /// hand-edited one may list a task twice; the first entry wins and the
+    /// missing ones get their defaults. A zero interval would make the task
+    /// due on every tick, so it is raised to one minute.
+    pub fn normalized(self) -> Self {
+        let mut seen = HashMap::new();
+        for entry in self.tasks {
+            seen.entry(entry.task).or_insert(entry);
+        }
+        let tasks = MaintenanceSchedule::default()
+            .tasks
+            .into_iter()
+            .map(|default| {
+                let mut entry = seen.remove(&default.task).unwrap_or(default);
+                entry.interval_minutes = entry.interval_minutes.max(1);
+                entry
+            })
+            .collect();
+        Self { tasks }
+    }
+}
+
+/// Outcome of one task run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceRunStatus {
+    Succeeded,
+    Failed,
+    /// Not run because the repository was busy
+    Skipped,
+}
+
+/// What started a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceTrigger {
+    Scheduled,
+    Manual,
+}
+
+/// One logged task run
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceRunRecord {
+    pub task: MaintenanceTask,
+    pub status: MaintenanceRunStatus,
+    pub trigger: MaintenanceTrigger,
+    /// Unix timestamp (seconds) the run started
+    pub started_at: i64,
+    pub duration_ms: u64,
+    /// git's output on failure, or why the run was skipped
+    pub message: Option<String>,
+}
+
+/// Result of one scheduler tick
+#[derive(Debug)]
+pub enum MaintenanceTick {
+    /// The repository was busy; nothing ran
+    Busy(String),
+    /// The due tasks that ran, possibly none
+    Ran(Vec<MaintenanceRunRecord>),
+}
+
+/// Scheduled maintenance state for a single repository
+struct RepoMaintenanceState {
+    task: JoinHandle<()>,
+}
+
+/// Global scheduled maintenance service state
+pub struct MaintenanceService {
+    repos: HashMap<String, RepoMaintenanceState>,
+}
+
+impl Default for MaintenanceService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MaintenanceService {
+    pub fn new() -> Self {
+        Self {
+            repos: HashMap::new(),
+        }
+    }
+
+    /// Start scheduled maintenance for a repository
+    pub fn start(&mut self, repo_path: String, app_handle: tauri::AppHandle) {
+        // Stop any existing task for this repo
+        self.stop(&repo_path);
+
+        let path = repo_path.clone();
+        let initial_delay = super::autofetch_service::stagger_offset(&repo_path, POLL_INTERVAL * 2);
+
+        let task = tokio::spawn(async move {
+            // Staggered like auto-fetch, so opening a workspace of twenty
+            // repositories doesn't start twenty repacks in the same second.
+            tokio::time::sleep(initial_delay).await;
+            let mut paused = false;
+            loop {
+                let tick_path = path.clone();
+                let tick = tokio::task::spawn_blocking(move || {
+                    run_due_tasks(&tick_path, MaintenanceTrigger::Scheduled)
+                })
+                .await;
+
+                match tick {
+                    Ok(Ok(MaintenanceTick::Busy(reason))) => {
+                        // Only the transition is reported; a long rebase
+                        // would otherwise emit the same event every minute.
+                        if !paused {
+                            tracing::info!("Maintenance paused for {}: {}", path, reason);
+                            let _ = app_handle.emit(
+                                "maintenance-paused",
+                                MaintenancePausedEvent {
+                                    repo_path: path.clone(),
+                                    reason,
+                                },
+                            );
+                        }
+                        paused = true;
+                    }
+                    Ok(Ok(MaintenanceTick::Ran(records))) => {
+                        paused = false;
+                        for record in records {
+                            tracing::info!(
+                                "Maintenance task {} for {}: {:?} in {}ms",
+                                record.task.name(),
+                                path,
+                                record.status,
+                                record.duration_ms
+                            );
+                            let _ = app_handle.emit(
+                                "maintenance-task-completed",
+                                MaintenanceTaskEvent {
+                                    repo_path: path.clone(),
+                                    record,
+                                },
+                            );
+                        }
+                    }
+                    Ok(Err(e)) => tracing::warn!("Maintenance failed for {}: {}", path, e),
+                    Err(e) => tracing::warn!("Maintenance task failed for {}: {}", path, e),
+                }
+
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        });
+
+        self.repos.insert(repo_path, RepoMaintenanceState { task });
+    }
+
+    /// Stop scheduled maintenance for a repository.
+    ///
+    /// A task already running in git is left to finish; aborting the loop
+    /// only prevents the next one from starting.
+    pub fn stop(&mut self, repo_path: &str) {
+        if let Some(state) = self.repos.remove(repo_path) {
+            state.task.abort();
+        }
+    }
+
+    /// Stop all scheduled maintenance
+    pub fn stop_all(&mut self) {
+        for (_, state) in self.repos.drain() {
+            state.task.abort();
+        }
+    }
+
+    /// Check if scheduled maintenance is running for a repository
+    pub fn is_running(&self, repo_path: &str) -> bool {
+        self.repos.contains_key(repo_path)
+    }
+
+    /// Repositories with scheduled maintenance running
+    pub fn running_repos(&self) -> Vec<String> {
+        let mut repos: Vec<String> = self.repos.keys().cloned().collect();
+        repos.sort();
+        repos
+    }
+}
+
+/// Task completion event
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MaintenanceTaskEvent {
+    repo_path: String,
+    record: MaintenanceRunRecord,
+}
+
+/// Emitted when a repository's maintenance pauses because it is busy
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MaintenancePausedEvent {
+    repo_path: String,
+    reason: String,
+}
+
+fn open_repo(path: &str) -> Result<git2::Repository> {
+    git2::Repository::open(path).map_err(|_| LeviathanError::RepositoryNotFound(path.to_string()))
+}
+
+fn leviathan_dir(repo: &git2::Repository) -> PathBuf {
+    repo.commondir().join("leviathan")
+}
+
+fn schedule_path(repo: &git2::Repository) -> PathBuf {
+    leviathan_dir(repo).join("maintenance.json")
+}
+
+fn log_path(repo: &git2::Repository) -> PathBuf {
+    leviathan_dir(repo).join("maintenance_log.jsonl")
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// The repository's schedule, or the default when none was saved
+pub fn load_schedule(repo: &git2::Repository) -> Result<MaintenanceSchedule> {
+    let path = schedule_path(repo);
+    if !path.exists() {
+        return Ok(MaintenanceSchedule::default());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    let schedule: MaintenanceSchedule = serde_json::from_str(&content).map_err(|e| {
+        LeviathanError::OperationFailed(format!("Failed to parse maintenance schedule: {}", e))
+    })?;
+    Ok(schedule.normalized())
+}
+
+/// Persist a repository's schedule, returning it normalized
+pub fn save_schedule(
+    repo: &git2::Repository,
+    schedule: MaintenanceSchedule,
+) -> Result<MaintenanceSchedule> {
+    let schedule = schedule.normalized();
+    let path = schedule_path(repo);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&schedule)?)?;
+    Ok(schedule)
+}
+
+/// All logged runs, oldest first. Lines that fail to parse are skipped so
+/// one torn write doesn't hide the rest of the history.
+pub fn read_log(repo: &git2::Repository) -> Result<Vec<MaintenanceRunRecord>> {
+    let path = log_path(repo);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+pub fn clear_log(repo: &git2::Repository) -> Result<()> {
+    let path = log_path(repo);
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Append runs to the log, compacting it to the newest
+/// [`MAX_LOG_ENTRIES`]. Never fails the caller: the maintenance itself
+/// happened whether or not its history could be written.
+fn record_runs(repo: &git2::Repository, records: &[MaintenanceRunRecord]) {
+    if records.is_empty() {
+        return;
+    }
+    if let Err(e) = append_log(&log_path(repo), records) {
+        tracing::warn!("failed to record maintenance runs: {}", e);
+    }
+}
+
+fn append_log(path: &Path, records: &[MaintenanceRunRecord]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(lines.as_bytes())?;
+    drop(file);
+
+    let content = std::fs::read_to_string(path)?;
+    let count = content.lines().count();
+    if count > MAX_LOG_ENTRIES {
+        let kept: Vec<&str> = content.lines().skip(count - MAX_LOG_ENTRIES).collect();
+        std::fs::write(path, kept.join("\n") + "\n")?;
+    }
+    Ok(())
+}
+
+/// The most recent run of each task that actually ran. Skipped runs don't
+/// count: a task skipped during a rebase is still due afterwards.
+pub fn last_runs(
+    records: &[MaintenanceRunRecord],
+) -> HashMap<MaintenanceTask, MaintenanceRunRecord> {
+    let mut last = HashMap::new();
+    for record in records {
+        if record.status != MaintenanceRunStatus::Skipped {
+            last.insert(record.task, record.clone());
+        }
+    }
+    last
+}
+
+/// When `entry` is next due, as a unix timestamp. A task that never ran is
+/// due immediately, which is reported as `now`.
+pub fn next_due_at(entry: &ScheduledTask, last: Option<&MaintenanceRunRecord>, now: i64) -> i64 {
+    match last {
+        Some(record) => record.started_at + entry.interval_minutes as i64 * 60,
+        None => now,
+    }
+}
+
+/// Enabled tasks whose interval has elapsed since their last run
+pub fn due_tasks(
+    schedule: &MaintenanceSchedule,
+    records: &[MaintenanceRunRecord],
+    now: i64,
+) -> Vec<MaintenanceTask> {
+    let last = last_runs(records);
+    schedule
+        .tasks
+        .iter()
+        .filter(|entry| entry.enabled && next_due_at(entry, last.get(&entry.task), now) <= now)
+        .map(|entry| entry.task)
+        .collect()
+}
+
+/// Why the repository shouldn't be touched right now, if anything.
+///
+/// Maintenance rewrites packs, the commit-graph and refs under
+/// `refs/prefetch/`; running it under a rebase, a commit or a concurrent
+/// `git gc` risks lock failures at best and, for the user, a "could not
+/// lock index" halfway through their own operation. Every lock git takes
+/// for the files maintenance contends with is checked, along with any
+/// in-progress sequencer state, and the tick is simply tried again later.
+pub fn busy_reason(repo: &git2::Repository) -> Option<String> {
+    let state = repo.state();
+    if state != git2::RepositoryState::Clean {
+        return Some(format!("{:?} in progress", state));
+    }
+
+    let git_dir = repo.path();
+    let common_dir = repo.commondir();
+    let locks = [
+        git_dir.join("index.lock"),
+        git_dir.join("HEAD.lock"),
+        common_dir.join("packed-refs.lock"),
+        common_dir.join("config.lock"),
+        common_dir.join("shallow.lock"),
+        common_dir.join("objects").join("maintenance.lock"),
+        common_dir
+            .join("objects")
+            .join("info")
+            .join("commit-graphs")
+            .join("commit-graph-chain.lock"),
+    ];
+    for lock in &locks {
+        if lock.exists() {
+            let name = lock.strip_prefix(common_dir).unwrap_or(lock);
+            return Some(format!("{} exists", name.display()));
+        }
+    }
+
+    let gc_pid = common_dir.join("gc.pid");
+    if let Ok(modified) = std::fs::metadata(&gc_pid).and_then(|m| m.modified()) {
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        if age < STALE_GC_PID {
+            return Some("git gc is running".to_string());
+        }
+    }
+
+    None
+}
+
+/// Repositories (by common dir) with maintenance running in this process.
+///
+/// The scheduled loop and a manual run from the UI would otherwise race on
+/// the same packs; git's own locks catch most of that, but not before one
+/// side has failed with an error the user has to make sense of.
+fn in_flight() -> &'static Mutex<HashSet<PathBuf>> {
+    static IN_FLIGHT: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+    IN_FLIGHT.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Marks a repository as under maintenance until dropped
+struct InFlightGuard(PathBuf);
+
+impl InFlightGuard {
+    fn acquire(repo: &git2::Repository) -> Option<Self> {
+        let key = repo.commondir().to_path_buf();
+        let mut set = in_flight().lock().unwrap_or_else(|e| e.into_inner());
+        if set.insert(key.clone()) {
+            Some(Self(key))
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        let mut set = in_flight().lock().unwrap_or_else(|e| e.into_inner());
+        set.remove(&self.0);
+    }
+}
+
+/// Keep the last [`MAX_MESSAGE_BYTES`] of `text`, cut on a char boundary.
+fn tail(text: &str) -> String {
+    let text = text.trim();
+    if text.len() <= MAX_MESSAGE_BYTES {
+        return text.to_string();
+    }
+    let mut start = text.len() - MAX_MESSAGE_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    text[start..].to_string()
+}
+
+/// Run a git command in `path`, returning its stderr (or stdout) on failure.
+fn run_git(path: &str, args: &[&str]) -> std::result::Result<(), String> {
+    let output = create_command("git")
+        .current_dir(path)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("Failed to execute git: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = if stderr.trim().is_empty() {
+        stdout
+    } else {
+        stderr
+    };
+    Err(tail(&message))
+}
+
+/// Run one task with git.
+///
+/// The four tasks git has are delegated to `git maintenance run`, which
+/// knows their details (the prefetch refmap, the repack batch size, the
+/// pack-expiry dance) and takes `objects/maintenance.lock` against an
+/// external `git maintenance` run. The multi-pack-index has no standalone
+/// task in git, so it does what `incremental-repack` does for it: write,
+/// verify, and rewrite from scratch if verification finds it corrupt.
+fn execute_task(path: &str, task: MaintenanceTask) -> std::result::Result<(), String> {
+    match task {
+        MaintenanceTask::MultiPackIndex => {
+            run_git(path, &["multi-pack-index", "write", "--no-progress"])?;
+            if run_git(path, &["multi-pack-index", "verify", "--no-progress"]).is_err() {
+                let repo = git2::Repository::open(path).map_err(|e| e.to_string())?;
+                let midx = repo
+                    .commondir()
+                    .join("objects")
+                    .join("pack")
+                    .join("multi-pack-index");
+                std::fs::remove_file(&midx).map_err(|e| e.to_string())?;
+                run_git(path, &["multi-pack-index", "write", "--no-progress"])?;
+            }
+            Ok(())
+        }
+        _ => run_git(
+            path,
+            &[
+                "maintenance",
+                "run",
+                &format!("--task={}", task.name()),
+                "--quiet",
+            ],
+        ),
+    }
+}
+
+/// Run `tasks` in order, logging each run.
+///
+/// When the repository is busy, or another maintenance run is already under
+/// way here, each task is logged as skipped with the reason rather than
+/// run. Blocking.
+pub fn run_tasks(
+    path: &str,
+    tasks: &[MaintenanceTask],
+    trigger: MaintenanceTrigger,
+) -> Result<Vec<MaintenanceRunRecord>> {
+    let repo = open_repo(path)?;
+    let guard = InFlightGuard::acquire(&repo);
+    let skip_reason = match guard {
+        Some(_) => busy_reason(&repo),
+        None => Some("maintenance is already running".to_string()),
+    };
+    Ok(execute_and_record(&repo, path, tasks, trigger, skip_reason))
+}
+
+/// One scheduler tick: run whatever is due, unless the repository is busy.
+///
+/// A busy tick logs nothing — the loop retries a minute later, and a
+/// skipped entry per minute of a long rebase would drown the real history.
+pub fn run_due_tasks(path: &str, trigger: MaintenanceTrigger) -> Result<MaintenanceTick> {
+    let repo = open_repo(path)?;
+    let due = due_tasks(&load_schedule(&repo)?, &read_log(&repo)?, now_secs());
+    if due.is_empty() {
+        return Ok(MaintenanceTick::Ran(Vec::new()));
+    }
+    let Some(_guard) = InFlightGuard::acquire(&repo) else {
+        return Ok(MaintenanceTick::Busy(
+            "maintenance is already running".to_string(),
+        ));
+    };
+    if let Some(reason) = busy_reason(&repo) {
+        return Ok(MaintenanceTick::Busy(reason));
+    }
+    Ok(MaintenanceTick::Ran(execute_and_record(
+        &repo, path, &due, trigger, None,
+    )))
+}
+
+/// Run each task (or skip it with `skip_reason`) and append the runs to the
+/// log. The caller holds the repository's [`InFlightGuard`].
+fn execute_and_record(
+    repo: &git2::Repository,
+    path: &str,
+    tasks: &[MaintenanceTask],
+    trigger: MaintenanceTrigger,
+    skip_reason: Option<String>,
+) -> Vec<MaintenanceRunRecord> {
+    let mut records = Vec::with_capacity(tasks.len());
+    for &task in tasks {
+        let started_at = now_secs();
+        if let Some(reason) = &skip_reason {
+            records.push(MaintenanceRunRecord {
+                task,
+                status: MaintenanceRunStatus::Skipped,
+                trigger,
+                started_at,
+                duration_ms: 0,
+                message: Some(reason.clone()),
+            });
+            continue;
+        }
+        let start = Instant::now();
+        let outcome = execute_task(path, task);
+        records.push(MaintenanceRunRecord {
+            task,
+            status: if outcome.is_ok() {
+                MaintenanceRunStatus::Succeeded
+            } else {
+                MaintenanceRunStatus::Failed
+            },
+            trigger,
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+            message: outcome.err(),
+        });
+    }
+
+    record_runs(repo, &records);
+    records
+}
+
+/// Global scheduled maintenance state
+pub type MaintenanceState = Arc<RwLock<MaintenanceService>>;
+
+/// Create default scheduled maintenance state
+pub fn create_maintenance_state() -> MaintenanceState {
+    Arc::new(RwLock::new(MaintenanceService::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestRepo;
+
+    fn record(
+        task: MaintenanceTask,
+        status: MaintenanceRunStatus,
+        at: i64,
+    ) -> MaintenanceRunRecord {
+        MaintenanceRunRecord {
+            task,
+            status,
+            trigger: MaintenanceTrigger::Scheduled,
+            started_at: at,
+            duration_ms: 0,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_normalized_fills_missing_tasks_and_dedupes() {
+        let schedule = MaintenanceSchedule {
+            tasks: vec![
+                ScheduledTask {
+                    task: MaintenanceTask::Prefetch,
+                    enabled: false,
+                    interval_minutes: 0,
+                },
+                ScheduledTask {
+                    task: MaintenanceTask::Prefetch,
+                    enabled: true,
+                    interval_minutes: 5,
+                },
+            ],
+        }
+        .normalized();
+
+        assert_eq!(schedule.tasks.len(), MaintenanceTask::ALL.len());
+        let prefetch = &schedule.tasks[1];
+        assert_eq!(prefetch.task, MaintenanceTask::Prefetch);
+        assert!(!prefetch.enabled, "first entry wins");
+        assert_eq!(prefetch.interval_minutes, 1, "zero interval is raised");
+        assert_eq!(schedule.tasks[0], MaintenanceSchedule::default().tasks[0]);
+    }
+
+    #[test]
+    fn test_due_tasks_follow_last_real_run() {
+        let schedule = MaintenanceSchedule::default();
+        let now = 1_000_000;
+        let records = vec![
+            record(
+                MaintenanceTask::CommitGraph,
+                MaintenanceRunStatus::Succeeded,
+                now - 30 * 60,
+            ),
+            record(
+                MaintenanceTask::Prefetch,
+                MaintenanceRunStatus::Failed,
+                now - 2 * 60 * 60,
+            ),
+            record(
+                MaintenanceTask::LooseObjects,
+                MaintenanceRunStatus::Succeeded,
+                now - 60,
+            ),
+            // A skip after the last real run must not push the task back.
+            record(
+                MaintenanceTask::LooseObjects,
+                MaintenanceRunStatus::Skipped,
+                now,
+            ),
+        ];
+
+        let due = due_tasks(&schedule, &records, now);
+
+        assert!(!due.contains(&MaintenanceTask::CommitGraph));
+        assert!(due.contains(&MaintenanceTask::Prefetch));
+        assert!(!due.contains(&MaintenanceTask::LooseObjects));
+        assert!(
+            due.contains(&MaintenanceTask::IncrementalRepack),
+            "never ran"
+        );
+    }
+
+    #[test]
+    fn test_disabled_tasks_are_never_due() {
+        let mut schedule = MaintenanceSchedule::default();
+        for entry in &mut schedule.tasks {
+            entry.enabled = false;
+        }
+        assert!(due_tasks(&schedule, &[], 0).is_empty());
+    }
+
+    #[test]
+    fn test_schedule_round_trips_through_commondir() {
+        let test_repo = TestRepo::with_initial_commit();
+        let repo = test_repo.repo();
+        assert_eq!(
+            load_schedule(&repo).unwrap(),
+            MaintenanceSchedule::default()
+        );
+
+        let mut schedule = MaintenanceSchedule::default();
+        schedule.tasks[0].interval_minutes = 15;
+        save_schedule(&repo, schedule.clone()).unwrap();
+
+        assert_eq!(load_schedule(&repo).unwrap(), schedule);
+        assert!(test_repo
+            .path
+            .join(".git/leviathan/maintenance.json")
+            .exists());
+    }
+
+    #[test]
+    fn test_busy_reason_detects_locks_and_sequencer_state() {
+        let test_repo = TestRepo::with_initial_commit();
+        let repo = test_repo.repo();
+        assert_eq!(busy_reason(&repo), None);
+
+        let lock = test_repo.path.join(".git/index.lock");
+        std::fs::write(&lock, "").unwrap();
+        assert!(busy_reason(&repo).unwrap().contains("index.lock"));
+        std::fs::remove_file(&lock).unwrap();
+
+        std::fs::write(
+            test_repo.path.join(".git/MERGE_HEAD"),
+            test_repo.head_oid().to_string(),
+        )
+        .unwrap();
+        assert!(busy_reason(&repo).unwrap().contains("Merge"));
+    }
+
+    #[test]
+    fn test_run_tasks_logs_results_with_durations() {
+        let test_repo = TestRepo::with_initial_commit();
+        test_repo.create_commit("second", &[("a.txt", "a")]);
+
+        let records = run_tasks(
+            &test_repo.path_str(),
+            &[
+                MaintenanceTask::CommitGraph,
+                MaintenanceTask::LooseObjects,
+                MaintenanceTask::MultiPackIndex,
+            ],
+            MaintenanceTrigger::Manual,
+        )
+        .unwrap();
+
+        for r in &records {
+            assert_eq!(r.status, MaintenanceRunStatus::Succeeded, "{:?}", r);
+        }
+        assert!(
+            test_repo
+                .path
+                .join(".git/objects/info/commit-graphs/commit-graph-chain")
+                .exists()
+                || test_repo
+                    .path
+                    .join(".git/objects/info/commit-graph")
+                    .exists()
+        );
+
+        let log = read_log(&test_repo.repo()).unwrap();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[0].task, MaintenanceTask::CommitGraph);
+        assert_eq!(log[0].trigger, MaintenanceTrigger::Manual);
+    }
+
+    #[test]
+    fn test_busy_repository_skips_and_tick_does_not_log() {
+        let test_repo = TestRepo::with_initial_commit();
+        std::fs::write(test_repo.path.join(".git/index.lock"), "").unwrap();
+
+        let tick = run_due_tasks(&test_repo.path_str(), MaintenanceTrigger::Scheduled).unwrap();
+        assert!(matches!(tick, MaintenanceTick::Busy(_)));
+        assert!(read_log(&test_repo.repo()).unwrap().is_empty());
+
+        let records = run_tasks(
+            &test_repo.path_str(),
+            &[MaintenanceTask::CommitGraph],
+            MaintenanceTrigger::Manual,
+        )
+        .unwrap();
+        assert_eq!(records[0].status, MaintenanceRunStatus::Skipped);
+        assert!(records[0]
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("index.lock"));
+    }
+
+    #[test]
+    fn test_log_is_compacted_to_newest_entries() {
+        let test_repo = TestRepo::with_initial_commit();
+        let repo = test_repo.repo();
+        let records: Vec<_> = (0..MAX_LOG_ENTRIES as i64 + 20)
+            .map(|i| {
+                record(
+                    MaintenanceTask::Prefetch,
+                    MaintenanceRunStatus::Succeeded,
+                    i,
+                )
+            })
+            .collect();
+        record_runs(&repo, &records);
+
+        let log = read_log(&repo).unwrap();
+        assert_eq!(log.len(), MAX_LOG_ENTRIES);
+        assert_eq!(log[0].started_at, 20);
+    }
+}
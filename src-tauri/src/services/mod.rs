@@ -14,6 +14,7 @@ pub mod github_app;
 #[cfg(not(target_os = "macos"))]
 pub mod keyring_util;
 pub mod loopback_server;
+pub mod maintenance_service;
 pub mod oauth;
 pub mod update_service;
 pub mod watcher_service;
@@ -23,5 +24,6 @@ pub use autofetch_service::{create_autofetch_state, AutoFetchState};
 pub use cancellation::CancellationRegistry;
 pub use credentials_service::CredentialsHelper;
 pub use git_service::GitService;
+pub use maintenance_service::{create_maintenance_state, MaintenanceState};
 pub use update_service::{create_update_state, UpdateState};
 pub use watcher_service::WatcherService;